@@ -0,0 +1,207 @@
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use cs_demo_parser::parser::{EntityEvent, Parser as DemoParser, ParserError};
+use cs_demo_parser::proto::msg::csvc_msg_class_info::ClassT;
+use cs_demo_parser::proto::msg::{CsvcMsgClassInfo, CsvcMsgPacketEntities, CsvcMsgServerInfo};
+use cs_demo_parser::sendtables2::proto::{
+    CsvcMsgFlattenedSerializer, ProtoFlattenedSerializerFieldT, ProtoFlattenedSerializerT,
+};
+use cs_demo_parser::sendtables2::{EntityStore, Parser};
+use prost::Message;
+use std::hint::black_box;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+/// Fields of the synthetic class, roughly the mix of a player pawn.
+const FIELDS: &[&str] = &["int32", "bool", "float32", "uint64", "Vector", "QAngle"];
+const FIELD_COUNT: usize = 120;
+const ENTITIES: usize = 64;
+
+struct BitWriter {
+    buf: Vec<u8>,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, value: u64, n: u32) {
+        for i in 0..n {
+            if self.bits.is_multiple_of(8) {
+                self.buf.push(0);
+            }
+            let bit = (value >> i) & 1;
+            *self.buf.last_mut().unwrap() |= (bit as u8) << (self.bits % 8);
+            self.bits += 1;
+        }
+    }
+
+    fn write_var(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.write_bits((value & 0x7f) | 0x80, 8);
+            value >>= 7;
+        }
+        self.write_bits(value, 8);
+    }
+}
+
+/// Every fourth float is sent uncompressed.
+fn bit_count(field: usize) -> i32 {
+    if field.is_multiple_of(4) { 32 } else { 12 }
+}
+
+fn parser() -> Parser {
+    let mut symbols = vec!["Bench".to_string()];
+    symbols.extend(FIELDS.iter().map(|s| s.to_string()));
+    let fields = (0..FIELD_COUNT)
+        .map(|i| {
+            symbols.push(format!("m_field{i}"));
+            ProtoFlattenedSerializerFieldT {
+                var_type_sym: Some(1 + (i % FIELDS.len()) as i32),
+                var_name_sym: Some(symbols.len() as i32 - 1),
+                bit_count: Some(bit_count(i)),
+                low_value: Some(-100.0),
+                high_value: Some(100.0),
+                ..Default::default()
+            }
+        })
+        .collect();
+    let msg = CsvcMsgFlattenedSerializer {
+        serializers: vec![ProtoFlattenedSerializerT {
+            serializer_name_sym: Some(0),
+            serializer_version: Some(0),
+            fields_index: (0..FIELD_COUNT as i32).collect(),
+        }],
+        symbols,
+        fields,
+    };
+    let buf = msg.encode_length_delimited_to_vec();
+
+    let mut p = Parser::new();
+    p.on_server_info(&CsvcMsgServerInfo {
+        max_classes: Some(1),
+        ..Default::default()
+    });
+    p.parse_packet(&buf).unwrap();
+    p.on_class_info(&CsvcMsgClassInfo {
+        create_on_client: Some(false),
+        classes: vec![ClassT {
+            class_id: Some(0),
+            data_table_name: None,
+            class_name: Some("Bench".into()),
+        }],
+    });
+    p
+}
+
+/// Creates `ENTITIES` entities, each with every field set.
+fn packet(p: &Parser) -> CsvcMsgPacketEntities {
+    let mut w = BitWriter {
+        buf: Vec::new(),
+        bits: 0,
+    };
+    for e in 0..ENTITIES {
+        w.write_bits(0, 6);
+        w.write_bits(2, 2);
+        w.write_bits(0, p.class_id_size());
+        w.write_bits(e as u64, 17);
+        w.write_var(0);
+        // PlusOne per field, then FieldPathEncodeFinish.
+        w.write_bits(0, FIELD_COUNT as u32);
+        w.write_bits(1, 2);
+        for i in 0..FIELD_COUNT {
+            let bits = bit_count(i) as u32;
+            match FIELDS[i % FIELDS.len()] {
+                | "int32" => w.write_var(i as u64 * 2),
+                | "bool" => w.write_bits(1, 1),
+                | "uint64" => w.write_var(u64::MAX >> 4),
+                | "float32" => w.write_bits(i as u64, bits),
+                | _ => {
+                    for _ in 0..3 {
+                        w.write_bits(i as u64, bits);
+                    }
+                },
+            }
+        }
+    }
+    CsvcMsgPacketEntities {
+        max_entries: Some(ENTITIES as i32),
+        updated_entries: Some(ENTITIES as i32),
+        is_delta: Some(false),
+        update_baseline: Some(false),
+        baseline: Some(0),
+        delta_from: Some(0),
+        entity_data: Some(w.buf),
+    }
+}
+
+fn bench_packet_entities(c: &mut Criterion) {
+    let msg = packet(&parser());
+    let mut group = c.benchmark_group("packet_entities");
+    group.throughput(Throughput::Elements(ENTITIES as u64));
+    for (name, lazy) in [("eager", false), ("lazy", true)] {
+        group.bench_function(name, |b| {
+            b.iter_batched(
+                || {
+                    let mut p = parser();
+                    p.set_lazy_properties(lazy);
+                    p
+                },
+                |mut p| black_box(p.parse_packet_entities(&msg, &mut EntityStore::new())),
+                criterion::BatchSize::SmallInput,
+            );
+        });
+    }
+    group.bench_function("lazy_read_one", |b| {
+        b.iter_batched(
+            parser,
+            |mut p| {
+                let mut entities = EntityStore::new();
+                let events = p.parse_packet_entities(&msg, &mut entities).events;
+                events
+                    .iter()
+                    .filter_map(|(h, _, _)| entities.get(*h))
+                    .map(|e| e.property_value("m_field0").unwrap().int_val)
+                    .sum::<i32>()
+            },
+            criterion::BatchSize::SmallInput,
+        );
+    });
+    group.finish();
+}
+
+/// Parses the Source 2 demos in `demos/s2`, with an entity handler so that
+/// entities are decoded. Demos that haven't been fetched from Git LFS are
+/// skipped.
+fn bench_s2_demos(c: &mut Criterion) {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("demos/s2");
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        eprintln!("skipping s2 demos: {} not found", dir.display());
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| Some(e.ok()?.path())).collect();
+    paths.sort();
+
+    let mut group = c.benchmark_group("parse_s2");
+    group.sample_size(10);
+    for path in paths {
+        let data = std::fs::read(&path).unwrap();
+        let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+        if let Err(ParserError::GitLfsPointer) = DemoParser::new(Cursor::new(&data)).parse_header()
+        {
+            eprintln!("skipping {name}: Git LFS pointer");
+            continue;
+        }
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(&name), &data, |b, data| {
+            b.iter(|| {
+                let mut parser = DemoParser::new(Cursor::new(data));
+                parser.register_sync_event_handler(|ev: &EntityEvent, gs| {
+                    black_box(gs.entity(ev.handle).map(|e| e.property_value("m_iHealth")));
+                });
+                parser.parse_to_end().ok();
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_packet_entities, bench_s2_demos);
+criterion_main!(benches);
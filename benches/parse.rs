@@ -1,13 +1,146 @@
-use criterion::{Criterion, criterion_group, criterion_main};
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use cs_demo_parser::bitreader::BitReader;
 use cs_demo_parser::parser::Parser;
 use std::fs::File;
+use std::hint::black_box;
 use std::io::{Cursor, Read};
 use std::path::PathBuf;
 
+/// Message type the parser doesn't handle, so that only reading is measured.
+const IGNORED_MSG: u8 = 60;
+
+fn varint(out: &mut Vec<u8>, mut v: u32) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+fn header(filestamp: &[u8; 8]) -> Vec<u8> {
+    let mut data = filestamp.to_vec();
+    data.resize(1072, 0);
+    data
+}
+
+/// A Source 1 demo of `frames` packets with `msgs` messages of `msg_len`
+/// bytes each.
+fn source1_demo(frames: usize, msgs: usize, msg_len: usize) -> Vec<u8> {
+    let mut packet = Vec::new();
+    for _ in 0..msgs {
+        varint(&mut packet, IGNORED_MSG as u32);
+        varint(&mut packet, msg_len as u32);
+        packet.extend(std::iter::repeat_n(0xab, msg_len));
+    }
+
+    let mut data = header(b"HL2DEMO\0");
+    for tick in 0..frames {
+        data.push(2);
+        data.extend((tick as i32).to_le_bytes());
+        data.push(0);
+        data.extend([0; 160]);
+        data.extend((packet.len() as i32).to_le_bytes());
+        data.extend(&packet);
+    }
+    // Stop
+    data.extend([7, 0, 0, 0, 0, 0]);
+    data
+}
+
+/// A Source 2 demo of `frames` frames with `len` byte payloads.
+fn source2_demo(frames: usize, len: usize) -> Vec<u8> {
+    let mut data = header(b"PBDEMS2\0");
+    for tick in 0..frames {
+        varint(&mut data, IGNORED_MSG as u32);
+        varint(&mut data, tick as u32);
+        varint(&mut data, len as u32);
+        data.extend(std::iter::repeat_n(0xab, len));
+    }
+    // Stop
+    data.extend([0, 0, 0]);
+    data
+}
+
+fn bench_synthetic(c: &mut Criterion) {
+    let demos = [
+        ("source1", source1_demo(2_000, 16, 512)),
+        ("source2", source2_demo(2_000, 8 * 1024)),
+    ];
+    let mut group = c.benchmark_group("parse_synthetic");
+    for (name, data) in &demos {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(BenchmarkId::new("slice", name), data, |b, data| {
+            b.iter(|| {
+                let mut parser = Parser::new(&data[..]);
+                parser.parse_to_end().unwrap();
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Parses the same demo from a slice, a file and, with the `mmap` feature,
+/// a memory map of the file.
+fn bench_inputs(c: &mut Criterion) {
+    let data = source2_demo(2_000, 8 * 1024);
+    let path = std::env::temp_dir().join(format!("cs-demo-bench-{}.dem", std::process::id()));
+    std::fs::write(&path, &data).unwrap();
+
+    let mut group = c.benchmark_group("parse_input");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("slice", |b| {
+        b.iter(|| Parser::new(&data[..]).parse_to_end().unwrap());
+    });
+    group.bench_function("file", |b| {
+        b.iter(|| Parser::new(File::open(&path).unwrap()).parse_to_end().unwrap());
+    });
+    #[cfg(feature = "mmap")]
+    group.bench_function("mmap", |b| {
+        b.iter(|| Parser::open_mmap(&path).unwrap().parse_to_end().unwrap());
+    });
+    group.finish();
+    let _ = std::fs::remove_file(&path);
+}
+
+fn bench_bitreader(c: &mut Criterion) {
+    let data = vec![0xabu8; 1024 * 1024];
+    let mut group = c.benchmark_group("bitreader");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("read_int_8", |b| {
+        b.iter(|| {
+            let mut r = BitReader::new_large(&data[..]);
+            let mut buf = Vec::with_capacity(data.len());
+            for _ in 0..data.len() {
+                buf.push(r.read_int(8) as u8);
+            }
+            black_box(buf)
+        });
+    });
+    group.bench_function("read_bytes", |b| {
+        b.iter(|| {
+            let mut r = BitReader::new_large(&data[..]);
+            black_box(r.read_bytes(data.len()))
+        });
+    });
+    group.finish();
+}
+
+/// Parses a real demo if one is available.
 fn bench_parse_to_end(c: &mut Criterion) {
-    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../test/cs-demos/default.dem");
+    let path = std::env::var_os("CS_DEMO_BENCH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../test/cs-demos/default.dem")
+        });
     let mut data = Vec::new();
-    File::open(&path).unwrap().read_to_end(&mut data).unwrap();
+    let Ok(mut file) = File::open(&path) else {
+        eprintln!(
+            "skipping parse_to_end_default: {} not found",
+            path.display()
+        );
+        return;
+    };
+    file.read_to_end(&mut data).unwrap();
     c.bench_function("parse_to_end_default", |b| {
         b.iter(|| {
             let mut parser = Parser::new(Cursor::new(&data));
@@ -16,5 +149,11 @@ fn bench_parse_to_end(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_parse_to_end);
+criterion_group!(
+    benches,
+    bench_synthetic,
+    bench_inputs,
+    bench_bitreader,
+    bench_parse_to_end
+);
 criterion_main!(benches);
@@ -1,34 +1,74 @@
+use cs_demo_parser::analyzer;
+use cs_demo_parser::maps::MapRegistry;
 use cs_demo_parser::parser::Parser;
+use cs_demo_parser::render::heatmap::{Heatmap, HeatmapCollector, HeatmapKind, HeatmapOptions};
+use cs_demo_parser::render::png;
 use std::env;
 use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
 
-fn demo_path_from_args() -> String {
+const RADAR_DIR: &str = "docs/examples/_assets/radar";
+
+struct Args {
+    demo: String,
+    kind: HeatmapKind,
+}
+
+fn args() -> Args {
+    let usage = "Usage: cargo run --example heatmap --features png -- -demo <path> [-kind kills|deaths|positions|grenades] > out.png";
+    let mut demo = None;
+    let mut kind = HeatmapKind::Positions;
     let mut args = env::args().skip(1);
-    match (args.next(), args.next()) {
-        | (Some(flag), Some(path)) if flag == "-demo" => path,
-        | _ => panic!("Usage: cargo run --example heatmap -- -demo <path>"),
+    while let Some(flag) = args.next() {
+        match (flag.as_str(), args.next()) {
+            | ("-demo", Some(path)) => demo = Some(path),
+            | ("-kind", Some(k)) => {
+                kind = match k.as_str() {
+                    | "kills" => HeatmapKind::Kills,
+                    | "deaths" => HeatmapKind::Deaths,
+                    | "positions" => HeatmapKind::Positions,
+                    | "grenades" => HeatmapKind::Grenades,
+                    | _ => panic!("{usage}"),
+                }
+            },
+            | _ => panic!("{usage}"),
+        }
+    }
+    Args {
+        demo: demo.expect(usage),
+        kind,
     }
 }
 
 fn main() {
-    let path = demo_path_from_args();
-    let file = File::open(&path).expect("failed to open demo file");
+    let args = args();
+    let file = File::open(&args.demo).expect("failed to open demo file");
     let mut parser = Parser::new(file);
+    let collector =
+        analyzer::run(&mut parser, HeatmapCollector::new(32)).expect("failed to parse demo");
 
-    match parser.parse_header() {
-        | Ok(header) => println!("map: {}", header.map_name),
-        | Err(err) => println!("failed to parse header: {:?}", err),
-    }
-
-    parser.register_event_handler::<u8, _>(|_| {
-        // handle events here
-    });
-    parser.register_net_message_handler::<u8, _>(|_| {
-        // handle net messages here
-    });
+    let maps = MapRegistry::builtin();
+    let header = parser.header().unwrap_or_default();
+    let map_name = parser
+        .game_state()
+        .map_name()
+        .unwrap_or(&header.map_name)
+        .to_string();
+    let map = maps
+        .get(&map_name)
+        .unwrap_or_else(|| panic!("no overview data for {map_name}"));
+    let radar = png::read_file(Path::new(RADAR_DIR).join(format!("{}_radar_psd.png", map.name)))
+        .map_err(|e| eprintln!("drawing without radar image: {e}"))
+        .ok();
 
-    if let Err(e) = parser.parse_to_end() {
-        println!("error while parsing: {:?}", e);
-    }
-    println!("parsed {} frames", parser.current_frame());
+    let options = HeatmapOptions {
+        level: (!map.levels.is_empty()).then(|| "default".to_string()),
+        ..Default::default()
+    };
+    let points = collector.points(args.kind);
+    eprintln!("{} positions on {}", points.len(), map.name);
+    let heatmap = Heatmap::new(map, points, &options);
+    let image = heatmap.render(radar.as_ref(), &options);
+    png::encode(&image, BufWriter::new(io::stdout().lock())).expect("failed to write image");
 }
@@ -1,4 +1,4 @@
-use cs_demo_parser::utils::parallel;
+use cs_demo_parser::utils::batch::{BatchOptions, run_batch};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
@@ -14,12 +14,15 @@ fn main() {
         .filter(|p| p.extension().map(|e| e == "dem").unwrap_or(false))
         .collect();
 
-    parallel::run(demos, |parser, path| {
-        if let Ok(header) = parser.parse_header() {
-            println!("{} -> {}", path.display(), header.map_name);
-        }
-        if let Err(e) = parser.parse_to_end() {
-            eprintln!("{}: {:?}", path.display(), e);
-        }
+    let results = run_batch(demos, &BatchOptions::default(), |parser, _| {
+        let header = parser.parse_header()?;
+        parser.parse_to_end()?;
+        Ok(header.map_name)
     });
+    for (path, res) in results {
+        match res {
+            | Ok(map) => println!("{} -> {}", path.display(), map),
+            | Err(e) => eprintln!("{}: {}", path.display(), e),
+        }
+    }
 }
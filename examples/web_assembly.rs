@@ -1,35 +1,16 @@
-use console_error_panic_hook;
-use cs_demo_parser::parser::Parser;
-use serde::Serialize;
-use std::io::Cursor;
+//! Parses a whole demo held in memory with [`WasmParser`] and returns its
+//! scoreboard. Streaming input and event callbacks are available on
+//! `WasmParser` directly, see the `wasm` module documentation.
+
+use cs_demo_parser::wasm::WasmParser;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
 pub fn parse_demo(data: &[u8]) -> Result<JsValue, JsValue> {
-    console_error_panic_hook::set_once();
-    let mut parser = Parser::new(Cursor::new(data));
-    parser
-        .parse_header()
-        .map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
-    parser
-        .parse_to_end()
-        .map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
-
-    let participants = parser.game_state().participants();
-    let stats: Vec<PlayerInfo> = participants
-        .connected()
-        .iter()
-        .map(|p| PlayerInfo {
-            name: p.name.clone(),
-        })
-        .collect();
-
-    serde_wasm_bindgen::to_value(&stats).map_err(|e| JsValue::from_str(&e.to_string()))
-}
-
-#[derive(Serialize)]
-struct PlayerInfo {
-    name: String,
+    let mut parser = WasmParser::new(Some(data.len() as f64));
+    parser.push(data)?;
+    parser.finish()?;
+    parser.scoreboard()
 }
 
 #[cfg(not(target_arch = "wasm32"))]
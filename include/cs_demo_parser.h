@@ -0,0 +1,352 @@
+#ifndef CS_DEMO_PARSER_H
+#define CS_DEMO_PARSER_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+// Result of every fallible function.
+typedef enum CsDemoError {
+  CS_DEMO_ERROR_OK = 0,
+  CS_DEMO_ERROR_NULL_POINTER = 1,
+  CS_DEMO_ERROR_INVALID_ARGUMENT = 2,
+  CS_DEMO_ERROR_IO = 3,
+  CS_DEMO_ERROR_UNEXPECTED_END_OF_DEMO = 4,
+  CS_DEMO_ERROR_INVALID_FILE_TYPE = 5,
+  CS_DEMO_ERROR_GIT_LFS_POINTER = 6,
+  CS_DEMO_ERROR_NOT_FOUND = 7,
+  // The parser panicked. The handle must not be used afterwards, other
+  // than to free it.
+  CS_DEMO_ERROR_PANIC = 8,
+} CsDemoError;
+
+typedef enum CsDemoGrenadeEventKind {
+  CS_DEMO_GRENADE_EVENT_KIND_HE_EXPLODE,
+  CS_DEMO_GRENADE_EVENT_KIND_FLASH_EXPLODE,
+  CS_DEMO_GRENADE_EVENT_KIND_SMOKE_START,
+  CS_DEMO_GRENADE_EVENT_KIND_SMOKE_EXPIRED,
+  CS_DEMO_GRENADE_EVENT_KIND_DECOY_START,
+  CS_DEMO_GRENADE_EVENT_KIND_DECOY_EXPIRED,
+  CS_DEMO_GRENADE_EVENT_KIND_FIRE_START,
+  CS_DEMO_GRENADE_EVENT_KIND_FIRE_EXPIRED,
+} CsDemoGrenadeEventKind;
+
+typedef enum CsDemoBombEventKind {
+  CS_DEMO_BOMB_EVENT_KIND_PLANT_BEGIN,
+  CS_DEMO_BOMB_EVENT_KIND_PLANTED,
+  CS_DEMO_BOMB_EVENT_KIND_DEFUSED,
+  CS_DEMO_BOMB_EVENT_KIND_EXPLODED,
+} CsDemoBombEventKind;
+
+typedef enum CsDemoBombsite {
+  CS_DEMO_BOMBSITE_UNKNOWN,
+  CS_DEMO_BOMBSITE_A,
+  CS_DEMO_BOMBSITE_B,
+} CsDemoBombsite;
+
+// Opaque parser handle.
+typedef struct CsDemoParser CsDemoParser;
+
+typedef struct CsDemoHeader {
+  char filestamp[8];
+  int32_t protocol;
+  int32_t network_protocol;
+  char server_name[260];
+  char client_name[260];
+  char map_name[260];
+  char game_directory[260];
+  float playback_time;
+  int32_t playback_ticks;
+  int32_t playback_frames;
+  int32_t signon_length;
+} CsDemoHeader;
+
+typedef struct CsDemoVector {
+  double x;
+  double y;
+  double z;
+} CsDemoVector;
+
+// State of a player. `name` is NUL terminated and truncated to 127 bytes.
+typedef struct CsDemoPlayer {
+  int32_t user_id;
+  int32_t entity_id;
+  uint64_t steam_id64;
+  char name[128];
+  // 0 unassigned, 1 spectators, 2 terrorists, 3 counter-terrorists.
+  uint8_t team;
+  bool is_bot;
+  bool is_connected;
+  bool is_alive;
+  int32_t health;
+  int32_t armor;
+  int32_t money;
+  struct CsDemoVector position;
+} CsDemoPlayer;
+
+typedef struct CsDemoRoundStart {
+  int32_t time_limit;
+  int32_t frag_limit;
+  const char *objective;
+} CsDemoRoundStart;
+
+// Event callbacks receive the event, the ingame tick and the `user_data`
+// they were registered with.
+typedef void (*CsDemoRoundStartCallback)(const struct CsDemoRoundStart*, int32_t, void*);
+
+typedef struct CsDemoRoundEnd {
+  // Team number of the winner, see [`CsDemoPlayer::team`].
+  uint8_t winner;
+  uint8_t reason;
+  const char *message;
+} CsDemoRoundEnd;
+
+typedef void (*CsDemoRoundEndCallback)(const struct CsDemoRoundEnd*, int32_t, void*);
+
+typedef struct CsDemoKill {
+  // `NULL` for world kills.
+  const struct CsDemoPlayer *killer;
+  const struct CsDemoPlayer *victim;
+  const struct CsDemoPlayer *assister;
+  // Equipment type as numbered by [`EquipmentType`].
+  int32_t weapon;
+  // Display name of the weapon, e.g. `"AK-47"`.
+  const char *weapon_name;
+  bool is_headshot;
+  int32_t penetrated_objects;
+  bool through_smoke;
+  bool no_scope;
+  bool attacker_blind;
+  bool assisted_flash;
+  float distance;
+} CsDemoKill;
+
+typedef void (*CsDemoKillCallback)(const struct CsDemoKill*, int32_t, void*);
+
+typedef struct CsDemoPlayerHurt {
+  const struct CsDemoPlayer *player;
+  const struct CsDemoPlayer *attacker;
+  int32_t health;
+  int32_t armor;
+  int32_t weapon;
+  const char *weapon_name;
+  int32_t health_damage;
+  int32_t armor_damage;
+  int32_t health_damage_taken;
+  int32_t armor_damage_taken;
+  uint8_t hit_group;
+} CsDemoPlayerHurt;
+
+typedef void (*CsDemoPlayerHurtCallback)(const struct CsDemoPlayerHurt*, int32_t, void*);
+
+typedef struct CsDemoWeaponFire {
+  const struct CsDemoPlayer *shooter;
+  int32_t weapon;
+  const char *weapon_name;
+} CsDemoWeaponFire;
+
+typedef void (*CsDemoWeaponFireCallback)(const struct CsDemoWeaponFire*, int32_t, void*);
+
+typedef struct CsDemoGrenadeEvent {
+  enum CsDemoGrenadeEventKind kind;
+  int32_t grenade;
+  int32_t entity_id;
+  struct CsDemoVector position;
+  const struct CsDemoPlayer *thrower;
+} CsDemoGrenadeEvent;
+
+typedef void (*CsDemoGrenadeEventCallback)(const struct CsDemoGrenadeEvent*, int32_t, void*);
+
+typedef struct CsDemoBombEvent {
+  enum CsDemoBombEventKind kind;
+  const struct CsDemoPlayer *player;
+  enum CsDemoBombsite site;
+} CsDemoBombEvent;
+
+typedef void (*CsDemoBombEventCallback)(const struct CsDemoBombEvent*, int32_t, void*);
+
+typedef struct CsDemoChatMessage {
+  const struct CsDemoPlayer *sender;
+  const char *text;
+  bool is_chat_all;
+} CsDemoChatMessage;
+
+typedef void (*CsDemoChatMessageCallback)(const struct CsDemoChatMessage*, int32_t, void*);
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Opens the demo at the NUL terminated, UTF-8 `path` and stores the handle
+// in `out`.
+//
+// # Safety
+//
+// `path` must be a valid C string and `out` valid for writes.
+enum CsDemoError cs_demo_parser_open_path(const char *path, struct CsDemoParser **out);
+
+// Opens a demo from `len` bytes at `data`, which are copied, and stores the
+// handle in `out`.
+//
+// # Safety
+//
+// `data` must be valid for reads of `len` bytes and `out` valid for writes.
+enum CsDemoError cs_demo_parser_open_buffer(const uint8_t *data,
+                                            size_t len,
+                                            struct CsDemoParser **out);
+
+// Releases a parser. `NULL` is ignored.
+//
+// # Safety
+//
+// `parser` must be a handle returned by one of the open functions that
+// hasn't been freed yet.
+void cs_demo_parser_free(struct CsDemoParser *parser);
+
+// Parses the header if needed and copies it to `out`.
+//
+// # Safety
+//
+// `parser` must be a valid handle and `out` valid for writes.
+enum CsDemoError cs_demo_parser_header(struct CsDemoParser *parser, struct CsDemoHeader *out);
+
+// Parses the next frame. `more` is set to false once the demo has ended.
+//
+// # Safety
+//
+// `parser` must be a valid handle and `more` valid for writes.
+enum CsDemoError cs_demo_parser_parse_next_frame(struct CsDemoParser *parser, bool *more);
+
+// Parses the demo until the end.
+//
+// # Safety
+//
+// `parser` must be a valid handle.
+enum CsDemoError cs_demo_parser_parse_to_end(struct CsDemoParser *parser);
+
+// Fraction of the demo parsed so far, between 0 and 1.
+//
+// # Safety
+//
+// `parser` must be a valid handle or `NULL`.
+float cs_demo_parser_progress(const struct CsDemoParser *parser);
+
+// Current ingame tick.
+//
+// # Safety
+//
+// `parser` must be a valid handle or `NULL`.
+int32_t cs_demo_parser_ingame_tick(const struct CsDemoParser *parser);
+
+// Number of rounds played so far.
+//
+// # Safety
+//
+// `parser` must be a valid handle or `NULL`.
+int32_t cs_demo_parser_total_rounds_played(const struct CsDemoParser *parser);
+
+// Copies up to `capacity` players to `out`, ordered by user id, and stores
+// the total number of players in `count`. Pass a `capacity` of 0 to only
+// query the count.
+//
+// # Safety
+//
+// `parser` must be a valid handle, `out` valid for writes of `capacity`
+// players (or `NULL` if `capacity` is 0) and `count` valid for writes.
+enum CsDemoError cs_demo_parser_players(const struct CsDemoParser *parser,
+                                        struct CsDemoPlayer *out,
+                                        size_t capacity,
+                                        size_t *count);
+
+// Copies the player with `user_id` to `out`.
+//
+// # Safety
+//
+// `parser` must be a valid handle and `out` valid for writes.
+enum CsDemoError cs_demo_parser_player_by_user_id(const struct CsDemoParser *parser,
+                                                  int32_t user_id,
+                                                  struct CsDemoPlayer *out);
+
+// Static description of an error code.
+const char *cs_demo_error_message(enum CsDemoError error);
+
+// Calls `callback` at the start of every round.
+//
+// # Safety
+//
+// `parser` must be a valid handle. `callback` is called with `user_data`.
+enum CsDemoError cs_demo_parser_on_round_start(struct CsDemoParser *parser,
+                                               CsDemoRoundStartCallback callback,
+                                               void *user_data);
+
+// Calls `callback` at the end of every round.
+//
+// # Safety
+//
+// `parser` must be a valid handle. `callback` is called with `user_data`.
+enum CsDemoError cs_demo_parser_on_round_end(struct CsDemoParser *parser,
+                                             CsDemoRoundEndCallback callback,
+                                             void *user_data);
+
+// Calls `callback` for every kill.
+//
+// # Safety
+//
+// `parser` must be a valid handle. `callback` is called with `user_data`.
+enum CsDemoError cs_demo_parser_on_kill(struct CsDemoParser *parser,
+                                        CsDemoKillCallback callback,
+                                        void *user_data);
+
+// Calls `callback` whenever a player takes damage.
+//
+// # Safety
+//
+// `parser` must be a valid handle. `callback` is called with `user_data`.
+enum CsDemoError cs_demo_parser_on_player_hurt(struct CsDemoParser *parser,
+                                               CsDemoPlayerHurtCallback callback,
+                                               void *user_data);
+
+// Calls `callback` for every shot and grenade throw.
+//
+// # Safety
+//
+// `parser` must be a valid handle. `callback` is called with `user_data`.
+enum CsDemoError cs_demo_parser_on_weapon_fire(struct CsDemoParser *parser,
+                                               CsDemoWeaponFireCallback callback,
+                                               void *user_data);
+
+// Calls `callback` for detonations and expirations of every grenade type.
+//
+// # Safety
+//
+// `parser` must be a valid handle. `callback` is called with `user_data`.
+enum CsDemoError cs_demo_parser_on_grenade_event(struct CsDemoParser *parser,
+                                                 CsDemoGrenadeEventCallback callback,
+                                                 void *user_data);
+
+// Calls `callback` when a plant begins, and when the bomb is planted,
+// defused or explodes.
+//
+// # Safety
+//
+// `parser` must be a valid handle. `callback` is called with `user_data`.
+enum CsDemoError cs_demo_parser_on_bomb_event(struct CsDemoParser *parser,
+                                              CsDemoBombEventCallback callback,
+                                              void *user_data);
+
+// Calls `callback` for every chat message.
+//
+// # Safety
+//
+// `parser` must be a valid handle. `callback` is called with `user_data`.
+enum CsDemoError cs_demo_parser_on_chat_message(struct CsDemoParser *parser,
+                                                CsDemoChatMessageCallback callback,
+                                                void *user_data);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* CS_DEMO_PARSER_H */
@@ -0,0 +1,192 @@
+//! Analyses that run alongside each other in a single pass over a demo.
+//!
+//! An [`Analyzer`] receives the demo's header, its events, the end of every
+//! frame and round, and the [`GameState`] at each of those points. [`run`]
+//! parses a demo with any number of analyzers, combined as tuples or
+//! vectors:
+//!
+//! ```no_run
+//! use cs_demo_parser::analyzer::{self, Analyzer};
+//! use cs_demo_parser::events::Kill;
+//! use cs_demo_parser::game_state::GameState;
+//! use cs_demo_parser::parser::Parser;
+//! use std::any::Any;
+//!
+//! #[derive(Default)]
+//! struct Kills(u32);
+//!
+//! impl Analyzer for Kills {
+//!     type Output = u32;
+//!
+//!     fn on_event(&mut self, event: &dyn Any, _: &GameState) {
+//!         if event.is::<Kill>() {
+//!             self.0 += 1;
+//!         }
+//!     }
+//!
+//!     fn finish(self, _: &GameState) -> u32 {
+//!         self.0
+//!     }
+//! }
+//!
+//! let file = std::fs::File::open("demo.dem").expect("demo not found");
+//! let mut parser = Parser::new(file);
+//! let analyzers = (Kills::default(), analyzer::RoundCount::default());
+//! let (kills, rounds) = analyzer::run(&mut parser, analyzers)?;
+//! # Ok::<(), cs_demo_parser::parser::ParserError>(())
+//! ```
+
+use std::any::Any;
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+
+use crate::events::{FrameDone, RoundEnd};
+use crate::game_state::GameState;
+use crate::parser::{DemoHeader, Parser, ParserError};
+
+/// An analysis of a demo. All hooks but [`finish`](Self::finish) default to
+/// doing nothing.
+///
+/// Hooks run on the parsing thread, after the [`GameState`] has been updated
+/// for the event or frame at hand.
+pub trait Analyzer: Send + 'static {
+    type Output;
+
+    fn on_header(&mut self, _header: &DemoHeader) {}
+
+    /// Called for every event the parser dispatches, including the
+    /// [`FrameDone`] and [`RoundEnd`] events the hooks below are called for.
+    /// Use `downcast_ref` to pick the events of interest.
+    fn on_event(&mut self, _event: &dyn Any, _state: &GameState) {}
+
+    /// Called at the end of every frame.
+    fn on_frame(&mut self, _state: &GameState) {}
+
+    fn on_round_end(&mut self, _round: &RoundEnd, _state: &GameState) {}
+
+    /// Called once the demo has been parsed, with the final game state.
+    fn finish(self, state: &GameState) -> Self::Output;
+}
+
+impl<A: Analyzer> Analyzer for Vec<A> {
+    type Output = Vec<A::Output>;
+
+    fn on_header(&mut self, header: &DemoHeader) {
+        self.iter_mut().for_each(|a| a.on_header(header));
+    }
+
+    fn on_event(&mut self, event: &dyn Any, state: &GameState) {
+        self.iter_mut().for_each(|a| a.on_event(event, state));
+    }
+
+    fn on_frame(&mut self, state: &GameState) {
+        self.iter_mut().for_each(|a| a.on_frame(state));
+    }
+
+    fn on_round_end(&mut self, round: &RoundEnd, state: &GameState) {
+        self.iter_mut().for_each(|a| a.on_round_end(round, state));
+    }
+
+    fn finish(self, state: &GameState) -> Self::Output {
+        self.into_iter().map(|a| a.finish(state)).collect()
+    }
+}
+
+macro_rules! tuple_analyzer {
+    ($($name:ident $i:tt),+) => {
+        impl<$($name: Analyzer),+> Analyzer for ($($name,)+) {
+            type Output = ($($name::Output,)+);
+
+            fn on_header(&mut self, header: &DemoHeader) {
+                $(self.$i.on_header(header);)+
+            }
+
+            fn on_event(&mut self, event: &dyn Any, state: &GameState) {
+                $(self.$i.on_event(event, state);)+
+            }
+
+            fn on_frame(&mut self, state: &GameState) {
+                $(self.$i.on_frame(state);)+
+            }
+
+            fn on_round_end(&mut self, round: &RoundEnd, state: &GameState) {
+                $(self.$i.on_round_end(round, state);)+
+            }
+
+            fn finish(self, state: &GameState) -> Self::Output {
+                ($(self.$i.finish(state),)+)
+            }
+        }
+    };
+}
+
+tuple_analyzer!(A 0);
+tuple_analyzer!(A 0, B 1);
+tuple_analyzer!(A 0, B 1, C 2);
+tuple_analyzer!(A 0, B 1, C 2, D 3);
+tuple_analyzer!(A 0, B 1, C 2, D 3, E 4);
+tuple_analyzer!(A 0, B 1, C 2, D 3, E 4, F 5);
+tuple_analyzer!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+tuple_analyzer!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Counts the rounds that ended, e.g. to sanity check other analyses.
+#[derive(Debug, Default, Clone)]
+pub struct RoundCount(u32);
+
+impl Analyzer for RoundCount {
+    type Output = u32;
+
+    fn on_round_end(&mut self, _: &RoundEnd, _: &GameState) {
+        self.0 += 1;
+    }
+
+    fn finish(self, _: &GameState) -> u32 {
+        self.0
+    }
+}
+
+/// Parses the rest of the demo and returns the analyzer's output.
+///
+/// The analyzer is hooked into `parser` with
+/// [`Parser::register_sync_any_handler`], so handlers registered before
+/// keep working. The hook is unregistered again before `run` returns.
+///
+/// It isn't built on [`Dispatcher`](crate::dispatcher::Dispatcher): the
+/// parser's dispatchers key handlers by event type, so there is no way to
+/// receive every event, and their handlers only get the event. By default
+/// they also run on a runner thread, after the parser has moved on, which
+/// leaves no [`GameState`] matching the event to hand to the analyzer.
+pub fn run<R: Read, A: Analyzer>(
+    parser: &mut Parser<R>,
+    analyzer: A,
+) -> Result<A::Output, ParserError> {
+    let header = parser.parse_header()?;
+    let shared = Arc::new(Mutex::new(Some(analyzer)));
+    if let Some(a) = shared.lock().unwrap().as_mut() {
+        a.on_header(&header);
+    }
+
+    let s = Arc::clone(&shared);
+    let handler = parser.register_sync_any_handler(move |event, state| {
+        let mut guard = s.lock().unwrap();
+        let Some(a) = guard.as_mut() else {
+            return;
+        };
+        a.on_event(event, state);
+        if event.is::<FrameDone>() {
+            a.on_frame(state);
+        } else if let Some(round) = event.downcast_ref::<RoundEnd>() {
+            a.on_round_end(round, state);
+        }
+    });
+
+    let res = parser.parse_to_end();
+    parser.unregister_sync_handler(handler);
+    let analyzer = shared
+        .lock()
+        .unwrap()
+        .take()
+        .expect("analyzer is only taken once");
+    res?;
+    Ok(analyzer.finish(parser.game_state()))
+}
@@ -0,0 +1,548 @@
+//! Command-line interface to the parser.
+//!
+//! Exit codes: `0` on success, `1` for I/O errors, `2` for invalid usage,
+//! `3` for truncated or corrupt demos (whatever could be parsed is still
+//! written) and `4` for files that aren't demos, e.g. Git LFS pointers.
+
+mod output;
+
+use std::cell::RefCell;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Args, Parser as _, Subcommand};
+use serde::Serialize;
+
+use cs_demo_parser::common::Team;
+use cs_demo_parser::export::{Dataset, DatasetCollector, DatasetConfig};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::parser::{Parser, ParserError};
+use cs_demo_parser::serialization::EventStreamWriter;
+
+use output::Format;
+
+#[derive(clap::Parser)]
+#[command(
+    name = "cs-demo",
+    version,
+    about = "Inspect and export CS:GO and CS2 demos"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Print the header, map, build, duration, tick rate and players.
+    Info {
+        demo: PathBuf,
+        /// Print JSON instead of text.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Write events as newline delimited JSON.
+    Events {
+        demo: PathBuf,
+        /// Only write events of this type, e.g. `Kill`. May be repeated.
+        #[arg(long = "type", short = 't', value_name = "TYPE")]
+        types: Vec<String>,
+        /// First ingame tick to write events for.
+        #[arg(long)]
+        from_tick: Option<i32>,
+        /// Last ingame tick to write events for. Parsing stops after it.
+        #[arg(long)]
+        to_tick: Option<i32>,
+        /// Write to a file instead of stdout.
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// List all kills.
+    Kills(TableArgs),
+    /// List all rounds.
+    Rounds(TableArgs),
+    /// Print per-player kills, deaths, assists and damage.
+    Scoreboard(TableArgs),
+    /// Dump the server classes and their properties.
+    Entities {
+        demo: PathBuf,
+        /// Only dump classes whose name contains this string.
+        #[arg(long)]
+        class: Option<String>,
+        /// Print JSON instead of text.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export the dataset tables of a demo, or of every demo in a
+    /// directory, as Parquet files.
+    Export {
+        /// A demo or a directory of demos.
+        input: PathBuf,
+        /// Directory the tables are written to.
+        #[arg(long, short)]
+        out: PathBuf,
+        /// Ingame ticks between two player-tick samples.
+        #[arg(long, default_value_t = 16, conflicts_with = "no_ticks")]
+        tick_interval: i32,
+        /// Don't write the player-tick table.
+        #[arg(long)]
+        no_ticks: bool,
+    },
+}
+
+#[derive(Args)]
+struct TableArgs {
+    demo: PathBuf,
+    #[arg(long, short, value_enum, default_value_t = Format::Table)]
+    format: Format,
+}
+
+enum Failure {
+    Io(io::Error),
+    Demo(ParserError),
+    Usage(String),
+}
+
+impl Failure {
+    fn exit_code(&self) -> u8 {
+        match self {
+            | Failure::Io(_) => 1,
+            | Failure::Usage(_) => 2,
+            | Failure::Demo(e) => demo_exit_code(e),
+        }
+    }
+}
+
+fn demo_exit_code(e: &ParserError) -> u8 {
+    match e {
+        | ParserError::UnexpectedEndOfDemo => 3,
+        | ParserError::InvalidFileType | ParserError::GitLfsPointer => 4,
+    }
+}
+
+fn describe(e: &ParserError) -> &'static str {
+    match e {
+        | ParserError::UnexpectedEndOfDemo => "demo is truncated or corrupt",
+        | ParserError::InvalidFileType => "not a demo file",
+        | ParserError::GitLfsPointer => "file is a Git LFS pointer, run `git lfs pull`",
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | Failure::Io(e) => write!(f, "{e}"),
+            | Failure::Demo(e) => f.write_str(describe(e)),
+            | Failure::Usage(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl From<io::Error> for Failure {
+    fn from(e: io::Error) -> Self {
+        Failure::Io(e)
+    }
+}
+
+impl From<ParserError> for Failure {
+    fn from(e: ParserError) -> Self {
+        Failure::Demo(e)
+    }
+}
+
+/// Result of a command. `Ok(Some(_))` means the demo ended early but the
+/// partial output was written.
+type Outcome = Result<Option<ParserError>, Failure>;
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    // Broken demos make the parser panic internally before it turns the
+    // panic into a `ParserError`. Report panics in one line instead of the
+    // default message and backtrace hint.
+    std::panic::set_hook(Box::new(|info| {
+        let location = info
+            .location()
+            .map(|l| format!(" at {}:{}", l.file(), l.line()))
+            .unwrap_or_default();
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("panic");
+        eprintln!("cs-demo: internal error{location}: {message}");
+    }));
+
+    let res = match cli.command {
+        | Command::Info { demo, json } => info(&demo, json),
+        | Command::Events {
+            demo,
+            types,
+            from_tick,
+            to_tick,
+            output,
+        } => events(&demo, &types, from_tick, to_tick, output.as_deref()),
+        | Command::Kills(args) => table(&args, |dataset, _| dataset.kills.clone()),
+        | Command::Rounds(args) => table(&args, |dataset, _| dataset.rounds.clone()),
+        | Command::Scoreboard(args) => table(&args, |dataset, state| dataset.scoreboard(state)),
+        | Command::Entities { demo, class, json } => entities(&demo, class.as_deref(), json),
+        | Command::Export {
+            input,
+            out,
+            tick_interval,
+            no_ticks,
+        } => export(&input, &out, (!no_ticks).then_some(tick_interval)),
+    };
+
+    match res {
+        | Ok(None) => ExitCode::SUCCESS,
+        | Ok(Some(e)) => {
+            eprintln!("cs-demo: {}, output is incomplete", describe(&e));
+            ExitCode::from(demo_exit_code(&e))
+        },
+        | Err(e) => {
+            eprintln!("cs-demo: {e}");
+            ExitCode::from(e.exit_code())
+        },
+    }
+}
+
+fn open(path: &Path) -> Result<Parser<File>, Failure> {
+    let file = File::open(path)
+        .map_err(|e| Failure::Io(io::Error::new(e.kind(), format!("{}: {e}", path.display()))))?;
+    Ok(Parser::new(file))
+}
+
+/// Parses frames until the demo ends or `stop` returns true. A truncated
+/// demo is returned as `Ok(Some(_))` so the caller can still write what was
+/// parsed; any other parser error is a failure.
+fn parse_until<R: Read>(parser: &mut Parser<R>, stop: impl Fn(&Parser<R>) -> bool) -> Outcome {
+    parser.parse_header()?;
+    while !stop(parser) {
+        match parser.parse_next_frame() {
+            | Ok(true) => {},
+            | Ok(false) => break,
+            | Err(ParserError::UnexpectedEndOfDemo) => {
+                return Ok(Some(ParserError::UnexpectedEndOfDemo));
+            },
+            | Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(None)
+}
+
+fn stdout() -> BufWriter<io::StdoutLock<'static>> {
+    BufWriter::new(io::stdout().lock())
+}
+
+#[derive(Serialize)]
+struct PlayerInfo {
+    name: String,
+    steam_id64: u64,
+    team: &'static str,
+    is_bot: bool,
+}
+
+#[derive(Serialize)]
+struct Info {
+    filestamp: String,
+    protocol: i32,
+    network_protocol: i32,
+    server_name: String,
+    client_name: String,
+    map_name: String,
+    build: Option<u32>,
+    playback_ticks: i32,
+    playback_frames: i32,
+    duration_secs: f64,
+    tick_rate: f64,
+    players: Vec<PlayerInfo>,
+}
+
+fn team_name(team: Team) -> &'static str {
+    match team {
+        | Team::Unassigned => "unassigned",
+        | Team::Spectators => "spectator",
+        | Team::Terrorists => "T",
+        | Team::CounterTerrorists => "CT",
+    }
+}
+
+fn info(path: &Path, json: bool) -> Outcome {
+    let mut parser = open(path)?;
+    let partial = parse_until(&mut parser, |_| false)?;
+    let header = parser.header().unwrap_or_default();
+    let state = parser.game_state();
+
+    let mut players: Vec<PlayerInfo> = state
+        .participants()
+        .all()
+        .into_iter()
+        .filter(|p| matches!(p.team, Team::Terrorists | Team::CounterTerrorists))
+        .map(|p| PlayerInfo {
+            name: p.name.clone(),
+            steam_id64: p.steam_id64,
+            team: team_name(p.team),
+            is_bot: p.is_bot,
+        })
+        .collect();
+    players.sort_by(|a, b| a.team.cmp(b.team).then(a.name.cmp(&b.name)));
+
+    // Source 2 headers don't always carry the playback time.
+    let duration_secs = if header.playback_time > 0.0 {
+        header.playback_time as f64
+    } else {
+        parser.current_time().as_secs_f64()
+    };
+    let info = Info {
+        map_name: state
+            .map_name()
+            .map(str::to_string)
+            .unwrap_or(header.map_name),
+        build: state.server_version(),
+        filestamp: header.filestamp,
+        protocol: header.protocol,
+        network_protocol: header.network_protocol,
+        server_name: header.server_name,
+        client_name: header.client_name,
+        playback_ticks: header.playback_ticks,
+        playback_frames: header.playback_frames,
+        duration_secs,
+        tick_rate: parser.tick_rate(),
+        players,
+    };
+
+    let mut out = stdout();
+    if json {
+        serde_json::to_writer_pretty(&mut out, &info).map_err(io::Error::from)?;
+        writeln!(out)?;
+    } else {
+        writeln!(out, "Demo:      {} ({})", path.display(), info.filestamp)?;
+        writeln!(out, "Map:       {}", info.map_name)?;
+        writeln!(out, "Server:    {}", info.server_name)?;
+        writeln!(out, "Client:    {}", info.client_name)?;
+        match info.build {
+            | Some(build) => writeln!(out, "Build:     {build}")?,
+            | None => writeln!(out, "Build:     unknown")?,
+        }
+        writeln!(
+            out,
+            "Protocol:  {} (network {})",
+            info.protocol, info.network_protocol
+        )?;
+        writeln!(
+            out,
+            "Duration:  {:.0}s, {} ticks, {} frames",
+            info.duration_secs, info.playback_ticks, info.playback_frames
+        )?;
+        writeln!(out, "Tick rate: {:.1}", info.tick_rate)?;
+        writeln!(out, "Players:")?;
+        for p in &info.players {
+            let bot = if p.is_bot { " (bot)" } else { "" };
+            writeln!(out, "  {:<2} {:<17} {}{bot}", p.team, p.steam_id64, p.name)?;
+        }
+    }
+    out.flush()?;
+    Ok(partial)
+}
+
+fn events(
+    path: &Path,
+    types: &[String],
+    from_tick: Option<i32>,
+    to_tick: Option<i32>,
+    output: Option<&Path>,
+) -> Outcome {
+    let mut parser = open(path)?;
+    let out: Box<dyn Write + Send> = match output {
+        | Some(p) => Box::new(File::create(p)?),
+        | None => Box::new(io::stdout()),
+    };
+
+    let known = RefCell::new(Vec::new());
+    let stream = EventStreamWriter::register(&mut parser, BufWriter::new(out), |name| {
+        known.borrow_mut().push(name.to_string());
+        types.is_empty() || types.iter().any(|t| t.eq_ignore_ascii_case(name))
+    });
+    let known = known.into_inner();
+    if let Some(t) = types
+        .iter()
+        .find(|t| !known.iter().any(|k| k.eq_ignore_ascii_case(t)))
+    {
+        return Err(Failure::Usage(format!(
+            "unknown event type `{t}`, expected one of: {}",
+            known.join(", ")
+        )));
+    }
+    stream
+        .lock()
+        .unwrap()
+        .set_tick_range(from_tick.unwrap_or(i32::MIN)..=to_tick.unwrap_or(i32::MAX));
+
+    let partial = parse_until(&mut parser, |p| {
+        to_tick.is_some_and(|t| p.game_state().ingame_tick() > t)
+    })?;
+
+    let mut stream = stream.lock().unwrap();
+    if let Some(e) = stream.take_error() {
+        return Err(e.into());
+    }
+    stream.flush()?;
+    Ok(partial)
+}
+
+fn table<T: Serialize>(
+    args: &TableArgs,
+    rows: impl FnOnce(&Dataset, &GameState) -> Vec<T>,
+) -> Outcome {
+    let mut parser = open(&args.demo)?;
+    let config = DatasetConfig {
+        tick_interval: None,
+        ..Default::default()
+    };
+    let collector = DatasetCollector::register(&mut parser, "", config);
+    let partial = parse_until(&mut parser, |_| false)?;
+
+    let dataset = collector.lock().unwrap().take_dataset();
+    let rows = rows(&dataset, parser.game_state());
+    let mut out = stdout();
+    output::write_rows(&mut out, &rows, args.format)?;
+    out.flush()?;
+    Ok(partial)
+}
+
+#[derive(Serialize)]
+struct ClassDump {
+    id: i32,
+    name: String,
+    /// Data table (Source 1) or serializer (Source 2) of the class.
+    table: Option<String>,
+    properties: Vec<PropertyDump>,
+}
+
+#[derive(Serialize)]
+struct PropertyDump {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
+
+fn entities(path: &Path, class: Option<&str>, json: bool) -> Outcome {
+    let mut parser = open(path)?;
+    // Class definitions arrive with the signon data, stop once we have them.
+    let partial = parse_until(&mut parser, |p| {
+        !p.server_classes().is_empty() || !p.source2_classes().is_empty()
+    })?;
+
+    let mut classes: Vec<ClassDump> = parser
+        .server_classes()
+        .iter()
+        .map(|c| ClassDump {
+            id: c.id,
+            name: c.name.clone(),
+            table: Some(c.data_table_name.clone()),
+            properties: c
+                .flattened_props
+                .iter()
+                .map(|p| PropertyDump {
+                    name: p.name.clone(),
+                    kind: format!("{:?}", p.prop.raw_type),
+                })
+                .collect(),
+        })
+        .collect();
+    classes.extend(parser.source2_classes().into_iter().map(|c| {
+        ClassDump {
+            id: c.class_id,
+            name: c.name.to_string(),
+            table: c
+                .serializer
+                .as_ref()
+                .map(|s| format!("{}/{}", s.name, s.version)),
+            properties: c
+                .serializer
+                .iter()
+                .flat_map(|s| &s.fields)
+                .map(|f| PropertyDump {
+                    name: f.var_name.clone(),
+                    kind: f.var_type.clone(),
+                })
+                .collect(),
+        }
+    }));
+    if let Some(filter) = class {
+        classes.retain(|c| c.name.contains(filter));
+    }
+
+    let mut out = stdout();
+    if json {
+        serde_json::to_writer_pretty(&mut out, &classes).map_err(io::Error::from)?;
+        writeln!(out)?;
+    } else {
+        for c in &classes {
+            match &c.table {
+                | Some(table) => writeln!(out, "{:>4} {} ({table})", c.id, c.name)?,
+                | None => writeln!(out, "{:>4} {}", c.id, c.name)?,
+            }
+            for p in &c.properties {
+                writeln!(out, "       {} : {}", p.name, p.kind)?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(partial)
+}
+
+#[cfg(feature = "parquet")]
+fn export(input: &Path, out_dir: &Path, tick_interval: Option<i32>) -> Outcome {
+    use cs_demo_parser::export::arrow;
+
+    let config = DatasetConfig {
+        tick_interval,
+        ..Default::default()
+    };
+    let results = if input.is_dir() {
+        arrow::export_dir(input, out_dir, config).map_err(export_failure)?
+    } else {
+        vec![(
+            input.to_path_buf(),
+            arrow::export_demo(input, out_dir, config),
+        )]
+    };
+
+    let mut failure = None;
+    for (demo, res) in results {
+        match res {
+            | Ok(files) => eprintln!("{}: wrote {} tables", demo.display(), files.len()),
+            | Err(e) => {
+                eprintln!("{}: {e}", demo.display());
+                failure.get_or_insert(e);
+            },
+        }
+    }
+    match failure {
+        | None => Ok(None),
+        | Some(e) => Err(export_failure(e)),
+    }
+}
+
+#[cfg(feature = "parquet")]
+fn export_failure(e: cs_demo_parser::export::arrow::ExportError) -> Failure {
+    use cs_demo_parser::export::arrow::ExportError;
+
+    match e {
+        | ExportError::Io(e) => Failure::Io(e),
+        | ExportError::Parser(e) => Failure::Demo(e),
+        | e => Failure::Io(io::Error::other(e.to_string())),
+    }
+}
+
+#[cfg(not(feature = "parquet"))]
+fn export(_input: &Path, _out_dir: &Path, _tick_interval: Option<i32>) -> Outcome {
+    Err(Failure::Usage(
+        "export needs the `parquet` feature, rebuild with `--features cli,parquet`".to_string(),
+    ))
+}
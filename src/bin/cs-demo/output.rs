@@ -0,0 +1,75 @@
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Aligned columns for reading in a terminal.
+    Table,
+    Csv,
+    /// A JSON array of objects.
+    Json,
+}
+
+/// Writes `rows` to `out` in the requested format.
+pub fn write_rows<T: Serialize, W: Write>(out: W, rows: &[T], format: Format) -> io::Result<()> {
+    match format {
+        | Format::Csv => write_csv(out, rows),
+        | Format::Json => {
+            let mut out = out;
+            serde_json::to_writer_pretty(&mut out, rows)?;
+            writeln!(out)
+        },
+        | Format::Table => {
+            let mut buf = Vec::new();
+            write_csv(&mut buf, rows)?;
+            write_table(out, &buf)
+        },
+    }
+}
+
+fn write_csv<T: Serialize, W: Write>(out: W, rows: &[T]) -> io::Result<()> {
+    let mut writer = csv::Writer::from_writer(out);
+    for row in rows {
+        writer.serialize(row).map_err(io::Error::other)?;
+    }
+    writer.flush()
+}
+
+/// Re-reads CSV written by [`write_csv`] and pads every column to its widest
+/// cell.
+fn write_table<W: Write>(mut out: W, csv: &[u8]) -> io::Result<()> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .from_reader(csv);
+    let mut records = Vec::new();
+    for record in reader.records() {
+        records.push(record.map_err(io::Error::other)?);
+    }
+
+    let mut widths: Vec<usize> = Vec::new();
+    for record in &records {
+        for (i, cell) in record.iter().enumerate() {
+            let len = cell.chars().count();
+            match widths.get_mut(i) {
+                | Some(w) => *w = (*w).max(len),
+                | None => widths.push(len),
+            }
+        }
+    }
+
+    for record in &records {
+        let mut line = String::new();
+        for (i, cell) in record.iter().enumerate() {
+            if i > 0 {
+                line.push_str("  ");
+            }
+            line.push_str(cell);
+            let pad = widths[i] - cell.chars().count();
+            line.extend(std::iter::repeat_n(' ', pad));
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
@@ -46,16 +46,40 @@ impl<R: Read> BitReader<R> {
 
     /// Skips the specified number of bits.
     pub fn skip_bits(&mut self, bits: u32) {
-        let bytes = bits / 8;
-        let rem = bits % 8;
-        for _ in 0..bytes {
-            self.read_int(8);
-        }
-        for _ in 0..rem {
-            self.read_bit();
+        self.inner.skip(bits).unwrap();
+    }
+
+    /// Skips the specified number of bytes. Byte aligned readers discard
+    /// them in bulk.
+    pub fn skip_bytes(&mut self, bytes: u64) {
+        match self.inner.reader() {
+            | Some(reader) => {
+                let skipped = std::io::copy(&mut reader.take(bytes), &mut std::io::sink()).unwrap();
+                if skipped < bytes {
+                    panic!("unexpected end of input");
+                }
+            },
+            | None => {
+                for _ in 0..bytes {
+                    self.read_int(8);
+                }
+            },
         }
     }
 
+    /// Reads `len` whole bytes. Byte aligned readers copy them in bulk
+    /// instead of bit by bit.
+    pub fn read_bytes(&mut self, len: usize) -> Vec<u8> {
+        let mut buf = vec![0; len];
+        self.read_bytes_into(&mut buf);
+        buf
+    }
+
+    /// Fills `buf` with whole bytes, see [`read_bytes`](Self::read_bytes).
+    pub fn read_bytes_into(&mut self, buf: &mut [u8]) {
+        self.inner.read_bytes(buf).unwrap();
+    }
+
     pub fn read_int(&mut self, bits: u32) -> u32 {
         self.inner.read(bits).unwrap()
     }
@@ -155,10 +179,7 @@ impl<R: Read> BitReader<R> {
     }
 
     pub fn read_c_string(&mut self, length: usize) -> String {
-        let mut buf = Vec::with_capacity(length);
-        for _ in 0..length {
-            buf.push(self.read_single_byte());
-        }
+        let buf = self.read_bytes(length);
         let end = buf.iter().position(|&b| b == 0).unwrap_or(length);
         String::from_utf8(buf[..end].to_vec()).unwrap_or_default()
     }
@@ -1,20 +1,452 @@
-use super::Player;
+use std::collections::HashMap;
+
+use super::{Player, PlayerRef};
+use crate::events::Bombsite;
 use crate::sendtables::entity::Vector;
-use crate::sendtables2::Entity;
+use crate::sendtables2::{Entity, EntityHandle};
+
+/// Seconds a defuse takes with a kit.
+pub const DEFUSE_TIME_WITH_KIT: f32 = 5.0;
+
+/// Seconds a defuse takes without a kit.
+pub const DEFUSE_TIME_WITHOUT_KIT: f32 = 10.0;
+
+/// Where the bomb is in its life during a round.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BombState {
+    #[default]
+    Carried,
+    Dropped,
+    Planting,
+    Planted,
+    Defusing,
+    Defused,
+    Exploded,
+}
+
+/// A player carrying the bomb from `start_tick` to `end_tick`.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BombCarrier {
+    pub player: PlayerRef,
+    pub start_tick: i32,
+    /// `None` while the player still carries the bomb.
+    pub end_tick: Option<i32>,
+}
+
+/// A drop or pickup of the bomb.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BombMove {
+    pub tick: i32,
+    pub player: Option<PlayerRef>,
+    pub position: Vector,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlantAttempt {
+    pub player: Option<PlayerRef>,
+    pub site: Bombsite,
+    pub start_tick: i32,
+    /// Tick of the plant or abort, `None` while planting.
+    pub end_tick: Option<i32>,
+    pub planted: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefuseAttempt {
+    pub player: Option<PlayerRef>,
+    pub has_kit: bool,
+    pub start_tick: i32,
+    /// Tick of the defuse or abort, `None` while defusing.
+    pub end_tick: Option<i32>,
+    pub defused: bool,
+    /// Seconds left on the bomb timer when the attempt started.
+    pub time_remaining: Option<f32>,
+}
+
+impl DefuseAttempt {
+    /// Whether the attempt could have finished before the bomb blew up.
+    pub fn in_time(&self) -> Option<bool> {
+        let needed = if self.has_kit {
+            DEFUSE_TIME_WITH_KIT
+        } else {
+            DEFUSE_TIME_WITHOUT_KIT
+        };
+        self.time_remaining.map(|left| left >= needed)
+    }
+}
+
+/// The planted bomb, from the `CPlantedC4` entity where available.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlantedBomb {
+    pub entity_id: Option<i32>,
+    pub tick: i32,
+    pub site: Bombsite,
+    pub planter: Option<PlayerRef>,
+    pub position: Vector,
+    /// Game time in seconds the bomb blows up at, `m_flC4Blow`, or
+    /// `mp_c4timer` after the plant without the entity.
+    pub blow_time: Option<f32>,
+    /// Seconds on the timer when the bomb was planted.
+    pub timer: Option<f32>,
+    /// Game time the running defuse completes at, `m_flDefuseCountDown`.
+    pub defuse_countdown: Option<f32>,
+    /// The player defusing right now, `m_hBombDefuser`.
+    pub defuser: Option<PlayerRef>,
+    /// `m_bBombDefused`, or a `bomb_defused` event.
+    pub defused: bool,
+    pub defuse_tick: Option<i32>,
+    /// Seconds left on the timer when the bomb was defused.
+    pub time_remaining_at_defuse: Option<f32>,
+    pub explode_tick: Option<i32>,
+}
+
+/// Everything that happened to the bomb during one round.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BombRound {
+    pub round: i32,
+    pub carriers: Vec<BombCarrier>,
+    pub drops: Vec<BombMove>,
+    pub pickups: Vec<BombMove>,
+    pub plants: Vec<PlantAttempt>,
+    pub defuses: Vec<DefuseAttempt>,
+    pub planted: Option<PlantedBomb>,
+}
 
-#[derive(Default)]
+impl BombRound {
+    pub fn aborted_plants(&self) -> usize {
+        self.plants
+            .iter()
+            .filter(|p| p.end_tick.is_some() && !p.planted)
+            .count()
+    }
+
+    pub fn aborted_defuses(&self) -> usize {
+        self.defuses
+            .iter()
+            .filter(|d| d.end_tick.is_some() && !d.defused)
+            .count()
+    }
+}
+
+/// The bomb's current state and its history per round. Fed by
+/// [`crate::game_state::GameState`] from bomb events and entities.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bomb {
-    pub entity: Option<Entity>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub entity: Option<EntityHandle>,
     pub last_on_ground_position: Vector,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub carrier: Option<Player>,
+    pub state: BombState,
+    /// Sites of the map's bomb target entities by entity index.
+    pub sites: HashMap<i32, Bombsite>,
+    rounds: Vec<BombRound>,
 }
 
 impl Bomb {
     pub fn position(&self) -> Vector {
         if let Some(carrier) = &self.carrier {
             carrier.position()
+        } else if let Some(planted) = self.planted() {
+            planted.position.clone()
         } else {
             self.last_on_ground_position.clone()
         }
     }
+
+    pub fn rounds(&self) -> &[BombRound] {
+        &self.rounds
+    }
+
+    /// The bomb planted in the current round.
+    pub fn planted(&self) -> Option<&PlantedBomb> {
+        self.rounds.last()?.planted.as_ref()
+    }
+
+    /// The site of the bomb target entity with index `index`, as sent in
+    /// the `site` key of bomb events.
+    pub fn site(&self, index: i32) -> Bombsite {
+        self.sites.get(&index).copied().unwrap_or_default()
+    }
+
+    /// Seconds until the planted bomb blows up, at game time `now`.
+    pub fn time_remaining(&self, now: f32) -> Option<f32> {
+        let blow_time = self.planted()?.blow_time?;
+        Some((blow_time - now).max(0.0))
+    }
+
+    fn round_mut(&mut self) -> &mut BombRound {
+        if self.rounds.is_empty() {
+            self.rounds.push(BombRound::default());
+        }
+        let last = self.rounds.len() - 1;
+        &mut self.rounds[last]
+    }
+
+    fn end_carry(&mut self, tick: i32) {
+        if let Some(c) = self
+            .round_mut()
+            .carriers
+            .last_mut()
+            .filter(|c| c.end_tick.is_none())
+        {
+            c.end_tick = Some(tick);
+        }
+        self.carrier = None;
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.rounds.clear();
+        self.carrier = None;
+        self.state = BombState::default();
+    }
+
+    pub(crate) fn round_started(&mut self, round: i32) {
+        self.rounds.push(BombRound {
+            round,
+            ..Default::default()
+        });
+        self.carrier = None;
+        self.state = BombState::Carried;
+    }
+
+    /// Reads a `CBombTarget` entity.
+    pub(crate) fn bomb_target_updated(&mut self, entity: &Entity) {
+        let site = match entity.property_value("m_bIsBombSiteB") {
+            | Some(b) if b.bool_val() => Bombsite::B,
+            | Some(_) => Bombsite::A,
+            | None => return,
+        };
+        self.sites.insert(entity.index, site);
+    }
+
+    pub(crate) fn picked_up(&mut self, tick: i32, player: &Player) {
+        self.end_carry(tick);
+        let round = self.round_mut();
+        round.carriers.push(BombCarrier {
+            player: PlayerRef::from(player),
+            start_tick: tick,
+            end_tick: None,
+        });
+        round.pickups.push(BombMove {
+            tick,
+            player: Some(PlayerRef::from(player)),
+            position: player.position(),
+        });
+        self.carrier = Some(player.clone());
+        self.state = BombState::Carried;
+    }
+
+    pub(crate) fn dropped(&mut self, tick: i32, player: Option<&Player>) {
+        let player = player.or(self.carrier.as_ref()).cloned();
+        if let Some(p) = &player {
+            self.last_on_ground_position = p.position();
+        }
+        self.end_carry(tick);
+        let position = self.last_on_ground_position.clone();
+        self.round_mut().drops.push(BombMove {
+            tick,
+            player: player.as_ref().map(PlayerRef::from),
+            position,
+        });
+        self.state = BombState::Dropped;
+    }
+
+    pub(crate) fn plant_started(&mut self, tick: i32, player: Option<&Player>, site: Bombsite) {
+        self.round_mut().plants.push(PlantAttempt {
+            player: player.map(PlayerRef::from),
+            site,
+            start_tick: tick,
+            ..Default::default()
+        });
+        self.state = BombState::Planting;
+    }
+
+    pub(crate) fn plant_aborted(&mut self, tick: i32) {
+        if let Some(p) = self.open_plant() {
+            p.end_tick = Some(tick);
+        }
+        self.state = BombState::Carried;
+    }
+
+    fn open_plant(&mut self) -> Option<&mut PlantAttempt> {
+        self.round_mut()
+            .plants
+            .last_mut()
+            .filter(|p| p.end_tick.is_none())
+    }
+
+    /// `now` is the game time in seconds and `bomb_time` the length of the
+    /// timer from `mp_c4timer`, used until the planted entity is known.
+    pub(crate) fn planted_by(
+        &mut self,
+        tick: i32,
+        now: f32,
+        player: Option<&Player>,
+        site: Bombsite,
+        bomb_time: Option<f32>,
+    ) {
+        let position = player
+            .or(self.carrier.as_ref())
+            .map(Player::position)
+            .unwrap_or_else(|| self.last_on_ground_position.clone());
+        let site = match self.open_plant() {
+            | Some(p) => {
+                p.end_tick = Some(tick);
+                p.planted = true;
+                if site == Bombsite::Unknown {
+                    p.site
+                } else {
+                    site
+                }
+            },
+            | None => site,
+        };
+        self.end_carry(tick);
+        self.last_on_ground_position = position.clone();
+        let round = self.round_mut();
+        match &mut round.planted {
+            // The entity arrived first.
+            | Some(planted) => {
+                planted.tick = tick;
+                planted.planter = player.map(PlayerRef::from);
+                if planted.site == Bombsite::Unknown {
+                    planted.site = site;
+                }
+            },
+            | None => {
+                round.planted = Some(PlantedBomb {
+                    tick,
+                    site,
+                    planter: player.map(PlayerRef::from),
+                    position,
+                    blow_time: bomb_time.map(|t| now + t),
+                    timer: bomb_time,
+                    ..Default::default()
+                })
+            },
+        }
+        self.state = BombState::Planted;
+    }
+
+    /// Reads the `CPlantedC4` entity. `players` are the players by entity
+    /// index, `m_hBombDefuser` is resolved through their pawns first.
+    pub(crate) fn planted_entity_updated(
+        &mut self,
+        tick: i32,
+        now: f32,
+        entity: &Entity,
+        players: &HashMap<i32, Player>,
+    ) {
+        let float = |name: &str| entity.property_value(name).map(|v| v.float_val);
+        let site = match entity.property_value("m_nBombSite").map(|v| v.int_val) {
+            | Some(0) => Bombsite::A,
+            | Some(1) => Bombsite::B,
+            | _ => Bombsite::Unknown,
+        };
+        let defuser = entity
+            .property_value("m_hBombDefuser")
+            .and_then(|v| EntityHandle::from_raw(v.int_val as u32))
+            .and_then(|h| crate::game_state::player_by_handle(players, h))
+            .map(PlayerRef::from);
+        let defused = entity
+            .property_value("m_bBombDefused")
+            .is_some_and(|v| v.bool_val());
+        let position = entity.position();
+
+        let planted = self.round_mut().planted.get_or_insert_with(|| PlantedBomb {
+            tick,
+            ..Default::default()
+        });
+        if planted.entity_id.is_none()
+            && let Some(blow_time) = float("m_flC4Blow")
+        {
+            planted.timer = Some(blow_time - now);
+        }
+        planted.entity_id = Some(entity.index);
+        if site != Bombsite::Unknown {
+            planted.site = site;
+        }
+        if let Some(position) = position {
+            planted.position = position;
+        }
+        if let Some(blow_time) = float("m_flC4Blow") {
+            planted.blow_time = Some(blow_time);
+        }
+        planted.defuse_countdown = float("m_flDefuseCountDown").filter(|_| defuser.is_some());
+        planted.defuser = defuser;
+        if defused && !planted.defused {
+            planted.defused = true;
+            planted.defuse_tick.get_or_insert(tick);
+            if let Some(blow_time) = planted.blow_time {
+                planted
+                    .time_remaining_at_defuse
+                    .get_or_insert(blow_time - now);
+            }
+        }
+    }
+
+    pub(crate) fn defuse_started(
+        &mut self,
+        tick: i32,
+        now: f32,
+        player: Option<&Player>,
+        has_kit: bool,
+    ) {
+        let time_remaining = self.time_remaining(now);
+        self.round_mut().defuses.push(DefuseAttempt {
+            player: player.map(PlayerRef::from),
+            has_kit,
+            start_tick: tick,
+            time_remaining,
+            ..Default::default()
+        });
+        self.state = BombState::Defusing;
+    }
+
+    fn open_defuse(&mut self) -> Option<&mut DefuseAttempt> {
+        self.round_mut()
+            .defuses
+            .last_mut()
+            .filter(|d| d.end_tick.is_none())
+    }
+
+    pub(crate) fn defuse_aborted(&mut self, tick: i32) {
+        if let Some(d) = self.open_defuse() {
+            d.end_tick = Some(tick);
+        }
+        self.state = BombState::Planted;
+    }
+
+    pub(crate) fn defused(&mut self, tick: i32, now: f32) {
+        if let Some(d) = self.open_defuse() {
+            d.end_tick = Some(tick);
+            d.defused = true;
+        }
+        let time_remaining = self.time_remaining(now);
+        if let Some(planted) = &mut self.round_mut().planted {
+            planted.defused = true;
+            planted.defuse_tick = Some(tick);
+            planted.time_remaining_at_defuse = planted.time_remaining_at_defuse.or(time_remaining);
+        }
+        self.state = BombState::Defused;
+    }
+
+    pub(crate) fn exploded(&mut self, tick: i32) {
+        if let Some(d) = self.open_defuse() {
+            d.end_tick = Some(tick);
+        }
+        if let Some(planted) = &mut self.round_mut().planted {
+            planted.explode_tick = Some(tick);
+        }
+        self.state = BombState::Exploded;
+    }
 }
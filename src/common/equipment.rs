@@ -1,6 +1,7 @@
 use crate::sendtables::entity::{Entity, Vector};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum EquipmentClass {
     #[default]
@@ -14,6 +15,7 @@ pub enum EquipmentClass {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum EquipmentType {
     #[default]
@@ -177,8 +179,10 @@ impl EquipmentClass {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Equipment {
     pub equipment_type: EquipmentType,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub entity: Option<Entity>,
     pub original_string: String,
     pub unique_id: i64,
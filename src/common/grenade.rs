@@ -1,24 +1,31 @@
-use super::{Equipment, Player};
+use super::{Equipment, EquipmentType, Player};
 use crate::sendtables::entity::Vector;
-use crate::sendtables2::Entity;
+use crate::sendtables2::EntityHandle;
 use std::time::Duration;
 
-#[derive(Default)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TrajectoryEntry {
     pub position: Vector,
     pub frame_id: i32,
     pub time: Duration,
 }
 
-#[derive(Default)]
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GrenadeProjectile {
-    pub entity: Option<Entity>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub entity: Option<EntityHandle>,
     pub weapon_instance: Option<Equipment>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub thrower: Option<Player>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub owner: Option<Player>,
     pub trajectory: Vec<Vector>,
     pub trajectory2: Vec<TrajectoryEntry>,
     pub unique_id: i64,
+    /// Number of times the projectile bounced off the world so far.
+    pub bounces: i32,
 }
 
 use std::sync::atomic::{AtomicI64, Ordering};
@@ -49,4 +56,25 @@ impl GrenadeProjectile {
     pub fn last_position(&self) -> Option<&Vector> {
         self.trajectory.last()
     }
+
+    pub fn grenade_type(&self) -> EquipmentType {
+        self.weapon_instance
+            .as_ref()
+            .map(|w| w.equipment_type)
+            .unwrap_or_default()
+    }
+}
+
+/// Type of the grenade a projectile entity of the class `class_name` belongs
+/// to. Molotovs and incendiary grenades share a class and are reported as
+/// molotovs, Source 1 HE grenades and flashbangs as [`EquipmentType::Unknown`].
+pub fn projectile_type(class_name: &str) -> EquipmentType {
+    match class_name {
+        | "CSmokeGrenadeProjectile" => EquipmentType::Smoke,
+        | "CFlashbangProjectile" => EquipmentType::Flash,
+        | "CHEGrenadeProjectile" => EquipmentType::He,
+        | "CMolotovProjectile" => EquipmentType::Molotov,
+        | "CDecoyProjectile" => EquipmentType::Decoy,
+        | _ => EquipmentType::Unknown,
+    }
 }
@@ -1,8 +1,14 @@
+use std::collections::HashMap;
+
+use super::Player;
 use crate::sendtables::entity::{Entity, Vector};
+use crate::sendtables2::{Entity as Source2Entity, EntityHandle};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum HostageState {
+    #[default]
     Idle = 0,
     BeingUntied = 1,
     GettingPickedUp = 2,
@@ -13,14 +19,120 @@ pub enum HostageState {
     Dead = 7,
 }
 
-#[derive(Default)]
+impl From<i32> for HostageState {
+    /// Maps `m_nHostageState`, unknown values are `Idle`.
+    fn from(v: i32) -> Self {
+        match v {
+            | 1 => HostageState::BeingUntied,
+            | 2 => HostageState::GettingPickedUp,
+            | 3 => HostageState::BeingCarried,
+            | 4 => HostageState::FollowingPlayer,
+            | 5 => HostageState::GettingDropped,
+            | 6 => HostageState::Rescued,
+            | 7 => HostageState::Dead,
+            | _ => HostageState::Idle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Hostage {
+    #[deprecated(note = "never set, Source 2 hostages have their `handle`")]
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub entity: Option<Entity>,
+    /// The Source 2 `CHostage` entity, look it up with
+    /// [`GameState::entity`](crate::game_state::GameState::entity). `None`
+    /// for hostages only game events tell about, e.g. in Source 1 demos.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub handle: Option<EntityHandle>,
+    pub entity_id: i32,
+    /// `m_nHostageState`.
+    pub state: HostageState,
+    /// The player the hostage follows or is carried by, `m_leader`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
+    pub leader: Option<Player>,
+    pub last_position: Vector,
+    pub health: i32,
 }
 
 impl Hostage {
     pub fn position(&self) -> Vector {
-        let _ = &self.entity;
-        Vector::default()
+        self.last_position.clone()
+    }
+
+    pub fn is_rescued(&self) -> bool {
+        self.state == HostageState::Rescued
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.state == HostageState::Dead
+    }
+
+    /// Reads a Source 2 `CHostage` entity. `players` are the players by
+    /// entity index, `m_leader` is resolved through their pawns first.
+    pub(crate) fn update(&mut self, entity: &Source2Entity, players: &HashMap<i32, Player>) {
+        let state = entity
+            .property_value("m_nHostageState")
+            .map_or(self.state, |v| HostageState::from(v.int_val));
+        let leader = entity
+            .property_value("m_leader")
+            .and_then(|v| EntityHandle::from_raw(v.int_val as u32))
+            .and_then(|h| crate::game_state::player_by_handle(players, h))
+            .cloned();
+        self.entity_id = entity.index;
+        self.handle = Some(entity.handle());
+        self.state = state;
+        self.leader = leader;
+        if let Some(position) = entity.position() {
+            self.last_position = position;
+        }
+        if let Some(health) = entity.property_value("m_iHealth") {
+            self.health = health.int_val;
+        }
+    }
+
+    /// The hostage's state and the user id of its leader, to tell whether
+    /// an update changed them.
+    pub fn status(&self) -> (HostageState, Option<i32>) {
+        (self.state, self.leader.as_ref().map(|p| p.user_id))
+    }
+}
+
+/// A hostage rescue zone, an axis-aligned box in world coordinates.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RescueZone {
+    pub entity_id: i32,
+    pub min: Vector,
+    pub max: Vector,
+}
+
+impl RescueZone {
+    /// Reads a `CHostageRescueZone` entity from its position and
+    /// `m_Collision` bounds. `None` without them.
+    pub fn of(entity: &Source2Entity) -> Option<Self> {
+        let origin = entity.position()?;
+        let mins = entity.property_value("m_Collision.m_vecMins")?.vector_val;
+        let maxs = entity.property_value("m_Collision.m_vecMaxs")?.vector_val;
+        Some(Self {
+            entity_id: entity.index,
+            min: Vector {
+                x: origin.x + mins.x,
+                y: origin.y + mins.y,
+                z: origin.z + mins.z,
+            },
+            max: Vector {
+                x: origin.x + maxs.x,
+                y: origin.y + maxs.y,
+                z: origin.z + maxs.z,
+            },
+        })
+    }
+
+    pub fn contains(&self, point: &Vector) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x)
+            && (self.min.y..=self.max.y).contains(&point.y)
+            && (self.min.z..=self.max.z).contains(&point.z)
     }
 }
@@ -1,11 +1,28 @@
 use crate::sendtables::entity::Vector;
-use crate::sendtables2::Entity;
+use crate::sendtables2::{Entity, EntityHandle};
+
+/// Most fires an inferno can have.
+pub const MAX_INFERNO_FIRES: usize = 64;
+
+/// One fire of an inferno. Fires that went out keep their position.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fire {
+    pub position: Vector,
+    pub burning: bool,
+}
 
 /// Representation of an active inferno (molotov/incendiary flames).
 #[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Inferno {
-    /// Underlying entity for the inferno if available.
-    pub entity: Option<Entity>,
+    /// The inferno's entity if available, see [`GameState::entity`].
+    ///
+    /// [`GameState::entity`]: crate::game_state::GameState::entity
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub entity: Option<EntityHandle>,
+    /// All fires of the inferno, see [`set_fires`](Self::set_fires).
+    pub fires: Vec<Fire>,
     /// Individual flame origins gathered from the entity properties.
     pub flames: Vec<Vector>,
     /// Cached convex hull around all flames in `flames`.
@@ -28,6 +45,58 @@ impl Inferno {
     pub fn hull(&self) -> &[Vector] {
         &self.hull
     }
+
+    /// Replaces the fires. `flames` becomes the burning ones and the hull
+    /// is recomputed. Returns whether anything changed.
+    pub fn set_fires(&mut self, fires: Vec<Fire>) -> bool {
+        if fires == self.fires {
+            return false;
+        }
+        self.flames = fires
+            .iter()
+            .filter(|f| f.burning)
+            .map(|f| f.position.clone())
+            .collect();
+        self.fires = fires;
+        self.update_hull();
+        true
+    }
+
+    /// Area of the convex hull in square units.
+    pub fn area(&self) -> f64 {
+        polygon_area(&self.hull)
+    }
+
+    /// Reads the fires of a Source 2 inferno from `m_firePositions` and
+    /// `m_bFireIsBurning`. `None` if the entity has no `m_fireCount`.
+    pub fn fires_of(entity: &Entity) -> Option<Vec<Fire>> {
+        let count = entity.property_value("m_fireCount")?.int_val;
+        let fires = (0..count.clamp(0, MAX_INFERNO_FIRES as i32))
+            .map(|i| Fire {
+                position: entity
+                    .property_value(&format!("m_firePositions.{i:04}"))
+                    .map(|v| v.vector_val)
+                    .unwrap_or_default(),
+                burning: entity
+                    .property_value(&format!("m_bFireIsBurning.{i:04}"))
+                    .is_some_and(|v| v.bool_val()),
+            })
+            .collect();
+        Some(fires)
+    }
+}
+
+/// Area of a simple polygon in the xy plane, using the shoelace formula.
+pub fn polygon_area(points: &[Vector]) -> f64 {
+    if points.len() < 3 {
+        return 0.0;
+    }
+    let twice: f64 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum();
+    twice.abs() / 2.0
 }
 
 /// Calculates the convex hull of a set of 2D points using the monotone chain
@@ -1,9 +1,11 @@
 use super::Equipment;
 use crate::constants;
 use crate::sendtables::entity::{Entity, Vector};
+use crate::sendtables2::EntityHandle;
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Team {
     #[default]
@@ -13,7 +15,19 @@ pub enum Team {
     CounterTerrorists = 3,
 }
 
+impl From<u8> for Team {
+    fn from(v: u8) -> Self {
+        match v {
+            | 1 => Team::Spectators,
+            | 2 => Team::Terrorists,
+            | 3 => Team::CounterTerrorists,
+            | _ => Team::Unassigned,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum Color {
     #[default]
@@ -26,6 +40,7 @@ pub enum Color {
 }
 
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Player {
     pub steam_id64: u64,
     pub last_alive_position: Vector,
@@ -34,7 +49,16 @@ pub struct Player {
     pub inventory: HashMap<i32, Equipment>,
     pub ammo_left: [i32; 32],
     pub entity_id: i32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub entity: Option<Entity>,
+    /// The Source 2 pawn, `m_hPlayerPawn` of the player's controller.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub pawn: Option<EntityHandle>,
+    /// `m_szLastPlaceName` of the Source 2 pawn.
+    pub last_place_name: String,
+    /// Values of the Source 2 controller and pawn.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub props: PlayerProps,
     pub flash_duration: f32,
     pub flash_tick: i32,
     pub team: Team,
@@ -47,6 +71,136 @@ pub struct Player {
     pub previous_frame_position: Vector,
 }
 
+/// Values of a Source 2 player's controller and pawn, copied whenever they
+/// change. The accessors of [`Player`] fall back to them when there is no
+/// Source 1 `entity`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerProps {
+    /// `m_pInGameMoneyServices.m_iAccount` of the controller.
+    pub money: i32,
+    /// `m_pInGameMoneyServices.m_iCashSpentThisRound` of the controller.
+    pub money_spent_this_round: i32,
+    pub health: i32,
+    pub armor: i32,
+    pub has_helmet: bool,
+    pub has_defuser: bool,
+    /// Units per second. Pawns don't send their velocity, so it's the
+    /// change of the position since the previous update.
+    pub velocity: Vector,
+    /// Yaw of the view in degrees.
+    pub view_direction_x: f32,
+    /// Pitch of the view in degrees.
+    pub view_direction_y: f32,
+    pub is_scoped: bool,
+    pub is_ducking: bool,
+    pub is_walking: bool,
+    pub is_airborne: bool,
+    /// Tick of the pawn's last position, `None` until it sent one.
+    pub position_tick: Option<i32>,
+}
+
+impl PlayerProps {
+    /// Copies the values `controller` sent.
+    pub(crate) fn update_from_controller(&mut self, controller: &crate::sendtables2::Entity) {
+        let int = |name: &str| controller.property_value(name).map(|v| v.int_val);
+        if let Some(money) = int("m_pInGameMoneyServices.m_iAccount") {
+            self.money = money;
+        }
+        if let Some(spent) = int("m_pInGameMoneyServices.m_iCashSpentThisRound") {
+            self.money_spent_this_round = spent;
+        }
+    }
+
+    /// Copies the values `pawn` sent at `tick`. `last` is the pawn's
+    /// previous position and `position` the new one, if it's alive.
+    pub(crate) fn update_from_pawn(
+        &mut self,
+        pawn: &crate::sendtables2::Entity,
+        tick: i32,
+        tick_rate: f64,
+        last: &Vector,
+        position: Option<&Vector>,
+    ) {
+        let value = |name: &str| pawn.property_value(name);
+        let flag = |name: &str| value(name).map(|v| v.bool_val());
+        if let Some(health) = value("m_iHealth") {
+            self.health = health.int_val;
+        }
+        if let Some(armor) = value("m_ArmorValue") {
+            self.armor = armor.int_val;
+        }
+        if let Some(helmet) = flag("m_pItemServices.m_bHasHelmet") {
+            self.has_helmet = helmet;
+        }
+        if let Some(defuser) = flag("m_pItemServices.m_bHasDefuser") {
+            self.has_defuser = defuser;
+        }
+        if let Some(angles) = value("m_angEyeAngles") {
+            self.view_direction_x = angles.vector_val.y as f32;
+            self.view_direction_y = angles.vector_val.x as f32;
+        }
+        if let Some(scoped) = flag("m_bIsScoped") {
+            self.is_scoped = scoped;
+        }
+        let ducked = flag("m_pMovementServices.m_bDucked");
+        let ducking = flag("m_pMovementServices.m_bDucking");
+        if ducked.is_some() || ducking.is_some() {
+            self.is_ducking = ducked == Some(true) || ducking == Some(true);
+        }
+        if let Some(walking) = flag("m_bIsWalking") {
+            self.is_walking = walking;
+        }
+        if let Some(ground) = value("m_hGroundEntity") {
+            self.is_airborne = EntityHandle::from_raw(ground.int_val as u32).is_none();
+        }
+        // Dead pawns don't move, the next position starts over.
+        let Some(position) = position else {
+            self.position_tick = None;
+            return;
+        };
+        if let Some(last_tick) = self.position_tick.filter(|&t| tick > t) {
+            let seconds = (tick - last_tick) as f64 / tick_rate;
+            self.velocity = Vector {
+                x: (position.x - last.x) / seconds,
+                y: (position.y - last.y) / seconds,
+                z: (position.z - last.z) / seconds,
+            };
+        }
+        self.position_tick = self.position_tick.max(Some(tick));
+    }
+}
+
+/// Compact reference to a player, used in serialized data instead of the
+/// full [`Player`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerRef {
+    pub user_id: i32,
+    pub steam_id64: u64,
+    pub name: String,
+}
+
+impl From<&Player> for PlayerRef {
+    fn from(p: &Player) -> Self {
+        Self {
+            user_id: p.user_id,
+            steam_id64: p.steam_id64,
+            name: p.name.clone(),
+        }
+    }
+}
+
+impl From<PlayerRef> for Player {
+    fn from(r: PlayerRef) -> Self {
+        Self {
+            user_id: r.user_id,
+            steam_id64: r.steam_id64,
+            name: r.name,
+            ..Default::default()
+        }
+    }
+}
+
 impl Player {
     pub fn position(&self) -> Vector {
         self.entity
@@ -55,6 +209,24 @@ impl Player {
             .unwrap_or_else(|| self.last_alive_position.clone())
     }
 
+    /// The callout the player was last in, `m_szLastPlaceName`, e.g.
+    /// `BombsiteB`. `None` if the demo doesn't send it; see
+    /// [`Places`](crate::places::Places) for a fallback.
+    pub fn place_name(&self) -> Option<String> {
+        self.entity
+            .as_ref()
+            .and_then(|e| e.property_value("m_szLastPlaceName"))
+            .map(|v| v.string_val)
+            .or_else(|| Some(self.last_place_name.clone()))
+            .filter(|name| !name.is_empty())
+    }
+
+    /// Whether the demo sent a position for the player. Until then
+    /// [`position`](Self::position) is the origin.
+    pub fn has_position(&self) -> bool {
+        self.entity.is_some() || self.pawn.is_some()
+    }
+
     pub fn ping(&self) -> i32 {
         self.entity
             .as_ref()
@@ -82,7 +254,50 @@ impl Player {
             .as_ref()
             .and_then(|e| e.property_value("m_iHealth"))
             .map(|v| v.int_val)
-            .unwrap_or(0)
+            .unwrap_or(self.props.health)
+    }
+
+    pub fn armor(&self) -> i32 {
+        self.entity
+            .as_ref()
+            .and_then(|e| e.property_value("m_ArmorValue"))
+            .map(|v| v.int_val)
+            .unwrap_or(self.props.armor)
+    }
+
+    pub fn velocity(&self) -> Vector {
+        self.entity
+            .as_ref()
+            .and_then(|e| {
+                e.property_value("m_vecAbsVelocity")
+                    .or_else(|| e.property_value("m_vecVelocity"))
+            })
+            .map(|v| v.vector_val)
+            .unwrap_or_else(|| self.props.velocity.clone())
+    }
+
+    /// Yaw of the player's view in degrees.
+    pub fn view_direction_x(&self) -> f32 {
+        self.entity
+            .as_ref()
+            .and_then(|e| {
+                e.property_value("m_angEyeAngles")
+                    .map(|v| v.vector_val.y as f32)
+                    .or_else(|| e.property_value("m_angEyeAngles[1]").map(|v| v.float_val))
+            })
+            .unwrap_or(self.props.view_direction_x)
+    }
+
+    /// Pitch of the player's view in degrees.
+    pub fn view_direction_y(&self) -> f32 {
+        self.entity
+            .as_ref()
+            .and_then(|e| {
+                e.property_value("m_angEyeAngles")
+                    .map(|v| v.vector_val.x as f32)
+                    .or_else(|| e.property_value("m_angEyeAngles[0]").map(|v| v.float_val))
+            })
+            .unwrap_or(self.props.view_direction_y)
     }
 
     pub fn is_alive(&self) -> bool {
@@ -141,6 +356,28 @@ impl Player {
             .unwrap_or(0)
     }
 
+    pub fn money(&self) -> i32 {
+        self.entity
+            .as_ref()
+            .and_then(|e| {
+                e.property_value("m_pInGameMoneyServices.m_iAccount")
+                    .or_else(|| e.property_value("m_iAccount"))
+            })
+            .map(|v| v.int_val)
+            .unwrap_or(self.props.money)
+    }
+
+    pub fn money_spent_this_round(&self) -> i32 {
+        self.entity
+            .as_ref()
+            .and_then(|e| {
+                e.property_value("m_pInGameMoneyServices.m_iCashSpentThisRound")
+                    .or_else(|| e.property_value("m_iCashSpentThisRound"))
+            })
+            .map(|v| v.int_val)
+            .unwrap_or(self.props.money_spent_this_round)
+    }
+
     pub fn has_defuse_kit(&self) -> bool {
         self.entity
             .as_ref()
@@ -149,7 +386,7 @@ impl Player {
                     .or_else(|| e.property_value("m_bHasDefuser"))
             })
             .map(|v| v.bool_val())
-            .unwrap_or(false)
+            .unwrap_or(self.props.has_defuser)
     }
 
     pub fn has_helmet(&self) -> bool {
@@ -160,7 +397,7 @@ impl Player {
                     .or_else(|| e.property_value("m_bHasHelmet"))
             })
             .map(|v| v.bool_val())
-            .unwrap_or(false)
+            .unwrap_or(self.props.has_helmet)
     }
 
     pub fn is_in_bomb_zone(&self) -> bool {
@@ -171,12 +408,20 @@ impl Player {
             .unwrap_or(false)
     }
 
+    pub fn is_in_hostage_rescue_zone(&self) -> bool {
+        self.entity
+            .as_ref()
+            .and_then(|e| e.property_value("m_bInHostageRescueZone"))
+            .map(|v| v.bool_val())
+            .unwrap_or(false)
+    }
+
     pub fn is_ducking(&self) -> bool {
         self.entity
             .as_ref()
             .and_then(|e| e.property_value("m_bDucking"))
             .map(|v| v.bool_val())
-            .unwrap_or(false)
+            .unwrap_or(self.props.is_ducking)
     }
 
     pub fn is_scoped(&self) -> bool {
@@ -184,7 +429,7 @@ impl Player {
             .as_ref()
             .and_then(|e| e.property_value("m_bIsScoped"))
             .map(|v| v.bool_val())
-            .unwrap_or(false)
+            .unwrap_or(self.props.is_scoped)
     }
 
     pub fn is_spotted_by(&self, other: &Player) -> bool {
@@ -219,7 +464,7 @@ impl Player {
             .as_ref()
             .and_then(|e| e.property_value("m_bIsWalking"))
             .map(|v| v.bool_val())
-            .unwrap_or(false)
+            .unwrap_or(self.props.is_walking)
     }
 
     pub fn is_grabbing_hostage(&self) -> bool {
@@ -235,7 +480,7 @@ impl Player {
             .as_ref()
             .and_then(|e| e.property_value("m_hGroundEntity"))
             .map(|v| v.int_val as u32 == constants::INVALID_ENTITY_HANDLE)
-            .unwrap_or(false)
+            .unwrap_or(self.props.is_airborne)
     }
 
     pub fn is_blinded(&self) -> bool {
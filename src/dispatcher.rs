@@ -28,7 +28,9 @@ struct HandlerEntry {
 
 pub struct EventDispatcher {
     handlers: RwLock<HashMap<TypeId, Vec<HandlerEntry>>>,
-    tx: Sender<Arc<dyn Any + Send + Sync>>,
+    /// Queue of the runner thread. `None` if events are handled inline by
+    /// the dispatching thread.
+    tx: Option<Sender<Arc<dyn Any + Send + Sync>>>,
     next_id: AtomicUsize,
 }
 
@@ -37,44 +39,55 @@ impl EventDispatcher {
         Self::with_capacity(None)
     }
 
+    /// Creates a dispatcher that hands events to a background thread. On
+    /// WebAssembly, which can't spawn threads, this is the same as
+    /// [`inline`](Self::inline).
     pub fn with_capacity(capacity: Option<usize>) -> Arc<Self> {
+        if cfg!(target_arch = "wasm32") {
+            return Self::inline();
+        }
+
         let (tx, rx) = match capacity {
             | Some(cap) => bounded(cap),
             | None => unbounded(),
         };
         let disp = Arc::new(Self {
             handlers: RwLock::new(HashMap::new()),
-            tx,
+            tx: Some(tx),
             next_id: AtomicUsize::new(1),
         });
         Self::spawn_runner(Arc::clone(&disp), rx);
         disp
     }
 
+    /// Creates a dispatcher without a runner thread. Handlers run on the
+    /// dispatching thread before [`Dispatcher::dispatch`] returns.
+    pub fn inline() -> Arc<Self> {
+        Arc::new(Self {
+            handlers: RwLock::new(HashMap::new()),
+            tx: None,
+            next_id: AtomicUsize::new(1),
+        })
+    }
+
     fn spawn_runner(this: Arc<Self>, rx: Receiver<Arc<dyn Any + Send + Sync>>) {
-        #[cfg(not(target_arch = "wasm32"))]
-        {
-            thread::spawn(move || {
-                for event in rx.iter() {
-                    let t = event.as_ref().type_id();
-                    let handlers = {
-                        let map = this.handlers.read().unwrap();
-                        map.get(&t).cloned()
-                    };
-                    if let Some(list) = handlers {
-                        for h in &list {
-                            (h.callback)(&event);
-                        }
-                    }
-                }
-            });
-        }
+        thread::spawn(move || {
+            for event in rx.iter() {
+                this.run_handlers(&event);
+            }
+        });
+    }
 
-        #[cfg(target_arch = "wasm32")]
-        {
-            // WebAssembly does not support spawning threads. Events will not be
-            // dispatched asynchronously when targeting wasm.
-            drop(rx);
+    fn run_handlers(&self, event: &Arc<dyn Any + Send + Sync>) {
+        let t = event.as_ref().type_id();
+        let handlers = {
+            let map = self.handlers.read().unwrap();
+            map.get(&t).cloned()
+        };
+        if let Some(list) = handlers {
+            for h in &list {
+                (h.callback)(event);
+            }
         }
     }
 }
@@ -102,7 +115,13 @@ impl Dispatcher for Arc<EventDispatcher> {
     where
         E: Send + Sync + 'static,
     {
-        let _ = self.tx.send(Arc::new(event));
+        let event: Arc<dyn Any + Send + Sync> = Arc::new(event);
+        match &self.tx {
+            | Some(tx) => {
+                let _ = tx.send(event);
+            },
+            | None => self.run_handlers(&event),
+        }
     }
 
     fn unregister_handler(&self, id: HandlerIdentifier) {
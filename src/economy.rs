@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+
+use crate::common::{EquipmentType, Player, Team};
+
+/// Classification of a team's buy in a round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BuyType {
+    #[default]
+    Pistol,
+    Eco,
+    SemiEco,
+    Force,
+    Full,
+}
+
+impl BuyType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            | BuyType::Pistol => "pistol",
+            | BuyType::Eco => "eco",
+            | BuyType::SemiEco => "semi-eco",
+            | BuyType::Force => "force",
+            | BuyType::Full => "full",
+        }
+    }
+}
+
+/// Average per-player equipment values (at the end of freeze time) separating
+/// the buy types. A team below `eco` is on an eco, below `semi_eco` on a
+/// semi-eco, below `full` on a force buy and a full buy otherwise.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BuyThresholds {
+    pub eco: i32,
+    pub semi_eco: i32,
+    pub full: i32,
+}
+
+impl Default for BuyThresholds {
+    fn default() -> Self {
+        Self {
+            eco: 1000,
+            semi_eco: 2000,
+            full: 4000,
+        }
+    }
+}
+
+impl BuyThresholds {
+    pub fn classify(&self, is_pistol_round: bool, avg_equipment_value: i32) -> BuyType {
+        match avg_equipment_value {
+            | _ if is_pistol_round => BuyType::Pistol,
+            | v if v < self.eco => BuyType::Eco,
+            | v if v < self.semi_eco => BuyType::SemiEco,
+            | v if v < self.full => BuyType::Force,
+            | _ => BuyType::Full,
+        }
+    }
+}
+
+/// An item bought by a player.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Purchase {
+    pub tick: i32,
+    pub equipment_type: EquipmentType,
+    pub original_string: String,
+}
+
+/// Economy of a single player in one round.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerEconomy {
+    pub user_id: i32,
+    pub steam_id64: u64,
+    pub name: String,
+    pub team: Team,
+    /// Money at the start of the round, after the previous round's payout.
+    pub start_money: i32,
+    pub freezetime_end_equipment_value: i32,
+    /// Sum of all decreases of `m_iAccount` during the round.
+    pub money_spent: i32,
+    /// Sum of all increases of `m_iAccount` from this round's start until the
+    /// next one, i.e. kill rewards, objective bonuses and the round payout.
+    pub cash_earned: i32,
+    pub purchases: Vec<Purchase>,
+}
+
+/// Economy of a team in one round, aggregated over its players.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TeamEconomy {
+    pub team: Team,
+    pub start_money: i32,
+    pub freezetime_end_equipment_value: i32,
+    pub money_spent: i32,
+    pub cash_earned: i32,
+    /// Number of consecutive rounds lost before this round. The counter is
+    /// reset by a round win and at the start of each half.
+    pub loss_streak: i32,
+    pub buy_type: BuyType,
+}
+
+impl TeamEconomy {
+    /// Loss bonus paid to the team for losing this round.
+    pub fn loss_bonus(&self) -> i32 {
+        1400 + 500 * self.loss_streak.min(4)
+    }
+}
+
+/// Economy of both teams in one round. Team totals are refreshed at the end
+/// of freeze time, at the end of the round and when the next round starts.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundEconomy {
+    pub round: i32,
+    pub start_tick: i32,
+    /// Tick of the `RoundEnd` event, `0` while the round is in progress.
+    pub end_tick: i32,
+    pub is_pistol_round: bool,
+    pub winner: Team,
+    pub players: Vec<PlayerEconomy>,
+    pub terrorists: TeamEconomy,
+    pub counter_terrorists: TeamEconomy,
+}
+
+impl RoundEconomy {
+    pub fn team(&self, team: Team) -> Option<&TeamEconomy> {
+        match team {
+            | Team::Terrorists => Some(&self.terrorists),
+            | Team::CounterTerrorists => Some(&self.counter_terrorists),
+            | _ => None,
+        }
+    }
+
+    pub fn player(&self, user_id: i32) -> Option<&PlayerEconomy> {
+        self.players.iter().find(|p| p.user_id == user_id)
+    }
+
+    fn player_mut(&mut self, user_id: i32) -> Option<&mut PlayerEconomy> {
+        self.players.iter_mut().find(|p| p.user_id == user_id)
+    }
+
+    fn aggregate(&mut self, thresholds: &BuyThresholds) {
+        for team in [&mut self.terrorists, &mut self.counter_terrorists] {
+            let members: Vec<&PlayerEconomy> = self
+                .players
+                .iter()
+                .filter(|p| p.team == team.team)
+                .collect();
+            team.start_money = members.iter().map(|p| p.start_money).sum();
+            team.freezetime_end_equipment_value = members
+                .iter()
+                .map(|p| p.freezetime_end_equipment_value)
+                .sum();
+            team.money_spent = members.iter().map(|p| p.money_spent).sum();
+            team.cash_earned = members.iter().map(|p| p.cash_earned).sum();
+            let avg = if members.is_empty() {
+                0
+            } else {
+                team.freezetime_end_equipment_value / members.len() as i32
+            };
+            team.buy_type = thresholds.classify(self.is_pistol_round, avg);
+        }
+    }
+}
+
+/// Tracks money, spending and purchases of all players round by round.
+///
+/// The tracker is driven by [`GameState`](crate::game_state::GameState) and
+/// reads `m_iAccount` and the equipment value properties of the players'
+/// entities.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Economy {
+    pub thresholds: BuyThresholds,
+    rounds: Vec<RoundEconomy>,
+    current: Option<usize>,
+    last_money: HashMap<i32, i32>,
+    t_loss_streak: i32,
+    ct_loss_streak: i32,
+    next_is_pistol: bool,
+    in_buytime: bool,
+    /// Set between the end of a half and the next round start, while the
+    /// game resets everyone's money.
+    money_reset: bool,
+    /// Set once the demo sends `item_purchase` events, pickups are ignored
+    /// from then on.
+    purchase_events: bool,
+    /// Pickups during buy time that become purchases if the player's money
+    /// drops in the same frame.
+    pending: Vec<(i32, Purchase)>,
+}
+
+impl Default for Economy {
+    fn default() -> Self {
+        Self {
+            thresholds: BuyThresholds::default(),
+            rounds: Vec::new(),
+            current: None,
+            last_money: HashMap::new(),
+            t_loss_streak: 0,
+            ct_loss_streak: 0,
+            next_is_pistol: true,
+            in_buytime: false,
+            money_reset: false,
+            purchase_events: false,
+            pending: Vec::new(),
+        }
+    }
+}
+
+impl Economy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all rounds seen so far. The last round may still be in
+    /// progress.
+    pub fn rounds(&self) -> &[RoundEconomy] {
+        &self.rounds
+    }
+
+    pub fn current_round(&self) -> Option<&RoundEconomy> {
+        self.current.and_then(|i| self.rounds.get(i))
+    }
+
+    /// Discards everything recorded so far, e.g. rounds played during warmup.
+    pub fn reset(&mut self) {
+        *self = Self {
+            thresholds: self.thresholds,
+            ..Default::default()
+        };
+    }
+
+    /// Marks the next round as a pistol round unless the half was played in
+    /// `overtime`, where every half starts with the overtime money, and
+    /// resets the loss streaks. Money changes until the next round start are
+    /// the reset to the start money and aren't booked.
+    pub fn half_ended(&mut self, overtime: bool) {
+        self.next_is_pistol |= !overtime;
+        self.money_reset = true;
+        self.t_loss_streak = 0;
+        self.ct_loss_streak = 0;
+    }
+
+    pub fn round_started<'a, I>(&mut self, round: i32, tick: i32, players: I)
+    where
+        I: IntoIterator<Item = &'a Player>,
+    {
+        if let Some(prev) = self.current.and_then(|i| self.rounds.get_mut(i)) {
+            prev.aggregate(&self.thresholds);
+        }
+        let players = players
+            .into_iter()
+            .filter(|p| matches!(p.team, Team::Terrorists | Team::CounterTerrorists))
+            .map(|p| {
+                let money = p.money();
+                self.last_money.insert(p.user_id, money);
+                PlayerEconomy {
+                    user_id: p.user_id,
+                    steam_id64: p.steam_id64,
+                    name: p.name.clone(),
+                    team: p.team,
+                    start_money: money,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        let mut r = RoundEconomy {
+            round,
+            start_tick: tick,
+            is_pistol_round: self.next_is_pistol,
+            players,
+            terrorists: TeamEconomy {
+                team: Team::Terrorists,
+                loss_streak: self.t_loss_streak,
+                ..Default::default()
+            },
+            counter_terrorists: TeamEconomy {
+                team: Team::CounterTerrorists,
+                loss_streak: self.ct_loss_streak,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        r.aggregate(&self.thresholds);
+        self.rounds.push(r);
+        self.current = Some(self.rounds.len() - 1);
+        self.next_is_pistol = false;
+        self.in_buytime = true;
+        self.money_reset = false;
+        self.pending.clear();
+    }
+
+    pub fn freezetime_ended<'a, I>(&mut self, players: I)
+    where
+        I: IntoIterator<Item = &'a Player>,
+    {
+        let Some(r) = self.current.and_then(|i| self.rounds.get_mut(i)) else {
+            return;
+        };
+        for p in players {
+            if let Some(pe) = r.player_mut(p.user_id) {
+                pe.freezetime_end_equipment_value = match p.equipment_value_freezetime_end() {
+                    | 0 => p.equipment_value_current(),
+                    | v => v,
+                };
+            }
+        }
+        r.aggregate(&self.thresholds);
+    }
+
+    pub fn buytime_ended(&mut self) {
+        self.in_buytime = false;
+    }
+
+    /// Records an `item_purchase` event. Demos that send these only count
+    /// them as purchases.
+    pub fn item_purchased(&mut self, tick: i32, ev: &crate::events::ItemPurchase) {
+        self.purchase_events = true;
+        let (Some(player), Some(weapon)) = (&ev.player, &ev.weapon) else {
+            return;
+        };
+        if let Some(pe) = self
+            .current
+            .and_then(|i| self.rounds.get_mut(i))
+            .and_then(|r| r.player_mut(player.user_id))
+        {
+            pe.purchases.push(Purchase {
+                tick,
+                equipment_type: weapon.equipment_type,
+                original_string: weapon.original_string.clone(),
+            });
+        }
+    }
+
+    /// Notes an item pickup during buy time. It is recorded as a purchase
+    /// by the next [`update_money`](Self::update_money) if the player paid
+    /// for it, picked up weapons dropped by teammates aren't.
+    pub fn item_picked_up(&mut self, tick: i32, ev: &crate::events::ItemPickup) {
+        if self.purchase_events || !self.in_buytime || ev.silent {
+            return;
+        }
+        let (Some(player), Some(weapon)) = (&ev.player, &ev.weapon) else {
+            return;
+        };
+        self.pending.push((
+            player.user_id,
+            Purchase {
+                tick,
+                equipment_type: weapon.equipment_type,
+                original_string: weapon.original_string.clone(),
+            },
+        ));
+    }
+
+    /// Accumulates changes of the players' money since the last call and
+    /// turns the pickups of players whose money dropped into purchases.
+    pub fn update_money<'a, I>(&mut self, players: I)
+    where
+        I: IntoIterator<Item = &'a Player>,
+    {
+        let mut round = self.current.and_then(|i| self.rounds.get_mut(i));
+        let pending = std::mem::take(&mut self.pending);
+        for p in players {
+            // Players without an entity have no money yet.
+            if p.entity.is_none() && p.pawn.is_none() {
+                continue;
+            }
+            let money = p.money();
+            let last = self.last_money.insert(p.user_id, money).unwrap_or(money);
+            let delta = money - last;
+            if delta == 0 || self.money_reset {
+                continue;
+            }
+            let Some(pe) = round.as_mut().and_then(|r| r.player_mut(p.user_id)) else {
+                continue;
+            };
+            if delta < 0 {
+                pe.money_spent -= delta;
+                pe.purchases.extend(
+                    pending
+                        .iter()
+                        .filter(|(user_id, _)| *user_id == p.user_id)
+                        .map(|(_, purchase)| purchase.clone()),
+                );
+            } else {
+                pe.cash_earned += delta;
+            }
+        }
+    }
+
+    /// Records the winner of the current round and updates the loss streaks.
+    pub fn round_ended(&mut self, tick: i32, winner: Team) {
+        match winner {
+            | Team::Terrorists => {
+                self.t_loss_streak = 0;
+                self.ct_loss_streak += 1;
+            },
+            | Team::CounterTerrorists => {
+                self.ct_loss_streak = 0;
+                self.t_loss_streak += 1;
+            },
+            | _ => {},
+        }
+        self.in_buytime = false;
+        if let Some(r) = self.current.and_then(|i| self.rounds.get_mut(i)) {
+            r.end_tick = tick;
+            r.winner = winner;
+            r.aggregate(&self.thresholds);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+pub use crate::common::{Equipment, GrenadeProjectile, Hostage, HostageState, Player};
 pub use crate::gamerules::GamePhase;
 use crate::proto::msgs2::csvc_msg_game_event;
 use crate::sendtables::entity::Vector;
@@ -6,31 +7,25 @@ use std::collections::HashMap;
 use std::time::Duration;
 
 #[derive(Clone, Debug, Default)]
-pub struct Player;
-
-#[derive(Clone, Debug, Default)]
-pub struct Equipment;
-
-#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamState;
 
 #[derive(Clone, Debug, Default)]
-pub struct GrenadeProjectile;
-
-#[derive(Clone, Debug, Default)]
-pub struct Hostage;
-
-#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Inferno;
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerInfoData;
 
+/// Grenade events carry a [`common::EquipmentType`](crate::common::EquipmentType)
+/// instead.
+#[deprecated(note = "use `common::EquipmentType`")]
 pub type EquipmentType = u32;
 pub type Team = u8;
-pub type HostageState = u32;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum RoundEndReason {
     StillInProgress = 0,
@@ -56,7 +51,36 @@ pub enum RoundEndReason {
     CTsReachedHostage = 20,
 }
 
+impl From<u8> for RoundEndReason {
+    fn from(v: u8) -> Self {
+        match v {
+            | 1 => RoundEndReason::TargetBombed,
+            | 2 => RoundEndReason::VIPEscaped,
+            | 3 => RoundEndReason::VIPKilled,
+            | 4 => RoundEndReason::TerroristsEscaped,
+            | 5 => RoundEndReason::CTStoppedEscape,
+            | 6 => RoundEndReason::TerroristsStopped,
+            | 7 => RoundEndReason::BombDefused,
+            | 8 => RoundEndReason::CTWin,
+            | 9 => RoundEndReason::TerroristsWin,
+            | 10 => RoundEndReason::Draw,
+            | 11 => RoundEndReason::HostagesRescued,
+            | 12 => RoundEndReason::TargetSaved,
+            | 13 => RoundEndReason::HostagesNotRescued,
+            | 14 => RoundEndReason::TerroristsNotEscaped,
+            | 15 => RoundEndReason::VIPNotEscaped,
+            | 16 => RoundEndReason::GameStart,
+            | 17 => RoundEndReason::TerroristsSurrender,
+            | 18 => RoundEndReason::CTSurrender,
+            | 19 => RoundEndReason::TerroristsPlanted,
+            | 20 => RoundEndReason::CTsReachedHostage,
+            | _ => RoundEndReason::StillInProgress,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum RoundMVPReason {
     MostEliminations = 1,
@@ -65,6 +89,7 @@ pub enum RoundMVPReason {
 }
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum HitGroup {
     Generic = 0,
@@ -79,7 +104,25 @@ pub enum HitGroup {
     Gear = 10,
 }
 
+impl From<u8> for HitGroup {
+    fn from(v: u8) -> Self {
+        match v {
+            | 1 => HitGroup::Head,
+            | 2 => HitGroup::Chest,
+            | 3 => HitGroup::Stomach,
+            | 4 => HitGroup::LeftArm,
+            | 5 => HitGroup::RightArm,
+            | 6 => HitGroup::LeftLeg,
+            | 7 => HitGroup::RightLeg,
+            | 8 => HitGroup::Neck,
+            | 10 => HitGroup::Gear,
+            | _ => HitGroup::Generic,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(i32)]
 pub enum WarnType {
     Undefined = 0,
@@ -93,21 +136,28 @@ pub enum WarnType {
     MissingItemDefinitionIndex,
     StringTableParsingFailure,
     PacketEntitiesPanic,
+    /// The rest of a PacketEntities message was dropped, see
+    /// [`PacketEntitiesError`](crate::sendtables2::PacketEntitiesError).
+    InvalidPacketEntities,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FrameDone;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct POVRecordingPlayerDetected {
     pub player_slot: i32,
     pub player_info: PlayerInfoData,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchStart;
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundStart {
     pub time_limit: i32,
     pub frag_limit: i32,
@@ -115,15 +165,18 @@ pub struct RoundStart {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundFreezetimeEnd;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundFreezetimeChanged {
     pub old_is_freezetime: bool,
     pub new_is_freezetime: bool,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundEnd {
     pub message: String,
     pub reason: RoundEndReason,
@@ -133,51 +186,68 @@ pub struct RoundEnd {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundEndOfficial;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundMVPAnnouncement {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub reason: RoundMVPReason,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnnouncementMatchStarted;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnnouncementLastRoundHalf;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnnouncementFinalRound;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnnouncementWinPanelMatch;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundAnnounceFinal;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundAnnounceLastRoundHalf;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundAnnounceMatchPoint;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundAnnounceMatchStart;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundAnnounceWarmup;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundEndUploadStats;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Footstep {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerTeamChange {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub new_team_state: Option<TeamState>,
     pub old_team_state: Option<TeamState>,
@@ -188,22 +258,30 @@ pub struct PlayerTeamChange {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerJump {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerSound {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub radius: i32,
     pub duration: Duration,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Kill {
     pub weapon: Option<Equipment>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub victim: Option<Player>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub killer: Option<Player>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub assister: Option<Player>,
     pub penetrated_objects: i32,
     pub is_headshot: bool,
@@ -215,180 +293,231 @@ pub struct Kill {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BotTakenOver {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub taker: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WeaponFire {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub shooter: Option<Player>,
     pub weapon: Option<Equipment>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WeaponReload {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GrenadeEvent {
-    pub grenade_type: EquipmentType,
+    pub grenade_type: crate::common::EquipmentType,
     pub grenade: Option<Equipment>,
     pub position: Vector,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub thrower: Option<Player>,
     pub grenade_entity_id: i32,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HeExplode {
     pub inner: GrenadeEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FlashExplode {
     pub inner: GrenadeEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DecoyStart {
     pub inner: GrenadeEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DecoyExpired {
     pub inner: GrenadeEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SmokeStart {
     pub inner: GrenadeEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SmokeExpired {
     pub inner: GrenadeEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FireGrenadeStart {
     pub inner: GrenadeEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FireGrenadeExpired {
     pub inner: GrenadeEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GrenadeProjectileBounce {
     pub projectile: Option<GrenadeProjectile>,
     pub bounce_nr: i32,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GrenadeProjectileThrow {
     pub projectile: Option<GrenadeProjectile>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GrenadeProjectileDestroy {
     pub projectile: Option<GrenadeProjectile>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerFlashed {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub attacker: Option<Player>,
     pub projectile: Option<GrenadeProjectile>,
+    /// Seconds the player is blinded for.
+    pub flash_duration: f32,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Bombsite {
+    #[default]
     Unknown,
     A,
     B,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombEvent {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub site: Bombsite,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombPlantBegin {
     pub inner: BombEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombPlantAborted {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombPlanted {
     pub inner: BombEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombDefused {
     pub inner: BombEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombExplode {
     pub inner: BombEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombDefuseStart {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub has_kit: bool,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombDefuseAborted {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombDropped {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub entity_id: i32,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombPickup {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BombBeep {
     pub inner: BombEvent,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HostageRescued {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub hostage: Option<Hostage>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HostageRescuedAll;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HostageHurt {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub hostage: Option<Hostage>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HostageKilled {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub killer: Option<Player>,
     pub hostage: Option<Hostage>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HostageStateChanged {
     pub old_state: HostageState,
     pub new_state: HostageState,
@@ -396,8 +525,11 @@ pub struct HostageStateChanged {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BulletDamage {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub attacker: Option<Player>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub victim: Option<Player>,
     pub distance: f32,
     pub damage_dir_x: f32,
@@ -409,8 +541,11 @@ pub struct BulletDamage {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerHurt {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub attacker: Option<Player>,
     pub health: i32,
     pub armor: i32,
@@ -424,68 +559,93 @@ pub struct PlayerHurt {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerConnect {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BotConnect {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerDisconnected {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerNameChange {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub old_name: String,
     pub new_name: String,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerSpawn {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerSpawned {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerTeam {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerPing {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerPingStop {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerGivenC4 {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerFallDamage {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StringTablePlayerUpdateApplied {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SayText {
     pub ent_idx: i32,
     pub text: String,
@@ -494,6 +654,7 @@ pub struct SayText {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SayText2 {
     pub ent_idx: i32,
     pub msg_name: String,
@@ -503,41 +664,53 @@ pub struct SayText2 {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChatMessage {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub sender: Option<Player>,
     pub text: String,
     pub is_chat_all: bool,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TickRateInfoAvailable {
     pub tick_rate: f64,
     pub tick_time: Duration,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParserWarn {
     pub message: String,
     pub r#type: WarnType,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GenericGameEvent {
     pub name: String,
+    #[cfg_attr(
+        feature = "serde",
+        serde(with = "crate::serialization::game_event_keys")
+    )]
     pub data: HashMap<String, csvc_msg_game_event::KeyT>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InfernoStart {
     pub inferno: Option<Inferno>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InfernoExpired {
     pub inferno: Option<Inferno>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ScoreUpdated {
     pub old_score: i32,
     pub new_score: i32,
@@ -545,78 +718,97 @@ pub struct ScoreUpdated {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GamePhaseChanged {
     pub old_game_phase: GamePhase,
     pub new_game_phase: GamePhase,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamSideSwitch;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameHalfEnded;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MatchStartedChanged {
     pub old_is_started: bool,
     pub new_is_started: bool,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct IsWarmupPeriodChanged {
     pub old_is_warmup_period: bool,
     pub new_is_warmup_period: bool,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerSpottersChanged {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub spotted: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ConVarsUpdated {
     pub updated_con_vars: HashMap<String, String>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundImpactScoreData {
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub raw_message: Option<crate::proto::msg::cs_demo_parser_rs::CcsUsrMsgRoundImpactScoreData>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RawRoundBackupFilenames {
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub raw_message: Option<crate::proto::msg::cs_demo_parser_rs::CcsUsrMsgRoundBackupFilenames>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerInfo {
     pub index: i32,
     pub info: PlayerInfoData,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OvertimeNumberChanged {
     pub old_count: i32,
     pub new_count: i32,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RankUpdate {
     pub steam_id32: i32,
     pub rank_change: f32,
     pub rank_old: i32,
     pub rank_new: i32,
     pub win_count: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ItemRefund {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub weapon: Option<Equipment>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VguiMenu {
     pub name: String,
     pub show: bool,
@@ -624,6 +816,7 @@ pub struct VguiMenu {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShowMenu {
     pub bits_valid_slots: i32,
     pub display_time: i32,
@@ -631,11 +824,13 @@ pub struct ShowMenu {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BarTime {
     pub time: String,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundBackupFilenames {
     pub count: i32,
     pub index: i32,
@@ -644,164 +839,397 @@ pub struct RoundBackupFilenames {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DataTablesParsed;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct StringTableCreated {
     pub table_name: String,
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamClanNameUpdated {
     pub old_name: String,
     pub new_name: String,
     pub team_state: Option<TeamState>,
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AnnouncePhaseEnd;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BuytimeEnded;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChoppersIncomingWarning;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsIntermission;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsMatchEndRestart;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsPreRestart;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsRoundFinalBeep;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsRoundStartBeep;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsWinPanelMatch;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsWinPanelRound;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnterBombzone;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExitBombzone;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnterBuyzone;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExitBuyzone;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntityVisible;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FirstBombsIncomingWarning;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HltvChase;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HltvFixed;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HltvMessage;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HltvStatus;
 
 #[derive(Clone, Debug)]
-pub struct HostageFollows;
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostageFollows {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
+    pub player: Option<Player>,
+    pub hostage: Option<Hostage>,
+}
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HostnameChanged;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct JoinTeamFailed;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OtherDeath;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerBlind;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ShowSurvivalRespawnStatus;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SurvivalParadropSpawn;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SwitchTeam;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WeaponFireOnEmpty;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WeaponZoom;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WeaponZoomRifle;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AmmoPickup;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ItemEquip;
 
-#[derive(Clone, Debug)]
-pub struct ItemPickup;
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ItemPickup {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
+    pub player: Option<Player>,
+    pub weapon: Option<Equipment>,
+    /// Set for items given by the game (e.g. spawn equipment) rather than
+    /// bought or picked up by the player.
+    pub silent: bool,
+}
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ItemPickupSlerp;
 
+/// An item bought by a player. Only sent by some demos, mostly CS:GO POV
+/// demos.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ItemPurchase {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
+    pub player: Option<Player>,
+    pub weapon: Option<Equipment>,
+}
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ItemDrop {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serialization::player_ref"))]
     pub player: Option<Player>,
     pub weapon: Option<Equipment>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InspectWeapon;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ServerCvar;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VoteCast;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TournamentReward;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EndmatchCmmStartRevealItems;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EntityKilled;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GrenadeThrown;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HltvTitle;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HltvVersionInfo;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PlayerActivate;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundPoststart;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundPrestart;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RoundTimeWarning;
+
+/// Calls the given macro with the paths of all event types, e.g. to register
+/// a handler for every event.
+#[allow(unused_macros)]
+macro_rules! for_each_event {
+    ($m:ident) => {
+        $m!(
+            $crate::events::FrameDone,
+            $crate::events::POVRecordingPlayerDetected,
+            $crate::events::MatchStart,
+            $crate::events::RoundStart,
+            $crate::events::RoundFreezetimeEnd,
+            $crate::events::RoundFreezetimeChanged,
+            $crate::events::RoundEnd,
+            $crate::events::RoundEndOfficial,
+            $crate::events::RoundMVPAnnouncement,
+            $crate::events::AnnouncementMatchStarted,
+            $crate::events::AnnouncementLastRoundHalf,
+            $crate::events::AnnouncementFinalRound,
+            $crate::events::AnnouncementWinPanelMatch,
+            $crate::events::RoundAnnounceFinal,
+            $crate::events::RoundAnnounceLastRoundHalf,
+            $crate::events::RoundAnnounceMatchPoint,
+            $crate::events::RoundAnnounceMatchStart,
+            $crate::events::RoundAnnounceWarmup,
+            $crate::events::RoundEndUploadStats,
+            $crate::events::Footstep,
+            $crate::events::PlayerTeamChange,
+            $crate::events::PlayerJump,
+            $crate::events::PlayerSound,
+            $crate::events::Kill,
+            $crate::events::BotTakenOver,
+            $crate::events::WeaponFire,
+            $crate::events::WeaponReload,
+            $crate::events::HeExplode,
+            $crate::events::FlashExplode,
+            $crate::events::DecoyStart,
+            $crate::events::DecoyExpired,
+            $crate::events::SmokeStart,
+            $crate::events::SmokeExpired,
+            $crate::events::FireGrenadeStart,
+            $crate::events::FireGrenadeExpired,
+            $crate::events::GrenadeProjectileBounce,
+            $crate::events::GrenadeProjectileThrow,
+            $crate::events::GrenadeProjectileDestroy,
+            $crate::events::PlayerFlashed,
+            $crate::events::BombPlantBegin,
+            $crate::events::BombPlantAborted,
+            $crate::events::BombPlanted,
+            $crate::events::BombDefused,
+            $crate::events::BombExplode,
+            $crate::events::BombDefuseStart,
+            $crate::events::BombDefuseAborted,
+            $crate::events::BombDropped,
+            $crate::events::BombPickup,
+            $crate::events::BombBeep,
+            $crate::events::HostageRescued,
+            $crate::events::HostageRescuedAll,
+            $crate::events::HostageHurt,
+            $crate::events::HostageKilled,
+            $crate::events::HostageStateChanged,
+            $crate::events::BulletDamage,
+            $crate::events::PlayerHurt,
+            $crate::events::PlayerConnect,
+            $crate::events::BotConnect,
+            $crate::events::PlayerDisconnected,
+            $crate::events::PlayerNameChange,
+            $crate::events::PlayerSpawn,
+            $crate::events::PlayerSpawned,
+            $crate::events::PlayerTeam,
+            $crate::events::PlayerPing,
+            $crate::events::PlayerPingStop,
+            $crate::events::PlayerGivenC4,
+            $crate::events::PlayerFallDamage,
+            $crate::events::StringTablePlayerUpdateApplied,
+            $crate::events::SayText,
+            $crate::events::SayText2,
+            $crate::events::ChatMessage,
+            $crate::events::TickRateInfoAvailable,
+            $crate::events::ParserWarn,
+            $crate::events::GenericGameEvent,
+            $crate::events::InfernoStart,
+            $crate::events::InfernoExpired,
+            $crate::events::ScoreUpdated,
+            $crate::events::GamePhaseChanged,
+            $crate::events::TeamSideSwitch,
+            $crate::events::GameHalfEnded,
+            $crate::events::MatchStartedChanged,
+            $crate::events::IsWarmupPeriodChanged,
+            $crate::events::PlayerSpottersChanged,
+            $crate::events::ConVarsUpdated,
+            $crate::events::RoundImpactScoreData,
+            $crate::events::RawRoundBackupFilenames,
+            $crate::events::PlayerInfo,
+            $crate::events::OvertimeNumberChanged,
+            $crate::events::RankUpdate,
+            $crate::events::ItemRefund,
+            $crate::events::VguiMenu,
+            $crate::events::ShowMenu,
+            $crate::events::BarTime,
+            $crate::events::RoundBackupFilenames,
+            $crate::events::DataTablesParsed,
+            $crate::events::StringTableCreated,
+            $crate::events::TeamClanNameUpdated,
+            $crate::events::AnnouncePhaseEnd,
+            $crate::events::BuytimeEnded,
+            $crate::events::ChoppersIncomingWarning,
+            $crate::events::CsIntermission,
+            $crate::events::CsMatchEndRestart,
+            $crate::events::CsPreRestart,
+            $crate::events::CsRoundFinalBeep,
+            $crate::events::CsRoundStartBeep,
+            $crate::events::CsWinPanelMatch,
+            $crate::events::CsWinPanelRound,
+            $crate::events::EnterBombzone,
+            $crate::events::ExitBombzone,
+            $crate::events::EnterBuyzone,
+            $crate::events::ExitBuyzone,
+            $crate::events::EntityVisible,
+            $crate::events::FirstBombsIncomingWarning,
+            $crate::events::HltvChase,
+            $crate::events::HltvFixed,
+            $crate::events::HltvMessage,
+            $crate::events::HltvStatus,
+            $crate::events::HostageFollows,
+            $crate::events::HostnameChanged,
+            $crate::events::JoinTeamFailed,
+            $crate::events::OtherDeath,
+            $crate::events::PlayerBlind,
+            $crate::events::ShowSurvivalRespawnStatus,
+            $crate::events::SurvivalParadropSpawn,
+            $crate::events::SwitchTeam,
+            $crate::events::WeaponFireOnEmpty,
+            $crate::events::WeaponZoom,
+            $crate::events::WeaponZoomRifle,
+            $crate::events::AmmoPickup,
+            $crate::events::ItemEquip,
+            $crate::events::ItemPickup,
+            $crate::events::ItemPickupSlerp,
+            $crate::events::ItemPurchase,
+            $crate::events::ItemDrop,
+            $crate::events::InspectWeapon,
+            $crate::events::ServerCvar,
+            $crate::events::VoteCast,
+            $crate::events::TournamentReward,
+            $crate::events::EndmatchCmmStartRevealItems,
+            $crate::events::EntityKilled,
+            $crate::events::GrenadeThrown,
+            $crate::events::HltvTitle,
+            $crate::events::HltvVersionInfo,
+            $crate::events::PlayerActivate,
+            $crate::events::RoundPoststart,
+            $crate::events::RoundPrestart,
+            $crate::events::RoundTimeWarning
+        )
+    };
+}
+#[allow(unused_imports)]
+pub(crate) use for_each_event;
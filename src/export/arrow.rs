@@ -0,0 +1,384 @@
+//! Arrow and Parquet output for [`Dataset`] tables.
+//!
+//! Every table starts with a `demo` column so files of many demos can be
+//! queried together, e.g. `read_parquet('out/kills/*.parquet')` in DuckDB.
+
+use std::fmt;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::array::{
+    ArrayRef, BooleanArray, Float32Array, Float64Array, Int32Array, StringArray, UInt8Array,
+    UInt64Array, new_null_array,
+};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::error::ArrowError;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::errors::ParquetError;
+use parquet::file::properties::WriterProperties;
+
+use super::{Dataset, DatasetCollector, DatasetConfig, SCHEMA_VERSION};
+use crate::parser::{Parser, ParserError};
+
+/// A table of a [`Dataset`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Table {
+    Kills,
+    Damages,
+    Grenades,
+    Rounds,
+    Chat,
+    PlayerTicks,
+}
+
+impl Table {
+    pub const ALL: [Table; 6] = [
+        Table::Kills,
+        Table::Damages,
+        Table::Grenades,
+        Table::Rounds,
+        Table::Chat,
+        Table::PlayerTicks,
+    ];
+
+    /// Name of the table, also used as directory name by [`write_parquet`].
+    pub fn name(self) -> &'static str {
+        match self {
+            | Table::Kills => "kills",
+            | Table::Damages => "damages",
+            | Table::Grenades => "grenades",
+            | Table::Rounds => "rounds",
+            | Table::Chat => "chat",
+            | Table::PlayerTicks => "player_ticks",
+        }
+    }
+
+    pub fn schema(self) -> SchemaRef {
+        to_record_batch(&Dataset::default(), self)
+            .expect("empty tables are always valid")
+            .schema()
+    }
+}
+
+#[derive(Debug)]
+pub enum ExportError {
+    Io(std::io::Error),
+    Parser(ParserError),
+    Arrow(ArrowError),
+    Parquet(ParquetError),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | ExportError::Io(e) => write!(f, "io error: {e}"),
+            | ExportError::Parser(e) => write!(f, "parser error: {e:?}"),
+            | ExportError::Arrow(e) => write!(f, "arrow error: {e}"),
+            | ExportError::Parquet(e) => write!(f, "parquet error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+impl From<std::io::Error> for ExportError {
+    fn from(e: std::io::Error) -> Self {
+        ExportError::Io(e)
+    }
+}
+
+impl From<ParserError> for ExportError {
+    fn from(e: ParserError) -> Self {
+        ExportError::Parser(e)
+    }
+}
+
+impl From<ArrowError> for ExportError {
+    fn from(e: ArrowError) -> Self {
+        ExportError::Arrow(e)
+    }
+}
+
+impl From<ParquetError> for ExportError {
+    fn from(e: ParquetError) -> Self {
+        ExportError::Parquet(e)
+    }
+}
+
+/// Accumulates the columns of one record batch.
+struct Columns {
+    len: usize,
+    fields: Vec<Field>,
+    arrays: Vec<ArrayRef>,
+}
+
+impl Columns {
+    fn new(demo: &str, len: usize) -> Self {
+        let mut c = Self {
+            len,
+            fields: Vec::new(),
+            arrays: Vec::new(),
+        };
+        c.push(
+            Field::new("demo", DataType::Utf8, false),
+            Arc::new(StringArray::from(vec![demo; len])),
+        );
+        c
+    }
+
+    fn push(&mut self, field: Field, array: ArrayRef) {
+        self.fields.push(field);
+        self.arrays.push(array);
+    }
+
+    fn col<A, T>(&mut self, name: &str, values: Vec<T>)
+    where
+        A: From<Vec<T>> + arrow::array::Array + 'static,
+    {
+        let array = A::from(values);
+        let field = Field::new(name, array.data_type().clone(), false);
+        self.push(field, Arc::new(array));
+    }
+
+    fn nullable<A, T>(&mut self, name: &str, values: Vec<Option<T>>)
+    where
+        A: From<Vec<Option<T>>> + arrow::array::Array + 'static,
+    {
+        let array = A::from(values);
+        let field = Field::new(name, array.data_type().clone(), true);
+        self.push(field, Arc::new(array));
+    }
+
+    /// Adds a player-tick column, which is all nulls if its property
+    /// wasn't sampled.
+    fn sampled<A, T: Clone>(&mut self, name: &str, data_type: DataType, values: &[T])
+    where
+        A: From<Vec<T>> + arrow::array::Array + 'static,
+    {
+        let array: ArrayRef = if values.len() == self.len {
+            Arc::new(A::from(values.to_vec()))
+        } else {
+            new_null_array(&data_type, self.len)
+        };
+        self.push(Field::new(name, data_type, true), array);
+    }
+
+    fn finish(self) -> Result<RecordBatch, ArrowError> {
+        let mut metadata = std::collections::HashMap::new();
+        metadata.insert("schema_version".to_string(), SCHEMA_VERSION.to_string());
+        let schema = Schema::new_with_metadata(self.fields, metadata);
+        RecordBatch::try_new(Arc::new(schema), self.arrays)
+    }
+}
+
+macro_rules! column {
+    ($rows:expr, $field:ident) => {
+        $rows.iter().map(|r| r.$field.clone()).collect::<Vec<_>>()
+    };
+}
+
+/// Converts one table of `dataset` into an Arrow record batch.
+pub fn to_record_batch(dataset: &Dataset, table: Table) -> Result<RecordBatch, ArrowError> {
+    let demo = dataset.demo.as_str();
+    match table {
+        | Table::Kills => {
+            let rows = &dataset.kills;
+            let mut c = Columns::new(demo, rows.len());
+            c.col::<Int32Array, _>("round", column!(rows, round));
+            c.col::<Int32Array, _>("tick", column!(rows, tick));
+            c.nullable::<UInt64Array, _>("killer_steam_id64", column!(rows, killer_steam_id64));
+            c.nullable::<StringArray, _>("killer_name", column!(rows, killer_name));
+            c.nullable::<UInt8Array, _>("killer_team", column!(rows, killer_team));
+            c.nullable::<StringArray, _>("killer_place", column!(rows, killer_place));
+            c.nullable::<UInt64Array, _>("victim_steam_id64", column!(rows, victim_steam_id64));
+            c.nullable::<StringArray, _>("victim_name", column!(rows, victim_name));
+            c.nullable::<UInt8Array, _>("victim_team", column!(rows, victim_team));
+            c.nullable::<StringArray, _>("victim_place", column!(rows, victim_place));
+            c.nullable::<UInt64Array, _>("assister_steam_id64", column!(rows, assister_steam_id64));
+            c.nullable::<StringArray, _>("assister_name", column!(rows, assister_name));
+            c.col::<StringArray, _>("weapon", column!(rows, weapon));
+            c.col::<BooleanArray, _>("is_headshot", column!(rows, is_headshot));
+            c.col::<Int32Array, _>("penetrated_objects", column!(rows, penetrated_objects));
+            c.col::<BooleanArray, _>("through_smoke", column!(rows, through_smoke));
+            c.col::<BooleanArray, _>("no_scope", column!(rows, no_scope));
+            c.col::<BooleanArray, _>("attacker_blind", column!(rows, attacker_blind));
+            c.col::<BooleanArray, _>("assisted_flash", column!(rows, assisted_flash));
+            c.col::<Float32Array, _>("distance", column!(rows, distance));
+            c.finish()
+        },
+        | Table::Damages => {
+            let rows = &dataset.damages;
+            let mut c = Columns::new(demo, rows.len());
+            c.col::<Int32Array, _>("round", column!(rows, round));
+            c.col::<Int32Array, _>("tick", column!(rows, tick));
+            c.nullable::<UInt64Array, _>("attacker_steam_id64", column!(rows, attacker_steam_id64));
+            c.nullable::<StringArray, _>("attacker_name", column!(rows, attacker_name));
+            c.nullable::<UInt8Array, _>("attacker_team", column!(rows, attacker_team));
+            c.nullable::<UInt64Array, _>("victim_steam_id64", column!(rows, victim_steam_id64));
+            c.nullable::<StringArray, _>("victim_name", column!(rows, victim_name));
+            c.nullable::<UInt8Array, _>("victim_team", column!(rows, victim_team));
+            c.col::<StringArray, _>("weapon", column!(rows, weapon));
+            c.col::<Int32Array, _>("health", column!(rows, health));
+            c.col::<Int32Array, _>("armor", column!(rows, armor));
+            c.col::<Int32Array, _>("health_damage", column!(rows, health_damage));
+            c.col::<Int32Array, _>("armor_damage", column!(rows, armor_damage));
+            c.col::<Int32Array, _>("health_damage_taken", column!(rows, health_damage_taken));
+            c.col::<Int32Array, _>("armor_damage_taken", column!(rows, armor_damage_taken));
+            c.col::<UInt8Array, _>("hit_group", column!(rows, hit_group));
+            c.finish()
+        },
+        | Table::Grenades => {
+            let rows = &dataset.grenades;
+            let mut c = Columns::new(demo, rows.len());
+            c.col::<Int32Array, _>("round", column!(rows, round));
+            c.col::<Int32Array, _>("tick", column!(rows, tick));
+            c.col::<StringArray, _>("event", column!(rows, event));
+            c.col::<StringArray, _>("grenade_type", column!(rows, grenade_type));
+            c.col::<Int32Array, _>("entity_id", column!(rows, entity_id));
+            c.nullable::<UInt64Array, _>("thrower_steam_id64", column!(rows, thrower_steam_id64));
+            c.nullable::<StringArray, _>("thrower_name", column!(rows, thrower_name));
+            c.nullable::<UInt8Array, _>("thrower_team", column!(rows, thrower_team));
+            c.col::<Float64Array, _>("x", column!(rows, x));
+            c.col::<Float64Array, _>("y", column!(rows, y));
+            c.col::<Float64Array, _>("z", column!(rows, z));
+            c.nullable::<StringArray, _>("place", column!(rows, place));
+            c.finish()
+        },
+        | Table::Rounds => {
+            let rows = &dataset.rounds;
+            let mut c = Columns::new(demo, rows.len());
+            c.col::<Int32Array, _>("round", column!(rows, round));
+            c.col::<Int32Array, _>("start_tick", column!(rows, start_tick));
+            c.nullable::<Int32Array, _>("freezetime_end_tick", column!(rows, freezetime_end_tick));
+            c.col::<Int32Array, _>("end_tick", column!(rows, end_tick));
+            c.col::<UInt8Array, _>("winner", column!(rows, winner));
+            c.col::<UInt8Array, _>("reason", column!(rows, reason));
+            c.col::<StringArray, _>("message", column!(rows, message));
+            c.finish()
+        },
+        | Table::Chat => {
+            let rows = &dataset.chat;
+            let mut c = Columns::new(demo, rows.len());
+            c.col::<Int32Array, _>("round", column!(rows, round));
+            c.col::<Int32Array, _>("tick", column!(rows, tick));
+            c.nullable::<UInt64Array, _>("sender_steam_id64", column!(rows, sender_steam_id64));
+            c.nullable::<StringArray, _>("sender_name", column!(rows, sender_name));
+            c.col::<StringArray, _>("text", column!(rows, text));
+            c.col::<BooleanArray, _>("is_chat_all", column!(rows, is_chat_all));
+            c.finish()
+        },
+        | Table::PlayerTicks => {
+            let t = &dataset.ticks;
+            let mut c = Columns::new(demo, t.len());
+            c.col::<Int32Array, _>("tick", t.tick.clone());
+            c.col::<Int32Array, _>("user_id", t.user_id.clone());
+            c.col::<UInt64Array, _>("steam_id64", t.steam_id64.clone());
+            c.col::<StringArray, _>("name", t.name.clone());
+            c.col::<UInt8Array, _>("team", t.team.iter().map(|t| *t as u8).collect());
+            c.sampled::<Float64Array, _>("x", DataType::Float64, &t.x);
+            c.sampled::<Float64Array, _>("y", DataType::Float64, &t.y);
+            c.sampled::<Float64Array, _>("z", DataType::Float64, &t.z);
+            c.sampled::<Float64Array, _>("velocity_x", DataType::Float64, &t.velocity_x);
+            c.sampled::<Float64Array, _>("velocity_y", DataType::Float64, &t.velocity_y);
+            c.sampled::<Float64Array, _>("velocity_z", DataType::Float64, &t.velocity_z);
+            c.sampled::<Float32Array, _>("pitch", DataType::Float32, &t.pitch);
+            c.sampled::<Float32Array, _>("yaw", DataType::Float32, &t.yaw);
+            c.sampled::<Int32Array, _>("health", DataType::Int32, &t.health);
+            c.sampled::<Int32Array, _>("armor", DataType::Int32, &t.armor);
+            c.sampled::<BooleanArray, _>("has_helmet", DataType::Boolean, &t.has_helmet);
+            c.sampled::<BooleanArray, _>("has_defuser", DataType::Boolean, &t.has_defuser);
+            let weapons: Vec<String> = t
+                .active_weapon
+                .iter()
+                .map(|w| w.as_str().to_string())
+                .collect();
+            c.sampled::<StringArray, _>("active_weapon", DataType::Utf8, &weapons);
+            c.sampled::<Int32Array, _>("money", DataType::Int32, &t.money);
+            c.sampled::<Float32Array, _>("flash_duration", DataType::Float32, &t.flash_duration);
+            c.sampled::<BooleanArray, _>("is_alive", DataType::Boolean, &t.is_alive);
+            c.sampled::<BooleanArray, _>("is_scoped", DataType::Boolean, &t.is_scoped);
+            c.sampled::<BooleanArray, _>("is_ducking", DataType::Boolean, &t.is_ducking);
+            c.sampled::<BooleanArray, _>("is_airborne", DataType::Boolean, &t.is_airborne);
+            c.sampled::<StringArray, _>("place_name", DataType::Utf8, &t.place_name);
+            c.finish()
+        },
+    }
+}
+
+/// Writes every table of `dataset` to `<out_dir>/<table>/<demo>.parquet`
+/// and returns the written paths.
+pub fn write_parquet(dataset: &Dataset, out_dir: &Path) -> Result<Vec<PathBuf>, ExportError> {
+    let props = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut written = Vec::new();
+    for table in Table::ALL {
+        let batch = to_record_batch(dataset, table)?;
+        let dir = out_dir.join(table.name());
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.parquet", dataset.demo));
+        let mut writer =
+            ArrowWriter::try_new(File::create(&path)?, batch.schema(), Some(props.clone()))?;
+        writer.write(&batch)?;
+        writer.close()?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Parses the demo at `path` and collects its [`Dataset`]. The demo column
+/// holds the file stem.
+pub fn collect_demo(path: &Path, config: DatasetConfig) -> Result<Dataset, ExportError> {
+    let demo = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut parser = Parser::new(File::open(path)?);
+    let collector = DatasetCollector::register(&mut parser, demo, config);
+    parser.parse_to_end()?;
+    let dataset = collector.lock().unwrap().take_dataset();
+    Ok(dataset)
+}
+
+/// Parses the demo at `path` and writes its tables below `out_dir`.
+pub fn export_demo(
+    path: &Path,
+    out_dir: &Path,
+    config: DatasetConfig,
+) -> Result<Vec<PathBuf>, ExportError> {
+    let dataset = collect_demo(path, config)?;
+    write_parquet(&dataset, out_dir)
+}
+
+/// Outcome of exporting one demo: its path and the written files.
+pub type DemoExport = (PathBuf, Result<Vec<PathBuf>, ExportError>);
+
+/// Exports every `.dem` file in `dir` in parallel and returns the outcome
+/// for each demo.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_dir(
+    dir: &Path,
+    out_dir: &Path,
+    config: DatasetConfig,
+) -> Result<Vec<DemoExport>, ExportError> {
+    use rayon::prelude::*;
+
+    let mut demos = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == "dem") {
+            demos.push(path);
+        }
+    }
+    demos.sort();
+    Ok(demos
+        .into_par_iter()
+        .map(|path| {
+            let res = export_demo(&path, out_dir, config.clone());
+            (path, res)
+        })
+        .collect())
+}
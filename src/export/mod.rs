@@ -0,0 +1,397 @@
+//! Tabular export of a demo's kills, damage, grenades, rounds, chat and
+//! per-tick player state.
+//!
+//! A [`DatasetCollector`] is registered on a [`Parser`] and fills one
+//! [`Dataset`] per demo from the parser's event stream. With the `parquet`
+//! feature enabled the tables can be written as Arrow record batches or
+//! Parquet files, see [`arrow`]. The column layout is documented in
+//! `docs/dataset-schema.md`.
+
+#[cfg(feature = "parquet")]
+pub mod arrow;
+mod scoreboard;
+
+pub use scoreboard::ScoreboardRow;
+
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+
+use crate::common::Player;
+use crate::events;
+use crate::game_state::GameState;
+use crate::parser::Parser;
+use crate::places::Places;
+use crate::sendtables::entity::Vector;
+use crate::ticks::{TickBuffer, TickProperties, TickSampler, TickSamplerConfig};
+
+/// Bumped whenever a column is added, removed or changes type.
+pub const SCHEMA_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct KillRow {
+    pub round: i32,
+    pub tick: i32,
+    pub killer_steam_id64: Option<u64>,
+    pub killer_name: Option<String>,
+    pub killer_team: Option<u8>,
+    pub killer_place: Option<String>,
+    pub victim_steam_id64: Option<u64>,
+    pub victim_name: Option<String>,
+    pub victim_team: Option<u8>,
+    pub victim_place: Option<String>,
+    pub assister_steam_id64: Option<u64>,
+    pub assister_name: Option<String>,
+    pub weapon: String,
+    pub is_headshot: bool,
+    pub penetrated_objects: i32,
+    pub through_smoke: bool,
+    pub no_scope: bool,
+    pub attacker_blind: bool,
+    pub assisted_flash: bool,
+    pub distance: f32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DamageRow {
+    pub round: i32,
+    pub tick: i32,
+    pub attacker_steam_id64: Option<u64>,
+    pub attacker_name: Option<String>,
+    pub attacker_team: Option<u8>,
+    pub victim_steam_id64: Option<u64>,
+    pub victim_name: Option<String>,
+    pub victim_team: Option<u8>,
+    pub weapon: String,
+    pub health: i32,
+    pub armor: i32,
+    pub health_damage: i32,
+    pub armor_damage: i32,
+    pub health_damage_taken: i32,
+    pub armor_damage_taken: i32,
+    pub hit_group: u8,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GrenadeRow {
+    pub round: i32,
+    pub tick: i32,
+    /// One of `flash_explode`, `he_explode`, `smoke_start`, `smoke_expired`,
+    /// `decoy_start`, `decoy_expired`, `inferno_start` and `inferno_expired`.
+    pub event: &'static str,
+    pub grenade_type: String,
+    pub entity_id: i32,
+    pub thrower_steam_id64: Option<u64>,
+    pub thrower_name: Option<String>,
+    pub thrower_team: Option<u8>,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    /// Callout of the position, only known with
+    /// [`DatasetConfig::places`].
+    pub place: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RoundRow {
+    pub round: i32,
+    pub start_tick: i32,
+    /// `None` if the round ended before freezetime did.
+    pub freezetime_end_tick: Option<i32>,
+    pub end_tick: i32,
+    pub winner: u8,
+    pub reason: u8,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChatRow {
+    pub round: i32,
+    pub tick: i32,
+    pub sender_steam_id64: Option<u64>,
+    pub sender_name: Option<String>,
+    pub text: String,
+    pub is_chat_all: bool,
+}
+
+/// All tables collected from one demo.
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    /// Name of the demo the rows came from, written to the `demo` column.
+    pub demo: String,
+    pub kills: Vec<KillRow>,
+    pub damages: Vec<DamageRow>,
+    pub grenades: Vec<GrenadeRow>,
+    pub rounds: Vec<RoundRow>,
+    pub chat: Vec<ChatRow>,
+    pub ticks: TickBuffer,
+}
+
+/// Selects what a [`DatasetCollector`] records.
+#[derive(Debug, Clone)]
+pub struct DatasetConfig {
+    /// Ingame ticks between two player-tick samples. `None` disables the
+    /// player-tick table.
+    pub tick_interval: Option<i32>,
+    pub tick_properties: TickProperties,
+    /// Callouts for the place columns of kills and grenades. Players'
+    /// places come from `m_szLastPlaceName` where the demo has it.
+    pub places: Option<Arc<Places>>,
+}
+
+impl Default for DatasetConfig {
+    fn default() -> Self {
+        Self {
+            tick_interval: Some(16),
+            tick_properties: TickProperties::default(),
+            places: None,
+        }
+    }
+}
+
+fn player_columns(p: Option<&Player>) -> (Option<u64>, Option<String>, Option<u8>) {
+    match p {
+        | Some(p) => (Some(p.steam_id64), Some(p.name.clone()), Some(p.team as u8)),
+        | None => (None, None, None),
+    }
+}
+
+/// Builds a [`Dataset`] from parser events.
+#[derive(Debug, Default)]
+pub struct DatasetCollector {
+    dataset: Dataset,
+    sampler: Option<TickSampler>,
+    pending_round: Option<RoundRow>,
+    places: Option<Arc<Places>>,
+}
+
+impl DatasetCollector {
+    pub fn new(demo: impl Into<String>, config: DatasetConfig) -> Self {
+        Self {
+            dataset: Dataset {
+                demo: demo.into(),
+                ..Default::default()
+            },
+            sampler: config.tick_interval.map(|interval| {
+                TickSampler::new(TickSamplerConfig {
+                    interval,
+                    properties: config.tick_properties,
+                    include_spectators: false,
+                })
+            }),
+            pending_round: None,
+            places: config.places,
+        }
+    }
+
+    /// Registers a collector on `parser`. Take the dataset from the returned
+    /// handle once parsing is done.
+    pub fn register<R: Read>(
+        parser: &mut Parser<R>,
+        demo: impl Into<String>,
+        config: DatasetConfig,
+    ) -> Arc<Mutex<DatasetCollector>> {
+        let collector = Arc::new(Mutex::new(Self::new(demo, config)));
+
+        macro_rules! forward {
+            ($($ty:ty),* $(,)?) => {$(
+                let c = Arc::clone(&collector);
+                parser.register_sync_event_handler::<$ty, _>(move |ev, gs| {
+                    c.lock().unwrap().handle_event(ev, gs)
+                });
+            )*};
+        }
+        forward!(
+            events::FrameDone,
+            events::RoundStart,
+            events::RoundFreezetimeEnd,
+            events::RoundEnd,
+            events::Kill,
+            events::PlayerHurt,
+            events::FlashExplode,
+            events::HeExplode,
+            events::SmokeStart,
+            events::SmokeExpired,
+            events::DecoyStart,
+            events::DecoyExpired,
+            events::FireGrenadeStart,
+            events::FireGrenadeExpired,
+            events::ChatMessage,
+        );
+        collector
+    }
+
+    pub fn dataset(&self) -> &Dataset {
+        &self.dataset
+    }
+
+    /// Returns the collected tables, including the samples of the
+    /// player-tick table, and starts a new dataset for the same demo.
+    pub fn take_dataset(&mut self) -> Dataset {
+        let mut dataset = Dataset {
+            demo: self.dataset.demo.clone(),
+            ..Default::default()
+        };
+        std::mem::swap(&mut dataset, &mut self.dataset);
+        if let Some(sampler) = &mut self.sampler {
+            dataset.ticks = sampler.take_buffer();
+        }
+        dataset
+    }
+
+    fn player_place(&self, state: &GameState, player: Option<&Player>) -> Option<String> {
+        let player = player?;
+        match &self.places {
+            | Some(places) => places.place_of(state.map_name().unwrap_or_default(), player),
+            | None => player.place_name(),
+        }
+    }
+
+    fn place_at(&self, state: &GameState, position: &Vector) -> Option<String> {
+        let places = self.places.as_ref()?;
+        places
+            .place_at(state.map_name().unwrap_or_default(), position)
+            .map(String::from)
+    }
+
+    /// Records `event`, which has already been applied to `state`.
+    pub fn handle_event<E: 'static>(&mut self, event: &E, state: &GameState) {
+        let any = event as &dyn std::any::Any;
+        let tick = state.ingame_tick();
+        // RoundEnd increments the number of rounds played before it reaches
+        // us, every other event belongs to the round in progress.
+        let round = state.total_rounds_played() + 1;
+
+        if any.is::<events::FrameDone>() {
+            if let Some(sampler) = &mut self.sampler {
+                sampler.sample(state);
+            }
+        } else if any.is::<events::RoundStart>() {
+            self.pending_round = Some(RoundRow {
+                round,
+                start_tick: tick,
+                ..Default::default()
+            });
+        } else if any.is::<events::RoundFreezetimeEnd>() {
+            if let Some(r) = &mut self.pending_round {
+                r.freezetime_end_tick = Some(tick);
+            }
+        } else if let Some(re) = any.downcast_ref::<events::RoundEnd>() {
+            let mut row = self.pending_round.take().unwrap_or(RoundRow {
+                start_tick: tick,
+                ..Default::default()
+            });
+            row.round = state.total_rounds_played();
+            row.end_tick = tick;
+            row.winner = re.winner;
+            row.reason = re.reason as u8;
+            row.message = re.message.clone();
+            self.dataset.rounds.push(row);
+        } else if let Some(k) = any.downcast_ref::<events::Kill>() {
+            let (killer_steam_id64, killer_name, killer_team) = player_columns(k.killer.as_ref());
+            let (victim_steam_id64, victim_name, victim_team) = player_columns(k.victim.as_ref());
+            let (assister_steam_id64, assister_name, _) = player_columns(k.assister.as_ref());
+            self.dataset.kills.push(KillRow {
+                round,
+                tick,
+                killer_steam_id64,
+                killer_name,
+                killer_team,
+                killer_place: self.player_place(state, k.killer.as_ref()),
+                victim_steam_id64,
+                victim_name,
+                victim_team,
+                victim_place: self.player_place(state, k.victim.as_ref()),
+                assister_steam_id64,
+                assister_name,
+                weapon: k
+                    .weapon
+                    .as_ref()
+                    .map(|w| w.original_string.clone())
+                    .unwrap_or_default(),
+                is_headshot: k.is_headshot,
+                penetrated_objects: k.penetrated_objects,
+                through_smoke: k.through_smoke,
+                no_scope: k.no_scope,
+                attacker_blind: k.attacker_blind,
+                assisted_flash: k.assisted_flash,
+                distance: k.distance,
+            });
+        } else if let Some(h) = any.downcast_ref::<events::PlayerHurt>() {
+            let (attacker_steam_id64, attacker_name, attacker_team) =
+                player_columns(h.attacker.as_ref());
+            let (victim_steam_id64, victim_name, victim_team) = player_columns(h.player.as_ref());
+            self.dataset.damages.push(DamageRow {
+                round,
+                tick,
+                attacker_steam_id64,
+                attacker_name,
+                attacker_team,
+                victim_steam_id64,
+                victim_name,
+                victim_team,
+                weapon: h.weapon_string.clone(),
+                health: h.health,
+                armor: h.armor,
+                health_damage: h.health_damage,
+                armor_damage: h.armor_damage,
+                health_damage_taken: h.health_damage_taken,
+                armor_damage_taken: h.armor_damage_taken,
+                hit_group: h.hit_group as u8,
+            });
+        } else if let Some((name, g)) = grenade_event(any) {
+            let (thrower_steam_id64, thrower_name, thrower_team) =
+                player_columns(g.thrower.as_ref());
+            self.dataset.grenades.push(GrenadeRow {
+                round,
+                tick,
+                event: name,
+                grenade_type: g.grenade_type.as_str().to_string(),
+                entity_id: g.grenade_entity_id,
+                thrower_steam_id64,
+                thrower_name,
+                thrower_team,
+                x: g.position.x,
+                y: g.position.y,
+                z: g.position.z,
+                place: self.place_at(state, &g.position),
+            });
+        } else if let Some(c) = any.downcast_ref::<events::ChatMessage>() {
+            let (sender_steam_id64, sender_name, _) = player_columns(c.sender.as_ref());
+            self.dataset.chat.push(ChatRow {
+                round,
+                tick,
+                sender_steam_id64,
+                sender_name,
+                text: c.text.clone(),
+                is_chat_all: c.is_chat_all,
+            });
+        }
+    }
+}
+
+fn grenade_event(any: &dyn std::any::Any) -> Option<(&'static str, &events::GrenadeEvent)> {
+    if let Some(e) = any.downcast_ref::<events::FlashExplode>() {
+        Some(("flash_explode", &e.inner))
+    } else if let Some(e) = any.downcast_ref::<events::HeExplode>() {
+        Some(("he_explode", &e.inner))
+    } else if let Some(e) = any.downcast_ref::<events::SmokeStart>() {
+        Some(("smoke_start", &e.inner))
+    } else if let Some(e) = any.downcast_ref::<events::SmokeExpired>() {
+        Some(("smoke_expired", &e.inner))
+    } else if let Some(e) = any.downcast_ref::<events::DecoyStart>() {
+        Some(("decoy_start", &e.inner))
+    } else if let Some(e) = any.downcast_ref::<events::DecoyExpired>() {
+        Some(("decoy_expired", &e.inner))
+    } else if let Some(e) = any.downcast_ref::<events::FireGrenadeStart>() {
+        Some(("inferno_start", &e.inner))
+    } else if let Some(e) = any.downcast_ref::<events::FireGrenadeExpired>() {
+        Some(("inferno_expired", &e.inner))
+    } else {
+        None
+    }
+}
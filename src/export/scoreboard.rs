@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use super::Dataset;
+use crate::common::Team;
+use crate::game_state::GameState;
+
+/// Final statistics of one player, see [`Dataset::scoreboard`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScoreboardRow {
+    pub steam_id64: u64,
+    pub name: String,
+    pub team: u8,
+    pub kills: i32,
+    pub deaths: i32,
+    pub assists: i32,
+    pub headshot_kills: i32,
+    /// Health damage dealt to enemies, capped to the health they had left.
+    pub damage: i32,
+    /// Average damage per round.
+    pub adr: f64,
+}
+
+/// Players are keyed by steam id, bots (which share steam id 0) by name.
+fn key(steam_id64: u64, name: &str) -> (u64, String) {
+    if steam_id64 == 0 {
+        (0, name.to_string())
+    } else {
+        (steam_id64, String::new())
+    }
+}
+
+fn row<'a>(
+    rows: &'a mut HashMap<(u64, String), ScoreboardRow>,
+    steam_id64: Option<u64>,
+    name: &Option<String>,
+    team: Option<u8>,
+) -> &'a mut ScoreboardRow {
+    let steam_id64 = steam_id64.unwrap_or_default();
+    let name = name.clone().unwrap_or_default();
+    let r = rows
+        .entry(key(steam_id64, &name))
+        .or_insert_with(|| ScoreboardRow {
+            steam_id64,
+            name,
+            ..Default::default()
+        });
+    if let Some(team) = team {
+        r.team = team;
+    }
+    r
+}
+
+impl Dataset {
+    /// Builds a scoreboard from the kill and damage tables. Players of
+    /// `state` without any kills or damage are included with zero values.
+    /// Rows are ordered by team, then by kills descending.
+    pub fn scoreboard(&self, state: &GameState) -> Vec<ScoreboardRow> {
+        let mut rows: HashMap<(u64, String), ScoreboardRow> = HashMap::new();
+
+        for k in &self.kills {
+            let same_team = k.killer_team.is_some() && k.killer_team == k.victim_team;
+            let suicide =
+                k.killer_steam_id64 == k.victim_steam_id64 && k.killer_name == k.victim_name;
+            if k.killer_steam_id64.is_some() && !suicide && !same_team {
+                let r = row(
+                    &mut rows,
+                    k.killer_steam_id64,
+                    &k.killer_name,
+                    k.killer_team,
+                );
+                r.kills += 1;
+                if k.is_headshot {
+                    r.headshot_kills += 1;
+                }
+            }
+            if k.victim_steam_id64.is_some() {
+                row(
+                    &mut rows,
+                    k.victim_steam_id64,
+                    &k.victim_name,
+                    k.victim_team,
+                )
+                .deaths += 1;
+            }
+            if k.assister_steam_id64.is_some() {
+                row(&mut rows, k.assister_steam_id64, &k.assister_name, None).assists += 1;
+            }
+        }
+        for d in &self.damages {
+            if d.attacker_steam_id64.is_some() && d.attacker_team != d.victim_team {
+                row(
+                    &mut rows,
+                    d.attacker_steam_id64,
+                    &d.attacker_name,
+                    d.attacker_team,
+                )
+                .damage += d.health_damage_taken;
+            }
+        }
+
+        for p in state.participants().all() {
+            if !matches!(p.team, Team::Terrorists | Team::CounterTerrorists) {
+                continue;
+            }
+            let r = rows
+                .entry(key(p.steam_id64, &p.name))
+                .or_insert_with(|| ScoreboardRow {
+                    steam_id64: p.steam_id64,
+                    name: p.name.clone(),
+                    ..Default::default()
+                });
+            r.team = p.team as u8;
+        }
+
+        let rounds = self.rounds.len().max(1) as f64;
+        let mut rows: Vec<ScoreboardRow> = rows
+            .into_values()
+            .map(|mut r| {
+                r.adr = r.damage as f64 / rounds;
+                r
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            a.team
+                .cmp(&b.team)
+                .then(b.kills.cmp(&a.kills))
+                .then(a.name.cmp(&b.name))
+        });
+        rows
+    }
+}
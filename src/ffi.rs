@@ -0,0 +1,831 @@
+//! C ABI, built with the `ffi` feature. `include/cs_demo_parser.h` declares
+//! everything exported here and has to be regenerated with cbindgen (see
+//! `cbindgen.toml`) whenever this module changes.
+//!
+//! Parsers are opaque handles created by [`cs_demo_parser_open_path`] or
+//! [`cs_demo_parser_open_buffer`] and released with [`cs_demo_parser_free`].
+//! Event callbacks are called synchronously from the parsing thread. Pointers
+//! passed to a callback, including strings and players, are only valid until
+//! the callback returns.
+
+use std::ffi::{CStr, CString, c_char, c_void};
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+
+use crate::common::{EquipmentType, Player};
+use crate::events;
+use crate::parser::{Parser, ParserError};
+
+/// Result of every fallible function.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsDemoError {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidArgument = 2,
+    Io = 3,
+    UnexpectedEndOfDemo = 4,
+    InvalidFileType = 5,
+    GitLfsPointer = 6,
+    NotFound = 7,
+    /// The parser panicked. The handle must not be used afterwards, other
+    /// than to free it.
+    Panic = 8,
+}
+
+impl From<ParserError> for CsDemoError {
+    fn from(e: ParserError) -> Self {
+        match e {
+            | ParserError::UnexpectedEndOfDemo => CsDemoError::UnexpectedEndOfDemo,
+            | ParserError::InvalidFileType => CsDemoError::InvalidFileType,
+            | ParserError::GitLfsPointer => CsDemoError::GitLfsPointer,
+        }
+    }
+}
+
+/// Opaque parser handle.
+pub struct CsDemoParser {
+    parser: Parser<Box<dyn Read + Send>>,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CsDemoVector {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl From<crate::sendtables::entity::Vector> for CsDemoVector {
+    fn from(v: crate::sendtables::entity::Vector) -> Self {
+        Self {
+            x: v.x,
+            y: v.y,
+            z: v.z,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoHeader {
+    pub filestamp: [c_char; 8],
+    pub protocol: i32,
+    pub network_protocol: i32,
+    pub server_name: [c_char; 260],
+    pub client_name: [c_char; 260],
+    pub map_name: [c_char; 260],
+    pub game_directory: [c_char; 260],
+    pub playback_time: f32,
+    pub playback_ticks: i32,
+    pub playback_frames: i32,
+    pub signon_length: i32,
+}
+
+/// State of a player. `name` is NUL terminated and truncated to 127 bytes.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoPlayer {
+    pub user_id: i32,
+    pub entity_id: i32,
+    pub steam_id64: u64,
+    pub name: [c_char; 128],
+    /// 0 unassigned, 1 spectators, 2 terrorists, 3 counter-terrorists.
+    pub team: u8,
+    pub is_bot: bool,
+    pub is_connected: bool,
+    pub is_alive: bool,
+    pub health: i32,
+    pub armor: i32,
+    pub money: i32,
+    pub position: CsDemoVector,
+}
+
+/// Copies `s` into `buf`, truncating it if needed, and NUL terminates it.
+fn copy_str<const N: usize>(buf: &mut [c_char; N], s: &str) {
+    let len = s.len().min(N - 1);
+    for (dst, src) in buf.iter_mut().zip(&s.as_bytes()[..len]) {
+        *dst = *src as c_char;
+    }
+    buf[len] = 0;
+}
+
+impl From<&Player> for CsDemoPlayer {
+    fn from(p: &Player) -> Self {
+        let mut player = Self {
+            user_id: p.user_id,
+            entity_id: p.entity_id,
+            steam_id64: p.steam_id64,
+            name: [0; 128],
+            team: p.team as u8,
+            is_bot: p.is_bot,
+            is_connected: p.is_connected,
+            is_alive: p.is_alive(),
+            health: p.health(),
+            armor: p.armor(),
+            money: p.money(),
+            position: p.position().into(),
+        };
+        copy_str(&mut player.name, &p.name);
+        player
+    }
+}
+
+fn player_ptr(p: &Option<CsDemoPlayer>) -> *const CsDemoPlayer {
+    p.as_ref().map_or(ptr::null(), |p| p as *const _)
+}
+
+/// Strings passed to callbacks. Interior NUL bytes end the string early.
+fn c_string(s: &str) -> CString {
+    let end = s.find('\0').unwrap_or(s.len());
+    CString::new(&s[..end]).unwrap_or_default()
+}
+
+fn weapon_type(weapon: &Option<crate::common::Equipment>) -> EquipmentType {
+    weapon
+        .as_ref()
+        .map_or(EquipmentType::Unknown, |w| w.equipment_type)
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoRoundStart {
+    pub time_limit: i32,
+    pub frag_limit: i32,
+    pub objective: *const c_char,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoRoundEnd {
+    /// Team number of the winner, see [`CsDemoPlayer::team`].
+    pub winner: u8,
+    pub reason: u8,
+    pub message: *const c_char,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoKill {
+    /// `NULL` for world kills.
+    pub killer: *const CsDemoPlayer,
+    pub victim: *const CsDemoPlayer,
+    pub assister: *const CsDemoPlayer,
+    /// Equipment type as numbered by [`EquipmentType`].
+    pub weapon: i32,
+    /// Display name of the weapon, e.g. `"AK-47"`.
+    pub weapon_name: *const c_char,
+    pub is_headshot: bool,
+    pub penetrated_objects: i32,
+    pub through_smoke: bool,
+    pub no_scope: bool,
+    pub attacker_blind: bool,
+    pub assisted_flash: bool,
+    pub distance: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoPlayerHurt {
+    pub player: *const CsDemoPlayer,
+    pub attacker: *const CsDemoPlayer,
+    pub health: i32,
+    pub armor: i32,
+    pub weapon: i32,
+    pub weapon_name: *const c_char,
+    pub health_damage: i32,
+    pub armor_damage: i32,
+    pub health_damage_taken: i32,
+    pub armor_damage_taken: i32,
+    pub hit_group: u8,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoWeaponFire {
+    pub shooter: *const CsDemoPlayer,
+    pub weapon: i32,
+    pub weapon_name: *const c_char,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsDemoGrenadeEventKind {
+    HeExplode,
+    FlashExplode,
+    SmokeStart,
+    SmokeExpired,
+    DecoyStart,
+    DecoyExpired,
+    FireStart,
+    FireExpired,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoGrenadeEvent {
+    pub kind: CsDemoGrenadeEventKind,
+    pub grenade: i32,
+    pub entity_id: i32,
+    pub position: CsDemoVector,
+    pub thrower: *const CsDemoPlayer,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsDemoBombEventKind {
+    PlantBegin,
+    Planted,
+    Defused,
+    Exploded,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsDemoBombsite {
+    Unknown,
+    A,
+    B,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoBombEvent {
+    pub kind: CsDemoBombEventKind,
+    pub player: *const CsDemoPlayer,
+    pub site: CsDemoBombsite,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct CsDemoChatMessage {
+    pub sender: *const CsDemoPlayer,
+    pub text: *const c_char,
+    pub is_chat_all: bool,
+}
+
+/// Event callbacks receive the event, the ingame tick and the `user_data`
+/// they were registered with.
+pub type CsDemoRoundStartCallback =
+    Option<unsafe extern "C" fn(*const CsDemoRoundStart, i32, *mut c_void)>;
+pub type CsDemoRoundEndCallback =
+    Option<unsafe extern "C" fn(*const CsDemoRoundEnd, i32, *mut c_void)>;
+pub type CsDemoKillCallback = Option<unsafe extern "C" fn(*const CsDemoKill, i32, *mut c_void)>;
+pub type CsDemoPlayerHurtCallback =
+    Option<unsafe extern "C" fn(*const CsDemoPlayerHurt, i32, *mut c_void)>;
+pub type CsDemoWeaponFireCallback =
+    Option<unsafe extern "C" fn(*const CsDemoWeaponFire, i32, *mut c_void)>;
+pub type CsDemoGrenadeEventCallback =
+    Option<unsafe extern "C" fn(*const CsDemoGrenadeEvent, i32, *mut c_void)>;
+pub type CsDemoBombEventCallback =
+    Option<unsafe extern "C" fn(*const CsDemoBombEvent, i32, *mut c_void)>;
+pub type CsDemoChatMessageCallback =
+    Option<unsafe extern "C" fn(*const CsDemoChatMessage, i32, *mut c_void)>;
+
+/// The caller's `user_data`. Callbacks are only ever invoked from the thread
+/// that is parsing, so it's up to the caller to make that safe.
+#[derive(Clone, Copy)]
+struct UserData(*mut c_void);
+
+unsafe impl Send for UserData {}
+
+fn guard(f: impl FnOnce() -> CsDemoError) -> CsDemoError {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or(CsDemoError::Panic)
+}
+
+fn open(reader: Box<dyn Read + Send>, out: *mut *mut CsDemoParser) -> CsDemoError {
+    let handle = Box::new(CsDemoParser {
+        parser: Parser::new(reader),
+    });
+    unsafe { *out = Box::into_raw(handle) };
+    CsDemoError::Ok
+}
+
+/// Opens the demo at the NUL terminated, UTF-8 `path` and stores the handle
+/// in `out`.
+///
+/// # Safety
+///
+/// `path` must be a valid C string and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_open_path(
+    path: *const c_char,
+    out: *mut *mut CsDemoParser,
+) -> CsDemoError {
+    if path.is_null() || out.is_null() {
+        return CsDemoError::NullPointer;
+    }
+    guard(|| {
+        let Ok(path) = unsafe { CStr::from_ptr(path) }.to_str() else {
+            return CsDemoError::InvalidArgument;
+        };
+        match File::open(path) {
+            | Ok(file) => open(Box::new(file), out),
+            | Err(_) => CsDemoError::Io,
+        }
+    })
+}
+
+/// Opens a demo from `len` bytes at `data`, which are copied, and stores the
+/// handle in `out`.
+///
+/// # Safety
+///
+/// `data` must be valid for reads of `len` bytes and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_open_buffer(
+    data: *const u8,
+    len: usize,
+    out: *mut *mut CsDemoParser,
+) -> CsDemoError {
+    if data.is_null() || out.is_null() {
+        return CsDemoError::NullPointer;
+    }
+    guard(|| {
+        let data = unsafe { std::slice::from_raw_parts(data, len) }.to_vec();
+        open(Box::new(Cursor::new(data)), out)
+    })
+}
+
+/// Releases a parser. `NULL` is ignored.
+///
+/// # Safety
+///
+/// `parser` must be a handle returned by one of the open functions that
+/// hasn't been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_free(parser: *mut CsDemoParser) {
+    if !parser.is_null() {
+        drop(unsafe { Box::from_raw(parser) });
+    }
+}
+
+/// Parses the header if needed and copies it to `out`.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_header(
+    parser: *mut CsDemoParser,
+    out: *mut CsDemoHeader,
+) -> CsDemoError {
+    let (Some(parser), false) = (unsafe { parser.as_mut() }, out.is_null()) else {
+        return CsDemoError::NullPointer;
+    };
+    guard(|| {
+        let h = match parser.parser.parse_header() {
+            | Ok(h) => h,
+            | Err(e) => return e.into(),
+        };
+        let mut header = CsDemoHeader {
+            filestamp: [0; 8],
+            protocol: h.protocol,
+            network_protocol: h.network_protocol,
+            server_name: [0; 260],
+            client_name: [0; 260],
+            map_name: [0; 260],
+            game_directory: [0; 260],
+            playback_time: h.playback_time,
+            playback_ticks: h.playback_ticks,
+            playback_frames: h.playback_frames,
+            signon_length: h.signon_length,
+        };
+        copy_str(&mut header.filestamp, &h.filestamp);
+        copy_str(&mut header.server_name, &h.server_name);
+        copy_str(&mut header.client_name, &h.client_name);
+        copy_str(&mut header.map_name, &h.map_name);
+        copy_str(&mut header.game_directory, &h.game_directory);
+        unsafe { *out = header };
+        CsDemoError::Ok
+    })
+}
+
+/// Parses the next frame. `more` is set to false once the demo has ended.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle and `more` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_parse_next_frame(
+    parser: *mut CsDemoParser,
+    more: *mut bool,
+) -> CsDemoError {
+    let (Some(parser), false) = (unsafe { parser.as_mut() }, more.is_null()) else {
+        return CsDemoError::NullPointer;
+    };
+    guard(|| match parser.parser.parse_next_frame() {
+        | Ok(m) => {
+            unsafe { *more = m };
+            CsDemoError::Ok
+        },
+        | Err(e) => e.into(),
+    })
+}
+
+/// Parses the demo until the end.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_parse_to_end(parser: *mut CsDemoParser) -> CsDemoError {
+    let Some(parser) = (unsafe { parser.as_mut() }) else {
+        return CsDemoError::NullPointer;
+    };
+    guard(|| match parser.parser.parse_to_end() {
+        | Ok(()) => CsDemoError::Ok,
+        | Err(e) => e.into(),
+    })
+}
+
+/// Fraction of the demo parsed so far, between 0 and 1.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle or `NULL`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_progress(parser: *const CsDemoParser) -> f32 {
+    unsafe { parser.as_ref() }.map_or(0.0, |p| p.parser.progress())
+}
+
+/// Current ingame tick.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle or `NULL`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_ingame_tick(parser: *const CsDemoParser) -> i32 {
+    unsafe { parser.as_ref() }.map_or(0, |p| p.parser.game_state().ingame_tick())
+}
+
+/// Number of rounds played so far.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle or `NULL`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_total_rounds_played(parser: *const CsDemoParser) -> i32 {
+    unsafe { parser.as_ref() }.map_or(0, |p| p.parser.game_state().total_rounds_played())
+}
+
+/// Copies up to `capacity` players to `out`, ordered by user id, and stores
+/// the total number of players in `count`. Pass a `capacity` of 0 to only
+/// query the count.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle, `out` valid for writes of `capacity`
+/// players (or `NULL` if `capacity` is 0) and `count` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_players(
+    parser: *const CsDemoParser,
+    out: *mut CsDemoPlayer,
+    capacity: usize,
+    count: *mut usize,
+) -> CsDemoError {
+    let Some(parser) = (unsafe { parser.as_ref() }) else {
+        return CsDemoError::NullPointer;
+    };
+    if count.is_null() || (out.is_null() && capacity > 0) {
+        return CsDemoError::NullPointer;
+    }
+    guard(|| {
+        let participants = parser.parser.game_state().participants();
+        let mut players = participants.all();
+        players.sort_by_key(|p| p.user_id);
+        for (i, p) in players.iter().take(capacity).enumerate() {
+            unsafe { *out.add(i) = CsDemoPlayer::from(*p) };
+        }
+        unsafe { *count = players.len() };
+        CsDemoError::Ok
+    })
+}
+
+/// Copies the player with `user_id` to `out`.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle and `out` valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_player_by_user_id(
+    parser: *const CsDemoParser,
+    user_id: i32,
+    out: *mut CsDemoPlayer,
+) -> CsDemoError {
+    let (Some(parser), false) = (unsafe { parser.as_ref() }, out.is_null()) else {
+        return CsDemoError::NullPointer;
+    };
+    guard(|| {
+        match parser
+            .parser
+            .game_state()
+            .participants()
+            .by_user_id()
+            .get(&user_id)
+        {
+            | Some(p) => {
+                unsafe { *out = CsDemoPlayer::from(p) };
+                CsDemoError::Ok
+            },
+            | None => CsDemoError::NotFound,
+        }
+    })
+}
+
+/// Static description of an error code.
+#[unsafe(no_mangle)]
+pub extern "C" fn cs_demo_error_message(error: CsDemoError) -> *const c_char {
+    let msg: &'static CStr = match error {
+        | CsDemoError::Ok => c"ok",
+        | CsDemoError::NullPointer => c"unexpected null pointer",
+        | CsDemoError::InvalidArgument => c"invalid argument",
+        | CsDemoError::Io => c"could not read the demo",
+        | CsDemoError::UnexpectedEndOfDemo => c"demo is truncated or corrupt",
+        | CsDemoError::InvalidFileType => c"not a demo file",
+        | CsDemoError::GitLfsPointer => c"file is a Git LFS pointer",
+        | CsDemoError::NotFound => c"not found",
+        | CsDemoError::Panic => c"parser panicked",
+    };
+    msg.as_ptr()
+}
+
+/// Registers `callback` for events of type `E`, converting each event with
+/// `convert` before passing it on.
+fn register<E: 'static, T: 'static>(
+    parser: *mut CsDemoParser,
+    callback: Option<unsafe extern "C" fn(*const T, i32, *mut c_void)>,
+    user_data: *mut c_void,
+    convert: impl Fn(&E, &mut dyn FnMut(&T)) + Send + 'static,
+) -> CsDemoError {
+    let (Some(parser), Some(callback)) = (unsafe { parser.as_mut() }, callback) else {
+        return CsDemoError::NullPointer;
+    };
+    let user_data = UserData(user_data);
+    parser
+        .parser
+        .register_sync_event_handler::<E, _>(move |ev, gs| {
+            let user_data = user_data;
+            let tick = gs.ingame_tick();
+            convert(ev, &mut |c| unsafe { callback(c, tick, user_data.0) });
+        });
+    CsDemoError::Ok
+}
+
+fn player(p: &Option<Player>) -> Option<CsDemoPlayer> {
+    p.as_ref().map(CsDemoPlayer::from)
+}
+
+/// Calls `callback` at the start of every round.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle. `callback` is called with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_on_round_start(
+    parser: *mut CsDemoParser,
+    callback: CsDemoRoundStartCallback,
+    user_data: *mut c_void,
+) -> CsDemoError {
+    register(parser, callback, user_data, |e: &events::RoundStart, f| {
+        let objective = c_string(&e.objective);
+        f(&CsDemoRoundStart {
+            time_limit: e.time_limit,
+            frag_limit: e.frag_limit,
+            objective: objective.as_ptr(),
+        })
+    })
+}
+
+/// Calls `callback` at the end of every round.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle. `callback` is called with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_on_round_end(
+    parser: *mut CsDemoParser,
+    callback: CsDemoRoundEndCallback,
+    user_data: *mut c_void,
+) -> CsDemoError {
+    register(parser, callback, user_data, |e: &events::RoundEnd, f| {
+        let message = c_string(&e.message);
+        f(&CsDemoRoundEnd {
+            winner: e.winner,
+            reason: e.reason as u8,
+            message: message.as_ptr(),
+        })
+    })
+}
+
+/// Calls `callback` for every kill.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle. `callback` is called with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_on_kill(
+    parser: *mut CsDemoParser,
+    callback: CsDemoKillCallback,
+    user_data: *mut c_void,
+) -> CsDemoError {
+    register(parser, callback, user_data, |e: &events::Kill, f| {
+        let (killer, victim, assister) =
+            (player(&e.killer), player(&e.victim), player(&e.assister));
+        let weapon = weapon_type(&e.weapon);
+        let weapon_name = c_string(weapon.as_str());
+        f(&CsDemoKill {
+            killer: player_ptr(&killer),
+            victim: player_ptr(&victim),
+            assister: player_ptr(&assister),
+            weapon: weapon as i32,
+            weapon_name: weapon_name.as_ptr(),
+            is_headshot: e.is_headshot,
+            penetrated_objects: e.penetrated_objects,
+            through_smoke: e.through_smoke,
+            no_scope: e.no_scope,
+            attacker_blind: e.attacker_blind,
+            assisted_flash: e.assisted_flash,
+            distance: e.distance,
+        })
+    })
+}
+
+/// Calls `callback` whenever a player takes damage.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle. `callback` is called with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_on_player_hurt(
+    parser: *mut CsDemoParser,
+    callback: CsDemoPlayerHurtCallback,
+    user_data: *mut c_void,
+) -> CsDemoError {
+    register(parser, callback, user_data, |e: &events::PlayerHurt, f| {
+        let (hurt, attacker) = (player(&e.player), player(&e.attacker));
+        let weapon = weapon_type(&e.weapon);
+        let weapon_name = c_string(weapon.as_str());
+        f(&CsDemoPlayerHurt {
+            player: player_ptr(&hurt),
+            attacker: player_ptr(&attacker),
+            health: e.health,
+            armor: e.armor,
+            weapon: weapon as i32,
+            weapon_name: weapon_name.as_ptr(),
+            health_damage: e.health_damage,
+            armor_damage: e.armor_damage,
+            health_damage_taken: e.health_damage_taken,
+            armor_damage_taken: e.armor_damage_taken,
+            hit_group: e.hit_group as u8,
+        })
+    })
+}
+
+/// Calls `callback` for every shot and grenade throw.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle. `callback` is called with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_on_weapon_fire(
+    parser: *mut CsDemoParser,
+    callback: CsDemoWeaponFireCallback,
+    user_data: *mut c_void,
+) -> CsDemoError {
+    register(parser, callback, user_data, |e: &events::WeaponFire, f| {
+        let shooter = player(&e.shooter);
+        let weapon = weapon_type(&e.weapon);
+        let weapon_name = c_string(weapon.as_str());
+        f(&CsDemoWeaponFire {
+            shooter: player_ptr(&shooter),
+            weapon: weapon as i32,
+            weapon_name: weapon_name.as_ptr(),
+        })
+    })
+}
+
+fn grenade_event(
+    kind: CsDemoGrenadeEventKind,
+    e: &events::GrenadeEvent,
+    f: &mut dyn FnMut(&CsDemoGrenadeEvent),
+) {
+    let thrower = player(&e.thrower);
+    f(&CsDemoGrenadeEvent {
+        kind,
+        grenade: e.grenade_type as i32,
+        entity_id: e.grenade_entity_id,
+        position: e.position.clone().into(),
+        thrower: player_ptr(&thrower),
+    })
+}
+
+/// Calls `callback` for detonations and expirations of every grenade type.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle. `callback` is called with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_on_grenade_event(
+    parser: *mut CsDemoParser,
+    callback: CsDemoGrenadeEventCallback,
+    user_data: *mut c_void,
+) -> CsDemoError {
+    use CsDemoGrenadeEventKind as Kind;
+
+    macro_rules! grenade {
+        ($($ty:ident => $kind:ident),* $(,)?) => {$(
+            let res = register(parser, callback, user_data, |e: &events::$ty, f| {
+                grenade_event(Kind::$kind, &e.inner, f)
+            });
+            if res != CsDemoError::Ok {
+                return res;
+            }
+        )*};
+    }
+    grenade!(
+        HeExplode => HeExplode,
+        FlashExplode => FlashExplode,
+        SmokeStart => SmokeStart,
+        SmokeExpired => SmokeExpired,
+        DecoyStart => DecoyStart,
+        DecoyExpired => DecoyExpired,
+        FireGrenadeStart => FireStart,
+        FireGrenadeExpired => FireExpired,
+    );
+    CsDemoError::Ok
+}
+
+fn bomb_event(
+    kind: CsDemoBombEventKind,
+    e: &events::BombEvent,
+    f: &mut dyn FnMut(&CsDemoBombEvent),
+) {
+    let p = player(&e.player);
+    f(&CsDemoBombEvent {
+        kind,
+        player: player_ptr(&p),
+        site: match e.site {
+            | events::Bombsite::Unknown => CsDemoBombsite::Unknown,
+            | events::Bombsite::A => CsDemoBombsite::A,
+            | events::Bombsite::B => CsDemoBombsite::B,
+        },
+    })
+}
+
+/// Calls `callback` when a plant begins, and when the bomb is planted,
+/// defused or explodes.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle. `callback` is called with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_on_bomb_event(
+    parser: *mut CsDemoParser,
+    callback: CsDemoBombEventCallback,
+    user_data: *mut c_void,
+) -> CsDemoError {
+    use CsDemoBombEventKind as Kind;
+
+    macro_rules! bomb {
+        ($($ty:ident => $kind:ident),* $(,)?) => {$(
+            let res = register(parser, callback, user_data, |e: &events::$ty, f| {
+                bomb_event(Kind::$kind, &e.inner, f)
+            });
+            if res != CsDemoError::Ok {
+                return res;
+            }
+        )*};
+    }
+    bomb!(
+        BombPlantBegin => PlantBegin,
+        BombPlanted => Planted,
+        BombDefused => Defused,
+        BombExplode => Exploded,
+    );
+    CsDemoError::Ok
+}
+
+/// Calls `callback` for every chat message.
+///
+/// # Safety
+///
+/// `parser` must be a valid handle. `callback` is called with `user_data`.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn cs_demo_parser_on_chat_message(
+    parser: *mut CsDemoParser,
+    callback: CsDemoChatMessageCallback,
+    user_data: *mut c_void,
+) -> CsDemoError {
+    register(parser, callback, user_data, |e: &events::ChatMessage, f| {
+        let sender = player(&e.sender);
+        let text = c_string(&e.text);
+        f(&CsDemoChatMessage {
+            sender: player_ptr(&sender),
+            text: text.as_ptr(),
+            is_chat_all: e.is_chat_all,
+        })
+    })
+}
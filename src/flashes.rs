@@ -0,0 +1,262 @@
+//! Effectiveness of flashbangs: who each one blinded, for how long, and
+//! whether the blinded players died before they could see again.
+//!
+//! `player_blind` events are sent in the tick a flashbang detonates, before
+//! or after `flashbang_detonate`. Victims are attributed to the flashbang
+//! by the projectile's entity id and otherwise by thrower, so two flashbangs
+//! popping in the same tick are told apart.
+
+use std::collections::BTreeMap;
+
+use crate::common::{Player, PlayerRef, Team};
+use crate::events::{GrenadeEvent, Kill, PlayerFlashed};
+use crate::sendtables::entity::Vector;
+use crate::sendtables2::Entity;
+
+/// A kill of a player who was still blind.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlindKill {
+    pub tick: i32,
+    pub killer: Option<PlayerRef>,
+    /// The thrower of the flashbang was credited with a flash assist.
+    pub flash_assist: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlashVictim {
+    pub player: PlayerRef,
+    pub team: Team,
+    /// On the thrower's team, including the thrower.
+    pub teammate: bool,
+    /// Seconds, from `m_flFlashDuration` of the victim's pawn when available.
+    pub duration: f32,
+    pub killed_while_blind: Option<BlindKill>,
+}
+
+/// A detonated flashbang and the players it blinded.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FlashResult {
+    pub tick: i32,
+    pub position: Vector,
+    pub thrower: Option<PlayerRef>,
+    pub thrower_team: Team,
+    pub entity_id: i32,
+    pub victims: Vec<FlashVictim>,
+}
+
+impl FlashResult {
+    pub fn enemies(&self) -> impl Iterator<Item = &FlashVictim> {
+        self.victims.iter().filter(|v| !v.teammate)
+    }
+
+    pub fn teammates(&self) -> impl Iterator<Item = &FlashVictim> {
+        self.victims.iter().filter(|v| v.teammate)
+    }
+
+    /// Seconds enemies were blinded for in total.
+    pub fn enemy_blind_time(&self) -> f32 {
+        self.enemies().map(|v| v.duration).sum()
+    }
+}
+
+/// Flashbang statistics of one thrower, see [`Flashes::player_stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerFlashStats {
+    pub player: PlayerRef,
+    pub flashes: usize,
+    pub enemies_flashed: usize,
+    pub teammates_flashed: usize,
+    pub enemy_blind_time: f32,
+    pub teammate_blind_time: f32,
+    /// Blinded enemies who died before they could see again.
+    pub blind_kills: usize,
+    pub flash_assists: usize,
+}
+
+impl PlayerFlashStats {
+    /// Share of the players blinded by the thrower who were teammates.
+    pub fn team_flash_rate(&self) -> f64 {
+        let blinded = self.enemies_flashed + self.teammates_flashed;
+        if blinded == 0 {
+            return 0.0;
+        }
+        self.teammates_flashed as f64 / blinded as f64
+    }
+
+    pub fn enemies_flashed_per_flash(&self) -> f64 {
+        self.enemies_flashed as f64 / self.flashes.max(1) as f64
+    }
+}
+
+/// A `player_blind` that couldn't be attributed to a detonation yet.
+#[derive(Debug, Clone)]
+struct PendingVictim {
+    tick: i32,
+    attacker: Option<i32>,
+    entity_id: Option<i32>,
+    victim: FlashVictim,
+}
+
+/// All flashbang detonations of a demo. Owned by
+/// [`crate::game_state::GameState`], see its `flashes` accessor.
+#[derive(Debug, Clone, Default)]
+pub struct Flashes {
+    results: Vec<FlashResult>,
+    pending: Vec<PendingVictim>,
+}
+
+impl Flashes {
+    pub fn results(&self) -> &[FlashResult] {
+        &self.results
+    }
+
+    /// Statistics of every player who threw a flashbang, by user id.
+    pub fn player_stats(&self) -> Vec<PlayerFlashStats> {
+        let mut stats: BTreeMap<i32, PlayerFlashStats> = BTreeMap::new();
+        for result in &self.results {
+            let Some(thrower) = &result.thrower else {
+                continue;
+            };
+            let s = stats
+                .entry(thrower.user_id)
+                .or_insert_with(|| PlayerFlashStats {
+                    player: thrower.clone(),
+                    ..Default::default()
+                });
+            s.flashes += 1;
+            for v in &result.victims {
+                if v.teammate {
+                    s.teammates_flashed += 1;
+                    s.teammate_blind_time += v.duration;
+                    continue;
+                }
+                s.enemies_flashed += 1;
+                s.enemy_blind_time += v.duration;
+                if let Some(kill) = &v.killed_while_blind {
+                    s.blind_kills += 1;
+                    s.flash_assists += kill.flash_assist as usize;
+                }
+            }
+        }
+        stats.into_values().collect()
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.results.clear();
+        self.pending.clear();
+    }
+
+    pub(crate) fn detonated(&mut self, tick: i32, ev: &GrenadeEvent) {
+        let thrower_team = ev.thrower.as_ref().map(|p| p.team).unwrap_or_default();
+        let attacker = ev.thrower.as_ref().map(|p| p.user_id);
+        let mut result = FlashResult {
+            tick,
+            position: ev.position.clone(),
+            thrower: ev.thrower.as_ref().map(PlayerRef::from),
+            thrower_team,
+            entity_id: ev.grenade_entity_id,
+            victims: Vec::new(),
+        };
+        let entity_id = ev.grenade_entity_id;
+        let (mine, others) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|p| match p.entity_id {
+                | Some(id) => id == entity_id,
+                | None => p.attacker.is_some() && p.attacker == attacker,
+            });
+        self.pending = others;
+        for mut p in mine {
+            p.victim.teammate = p.victim.team == thrower_team;
+            result.victims.push(p.victim);
+        }
+        self.results.push(result);
+    }
+
+    /// `duration` is the victim's [`flash_duration`].
+    pub(crate) fn player_flashed(
+        &mut self,
+        tick: i32,
+        victim: &Player,
+        ev: &PlayerFlashed,
+        duration: f32,
+    ) {
+        let attacker = ev.attacker.as_ref().map(|p| p.user_id);
+        let entity_id = ev
+            .projectile
+            .as_ref()
+            .and_then(|p| p.entity)
+            .map(|e| e.index);
+        let victim = FlashVictim {
+            player: PlayerRef::from(victim),
+            team: victim.team,
+            duration,
+            ..Default::default()
+        };
+
+        let detonated = self.results.iter_mut().rev().find(|r| {
+            r.tick == tick
+                && match entity_id {
+                    | Some(id) => id == r.entity_id,
+                    | None => {
+                        attacker.is_some() && attacker == r.thrower.as_ref().map(|t| t.user_id)
+                    },
+                }
+        });
+        match detonated {
+            | Some(r) => {
+                let teammate = victim.team == r.thrower_team;
+                r.victims.push(FlashVictim { teammate, ..victim });
+            },
+            | None => self.pending.push(PendingVictim {
+                tick,
+                attacker,
+                entity_id,
+                victim,
+            }),
+        }
+    }
+
+    /// Marks the victim of `kill` if they were still blind. `tick_rate` is
+    /// the number of ticks per second.
+    pub(crate) fn killed(&mut self, tick: i32, tick_rate: f64, kill: &Kill) {
+        let Some(victim) = &kill.victim else {
+            return;
+        };
+        let blind = self.results.iter_mut().rev().find_map(|r| {
+            let elapsed = (tick - r.tick) as f64 / tick_rate;
+            let thrower = r.thrower.as_ref().map(|t| t.user_id);
+            r.victims
+                .iter_mut()
+                .find(|v| v.player.user_id == victim.user_id && elapsed <= v.duration as f64)
+                .map(|v| (v, thrower))
+        });
+        let Some((v, thrower)) = blind else {
+            return;
+        };
+        let assister = kill.assister.as_ref().map(|p| p.user_id);
+        v.killed_while_blind = Some(BlindKill {
+            tick,
+            killer: kill.killer.as_ref().map(PlayerRef::from),
+            flash_assist: kill.assisted_flash && assister.is_some() && assister == thrower,
+        });
+    }
+
+    /// Drops `player_blind` events whose flashbang never detonated.
+    pub(crate) fn frame_done(&mut self, tick: i32) {
+        self.pending.retain(|p| p.tick >= tick);
+    }
+}
+
+/// Seconds a player is blinded for: `m_flFlashDuration` of their Source 2
+/// `pawn` where the demo has entities, otherwise the duration of the
+/// `player_blind` event.
+pub(crate) fn flash_duration(pawn: Option<&Entity>, ev: &PlayerFlashed) -> f32 {
+    pawn.and_then(|e| e.property_value("m_flFlashDuration"))
+        .map(|v| v.float_val)
+        .filter(|d| *d > 0.0)
+        .unwrap_or(ev.flash_duration)
+}
@@ -10,6 +10,131 @@ use crate::parser::Parser;
 
 struct Descriptor {
     name: String,
+    keys: Vec<String>,
+}
+
+/// Values of a single game event, addressed by the key names of its
+/// descriptor.
+struct EventKeys<'a> {
+    values: HashMap<&'a str, &'a msg::csvc_msg_game_event::KeyT>,
+}
+
+impl<'a> EventKeys<'a> {
+    fn new(desc: &'a Descriptor, event: &'a msg::CsvcMsgGameEvent) -> Self {
+        Self {
+            values: desc
+                .keys
+                .iter()
+                .map(String::as_str)
+                .zip(&event.keys)
+                .collect(),
+        }
+    }
+
+    fn int(&self, name: &str) -> i32 {
+        self.values
+            .get(name)
+            .and_then(|k| k.val_long.or(k.val_short).or(k.val_byte))
+            .unwrap_or(0)
+    }
+
+    fn string(&self, name: &str) -> String {
+        self.values
+            .get(name)
+            .and_then(|k| k.val_string.clone())
+            .unwrap_or_default()
+    }
+
+    fn float(&self, name: &str) -> f32 {
+        self.values
+            .get(name)
+            .and_then(|k| k.val_float)
+            .unwrap_or(0.0)
+    }
+
+    /// Looks up the player whose user id is stored under `name`.
+    fn player<R: Read>(&self, parser: &Parser<R>, name: &str) -> Option<events::Player> {
+        parser
+            .game_state()
+            .participants()
+            .by_user_id()
+            .get(&self.int(name))
+            .cloned()
+    }
+
+    /// Builds the equipment named by the string stored under `name`.
+    fn equipment(&self, name: &str) -> Option<events::Equipment> {
+        let item = self.string(name);
+        if item.is_empty() {
+            return None;
+        }
+        Some(events::Equipment {
+            equipment_type: crate::common::map_equipment(&item),
+            original_string: item,
+            ..Default::default()
+        })
+    }
+
+    fn grenade_event<R: Read>(
+        &self,
+        parser: &Parser<R>,
+        grenade_type: crate::common::EquipmentType,
+    ) -> events::GrenadeEvent {
+        events::GrenadeEvent {
+            grenade_type,
+            grenade: None,
+            position: crate::sendtables::entity::Vector {
+                x: self.float("x") as f64,
+                y: self.float("y") as f64,
+                z: self.float("z") as f64,
+            },
+            thrower: self.player(parser, "userid"),
+            grenade_entity_id: self.int("entityid"),
+        }
+    }
+
+    /// Builds a bomb event, resolving the `site` key through the map's bomb
+    /// target entities. Warns if that fails, unless
+    /// [`ParserConfig::ignore_bombsite_index_not_found`](crate::parser::ParserConfig::ignore_bombsite_index_not_found)
+    /// is set.
+    fn bomb_event<R: Read>(&self, parser: &mut Parser<R>) -> events::BombEvent {
+        let index = self.int("site");
+        let bomb = &parser.game_state().bomb;
+        let mut site = bomb.site(index);
+        if site == events::Bombsite::Unknown {
+            // Source 1 demos without entities, or the plant started before
+            // the demo; the planted bomb knows its site.
+            site = bomb.planted().map(|p| p.site).unwrap_or_default();
+        }
+        if site == events::Bombsite::Unknown && !parser.config().ignore_bombsite_index_not_found {
+            parser.dispatch_event(events::ParserWarn {
+                message: format!("bombsite with index {index} not found"),
+                r#type: events::WarnType::BombsiteUnknown,
+            });
+        }
+        events::BombEvent {
+            player: self.player(parser, "userid"),
+            site,
+        }
+    }
+
+    /// The hostage whose entity index is stored under `hostage`. Only the
+    /// index is known without the hostage entities.
+    fn hostage<R: Read>(&self, parser: &Parser<R>) -> Option<events::Hostage> {
+        let index = self.int("hostage");
+        let hostage = parser.game_state().hostages.get(&index).cloned();
+        Some(hostage.unwrap_or_else(|| events::Hostage {
+            entity_id: index,
+            ..Default::default()
+        }))
+    }
+
+    fn bool(&self, name: &str) -> bool {
+        self.values
+            .get(name)
+            .and_then(|k| k.val_bool)
+            .unwrap_or(false)
+    }
 }
 
 #[derive(Default)]
@@ -28,8 +153,18 @@ impl GameEventHandler {
         self.descriptors.clear();
         for desc in &list.descriptors {
             if let (Some(id), Some(name)) = (desc.eventid, desc.name.as_ref()) {
-                self.descriptors
-                    .insert(id, Descriptor { name: name.clone() });
+                let keys = desc
+                    .keys
+                    .iter()
+                    .map(|k| k.name.clone().unwrap_or_default())
+                    .collect();
+                self.descriptors.insert(
+                    id,
+                    Descriptor {
+                        name: name.clone(),
+                        keys,
+                    },
+                );
             }
         }
     }
@@ -51,14 +186,15 @@ impl GameEventHandler {
             | Some(d) => d,
             | None => return,
         };
+        let keys = EventKeys::new(desc, event);
 
         match desc.name.as_str() {
             | "begin_new_match" => parser.dispatch_event(events::MatchStart),
             | "round_start" => parser.dispatch_event(events::RoundStart::default()),
             | "round_end" => parser.dispatch_event(events::RoundEnd {
-                message: String::new(),
-                reason: events::RoundEndReason::StillInProgress,
-                winner: 0,
+                message: keys.string("message"),
+                reason: events::RoundEndReason::from(keys.int("reason") as u8),
+                winner: keys.int("winner") as events::Team,
                 winner_state: None,
                 loser_state: None,
             }),
@@ -102,67 +238,80 @@ impl GameEventHandler {
             | "player_given_c4" => parser.dispatch_event(events::PlayerGivenC4 { player: None }),
             | "player_jump" => parser.dispatch_event(events::PlayerJump { player: None }),
             | "player_footstep" => parser.dispatch_event(events::Footstep { player: None }),
-            | "flashbang_detonate" => parser.dispatch_event(events::FlashExplode {
-                inner: events::GrenadeEvent::default(),
-            }),
-            | "hegrenade_detonate" => parser.dispatch_event(events::HeExplode {
-                inner: events::GrenadeEvent::default(),
-            }),
-            | "decoy_started" => parser.dispatch_event(events::DecoyStart {
-                inner: events::GrenadeEvent::default(),
-            }),
-            | "decoy_detonate" => parser.dispatch_event(events::DecoyExpired {
-                inner: events::GrenadeEvent::default(),
-            }),
-            | "smokegrenade_detonate" => parser.dispatch_event(events::SmokeStart {
-                inner: events::GrenadeEvent::default(),
-            }),
-            | "smokegrenade_expired" => parser.dispatch_event(events::SmokeExpired {
-                inner: events::GrenadeEvent::default(),
-            }),
-            | "inferno_startburn" => parser.dispatch_event(events::FireGrenadeStart {
-                inner: events::GrenadeEvent::default(),
-            }),
-            | "inferno_expire" => parser.dispatch_event(events::FireGrenadeExpired {
-                inner: events::GrenadeEvent::default(),
-            }),
-            | "bomb_beginplant" => parser.dispatch_event(events::BombPlantBegin {
-                inner: events::BombEvent {
-                    player: None,
-                    site: events::Bombsite::Unknown,
-                },
+            | "flashbang_detonate" => {
+                let inner = keys.grenade_event(parser, crate::common::EquipmentType::Flash);
+                parser.dispatch_event(events::FlashExplode { inner })
+            },
+            | "hegrenade_detonate" => {
+                let inner = keys.grenade_event(parser, crate::common::EquipmentType::He);
+                parser.dispatch_event(events::HeExplode { inner })
+            },
+            | "decoy_started" => {
+                let inner = keys.grenade_event(parser, crate::common::EquipmentType::Decoy);
+                parser.dispatch_event(events::DecoyStart { inner })
+            },
+            | "decoy_detonate" => {
+                let inner = keys.grenade_event(parser, crate::common::EquipmentType::Decoy);
+                parser.dispatch_event(events::DecoyExpired { inner })
+            },
+            | "smokegrenade_detonate" => {
+                let inner = keys.grenade_event(parser, crate::common::EquipmentType::Smoke);
+                parser.dispatch_event(events::SmokeStart { inner })
+            },
+            | "smokegrenade_expired" => {
+                let inner = keys.grenade_event(parser, crate::common::EquipmentType::Smoke);
+                parser.dispatch_event(events::SmokeExpired { inner })
+            },
+            | "inferno_startburn" => {
+                let inner = keys.grenade_event(parser, crate::common::EquipmentType::Incendiary);
+                parser.dispatch_event(events::FireGrenadeStart { inner })
+            },
+            | "inferno_expire" => {
+                let inner = keys.grenade_event(parser, crate::common::EquipmentType::Incendiary);
+                parser.dispatch_event(events::FireGrenadeExpired { inner })
+            },
+            | "bomb_beginplant" => {
+                let inner = keys.bomb_event(parser);
+                parser.dispatch_event(events::BombPlantBegin { inner })
+            },
+            | "bomb_abortplant" => parser.dispatch_event(events::BombPlantAborted {
+                player: keys.player(parser, "userid"),
             }),
             | "bomb_begindefuse" => parser.dispatch_event(events::BombDefuseStart {
-                player: None,
-                has_kit: false,
-            }),
-            | "bomb_defused" => parser.dispatch_event(events::BombDefused {
-                inner: events::BombEvent {
-                    player: None,
-                    site: events::Bombsite::Unknown,
-                },
+                player: keys.player(parser, "userid"),
+                has_kit: keys.bool("haskit"),
             }),
-            | "bomb_exploded" => parser.dispatch_event(events::BombExplode {
-                inner: events::BombEvent {
-                    player: None,
-                    site: events::Bombsite::Unknown,
-                },
+            | "bomb_abortdefuse" => parser.dispatch_event(events::BombDefuseAborted {
+                player: keys.player(parser, "userid"),
             }),
+            | "bomb_defused" => {
+                let inner = keys.bomb_event(parser);
+                parser.dispatch_event(events::BombDefused { inner })
+            },
+            | "bomb_exploded" => {
+                let inner = keys.bomb_event(parser);
+                parser.dispatch_event(events::BombExplode { inner })
+            },
             | "bomb_dropped" => parser.dispatch_event(events::BombDropped {
-                player: None,
-                entity_id: 0,
+                player: keys.player(parser, "userid"),
+                entity_id: keys.int("entindex"),
             }),
-            | "bomb_pickup" => parser.dispatch_event(events::BombPickup { player: None }),
-            | "bomb_planted" => parser.dispatch_event(events::BombPlanted {
-                inner: events::BombEvent {
-                    player: None,
-                    site: events::Bombsite::Unknown,
-                },
+            | "bomb_pickup" => parser.dispatch_event(events::BombPickup {
+                player: keys.player(parser, "userid"),
             }),
+            | "bomb_planted" => {
+                let inner = keys.bomb_event(parser);
+                parser.dispatch_event(events::BombPlanted { inner })
+            },
             | "bomb_beep" => parser.dispatch_event(events::BombBeep {
                 inner: events::BombEvent {
                     player: None,
-                    site: events::Bombsite::Unknown,
+                    site: parser
+                        .game_state()
+                        .bomb
+                        .planted()
+                        .map(|p| p.site)
+                        .unwrap_or_default(),
                 },
             }),
             | "announce_phase_end" => parser.dispatch_event(events::AnnouncePhaseEnd),
@@ -189,11 +338,35 @@ impl GameEventHandler {
             | "hltv_status" => parser.dispatch_event(events::HltvStatus),
             | "hltv_title" => parser.dispatch_event(events::HltvTitle),
             | "hltv_versioninfo" => parser.dispatch_event(events::HltvVersionInfo),
-            | "hostage_follows" => parser.dispatch_event(events::HostageFollows),
+            | "hostage_follows" => {
+                let player = keys.player(parser, "userid");
+                parser.dispatch_event(events::HostageFollows {
+                    player: player.clone(),
+                    hostage: keys.hostage(parser),
+                });
+                parser.hostage_game_event(
+                    keys.int("hostage"),
+                    events::HostageState::FollowingPlayer,
+                    player,
+                );
+            },
             | "hostname_changed" => parser.dispatch_event(events::HostnameChanged),
             | "jointeam_failed" => parser.dispatch_event(events::JoinTeamFailed),
             | "other_death" => parser.dispatch_event(events::OtherDeath),
-            | "player_blind" => parser.dispatch_event(events::PlayerBlind),
+            | "player_blind" => {
+                parser.dispatch_event(events::PlayerBlind);
+                let projectile = parser
+                    .game_state()
+                    .grenade_projectiles()
+                    .get(&keys.int("entityid"))
+                    .cloned();
+                parser.dispatch_event(events::PlayerFlashed {
+                    player: keys.player(parser, "userid"),
+                    attacker: keys.player(parser, "attacker"),
+                    projectile,
+                    flash_duration: keys.float("blind_duration"),
+                })
+            },
             | "bot_takeover" => parser.dispatch_event(events::BotTakenOver { taker: None }),
             | "bullet_damage" => parser.dispatch_event(events::BulletDamage {
                 attacker: None,
@@ -218,16 +391,30 @@ impl GameEventHandler {
             | "switch_team" => parser.dispatch_event(events::SwitchTeam),
             | "weapon_fire_on_empty" => parser.dispatch_event(events::WeaponFireOnEmpty),
             | "weapon_fire" => parser.dispatch_event(events::WeaponFire {
-                shooter: None,
-                weapon: None,
+                shooter: keys.player(parser, "userid"),
+                weapon: keys.equipment("weapon"),
             }),
             | "weapon_reload" => parser.dispatch_event(events::WeaponReload { player: None }),
             | "weapon_zoom" => parser.dispatch_event(events::WeaponZoom),
             | "weapon_zoom_rifle" => parser.dispatch_event(events::WeaponZoomRifle),
             | "ammo_pickup" => parser.dispatch_event(events::AmmoPickup),
             | "item_equip" => parser.dispatch_event(events::ItemEquip),
-            | "item_pickup" => parser.dispatch_event(events::ItemPickup),
+            | "item_pickup" => {
+                let player = keys.player(parser, "userid");
+                parser.dispatch_event(events::ItemPickup {
+                    player,
+                    weapon: keys.equipment("item"),
+                    silent: keys.bool("silent"),
+                })
+            },
             | "item_pickup_slerp" => parser.dispatch_event(events::ItemPickupSlerp),
+            | "item_purchase" => {
+                let player = keys.player(parser, "userid");
+                parser.dispatch_event(events::ItemPurchase {
+                    player,
+                    weapon: keys.equipment("weapon"),
+                })
+            },
             | "item_remove" => parser.dispatch_event(events::ItemDrop {
                 player: None,
                 weapon: None,
@@ -237,45 +424,71 @@ impl GameEventHandler {
             | "vote_cast" => parser.dispatch_event(events::VoteCast),
             | "tournament_reward" => parser.dispatch_event(events::TournamentReward),
             | "hostage_hurt" => parser.dispatch_event(events::HostageHurt {
-                player: None,
-                hostage: None,
-            }),
-            | "hostage_killed" => parser.dispatch_event(events::HostageKilled {
-                killer: None,
-                hostage: None,
-            }),
-            | "hostage_rescued" => parser.dispatch_event(events::HostageRescued {
-                player: None,
-                hostage: None,
+                player: keys.player(parser, "userid"),
+                hostage: keys.hostage(parser),
             }),
+            | "hostage_killed" => {
+                parser.dispatch_event(events::HostageKilled {
+                    killer: keys.player(parser, "userid"),
+                    hostage: keys.hostage(parser),
+                });
+                let index = keys.int("hostage");
+                parser.hostage_game_event(index, events::HostageState::Dead, None);
+            },
+            | "hostage_rescued" => {
+                parser.dispatch_event(events::HostageRescued {
+                    player: keys.player(parser, "userid"),
+                    hostage: keys.hostage(parser),
+                });
+                let index = keys.int("hostage");
+                parser.hostage_game_event(index, events::HostageState::Rescued, None);
+            },
             | "hostage_rescued_all" => parser.dispatch_event(events::HostageRescuedAll),
             | "player_activate" => parser.dispatch_event(events::PlayerActivate),
-            | "player_death" => parser.dispatch_event(events::Kill {
-                weapon: None,
-                victim: None,
-                killer: None,
-                assister: None,
-                penetrated_objects: 0,
-                is_headshot: false,
-                assisted_flash: false,
-                attacker_blind: false,
-                no_scope: false,
-                through_smoke: false,
-                distance: 0.0,
-            }),
-            | "player_hurt" => parser.dispatch_event(events::PlayerHurt {
-                player: None,
-                attacker: None,
-                health: 0,
-                armor: 0,
-                weapon: None,
-                weapon_string: String::new(),
-                health_damage: 0,
-                armor_damage: 0,
-                health_damage_taken: 0,
-                armor_damage_taken: 0,
-                hit_group: events::HitGroup::Generic,
-            }),
+            | "player_death" => {
+                let kill = events::Kill {
+                    weapon: keys.equipment("weapon"),
+                    victim: keys.player(parser, "userid"),
+                    killer: keys.player(parser, "attacker"),
+                    assister: keys.player(parser, "assister"),
+                    penetrated_objects: keys.int("penetrated"),
+                    is_headshot: keys.bool("headshot"),
+                    assisted_flash: keys.bool("assistedflash"),
+                    attacker_blind: keys.bool("attackerblind"),
+                    no_scope: keys.bool("noscope"),
+                    through_smoke: keys.bool("thrusmoke"),
+                    distance: keys.float("distance"),
+                };
+                parser.dispatch_event(kill)
+            },
+            | "player_hurt" => {
+                let player = keys.player(parser, "userid");
+                let health = keys.int("health");
+                let armor = keys.int("armor");
+                let health_damage = keys.int("dmg_health");
+                let armor_damage = keys.int("dmg_armor");
+                // The event reports the damage dealt, which can exceed what
+                // the victim had left before the hit.
+                let taken = |dealt: i32, before: Option<i32>| {
+                    before.filter(|v| *v > 0).map_or(dealt, |v| dealt.min(v))
+                };
+                let health_taken = taken(health_damage, player.as_ref().map(|p| p.health()));
+                let armor_taken = taken(armor_damage, player.as_ref().map(|p| p.armor()));
+                let hurt = events::PlayerHurt {
+                    attacker: keys.player(parser, "attacker"),
+                    health,
+                    armor,
+                    weapon: keys.equipment("weapon"),
+                    weapon_string: keys.string("weapon"),
+                    health_damage,
+                    armor_damage,
+                    health_damage_taken: health_taken,
+                    armor_damage_taken: armor_taken,
+                    hit_group: events::HitGroup::from(keys.int("hitgroup") as u8),
+                    player,
+                };
+                parser.dispatch_event(hurt)
+            },
             | "player_sound" => parser.dispatch_event(events::PlayerSound {
                 player: None,
                 radius: 0,
@@ -1,11 +1,11 @@
 use std::collections::HashMap;
 
-use crate::sendtables2::Entity;
+use crate::sendtables2::EntityHandle;
 
 #[derive(Clone, Default)]
 pub struct GameRules {
     pub con_vars: HashMap<String, String>,
-    pub entity: Option<Entity>,
+    pub entity: Option<EntityHandle>,
 }
 
 impl GameRules {
@@ -1,16 +1,27 @@
 use std::any::Any;
 use std::collections::HashMap;
 
-use crate::common::{Bomb, Equipment, GrenadeProjectile, Hostage, Inferno, Player, Team};
+use crate::common::{
+    Bomb, Equipment, EquipmentType, GrenadeProjectile, Hostage, HostageState, Inferno, Player,
+    PlayerRef, RescueZone, Team,
+};
+use crate::economy::Economy;
+use crate::flashes::Flashes;
 use crate::game_rules::GameRules;
+use crate::grenades::Grenades;
+use crate::hostages::HostageRounds;
+use crate::infernos::Infernos;
 use crate::match_info::MatchInfo;
 use crate::proto::msg::cs_demo_parser_rs as proto_msg;
 use crate::proto::msgs2::CMsgPlayerInfo;
-use crate::sendtables2::Entity;
+use crate::sendtables::entity::Vector;
+use crate::sendtables2::{Entity, EntityHandle, EntityStore};
+use crate::smokes::Smokes;
 use prost::Message;
 
 /// Very small placeholder for a team state.
 #[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TeamState {
     pub id: i32,
     pub score: i32,
@@ -50,12 +61,14 @@ impl<'a> Participants<'a> {
     }
 }
 
+#[deprecated(note = "never set, see `GameState::flashes`")]
 #[derive(Default)]
 pub struct LastFlash {
     pub player: Option<Player>,
     pub projectile_by_player: HashMap<i32, GrenadeProjectile>,
 }
 
+#[deprecated(note = "never set, see `GameState::flashes`")]
 #[derive(Default)]
 pub struct FlyingFlashbang {
     pub projectile: Option<GrenadeProjectile>,
@@ -63,6 +76,9 @@ pub struct FlyingFlashbang {
     pub exploded_frame: i32,
 }
 
+/// Tick rate assumed for timings when the demo doesn't tell.
+const DEFAULT_TICK_RATE: f64 = 64.0;
+
 /// Representation of the current game state. This is a very small subset of the
 /// Go implementation. It only tracks a few basic structures so tests can
 /// compile. Further logic will be added as the parser grows.
@@ -86,7 +102,8 @@ pub struct GameState {
     pub infernos: HashMap<i32, Inferno>,
     pub weapons: HashMap<i32, Equipment>,
     pub hostages: HashMap<i32, Hostage>,
-    pub entities: HashMap<i32, Entity>,
+    pub rescue_zones: HashMap<i32, RescueZone>,
+    pub entities: EntityStore,
     pub projectile_owners: HashMap<i32, i32>,
     pub dropped_weapons: HashMap<i32, String>,
     pub bomb: Bomb,
@@ -95,13 +112,123 @@ pub struct GameState {
     pub current_planter: Option<Player>,
 
     pub thrown_grenades: HashMap<i32, Vec<Equipment>>,
+    #[deprecated(note = "never set, see `GameState::flashes`")]
+    #[allow(deprecated)]
     pub flying_flashbangs: Vec<FlyingFlashbang>,
+    #[deprecated(note = "never set, see `GameState::flashes`")]
+    #[allow(deprecated)]
     pub last_flash: LastFlash,
 
     pub equipment_mapping: HashMap<String, crate::common::EquipmentType>,
 
     pub rules: GameRules,
     pub match_info: MatchInfo,
+    pub economy: Economy,
+    pub grenades: Grenades,
+    pub flashes: Flashes,
+    pub fire_timelines: Infernos,
+    pub smokes: Smokes,
+    pub hostage_rounds: HostageRounds,
+
+    /// Ticks per second from the demo header or
+    /// [`ParserConfig::tick_rate_override`](crate::parser::ParserConfig::tick_rate_override),
+    /// `0` if unknown.
+    pub tick_rate: f64,
+}
+
+/// State of a player at one point in time, see [`GameState::snapshot`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PlayerSnapshot {
+    pub user_id: i32,
+    pub steam_id64: u64,
+    pub name: String,
+    pub team: Team,
+    pub is_bot: bool,
+    pub is_connected: bool,
+    pub is_alive: bool,
+    pub position: Vector,
+    pub velocity: Vector,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub health: i32,
+    pub armor: i32,
+    pub has_helmet: bool,
+    pub has_defuser: bool,
+    pub money: i32,
+    pub flash_duration: f32,
+    pub active_weapon: Option<EquipmentType>,
+    pub weapons: Vec<EquipmentType>,
+}
+
+impl From<&Player> for PlayerSnapshot {
+    fn from(p: &Player) -> Self {
+        let mut weapons: Vec<EquipmentType> =
+            p.weapons().iter().map(|w| w.equipment_type).collect();
+        weapons.sort_by_key(|w| *w as i32);
+        Self {
+            user_id: p.user_id,
+            steam_id64: p.steam_id64,
+            name: p.name.clone(),
+            team: p.team,
+            is_bot: p.is_bot,
+            is_connected: p.is_connected,
+            is_alive: p.is_alive(),
+            position: p.position(),
+            velocity: p.velocity(),
+            pitch: p.view_direction_y(),
+            yaw: p.view_direction_x(),
+            health: p.health(),
+            armor: p.armor(),
+            has_helmet: p.has_helmet(),
+            has_defuser: p.has_defuse_kit(),
+            money: p.money(),
+            flash_duration: p.flash_duration,
+            active_weapon: p.active_weapon().map(|w| w.equipment_type),
+            weapons,
+        }
+    }
+}
+
+/// A grenade in flight, see [`GameState::snapshot`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GrenadeSnapshot {
+    pub entity_id: i32,
+    pub grenade_type: EquipmentType,
+    pub thrower: Option<PlayerRef>,
+    pub position: Option<Vector>,
+}
+
+/// A burning inferno, see [`GameState::snapshot`].
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InfernoSnapshot {
+    pub entity_id: i32,
+    pub flames: Vec<Vector>,
+}
+
+/// Owned, serializable copy of the parts of a [`GameState`] that are useful
+/// outside of the parser. Players are sorted by user id, grenades and
+/// infernos by entity id.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GameStateSnapshot {
+    pub tick: i32,
+    pub map_name: Option<String>,
+    pub total_rounds_played: i32,
+    pub game_phase: crate::events::GamePhase,
+    pub is_warmup_period: bool,
+    pub is_freezetime: bool,
+    pub is_match_started: bool,
+    pub overtime_count: i32,
+    pub t_score: i32,
+    pub ct_score: i32,
+    pub players: Vec<PlayerSnapshot>,
+    pub bomb_position: Vector,
+    pub bomb_carrier: Option<PlayerRef>,
+    pub grenades: Vec<GrenadeSnapshot>,
+    pub infernos: Vec<InfernoSnapshot>,
 }
 
 impl GameState {
@@ -175,10 +302,15 @@ impl GameState {
         &self.dropped_weapons
     }
 
-    pub fn entities(&self) -> &HashMap<i32, Entity> {
+    pub fn entities(&self) -> &EntityStore {
         &self.entities
     }
 
+    /// The entity `handle` refers to, if it still exists.
+    pub fn entity(&self, handle: EntityHandle) -> Option<&Entity> {
+        self.entities.get(handle)
+    }
+
     pub fn bomb(&self) -> &Bomb {
         &self.bomb
     }
@@ -215,6 +347,42 @@ impl GameState {
         &self.match_info
     }
 
+    /// Per-round money, spending and buy types of all players and teams.
+    pub fn economy(&self) -> &Economy {
+        &self.economy
+    }
+
+    /// Every grenade thrown so far, from the throw to the detonation.
+    pub fn grenades(&self) -> &Grenades {
+        &self.grenades
+    }
+
+    /// Every flashbang detonated so far and the players it blinded.
+    pub fn flashes(&self) -> &Flashes {
+        &self.flashes
+    }
+
+    /// Every inferno so far, with its spread over time and the damage it
+    /// dealt.
+    pub fn fire_timelines(&self) -> &Infernos {
+        &self.fire_timelines
+    }
+
+    /// Every smoke so far, with its voxels over time on CS2.
+    pub fn smokes(&self) -> &Smokes {
+        &self.smokes
+    }
+
+    /// Every round of a hostage map with its rescues and outcome.
+    pub fn hostage_rounds(&self) -> &HostageRounds {
+        &self.hostage_rounds
+    }
+
+    /// The hostage rescue zone containing `position`.
+    pub fn rescue_zone_at(&self, position: &Vector) -> Option<&RescueZone> {
+        self.rescue_zones.values().find(|z| z.contains(position))
+    }
+
     pub fn map_name(&self) -> Option<&str> {
         self.match_info.map.as_deref()
     }
@@ -243,6 +411,74 @@ impl GameState {
         self.rules.bomb_time()
     }
 
+    /// Seconds of game time at the current tick, the clock of timers like
+    /// `m_flC4Blow`.
+    pub fn game_time(&self) -> f32 {
+        (self.ingame_tick as f64 / self.tick_rate_or_default()) as f32
+    }
+
+    fn tick_rate_or_default(&self) -> f64 {
+        if self.tick_rate > 0.0 {
+            self.tick_rate
+        } else {
+            DEFAULT_TICK_RATE
+        }
+    }
+
+    /// Copies the current state into a [`GameStateSnapshot`].
+    pub fn snapshot(&self) -> GameStateSnapshot {
+        let mut players: Vec<PlayerSnapshot> = self
+            .players_by_user_id
+            .values()
+            .map(PlayerSnapshot::from)
+            .collect();
+        players.sort_by_key(|p| p.user_id);
+
+        let mut grenades: Vec<GrenadeSnapshot> = self
+            .grenade_projectiles
+            .iter()
+            .map(|(id, g)| GrenadeSnapshot {
+                entity_id: *id,
+                grenade_type: g
+                    .weapon_instance
+                    .as_ref()
+                    .map(|w| w.equipment_type)
+                    .unwrap_or_default(),
+                thrower: g.thrower.as_ref().map(PlayerRef::from),
+                position: g.last_position().cloned(),
+            })
+            .collect();
+        grenades.sort_by_key(|g| g.entity_id);
+
+        let mut infernos: Vec<InfernoSnapshot> = self
+            .infernos
+            .iter()
+            .map(|(id, inf)| InfernoSnapshot {
+                entity_id: *id,
+                flames: inf.flames.clone(),
+            })
+            .collect();
+        infernos.sort_by_key(|i| i.entity_id);
+
+        GameStateSnapshot {
+            tick: self.ingame_tick,
+            map_name: self.match_info.map.clone(),
+            total_rounds_played: self.total_rounds_played,
+            game_phase: self.game_phase,
+            is_warmup_period: self.is_warmup_period,
+            is_freezetime: self.is_freezetime,
+            is_match_started: self.is_match_started,
+            overtime_count: self.overtime_count,
+            t_score: self.t_state.score,
+            ct_score: self.ct_state.score,
+            players,
+            bomb_position: self.bomb.position(),
+            bomb_carrier: self.bomb.carrier.as_ref().map(PlayerRef::from),
+            grenades,
+            infernos,
+        }
+    }
+
     pub fn ingame_tick(&self) -> i32 {
         self.ingame_tick
     }
@@ -252,11 +488,11 @@ impl GameState {
     }
 
     pub fn add_entity(&mut self, entity: Entity) {
-        self.entities.insert(entity.index, entity);
+        self.entities.insert(entity);
     }
 
     pub fn remove_entity(&mut self, id: i32) {
-        self.entities.remove(&id);
+        self.entities.remove(id);
     }
 
     pub fn apply_userinfo_table(&mut self, table: &crate::stringtables::StringTable) {
@@ -270,7 +506,6 @@ impl GameState {
                     let steam_id = info.steamid.or(info.xuid).unwrap_or(0);
                     let name = info.name.unwrap_or_default();
                     let is_bot = info.fakeplayer.unwrap_or(false);
-                    println!("userinfo: {} -> {}", user_id, name);
                     let p = self
                         .players_by_user_id
                         .entry(user_id)
@@ -292,6 +527,10 @@ impl GameState {
     }
 
     fn update_player_from_entity(&mut self, ent: &Entity) {
+        if ent.class.name.contains("PlayerPawn") {
+            self.update_player_pawn(ent);
+            return;
+        }
         if !ent.class.name.to_lowercase().contains("player") {
             return;
         }
@@ -304,43 +543,141 @@ impl GameState {
         if p.user_id == 0 {
             p.user_id = ent.index;
         }
-        // team information might be available via entity properties in a
-        // complete implementation. This simplified version does not decode
-        // properties and leaves the team unchanged.
+        if let Some(team) = ent.property_value("m_iTeamNum") {
+            p.team = Team::from(team.int_val as u8);
+        }
+        let pawn = ent
+            .property_value("m_hPlayerPawn")
+            .and_then(|v| EntityHandle::from_raw(v.int_val as u32));
+        if pawn.is_some() {
+            p.pawn = pawn;
+        }
+        p.props.update_from_controller(ent);
+        self.players_by_user_id.insert(p.user_id, p.clone());
+    }
+
+    /// Copies the place, position and [`PlayerProps`] of a Source 2 pawn to
+    /// the player whose controller points at it.
+    ///
+    /// [`PlayerProps`]: crate::common::PlayerProps
+    fn update_player_pawn(&mut self, pawn: &Entity) {
+        let tick = self.ingame_tick;
+        let tick_rate = self.tick_rate_or_default();
+        let Some(p) = self
+            .players_by_entity_id
+            .values_mut()
+            .find(|p| p.pawn.is_some_and(|h| h.index == pawn.index))
+        else {
+            return;
+        };
+        if let Some(place) = pawn.property_value("m_szLastPlaceName") {
+            p.last_place_name = place.string_val;
+        }
+        let alive = pawn
+            .property_value("m_lifeState")
+            .is_none_or(|v| v.int_val == 0);
+        let position = pawn.position().filter(|_| alive);
+        p.props.update_from_pawn(
+            pawn,
+            tick,
+            tick_rate,
+            &p.last_alive_position,
+            position.as_ref(),
+        );
+        if let Some(position) = position {
+            p.last_alive_position = position;
+        }
         self.players_by_user_id.insert(p.user_id, p.clone());
     }
 
     fn update_special_entities(&mut self, ent: &Entity) {
-        let name = ent.class.name.as_str();
+        let name = &*ent.class.name;
         if name.contains("Projectile") {
-            self.projectile_owners.entry(ent.index).or_insert(0);
-            self.grenade_projectiles
+            let thrower = ent
+                .property_value("m_hThrower")
+                .and_then(|v| EntityHandle::from_raw(v.int_val as u32))
+                .and_then(|h| player_by_handle(&self.players_by_entity_id, h))
+                .cloned();
+            if let Some(p) = &thrower {
+                self.projectile_owners.insert(ent.index, p.entity_id);
+            } else {
+                self.projectile_owners.entry(ent.index).or_insert(0);
+            }
+            let g = self
+                .grenade_projectiles
                 .entry(ent.index)
                 .or_insert_with(|| {
+                    let mut grenade_type = crate::common::projectile_type(name);
+                    let incendiary = ent.property_value("m_bIsIncGrenade");
+                    if incendiary.is_some_and(|v| v.bool_val()) {
+                        grenade_type = crate::common::EquipmentType::Incendiary;
+                    }
                     let mut g = crate::common::new_grenade_projectile();
-                    g.entity = Some(ent.clone());
+                    g.entity = Some(ent.handle());
+                    g.weapon_instance = Some(Equipment {
+                        equipment_type: grenade_type,
+                        original_string: name.to_string(),
+                        ..Default::default()
+                    });
+                    g.thrower = thrower.clone();
+                    g.owner = thrower;
                     g
                 });
+            if let Some(bounces) = ent.property_value("m_nBounces") {
+                g.bounces = bounces.int_val;
+            }
+            if name.contains("SmokeGrenade") {
+                let thrower = g.thrower.as_ref();
+                self.smokes.entity_updated(self.ingame_tick, ent, thrower);
+            }
         } else if name.contains("Inferno") {
-            self.infernos
-                .entry(ent.index)
-                .or_insert_with(|| crate::common::Inferno {
-                    entity: Some(ent.clone()),
-                    ..Default::default()
-                });
-        } else if name.contains("Hostage") {
+            let owner = ent
+                .property_value("m_hOwnerEntity")
+                .and_then(|v| EntityHandle::from_raw(v.int_val as u32))
+                .and_then(|h| player_by_handle(&self.players_by_entity_id, h));
+            if !self.infernos.contains_key(&ent.index) {
+                self.grenades
+                    .inferno_spawned(ent.index, owner, ent.position().as_ref());
+            }
+            let inferno =
+                self.infernos
+                    .entry(ent.index)
+                    .or_insert_with(|| crate::common::Inferno {
+                        entity: Some(ent.handle()),
+                        ..Default::default()
+                    });
+            let changed = Inferno::fires_of(ent).is_some_and(|fires| inferno.set_fires(fires));
+            if changed {
+                self.fire_timelines
+                    .fires_changed(self.ingame_tick, ent.index, inferno, owner);
+            }
+        } else if name.contains("PlantedC4") {
+            let now = self.game_time();
+            self.bomb.planted_entity_updated(
+                self.ingame_tick,
+                now,
+                ent,
+                &self.players_by_entity_id,
+            );
+        } else if name.contains("BombTarget") {
+            self.bomb.bomb_target_updated(ent);
+        } else if name.contains("HostageRescueZone") {
+            if let Some(zone) = RescueZone::of(ent) {
+                self.rescue_zones.insert(ent.index, zone);
+            }
+        } else if name == "CHostage" {
             self.hostages
                 .entry(ent.index)
-                .or_insert_with(|| crate::common::Hostage {
-                    ..Default::default()
-                });
+                .or_default()
+                .update(ent, &self.players_by_entity_id);
+            self.hostage_rounds.hostages_present();
         } else if name.contains("DroppedWeapon") || name.contains("Dropped") {
             self.dropped_weapons
                 .entry(ent.index)
                 .or_insert_with(|| name.to_string());
         } else if let Some(eq) = self.equipment_mapping.get(name) {
             if *eq == crate::common::EquipmentType::Bomb {
-                self.bomb.entity = Some(ent.clone());
+                self.bomb.entity = Some(ent.handle());
             }
             self.weapons.entry(ent.index).or_insert_with(|| Equipment {
                 equipment_type: *eq,
@@ -350,7 +687,57 @@ impl GameState {
                 position: Default::default(),
             });
         } else if name.contains("GameRules") {
-            self.rules.entity = Some(ent.clone());
+            self.rules.entity = Some(ent.handle());
+            if let Some(ot) = ent.property_value("m_pGameRules.m_nOvertimePlaying") {
+                self.overtime_count = ot.int_val;
+            }
+        } else if name == "CCSTeam" {
+            let team = ent.property_value("m_iTeamNum").map(|v| v.int_val);
+            let state = match team.map(|t| Team::from(t as u8)) {
+                | Some(Team::Terrorists) => &mut self.t_state,
+                | Some(Team::CounterTerrorists) => &mut self.ct_state,
+                | _ => return,
+            };
+            state.id = team.unwrap_or_default();
+            if let Some(score) = ent.property_value("m_iScore") {
+                state.score = score.int_val;
+            }
+        }
+    }
+
+    /// Moves a hostage without an entity to `state` after a game event
+    /// about it. Returns its previous state and the hostage if that changed
+    /// anything.
+    pub(crate) fn hostage_game_event(
+        &mut self,
+        index: i32,
+        state: HostageState,
+        leader: Option<Player>,
+    ) -> Option<(HostageState, Hostage)> {
+        let h = self.hostages.entry(index).or_insert_with(|| Hostage {
+            entity_id: index,
+            ..Default::default()
+        });
+        if h.handle.is_some() {
+            return None;
+        }
+        let (old_state, old_leader) = h.status();
+        h.state = state;
+        h.leader = leader;
+        (h.status() != (old_state, old_leader)).then(|| (old_state, h.clone()))
+    }
+
+    /// Takes the counters that game events accumulate from the entities of
+    /// a `DEM_FullPacket`, for parsers that start at one instead of at the
+    /// beginning of the demo.
+    pub(crate) fn full_packet_applied(&mut self) {
+        let rounds = self
+            .rules
+            .entity
+            .and_then(|h| self.entities.get(h))
+            .and_then(|e| e.property_value("m_pGameRules.m_totalRoundsPlayed"));
+        if let Some(rounds) = rounds {
+            self.total_rounds_played = rounds.int_val;
         }
     }
 
@@ -361,8 +748,167 @@ impl GameState {
                 self.rules.con_vars.insert(k.clone(), v.clone());
             }
         } else if let Some(re) = any.downcast_ref::<crate::events::RoundEnd>() {
-            let _ = re;
+            self.hostage_rounds
+                .round_ended(self.total_rounds_played + 1, re);
             self.total_rounds_played += 1;
+            self.economy
+                .round_ended(self.ingame_tick, Team::from(re.winner));
+        } else if any.is::<crate::events::MatchStart>() {
+            self.economy.reset();
+            self.grenades.reset();
+            self.flashes.reset();
+            self.fire_timelines.reset();
+            self.smokes.reset();
+            self.bomb.reset();
+            self.hostage_rounds.reset();
+        } else if any.is::<crate::events::RoundStart>() {
+            self.bomb.round_started(self.total_rounds_played + 1);
+            self.hostage_rounds
+                .round_started(self.total_rounds_played + 1);
+            self.economy.round_started(
+                self.total_rounds_played + 1,
+                self.ingame_tick,
+                self.players_by_user_id.values(),
+            );
+        } else if any.is::<crate::events::RoundFreezetimeEnd>() {
+            self.economy
+                .freezetime_ended(self.players_by_user_id.values());
+        } else if any.is::<crate::events::BuytimeEnded>() {
+            self.economy.buytime_ended();
+        } else if let Some(ip) = any.downcast_ref::<crate::events::ItemPickup>() {
+            self.economy.item_picked_up(self.ingame_tick, ip);
+        } else if let Some(ip) = any.downcast_ref::<crate::events::ItemPurchase>() {
+            self.economy.item_purchased(self.ingame_tick, ip);
+        } else if any.is::<crate::events::TeamSideSwitch>()
+            || any.is::<crate::events::GameHalfEnded>()
+        {
+            self.economy.half_ended(self.overtime_count > 0);
+        } else if let Some(wf) = any.downcast_ref::<crate::events::WeaponFire>() {
+            let round = self.total_rounds_played + 1;
+            self.grenades.weapon_fired(round, self.ingame_tick, wf);
+        } else if let Some(t) = any.downcast_ref::<crate::events::GrenadeProjectileThrow>() {
+            let round = self.total_rounds_played + 1;
+            self.grenades.thrown(round, self.ingame_tick, t);
+        } else if let Some(b) = any.downcast_ref::<crate::events::GrenadeProjectileBounce>() {
+            self.grenades.bounced(self.ingame_tick, b);
+        } else if let Some(d) = any.downcast_ref::<crate::events::GrenadeProjectileDestroy>() {
+            self.grenades.destroyed(self.ingame_tick, d);
+        } else if let Some(inner) = detonation(any) {
+            let round = self.total_rounds_played + 1;
+            self.grenades.detonated(round, self.ingame_tick, inner);
+            if any.is::<crate::events::FlashExplode>() {
+                self.flashes.detonated(self.ingame_tick, inner);
+            } else if any.is::<crate::events::FireGrenadeStart>() {
+                self.fire_timelines.started(self.ingame_tick, inner);
+            } else if any.is::<crate::events::SmokeStart>() {
+                self.fire_timelines.smoke_started(inner, &self.infernos);
+                self.smokes.detonated(self.ingame_tick, inner);
+            }
+        } else if let Some(ex) = any.downcast_ref::<crate::events::FireGrenadeExpired>() {
+            self.fire_timelines
+                .ended(self.ingame_tick, ex.inner.grenade_entity_id);
+        } else if let Some(ex) = any.downcast_ref::<crate::events::SmokeExpired>() {
+            self.fire_timelines.smoke_expired(&ex.inner);
+            self.smokes
+                .expired(self.ingame_tick, ex.inner.grenade_entity_id);
+        } else if let Some(pf) = any.downcast_ref::<crate::events::PlayerFlashed>() {
+            if let Some(victim) = &pf.player {
+                let tick = self.ingame_tick;
+                let victim = self
+                    .players_by_user_id
+                    .get(&victim.user_id)
+                    .unwrap_or(victim)
+                    .clone();
+                let pawn = victim.pawn.and_then(|h| self.entities.get(h));
+                let duration = crate::flashes::flash_duration(pawn, pf);
+                self.grenades.player_flashed(tick, pf, duration);
+                self.flashes.player_flashed(tick, &victim, pf, duration);
+                for p in [
+                    self.players_by_user_id.get_mut(&victim.user_id),
+                    self.players_by_entity_id.get_mut(&victim.entity_id),
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    p.flash_duration = duration;
+                    p.flash_tick = tick;
+                }
+            }
+        } else if let Some(kill) = any.downcast_ref::<crate::events::Kill>() {
+            let tick_rate = self.tick_rate_or_default();
+            self.flashes.killed(self.ingame_tick, tick_rate, kill);
+        } else if let Some(ev) = any.downcast_ref::<crate::events::BombPickup>() {
+            if let Some(p) = &ev.player {
+                self.bomb.picked_up(self.ingame_tick, p);
+            }
+        } else if let Some(ev) = any.downcast_ref::<crate::events::BombDropped>() {
+            self.bomb.dropped(self.ingame_tick, ev.player.as_ref());
+        } else if let Some(ev) = any.downcast_ref::<crate::events::BombPlantBegin>() {
+            self.current_planter = ev.inner.player.clone();
+            self.bomb
+                .plant_started(self.ingame_tick, ev.inner.player.as_ref(), ev.inner.site);
+        } else if any.is::<crate::events::BombPlantAborted>() {
+            self.current_planter = None;
+            self.bomb.plant_aborted(self.ingame_tick);
+        } else if let Some(ev) = any.downcast_ref::<crate::events::BombPlanted>() {
+            self.current_planter = None;
+            let bomb_time = self.bomb_time().map(|t| t.as_secs_f32());
+            self.bomb.planted_by(
+                self.ingame_tick,
+                self.game_time(),
+                ev.inner.player.as_ref(),
+                ev.inner.site,
+                bomb_time,
+            );
+        } else if let Some(ev) = any.downcast_ref::<crate::events::BombDefuseStart>() {
+            self.current_defuser = ev.player.clone();
+            self.bomb.defuse_started(
+                self.ingame_tick,
+                self.game_time(),
+                ev.player.as_ref(),
+                ev.has_kit,
+            );
+        } else if any.is::<crate::events::BombDefuseAborted>() {
+            self.current_defuser = None;
+            self.bomb.defuse_aborted(self.ingame_tick);
+        } else if any.is::<crate::events::BombDefused>() {
+            self.current_defuser = None;
+            self.bomb.defused(self.ingame_tick, self.game_time());
+        } else if any.is::<crate::events::BombExplode>() {
+            self.current_defuser = None;
+            self.bomb.exploded(self.ingame_tick);
+        } else if let Some(ev) = any.downcast_ref::<crate::events::HostageStateChanged>() {
+            if let Some(h) = &ev.hostage {
+                let round = self.total_rounds_played + 1;
+                self.hostage_rounds
+                    .state_changed(round, self.ingame_tick, ev.old_state, h);
+            }
+        } else if let Some(ev) = any.downcast_ref::<crate::events::HostageRescued>() {
+            let round = self.total_rounds_played + 1;
+            let zone = ev
+                .hostage
+                .as_ref()
+                .and_then(|h| self.rescue_zone_at(&h.position()))
+                .map(|z| z.entity_id);
+            self.hostage_rounds.rescued(
+                round,
+                self.ingame_tick,
+                ev.player.as_ref(),
+                ev.hostage.as_ref(),
+                zone,
+            );
+        } else if let Some(ev) = any.downcast_ref::<crate::events::HostageKilled>() {
+            let round = self.total_rounds_played + 1;
+            self.hostage_rounds.killed(
+                round,
+                self.ingame_tick,
+                ev.killer.as_ref(),
+                ev.hostage.as_ref(),
+            );
+        } else if let Some(ph) = any.downcast_ref::<crate::events::PlayerHurt>() {
+            self.grenades.player_hurt(self.ingame_tick, ph);
+            self.fire_timelines
+                .player_hurt(self.ingame_tick, ph, &self.infernos);
         } else if let Some(ge) = any.downcast_ref::<crate::events::GamePhaseChanged>() {
             self.game_phase = ge.new_game_phase;
         } else if let Some(wu) = any.downcast_ref::<crate::events::IsWarmupPeriodChanged>() {
@@ -376,33 +922,42 @@ impl GameState {
         } else if let Some(ev) = any.downcast_ref::<crate::parser::EntityEvent>() {
             use crate::sendtables::EntityOp;
             if ev.op.contains(EntityOp::DELETED) {
-                self.remove_entity(ev.entity.index);
-                self.weapons.remove(&ev.entity.index);
-                self.projectile_owners.remove(&ev.entity.index);
-                self.dropped_weapons.remove(&ev.entity.index);
-                self.grenade_projectiles.remove(&ev.entity.index);
-                self.infernos.remove(&ev.entity.index);
-                self.hostages.remove(&ev.entity.index);
-                if let Some(p) = self.players_by_entity_id.get_mut(&ev.entity.index) {
+                // The slot may hold the entity replacing it already.
+                if self.entities.contains(ev.handle) {
+                    self.remove_entity(ev.handle.index);
+                }
+                self.weapons.remove(&ev.handle.index);
+                self.projectile_owners.remove(&ev.handle.index);
+                self.dropped_weapons.remove(&ev.handle.index);
+                self.grenade_projectiles.remove(&ev.handle.index);
+                self.smokes.expired(self.ingame_tick, ev.handle.index);
+                if self.infernos.remove(&ev.handle.index).is_some() {
+                    self.fire_timelines.ended(self.ingame_tick, ev.handle.index);
+                }
+                self.hostages.remove(&ev.handle.index);
+                self.rescue_zones.remove(&ev.handle.index);
+                if let Some(p) = self.players_by_entity_id.get_mut(&ev.handle.index) {
                     p.is_connected = false;
                     self.players_by_user_id.insert(p.user_id, p.clone());
                 }
                 if let Some(b) = &self.bomb.entity {
-                    if b.index == ev.entity.index {
+                    if b.index == ev.handle.index {
                         self.bomb.entity = None;
                     }
                 }
             } else if ev.op.contains(EntityOp::CREATED) || ev.op.contains(EntityOp::UPDATED) {
-                self.add_entity(ev.entity.clone());
-                self.update_special_entities(&ev.entity);
-                self.update_player_from_entity(&ev.entity);
-            }
-        } else if any.is::<crate::events::FrameDone>() {
-            if let Some(fb) = self.flying_flashbangs.first() {
-                if fb.exploded_frame > 0 && fb.exploded_frame < self.ingame_tick {
-                    self.flying_flashbangs.remove(0);
+                // Taken out of `self` for the duration, so that the entity
+                // can be borrowed without copying it.
+                let entities = std::mem::take(&mut self.entities);
+                if let Some(ent) = entities.get(ev.handle) {
+                    self.update_special_entities(ent);
+                    self.update_player_from_entity(ent);
                 }
+                self.entities = entities;
             }
+        } else if any.is::<crate::events::FrameDone>() {
+            self.economy.update_money(self.players_by_user_id.values());
+            self.flashes.frame_done(self.ingame_tick);
         }
     }
 
@@ -452,3 +1007,28 @@ impl GameState {
         }
     }
 }
+
+/// The grenade of a `HeExplode`, `FlashExplode`, `SmokeStart`, `DecoyStart` or
+/// `FireGrenadeStart` event.
+fn detonation(any: &dyn Any) -> Option<&crate::events::GrenadeEvent> {
+    use crate::events::{DecoyStart, FireGrenadeStart, FlashExplode, HeExplode, SmokeStart};
+    any.downcast_ref::<HeExplode>()
+        .map(|e| &e.inner)
+        .or_else(|| any.downcast_ref::<FlashExplode>().map(|e| &e.inner))
+        .or_else(|| any.downcast_ref::<SmokeStart>().map(|e| &e.inner))
+        .or_else(|| any.downcast_ref::<DecoyStart>().map(|e| &e.inner))
+        .or_else(|| any.downcast_ref::<FireGrenadeStart>().map(|e| &e.inner))
+}
+
+/// The player an entity handle such as `m_hOwnerEntity` refers to, either by
+/// the player's Source 2 pawn or by the entity id of the player. Pawns come
+/// first, players from `userinfo` are keyed by their user id.
+pub(crate) fn player_by_handle(
+    players: &HashMap<i32, crate::common::Player>,
+    handle: EntityHandle,
+) -> Option<&crate::common::Player> {
+    players
+        .values()
+        .find(|p| p.pawn.is_some_and(|pawn| pawn.index == handle.index))
+        .or_else(|| players.get(&handle.index))
+}
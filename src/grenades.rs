@@ -0,0 +1,423 @@
+//! Lifecycle of every grenade thrown in a demo, from the throw to the
+//! detonation, e.g. to build lineup libraries.
+//!
+//! Records are started by the thrower's `weapon_fire` event, which carries
+//! the thrower's position and eye angles at release in both engines. Source 2
+//! demos add the projectile entity, its bounces and trajectory, through
+//! [`GrenadeProjectileThrow`], [`GrenadeProjectileBounce`] and
+//! [`GrenadeProjectileDestroy`]. Detonation events are matched by the
+//! projectile's entity id and otherwise by thrower and grenade type, oldest
+//! throw first. Infernos are linked to the molotov or incendiary that
+//! started them when their entity spawns.
+
+use crate::common::{EquipmentClass, EquipmentType, GrenadeProjectile, Player, PlayerRef, Team};
+use crate::events::{
+    GrenadeEvent, GrenadeProjectileBounce, GrenadeProjectileDestroy, GrenadeProjectileThrow,
+    PlayerFlashed, PlayerHurt, WeaponFire,
+};
+use crate::sendtables::entity::Vector;
+
+/// State of the thrower when the grenade was released.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Release {
+    pub tick: i32,
+    pub position: Vector,
+    pub velocity: Vector,
+    /// Eye angles in degrees.
+    pub pitch: f32,
+    pub yaw: f32,
+    pub airborne: bool,
+    pub ducking: bool,
+    pub walking: bool,
+}
+
+impl Release {
+    fn of(player: &Player, tick: i32) -> Self {
+        Self {
+            tick,
+            position: player.position(),
+            velocity: player.velocity(),
+            pitch: player.view_direction_y(),
+            yaw: player.view_direction_x(),
+            airborne: player.is_airborne(),
+            ducking: player.is_ducking(),
+            walking: player.is_walking(),
+        }
+    }
+}
+
+/// Where and when a projectile bounced or detonated.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GrenadePoint {
+    pub tick: i32,
+    pub position: Vector,
+}
+
+/// How a grenade affected a player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Effect {
+    /// Blinded by a flashbang, for the longest duration in seconds if the
+    /// player was flashed more than once.
+    Flashed { duration: f32 },
+    /// Damage taken from an HE grenade or from the fire of a molotov, summed
+    /// over all hits.
+    Damaged { health: i32, armor: i32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AffectedPlayer {
+    pub player: PlayerRef,
+    pub team: Team,
+    /// Tick of the first effect.
+    pub tick: i32,
+    pub effect: Effect,
+}
+
+/// A grenade from the throw to the detonation. Parts of the lifecycle the
+/// demo doesn't contain are left empty, e.g. bounces of Source 1 demos.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GrenadeRecord {
+    pub grenade_type: EquipmentType,
+    pub round: i32,
+    pub thrower: Option<PlayerRef>,
+    pub thrower_team: Team,
+    /// `None` if the throw wasn't seen, e.g. for grenades thrown before the
+    /// demo started.
+    pub release: Option<Release>,
+    /// Entity id of the projectile, `0` if unknown.
+    pub entity_id: i32,
+    /// Entity id of the inferno a molotov or incendiary started, `0` if
+    /// unknown.
+    pub inferno_id: i32,
+    /// [`GrenadeProjectile::unique_id`] of the projectile, `0` if unknown.
+    pub projectile_id: i64,
+    pub trajectory: Vec<Vector>,
+    pub bounces: Vec<GrenadePoint>,
+    pub detonation: Option<GrenadePoint>,
+    /// Tick the projectile entity was removed.
+    pub destroy_tick: Option<i32>,
+    pub affected: Vec<AffectedPlayer>,
+}
+
+impl GrenadeRecord {
+    fn affect(&mut self, player: &Player, tick: i32, effect: Effect) {
+        let existing = self
+            .affected
+            .iter_mut()
+            .find(|a| a.player.user_id == player.user_id);
+        match (existing, effect) {
+            | (None, _) => self.affected.push(AffectedPlayer {
+                player: PlayerRef::from(player),
+                team: player.team,
+                tick,
+                effect,
+            }),
+            | (Some(a), Effect::Flashed { duration }) => {
+                if let Effect::Flashed { duration: d } = &mut a.effect {
+                    *d = d.max(duration);
+                }
+            },
+            | (Some(a), Effect::Damaged { health, armor }) => {
+                if let Effect::Damaged {
+                    health: h,
+                    armor: ar,
+                } = &mut a.effect
+                {
+                    *h += health;
+                    *ar += armor;
+                }
+            },
+        }
+    }
+}
+
+/// Molotovs and incendiary grenades are told apart by the weapon and the
+/// projectile but not by the inferno events.
+fn same_kind(a: EquipmentType, b: EquipmentType) -> bool {
+    let fire = |t| matches!(t, EquipmentType::Molotov | EquipmentType::Incendiary);
+    a == b || (fire(a) && fire(b))
+}
+
+fn thrown_by(record: &GrenadeRecord, player: Option<&Player>) -> bool {
+    match (&record.thrower, player) {
+        | (Some(t), Some(p)) => t.user_id == p.user_id,
+        | _ => false,
+    }
+}
+
+/// All grenades of a demo, in the order they were thrown. Owned by
+/// [`crate::game_state::GameState`], see its `grenades` accessor.
+#[derive(Debug, Clone, Default)]
+pub struct Grenades {
+    records: Vec<GrenadeRecord>,
+}
+
+impl Grenades {
+    pub fn records(&self) -> &[GrenadeRecord] {
+        &self.records
+    }
+
+    /// Grenades thrown in `round`, starting at 1.
+    pub fn round(&self, round: i32) -> impl Iterator<Item = &GrenadeRecord> {
+        self.records.iter().filter(move |r| r.round == round)
+    }
+
+    /// Grenades that haven't detonated yet.
+    pub fn in_flight(&self) -> impl Iterator<Item = &GrenadeRecord> {
+        self.records.iter().filter(|r| r.detonation.is_none())
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.records.clear();
+    }
+
+    pub(crate) fn weapon_fired(&mut self, round: i32, tick: i32, ev: &WeaponFire) {
+        let (Some(player), Some(weapon)) = (&ev.shooter, &ev.weapon) else {
+            return;
+        };
+        if weapon.equipment_type.class() != EquipmentClass::Grenade {
+            return;
+        }
+        self.records.push(GrenadeRecord {
+            grenade_type: weapon.equipment_type,
+            round,
+            thrower: Some(PlayerRef::from(player)),
+            thrower_team: player.team,
+            release: Some(Release::of(player, tick)),
+            ..Default::default()
+        });
+    }
+
+    pub(crate) fn thrown(&mut self, round: i32, tick: i32, ev: &GrenadeProjectileThrow) {
+        let Some(projectile) = &ev.projectile else {
+            return;
+        };
+        let entity_id = projectile.entity.map(|e| e.index).unwrap_or_default();
+        let grenade_type = projectile.grenade_type();
+        let thrower = projectile.thrower.as_ref();
+        // The projectile spawns right after the `weapon_fire` event.
+        let pending = self.records.iter().rposition(|r| {
+            r.entity_id == 0
+                && r.detonation.is_none()
+                && thrown_by(r, thrower)
+                && (grenade_type == EquipmentType::Unknown
+                    || same_kind(r.grenade_type, grenade_type))
+        });
+        let index = pending.unwrap_or_else(|| {
+            self.records.push(GrenadeRecord {
+                grenade_type,
+                round,
+                thrower: thrower.map(PlayerRef::from),
+                thrower_team: thrower.map(|p| p.team).unwrap_or_default(),
+                release: thrower.map(|p| Release::of(p, tick)),
+                ..Default::default()
+            });
+            self.records.len() - 1
+        });
+        let record = &mut self.records[index];
+        if record.grenade_type == EquipmentType::Unknown {
+            record.grenade_type = grenade_type;
+        }
+        record.entity_id = entity_id;
+        record.projectile_id = projectile.unique_id;
+        record.trajectory = projectile.trajectory.clone();
+    }
+
+    fn by_projectile(&mut self, projectile: &GrenadeProjectile) -> Option<&mut GrenadeRecord> {
+        self.records
+            .iter_mut()
+            .rev()
+            .find(|r| r.projectile_id != 0 && r.projectile_id == projectile.unique_id)
+    }
+
+    pub(crate) fn bounced(&mut self, tick: i32, ev: &GrenadeProjectileBounce) {
+        let Some(projectile) = &ev.projectile else {
+            return;
+        };
+        if let Some(record) = self.by_projectile(projectile) {
+            record.trajectory = projectile.trajectory.clone();
+            record.bounces.push(GrenadePoint {
+                tick,
+                position: projectile.last_position().cloned().unwrap_or_default(),
+            });
+        }
+    }
+
+    pub(crate) fn destroyed(&mut self, tick: i32, ev: &GrenadeProjectileDestroy) {
+        let Some(projectile) = &ev.projectile else {
+            return;
+        };
+        if let Some(record) = self.by_projectile(projectile) {
+            record.trajectory = projectile.trajectory.clone();
+            record.destroy_tick = Some(tick);
+        }
+    }
+
+    /// Links a Source 2 inferno entity to the molotov or incendiary that
+    /// started it. `inferno_startburn` only has the inferno's entity id, so
+    /// the grenade is found by the inferno's owner and the projectile that
+    /// ended closest to where the inferno spawned.
+    pub(crate) fn inferno_spawned(
+        &mut self,
+        inferno_id: i32,
+        owner: Option<&Player>,
+        position: Option<&Vector>,
+    ) {
+        let distance = |r: &GrenadeRecord| match (r.trajectory.last(), position) {
+            | (Some(a), Some(b)) => {
+                Some((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2))
+            },
+            | _ => None,
+        };
+        let linked = self
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| {
+                r.inferno_id == 0
+                    && r.entity_id != inferno_id
+                    && r.detonation.is_none()
+                    && same_kind(r.grenade_type, EquipmentType::Incendiary)
+            })
+            .filter(|(_, r)| match owner {
+                | Some(_) => thrown_by(r, owner),
+                | None => distance(r).is_some(),
+            })
+            .min_by(|(_, a), (_, b)| {
+                let (a, b) = (distance(a), distance(b));
+                a.is_none()
+                    .cmp(&b.is_none())
+                    .then(a.unwrap_or_default().total_cmp(&b.unwrap_or_default()))
+            })
+            .map(|(i, _)| i);
+        let Some(mut index) = linked else {
+            return;
+        };
+        // `inferno_startburn` may have come first and started a record of
+        // its own.
+        let orphan = self.records.iter().position(|r| {
+            r.entity_id == inferno_id
+                && r.release.is_none()
+                && r.projectile_id == 0
+                && r.detonation.is_some()
+        });
+        if let Some(i) = orphan {
+            let orphan = self.records.remove(i);
+            if i < index {
+                index -= 1;
+            }
+            let record = &mut self.records[index];
+            record.detonation = orphan.detonation;
+            record.affected = orphan.affected;
+        }
+        self.records[index].inferno_id = inferno_id;
+    }
+
+    /// Handles the `HeExplode`, `FlashExplode`, `SmokeStart`, `DecoyStart`
+    /// and `FireGrenadeStart` events.
+    pub(crate) fn detonated(&mut self, round: i32, tick: i32, ev: &GrenadeEvent) {
+        let id = ev.grenade_entity_id;
+        let by_entity = self.records.iter().position(|r| {
+            r.detonation.is_none()
+                && ((r.entity_id != 0 && r.entity_id == id)
+                    || (r.inferno_id != 0 && r.inferno_id == id))
+        });
+        let index = by_entity.or_else(|| {
+            self.records.iter().position(|r| {
+                r.detonation.is_none()
+                    && thrown_by(r, ev.thrower.as_ref())
+                    && same_kind(r.grenade_type, ev.grenade_type)
+            })
+        });
+        let index = index.unwrap_or_else(|| {
+            self.records.push(GrenadeRecord {
+                grenade_type: ev.grenade_type,
+                round,
+                thrower: ev.thrower.as_ref().map(PlayerRef::from),
+                thrower_team: ev.thrower.as_ref().map(|p| p.team).unwrap_or_default(),
+                entity_id: ev.grenade_entity_id,
+                ..Default::default()
+            });
+            self.records.len() - 1
+        });
+        self.records[index].detonation = Some(GrenadePoint {
+            tick,
+            position: ev.position.clone(),
+        });
+    }
+
+    /// The last detonated grenade of `thrower` matching `grenade_type`.
+    fn last_detonated(
+        &mut self,
+        thrower: Option<&Player>,
+        grenade_type: EquipmentType,
+    ) -> Option<&mut GrenadeRecord> {
+        self.records.iter_mut().rev().find(|r| {
+            r.detonation.is_some()
+                && thrown_by(r, thrower)
+                && same_kind(r.grenade_type, grenade_type)
+        })
+    }
+
+    /// `duration` is the victim's [`crate::flashes::flash_duration`].
+    pub(crate) fn player_flashed(&mut self, tick: i32, ev: &PlayerFlashed, duration: f32) {
+        let Some(player) = &ev.player else {
+            return;
+        };
+        let by_projectile = ev.projectile.as_ref().and_then(|p| p.entity).and_then(|e| {
+            self.records
+                .iter()
+                .rposition(|r| r.entity_id == e.index && r.grenade_type == EquipmentType::Flash)
+        });
+        // `player_blind` is sent in the tick the flashbang detonates, before
+        // or after `flashbang_detonate`.
+        let attacker = ev.attacker.as_ref();
+        let index = by_projectile
+            .or_else(|| {
+                self.records.iter().rposition(|r| {
+                    r.detonation.as_ref().is_some_and(|d| d.tick == tick)
+                        && thrown_by(r, attacker)
+                        && r.grenade_type == EquipmentType::Flash
+                })
+            })
+            .or_else(|| {
+                self.records.iter().position(|r| {
+                    r.detonation.is_none()
+                        && thrown_by(r, attacker)
+                        && r.grenade_type == EquipmentType::Flash
+                })
+            });
+        let record = index.map(|i| &mut self.records[i]);
+        if let Some(record) = record {
+            record.affect(player, tick, Effect::Flashed { duration });
+        }
+    }
+
+    pub(crate) fn player_hurt(&mut self, tick: i32, ev: &PlayerHurt) {
+        let (Some(player), Some(weapon)) = (&ev.player, &ev.weapon) else {
+            return;
+        };
+        let grenade_type = weapon.equipment_type;
+        if !matches!(
+            grenade_type,
+            EquipmentType::He | EquipmentType::Molotov | EquipmentType::Incendiary
+        ) {
+            return;
+        }
+        if let Some(record) = self.last_detonated(ev.attacker.as_ref(), grenade_type) {
+            record.affect(
+                player,
+                tick,
+                Effect::Damaged {
+                    health: ev.health_damage_taken,
+                    armor: ev.armor_damage_taken,
+                },
+            );
+        }
+    }
+}
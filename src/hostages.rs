@@ -0,0 +1,183 @@
+//! What happened to the hostages in each round of a hostage map: who
+//! reached, led and rescued them, which died, and how the round ended.
+//!
+//! The live state of every hostage is in
+//! [`GameState::hostages`](crate::game_state::GameState::hostages). Changes
+//! of its state or leader are dispatched as
+//! [`HostageStateChanged`](crate::events::HostageStateChanged) and recorded
+//! here. Source 2 demos have them from the hostage entities, Source 1 demos
+//! from the `hostage_follows`, `hostage_rescued` and `hostage_killed` game
+//! events, which leave the hostages' positions and health unknown.
+
+use crate::common::{Hostage, HostageState, Player, PlayerRef, Team};
+use crate::events::{RoundEnd, RoundEndReason};
+use crate::sendtables::entity::Vector;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostageChange {
+    pub tick: i32,
+    pub hostage_id: i32,
+    pub old_state: HostageState,
+    pub new_state: HostageState,
+    pub leader: Option<PlayerRef>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostageRescue {
+    pub tick: i32,
+    pub hostage_id: i32,
+    pub player: Option<PlayerRef>,
+    pub position: Vector,
+    /// Entity id of the rescue zone the hostage was in.
+    pub zone: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostageDeath {
+    pub tick: i32,
+    pub hostage_id: i32,
+    pub killer: Option<PlayerRef>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HostageRound {
+    pub round: i32,
+    /// First tick a player started untying or picking up a hostage.
+    pub first_reached_tick: Option<i32>,
+    pub changes: Vec<HostageChange>,
+    pub rescues: Vec<HostageRescue>,
+    pub deaths: Vec<HostageDeath>,
+    /// `None` while the round is running.
+    pub end_reason: Option<RoundEndReason>,
+    pub winner: Team,
+}
+
+impl HostageRound {
+    /// Whether the round was decided by the hostages rather than by
+    /// eliminations or the bomb.
+    pub fn decided_by_hostages(&self) -> bool {
+        matches!(
+            self.end_reason,
+            Some(
+                RoundEndReason::HostagesRescued
+                    | RoundEndReason::HostagesNotRescued
+                    | RoundEndReason::CTsReachedHostage
+            )
+        )
+    }
+}
+
+/// The hostage rounds of a demo. Owned by
+/// [`crate::game_state::GameState`], see its `hostage_rounds` accessor.
+#[derive(Debug, Clone, Default)]
+pub struct HostageRounds {
+    rounds: Vec<HostageRound>,
+    /// Whether the demo has shown any hostage yet.
+    hostages: bool,
+}
+
+impl HostageRounds {
+    /// Every round of the match, empty until a hostage entity or game event
+    /// shows that the map has hostages.
+    pub fn rounds(&self) -> &[HostageRound] {
+        if self.hostages { &self.rounds } else { &[] }
+    }
+
+    pub fn round(&self, round: i32) -> Option<&HostageRound> {
+        self.rounds().iter().find(|r| r.round == round)
+    }
+
+    pub(crate) fn hostages_present(&mut self) {
+        self.hostages = true;
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.rounds.clear();
+    }
+
+    fn current(&mut self, round: i32) -> &mut HostageRound {
+        if self.rounds.last().is_none_or(|r| r.round != round) {
+            self.rounds.push(HostageRound {
+                round,
+                ..Default::default()
+            });
+        }
+        let last = self.rounds.len() - 1;
+        &mut self.rounds[last]
+    }
+
+    /// Starts a round, also before any hostage is known.
+    pub(crate) fn round_started(&mut self, round: i32) {
+        self.current(round);
+    }
+
+    pub(crate) fn state_changed(
+        &mut self,
+        round: i32,
+        tick: i32,
+        old_state: HostageState,
+        hostage: &Hostage,
+    ) {
+        self.hostages = true;
+        let r = self.current(round);
+        let reached = matches!(
+            hostage.state,
+            HostageState::BeingUntied | HostageState::GettingPickedUp
+        );
+        if reached {
+            r.first_reached_tick.get_or_insert(tick);
+        }
+        r.changes.push(HostageChange {
+            tick,
+            hostage_id: hostage.entity_id,
+            old_state,
+            new_state: hostage.state,
+            leader: hostage.leader.as_ref().map(PlayerRef::from),
+        });
+    }
+
+    pub(crate) fn rescued(
+        &mut self,
+        round: i32,
+        tick: i32,
+        player: Option<&Player>,
+        hostage: Option<&Hostage>,
+        zone: Option<i32>,
+    ) {
+        self.hostages = true;
+        self.current(round).rescues.push(HostageRescue {
+            tick,
+            hostage_id: hostage.map_or(0, |h| h.entity_id),
+            player: player.map(PlayerRef::from),
+            position: hostage.map(Hostage::position).unwrap_or_default(),
+            zone,
+        });
+    }
+
+    pub(crate) fn killed(
+        &mut self,
+        round: i32,
+        tick: i32,
+        killer: Option<&Player>,
+        hostage: Option<&Hostage>,
+    ) {
+        self.hostages = true;
+        self.current(round).deaths.push(HostageDeath {
+            tick,
+            hostage_id: hostage.map_or(0, |h| h.entity_id),
+            killer: killer.map(PlayerRef::from),
+        });
+    }
+
+    /// Records the outcome of `round`.
+    pub(crate) fn round_ended(&mut self, round: i32, ev: &RoundEnd) {
+        if let Some(r) = self.rounds.last_mut().filter(|r| r.round == round) {
+            r.end_reason = Some(ev.reason);
+            r.winner = Team::from(ev.winner);
+        }
+    }
+}
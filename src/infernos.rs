@@ -0,0 +1,276 @@
+//! Timelines of molotov and incendiary fires: how far they spread, how long
+//! they burned, whether a smoke put them out and whom they damaged.
+//!
+//! Fires are read from the inferno entities whenever they change, see
+//! [`Inferno::fires_of`]. Without entities, e.g. for Source 1 demos, a
+//! timeline only has its start, end and damage, and its thrower is the
+//! attacker of the first damage it deals.
+
+use std::collections::HashMap;
+
+use crate::common::{Inferno, Player, PlayerRef, Team};
+use crate::events::{GrenadeEvent, PlayerHurt};
+use crate::sendtables::entity::Vector;
+
+/// Distance in units from a smoke's detonation within which fires are put
+/// out.
+pub const SMOKE_EXTINGUISH_RADIUS: f64 = 144.0;
+
+/// The state of an inferno's fires from `tick` until the next sample.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InfernoSample {
+    pub tick: i32,
+    pub burning: usize,
+    /// Area of the convex hull of the burning fires in square units.
+    pub area: f64,
+}
+
+/// Damage a player took from one inferno, summed over all hits.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FireDamage {
+    pub player: PlayerRef,
+    pub team: Team,
+    pub health: i32,
+    pub armor: i32,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InfernoTimeline {
+    pub entity_id: i32,
+    pub thrower: Option<PlayerRef>,
+    pub thrower_team: Team,
+    /// Where the grenade started burning.
+    pub origin: Vector,
+    pub start_tick: i32,
+    /// `None` while the inferno is burning.
+    pub end_tick: Option<i32>,
+    /// A sample for every change of the fires.
+    pub samples: Vec<InfernoSample>,
+    /// A smoke detonated on the fires while they were burning.
+    pub extinguished_by_smoke: bool,
+    pub damage: Vec<FireDamage>,
+}
+
+impl InfernoTimeline {
+    pub fn max_area(&self) -> f64 {
+        self.samples.iter().map(|s| s.area).fold(0.0, f64::max)
+    }
+
+    /// Ticks the inferno burned for, `None` while it's burning.
+    pub fn duration(&self) -> Option<i32> {
+        self.end_tick.map(|end| end - self.start_tick)
+    }
+
+    /// Health damage dealt to the thrower's enemies.
+    pub fn enemy_damage(&self) -> i32 {
+        self.damage
+            .iter()
+            .filter(|d| d.team != self.thrower_team)
+            .map(|d| d.health)
+            .sum()
+    }
+
+    /// Health damage dealt to the thrower and teammates.
+    pub fn team_damage(&self) -> i32 {
+        self.damage
+            .iter()
+            .filter(|d| d.team == self.thrower_team)
+            .map(|d| d.health)
+            .sum()
+    }
+
+    fn burns_near(&self, position: &Vector, radius: f64, inferno: Option<&Inferno>) -> bool {
+        let near = |p: &Vector| (p.x - position.x).hypot(p.y - position.y) <= radius;
+        match inferno {
+            | Some(inf) if !inf.flames.is_empty() => inf.flames.iter().any(near),
+            | _ => near(&self.origin),
+        }
+    }
+}
+
+/// All infernos of a demo, in the order they started. Owned by
+/// [`crate::game_state::GameState`], see its `fire_timelines` accessor.
+#[derive(Debug, Clone, Default)]
+pub struct Infernos {
+    timelines: Vec<InfernoTimeline>,
+    /// Timelines of burning infernos by entity id.
+    active: HashMap<i32, usize>,
+    /// Positions of the smokes that haven't expired, by entity id.
+    smokes: HashMap<i32, Vector>,
+}
+
+impl Infernos {
+    pub fn timelines(&self) -> &[InfernoTimeline] {
+        &self.timelines
+    }
+
+    pub fn burning(&self) -> impl Iterator<Item = &InfernoTimeline> {
+        self.active.values().map(|&i| &self.timelines[i])
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn timeline(&mut self, entity_id: i32, tick: i32) -> &mut InfernoTimeline {
+        let index = *self.active.entry(entity_id).or_insert_with(|| {
+            self.timelines.push(InfernoTimeline {
+                entity_id,
+                start_tick: tick,
+                ..Default::default()
+            });
+            self.timelines.len() - 1
+        });
+        &mut self.timelines[index]
+    }
+
+    /// Handles `FireGrenadeStart`, which may arrive before or after the
+    /// inferno entity.
+    pub(crate) fn started(&mut self, tick: i32, ev: &GrenadeEvent) {
+        let in_smoke = self.smokes.values().any(|smoke| {
+            (smoke.x - ev.position.x).hypot(smoke.y - ev.position.y) <= SMOKE_EXTINGUISH_RADIUS
+        });
+        let t = self.timeline(ev.grenade_entity_id, tick);
+        if ev.thrower.is_some() {
+            t.thrower = ev.thrower.as_ref().map(PlayerRef::from);
+            t.thrower_team = ev.thrower.as_ref().map(|p| p.team).unwrap_or_default();
+        }
+        t.origin = ev.position.clone();
+        t.extinguished_by_smoke |= in_smoke;
+    }
+
+    /// Records a sample of the inferno entity's fires.
+    pub(crate) fn fires_changed(
+        &mut self,
+        tick: i32,
+        entity_id: i32,
+        inferno: &Inferno,
+        owner: Option<&Player>,
+    ) {
+        let t = self.timeline(entity_id, tick);
+        if t.thrower.is_none() {
+            t.thrower = owner.map(PlayerRef::from);
+            t.thrower_team = owner.map(|p| p.team).unwrap_or_default();
+        }
+        let sample = InfernoSample {
+            tick,
+            burning: inferno.flames.len(),
+            area: inferno.area(),
+        };
+        match t.samples.last_mut() {
+            | Some(last) if last.tick == tick => *last = sample,
+            | _ => t.samples.push(sample),
+        }
+    }
+
+    /// Handles `FireGrenadeExpired` and the removal of the inferno entity.
+    pub(crate) fn ended(&mut self, tick: i32, entity_id: i32) {
+        if let Some(index) = self.active.remove(&entity_id) {
+            self.timelines[index].end_tick = Some(tick);
+        }
+    }
+
+    /// Handles `SmokeStart`. `infernos` are the inferno entities by id.
+    pub(crate) fn smoke_started(&mut self, ev: &GrenadeEvent, infernos: &HashMap<i32, Inferno>) {
+        for (&entity_id, &index) in &self.active {
+            let t = &mut self.timelines[index];
+            if t.burns_near(
+                &ev.position,
+                SMOKE_EXTINGUISH_RADIUS,
+                infernos.get(&entity_id),
+            ) {
+                t.extinguished_by_smoke = true;
+            }
+        }
+        self.smokes
+            .insert(ev.grenade_entity_id, ev.position.clone());
+    }
+
+    pub(crate) fn smoke_expired(&mut self, ev: &GrenadeEvent) {
+        self.smokes.remove(&ev.grenade_entity_id);
+    }
+
+    /// Attributes fire damage to the attacker's inferno burning closest to
+    /// the victim. Without entities, e.g. for Source 1 demos, the thrower
+    /// isn't known when the fire starts, so the first damage claims the
+    /// closest burning inferno without a thrower for the attacker.
+    pub(crate) fn player_hurt(
+        &mut self,
+        tick: i32,
+        ev: &PlayerHurt,
+        infernos: &HashMap<i32, Inferno>,
+    ) {
+        let (Some(victim), Some(attacker)) = (&ev.player, &ev.attacker) else {
+            return;
+        };
+        if ev.weapon_string != "inferno" {
+            return;
+        }
+        let thrown_by_attacker = |t: &InfernoTimeline| {
+            t.thrower
+                .as_ref()
+                .is_some_and(|p| p.user_id == attacker.user_id)
+        };
+        let position = victim.position();
+        let distance = |t: &InfernoTimeline| {
+            infernos
+                .get(&t.entity_id)
+                .and_then(|inf| {
+                    inf.flames
+                        .iter()
+                        .map(|f| (f.x - position.x).hypot(f.y - position.y))
+                        .min_by(f64::total_cmp)
+                })
+                .unwrap_or_else(|| (t.origin.x - position.x).hypot(t.origin.y - position.y))
+        };
+        let closest_burning = |matches: &dyn Fn(&InfernoTimeline) -> bool| {
+            self.active
+                .values()
+                .filter(|&&i| matches(&self.timelines[i]))
+                .min_by(|&&a, &&b| {
+                    distance(&self.timelines[a]).total_cmp(&distance(&self.timelines[b]))
+                })
+                .copied()
+        };
+        let burning = closest_burning(&thrown_by_attacker);
+        // Damage can be reported in the tick the fire went out, but not
+        // later; an earlier fire of the attacker doesn't take the damage of
+        // one whose thrower isn't known yet.
+        let index = burning.or_else(|| {
+            self.timelines
+                .iter()
+                .rposition(|t| t.end_tick == Some(tick) && thrown_by_attacker(t))
+        });
+        let index = match index {
+            | Some(index) => index,
+            | None => {
+                let Some(index) = closest_burning(&|t| t.thrower.is_none()) else {
+                    return;
+                };
+                let t = &mut self.timelines[index];
+                t.thrower = Some(PlayerRef::from(attacker));
+                t.thrower_team = attacker.team;
+                index
+            },
+        };
+        let damage = &mut self.timelines[index].damage;
+        match damage
+            .iter_mut()
+            .find(|d| d.player.user_id == victim.user_id)
+        {
+            | Some(d) => {
+                d.health += ev.health_damage_taken;
+                d.armor += ev.armor_damage_taken;
+            },
+            | None => damage.push(FireDamage {
+                player: PlayerRef::from(victim),
+                team: victim.team,
+                health: ev.health_damage_taken,
+                armor: ev.armor_damage_taken,
+            }),
+        }
+    }
+}
@@ -1,22 +1,43 @@
+pub mod analyzer;
 pub mod bitreader;
 pub mod commands;
 pub mod common;
 pub mod constants;
 pub mod dispatcher;
+pub mod economy;
 pub mod events;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod flashes;
 pub mod game_events;
 pub mod game_rules;
 pub mod game_state;
 pub mod gamerules;
+pub mod grenades;
+pub mod hostages;
+pub mod infernos;
+pub mod lineups;
+pub mod maps;
 pub mod match_info;
 pub mod matchinfo;
 pub mod parser;
+pub mod places;
 pub use proto;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod render;
 pub mod sendtables;
 pub mod sendtables1;
 pub mod sendtables2;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod smokes;
 pub mod stringtables;
+pub mod ticks;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
@@ -0,0 +1,387 @@
+//! Grenade lineups, clustered from the throws of many demos.
+//!
+//! Each detonated grenade with a known release (see
+//! [`crate::grenades::GrenadeRecord`]) becomes a [`LineupThrow`]. Throws of
+//! the same map, grenade type, side and [`Technique`] are clustered when
+//! their throw positions, view angles and landing spots are close, which
+//! makes a [`Lineup`] with a `setpos`/`setang` command and the throws'
+//! success metrics.
+//!
+//! ```no_run
+//! use cs_demo_parser::analyzer;
+//! use cs_demo_parser::lineups::{LineupOptions, Lineups};
+//! use cs_demo_parser::parser::Parser;
+//!
+//! let file = std::fs::File::open("demo.dem").expect("demo not found");
+//! let mut parser = Parser::new(file);
+//! let lineups = analyzer::run(&mut parser, Lineups::default())?;
+//! for lineup in lineups.lineups("de_mirage", &LineupOptions::default()) {
+//!     println!("{} ({} throws)", lineup.console_command(), lineup.throws);
+//! }
+//! # Ok::<(), cs_demo_parser::parser::ParserError>(())
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::analyzer::Analyzer;
+use crate::common::{EquipmentType, Team};
+use crate::game_state::GameState;
+use crate::grenades::{Effect, GrenadeRecord, Release};
+use crate::parser::DemoHeader;
+use crate::sendtables::entity::Vector;
+
+/// How the thrower moved when releasing the grenade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Technique {
+    #[default]
+    Stand,
+    Crouch,
+    Walk,
+    Run,
+    Jump,
+}
+
+impl Technique {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            | Technique::Stand => "stand",
+            | Technique::Crouch => "crouch",
+            | Technique::Walk => "walk",
+            | Technique::Run => "run",
+            | Technique::Jump => "jump",
+        }
+    }
+
+    /// Classifies a release by the thrower's airborne and movement flags
+    /// and horizontal speed.
+    pub fn of(release: &Release, options: &LineupOptions) -> Self {
+        let speed = release.velocity.x.hypot(release.velocity.y);
+        match speed {
+            | _ if release.airborne => Technique::Jump,
+            | s if s < options.stand_speed && release.ducking => Technique::Crouch,
+            | s if s < options.stand_speed => Technique::Stand,
+            | s if release.walking || s <= options.walk_speed => Technique::Walk,
+            | _ => Technique::Run,
+        }
+    }
+}
+
+/// An area a lineup is meant to land in, e.g. a smoke spot.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TargetArea {
+    pub name: String,
+    pub center: Vector,
+    /// Horizontal radius in units.
+    pub radius: f64,
+}
+
+impl TargetArea {
+    pub fn contains(&self, position: &Vector) -> bool {
+        horizontal_distance(&self.center, position) <= self.radius
+    }
+}
+
+/// Thresholds of [`Lineups::lineups`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineupOptions {
+    /// Largest distance in units between a throw position and a lineup's.
+    pub position_radius: f64,
+    /// Largest difference in degrees of pitch and of yaw.
+    pub angle_tolerance: f32,
+    /// Largest horizontal distance in units between landing spots.
+    pub landing_radius: f64,
+    /// Horizontal speed below which a throw counts as standing.
+    pub stand_speed: f64,
+    /// Horizontal speed up to which a throw counts as walking. Walking with
+    /// a grenade out tops out at about 130 units per second.
+    pub walk_speed: f64,
+    /// Lineups with fewer throws are left out.
+    pub min_throws: usize,
+    /// Areas to check landing spots against, see [`Lineup::target`].
+    pub targets: BTreeMap<String, Vec<TargetArea>>,
+}
+
+impl Default for LineupOptions {
+    fn default() -> Self {
+        Self {
+            position_radius: 16.0,
+            angle_tolerance: 1.0,
+            landing_radius: 128.0,
+            stand_speed: 10.0,
+            walk_speed: 135.0,
+            min_throws: 1,
+            targets: BTreeMap::new(),
+        }
+    }
+}
+
+/// The parts of a grenade record a lineup is built from.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineupThrow {
+    pub grenade_type: EquipmentType,
+    pub team: Team,
+    pub release: Release,
+    pub landing: Vector,
+    pub enemies_flashed: usize,
+    pub teammates_flashed: usize,
+    /// Health damage dealt to enemies.
+    pub damage: i32,
+}
+
+impl LineupThrow {
+    /// `None` for grenades that weren't seen being thrown or didn't
+    /// detonate.
+    pub fn from_record(record: &GrenadeRecord) -> Option<Self> {
+        let release = record.release.clone()?;
+        let landing = record.detonation.as_ref()?.position.clone();
+        let mut throw = Self {
+            grenade_type: record.grenade_type,
+            team: record.thrower_team,
+            release,
+            landing,
+            ..Default::default()
+        };
+        for affected in &record.affected {
+            let enemy = affected.team != record.thrower_team;
+            match affected.effect {
+                | Effect::Flashed { .. } if enemy => throw.enemies_flashed += 1,
+                | Effect::Flashed { .. } => throw.teammates_flashed += 1,
+                | Effect::Damaged { health, .. } if enemy => throw.damage += health,
+                | Effect::Damaged { .. } => {},
+            }
+        }
+        Some(throw)
+    }
+}
+
+/// Throws clustered into one lineup. Positions and angles are the means of
+/// the throws.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lineup {
+    pub map: String,
+    pub grenade_type: EquipmentType,
+    pub team: Team,
+    pub technique: Technique,
+    pub position: Vector,
+    pub pitch: f32,
+    pub yaw: f32,
+    pub landing: Vector,
+    /// The first of the map's [`LineupOptions::targets`] containing
+    /// `landing`.
+    pub target: Option<String>,
+    pub throws: usize,
+    pub enemies_flashed: usize,
+    pub teammates_flashed: usize,
+    pub damage: i32,
+    /// Throws that landed in the target area.
+    pub on_target: usize,
+}
+
+impl Lineup {
+    /// The console commands placing a player at the lineup, e.g.
+    /// `setpos -1.00 2.00 3.00;setang -10.00 90.00 0`.
+    pub fn console_command(&self) -> String {
+        format!(
+            "setpos {:.2} {:.2} {:.2};setang {:.2} {:.2} 0",
+            self.position.x, self.position.y, self.position.z, self.pitch, self.yaw
+        )
+    }
+
+    pub fn enemies_flashed_per_throw(&self) -> f64 {
+        self.enemies_flashed as f64 / self.throws.max(1) as f64
+    }
+
+    pub fn damage_per_throw(&self) -> f64 {
+        self.damage as f64 / self.throws.max(1) as f64
+    }
+
+    /// Share of throws that landed in the target area, `0` without one.
+    pub fn on_target_rate(&self) -> f64 {
+        self.on_target as f64 / self.throws.max(1) as f64
+    }
+}
+
+/// Throws of any number of demos by map, clustered on demand.
+///
+/// Also an [`Analyzer`] collecting the grenades of one demo, and
+/// [`Merge`](crate::utils::parallel::Merge) combines the results of many.
+#[derive(Debug, Clone, Default)]
+pub struct Lineups {
+    throws: BTreeMap<String, Vec<LineupThrow>>,
+    map: String,
+}
+
+impl Lineups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the grenades of a demo played on `map`.
+    pub fn add<'a>(&mut self, map: &str, records: impl IntoIterator<Item = &'a GrenadeRecord>) {
+        let throws = self.throws.entry(map.to_string()).or_default();
+        throws.extend(records.into_iter().filter_map(LineupThrow::from_record));
+    }
+
+    pub fn maps(&self) -> impl Iterator<Item = &str> {
+        self.throws.keys().map(String::as_str)
+    }
+
+    pub fn throws(&self, map: &str) -> &[LineupThrow] {
+        self.throws.get(map).map_or(&[], Vec::as_slice)
+    }
+
+    /// Clusters the throws on `map`, most used lineups first.
+    ///
+    /// Throws are assigned in the order they were added to the first
+    /// lineup within all thresholds, so the same throws always give the
+    /// same lineups.
+    pub fn lineups(&self, map: &str, options: &LineupOptions) -> Vec<Lineup> {
+        let mut clusters: Vec<Cluster> = Vec::new();
+        for throw in self.throws(map) {
+            let technique = Technique::of(&throw.release, options);
+            let cluster = clusters
+                .iter_mut()
+                .find(|c| c.technique == technique && c.accepts(throw, options));
+            match cluster {
+                | Some(c) => c.add(throw),
+                | None => clusters.push(Cluster::new(throw, technique)),
+            }
+        }
+
+        let targets = options.targets.get(map).map_or(&[][..], Vec::as_slice);
+        let mut lineups: Vec<Lineup> = clusters
+            .into_iter()
+            .filter(|c| c.throws.len() >= options.min_throws)
+            .map(|c| c.lineup(map, targets))
+            .collect();
+        lineups.sort_by_key(|l| std::cmp::Reverse(l.throws));
+        lineups
+    }
+}
+
+impl Analyzer for Lineups {
+    type Output = Lineups;
+
+    fn on_header(&mut self, header: &DemoHeader) {
+        self.map = header.map_name.clone();
+    }
+
+    fn finish(mut self, state: &GameState) -> Lineups {
+        let map = match &state.match_info.map {
+            | Some(map) if !map.is_empty() => map.clone(),
+            | _ => std::mem::take(&mut self.map),
+        };
+        self.add(&map, state.grenades().records());
+        self
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl crate::utils::parallel::Merge for Lineups {
+    fn merge(&mut self, later: Self) {
+        crate::utils::parallel::Merge::merge(&mut self.throws, later.throws);
+    }
+}
+
+fn horizontal_distance(a: &Vector, b: &Vector) -> f64 {
+    (a.x - b.x).hypot(a.y - b.y)
+}
+
+fn distance(a: &Vector, b: &Vector) -> f64 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2) + (a.z - b.z).powi(2)).sqrt()
+}
+
+/// Difference of two angles in degrees, wrapped to [0, 180].
+fn angle_difference(a: f32, b: f32) -> f32 {
+    let d = (a - b).rem_euclid(360.0);
+    d.min(360.0 - d)
+}
+
+struct Cluster {
+    grenade_type: EquipmentType,
+    team: Team,
+    technique: Technique,
+    throws: Vec<LineupThrow>,
+    position: Vector,
+    pitch: f32,
+    yaw: f32,
+    landing: Vector,
+}
+
+impl Cluster {
+    fn new(throw: &LineupThrow, technique: Technique) -> Self {
+        let mut c = Self {
+            grenade_type: throw.grenade_type,
+            team: throw.team,
+            technique,
+            throws: Vec::new(),
+            position: Vector::default(),
+            pitch: 0.0,
+            yaw: 0.0,
+            landing: Vector::default(),
+        };
+        c.add(throw);
+        c
+    }
+
+    fn accepts(&self, throw: &LineupThrow, options: &LineupOptions) -> bool {
+        self.grenade_type == throw.grenade_type
+            && self.team == throw.team
+            && distance(&self.position, &throw.release.position) <= options.position_radius
+            && angle_difference(self.pitch, throw.release.pitch) <= options.angle_tolerance
+            && angle_difference(self.yaw, throw.release.yaw) <= options.angle_tolerance
+            && horizontal_distance(&self.landing, &throw.landing) <= options.landing_radius
+    }
+
+    /// Adds the throw and updates the means.
+    fn add(&mut self, throw: &LineupThrow) {
+        self.throws.push(throw.clone());
+        let n = self.throws.len() as f64;
+        let mean = |f: fn(&LineupThrow) -> f64| self.throws.iter().map(f).sum::<f64>() / n;
+        self.position = Vector {
+            x: mean(|t| t.release.position.x),
+            y: mean(|t| t.release.position.y),
+            z: mean(|t| t.release.position.z),
+        };
+        self.landing = Vector {
+            x: mean(|t| t.landing.x),
+            y: mean(|t| t.landing.y),
+            z: mean(|t| t.landing.z),
+        };
+        self.pitch = mean(|t| t.release.pitch as f64) as f32;
+        // Yaw wraps around, average the directions instead of the degrees.
+        let sin = mean(|t| (t.release.yaw as f64).to_radians().sin());
+        let cos = mean(|t| (t.release.yaw as f64).to_radians().cos());
+        self.yaw = sin.atan2(cos).to_degrees() as f32;
+    }
+
+    fn lineup(self, map: &str, targets: &[TargetArea]) -> Lineup {
+        let target = targets.iter().find(|t| t.contains(&self.landing));
+        Lineup {
+            map: map.to_string(),
+            grenade_type: self.grenade_type,
+            team: self.team,
+            technique: self.technique,
+            target: target.map(|t| t.name.clone()),
+            throws: self.throws.len(),
+            enemies_flashed: self.throws.iter().map(|t| t.enemies_flashed).sum(),
+            teammates_flashed: self.throws.iter().map(|t| t.teammates_flashed).sum(),
+            damage: self.throws.iter().map(|t| t.damage).sum(),
+            on_target: target.map_or(0, |area| {
+                self.throws
+                    .iter()
+                    .filter(|t| area.contains(&t.landing))
+                    .count()
+            }),
+            position: self.position,
+            pitch: self.pitch,
+            yaw: self.yaw,
+            landing: self.landing,
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Radar overview data of maps, to draw world positions on radar images.
+//!
+//! [`MapRegistry::builtin`] knows the official maps from their Valve
+//! overview files (`resource/overviews/<map>.txt`). Other maps or newer
+//! versions are loaded from such files and replace the built-in entries.
+//!
+//! ```
+//! use cs_demo_parser::maps::MapRegistry;
+//! use cs_demo_parser::sendtables::entity::Vector;
+//!
+//! let maps = MapRegistry::builtin();
+//! let nuke = maps.get("de_nuke").unwrap();
+//! let (x, y) = nuke.to_radar(&Vector { x: 0.0, y: 0.0, z: -600.0 });
+//! assert_eq!("lower", nuke.level(-600.0).unwrap().name);
+//! # let _ = (x, y);
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::sendtables::entity::Vector;
+use crate::utils::{DataError, load_dir};
+
+/// Width and height in pixels of the radar images the overview data is
+/// made for.
+pub const RADAR_SIZE: f64 = 1024.0;
+
+/// A vertical section of a map with its own radar image, e.g. the lower
+/// level of de_nuke.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapLevel {
+    /// `default` for the primary radar image, else the suffix of the
+    /// level's image, e.g. `lower` for `de_nuke_lower_radar`.
+    pub name: String,
+    pub altitude_min: f64,
+    pub altitude_max: f64,
+}
+
+impl MapLevel {
+    pub fn contains(&self, z: f64) -> bool {
+        (self.altitude_min..self.altitude_max).contains(&z)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapMetadata {
+    pub name: String,
+    /// World x coordinate of the left edge of the radar image.
+    pub pos_x: f64,
+    /// World y coordinate of the top edge of the radar image.
+    pub pos_y: f64,
+    /// World units per radar pixel.
+    pub scale: f64,
+    /// Empty for maps with a single level.
+    pub levels: Vec<MapLevel>,
+}
+
+impl MapMetadata {
+    /// Parses a Valve overview file.
+    pub fn from_overview(text: &str) -> Result<Self, DataError> {
+        let root = parse_key_values(text)?;
+        let (name, map) = root
+            .iter()
+            .find_map(|(k, v)| v.block().map(|b| (k, b)))
+            .ok_or(DataError::Missing("map block"))?;
+        let number = |key: &'static str| {
+            value(map, key)
+                .ok_or(DataError::Missing(key))?
+                .parse::<f64>()
+                .map_err(|_| DataError::Parse(format!("{key} is not a number")))
+        };
+        let mut levels = Vec::new();
+        if let Some(sections) = get(map, "verticalsections").and_then(KeyValue::block) {
+            for (level, section) in sections {
+                let Some(section) = section.block() else {
+                    continue;
+                };
+                let altitude = |key: &'static str| {
+                    value(section, key)
+                        .and_then(|v| v.parse::<f64>().ok())
+                        .ok_or(DataError::Missing(key))
+                };
+                levels.push(MapLevel {
+                    name: level.clone(),
+                    altitude_min: altitude("AltitudeMin")?,
+                    altitude_max: altitude("AltitudeMax")?,
+                });
+            }
+        }
+        Ok(Self {
+            name: name.clone(),
+            pos_x: number("pos_x")?,
+            pos_y: number("pos_y")?,
+            scale: number("scale")?,
+            levels,
+        })
+    }
+
+    /// Pixel coordinates of `position` on the radar image, from the top
+    /// left corner.
+    pub fn to_radar(&self, position: &Vector) -> (f64, f64) {
+        (
+            (position.x - self.pos_x) / self.scale,
+            (self.pos_y - position.y) / self.scale,
+        )
+    }
+
+    /// Inverse of [`to_radar`](Self::to_radar), at height `0`.
+    pub fn to_world(&self, x: f64, y: f64) -> Vector {
+        Vector {
+            x: self.pos_x + x * self.scale,
+            y: self.pos_y - y * self.scale,
+            z: 0.0,
+        }
+    }
+
+    /// The level at height `z`, `None` for maps with a single level.
+    /// Heights outside every section belong to the `default` level.
+    pub fn level(&self, z: f64) -> Option<&MapLevel> {
+        self.levels
+            .iter()
+            .find(|l| l.contains(z))
+            .or_else(|| self.levels.iter().find(|l| l.name == "default"))
+    }
+}
+
+/// Overview data of official maps: name, `pos_x`, `pos_y`, `scale` and the
+/// vertical sections as name, `AltitudeMin` and `AltitudeMax`.
+#[allow(clippy::type_complexity)]
+const BUILTIN: &[(&str, f64, f64, f64, &[(&str, f64, f64)])] = &[
+    (
+        "ar_baggage",
+        -1316.0,
+        1288.0,
+        2.539062,
+        &[("default", -5.0, 10000.0), ("lower", -10000.0, -5.0)],
+    ),
+    ("ar_shoots", -1368.0, 1952.0, 2.6875, &[]),
+    ("cs_italy", -2647.0, 2592.0, 4.6, &[]),
+    ("cs_office", -1838.0, 1858.0, 4.1, &[]),
+    ("de_ancient", -2953.0, 2164.0, 5.0, &[]),
+    ("de_anubis", -2796.0, 3328.0, 5.22, &[]),
+    ("de_dust", -2850.0, 4073.0, 6.0, &[]),
+    ("de_dust2", -2476.0, 3239.0, 4.4, &[]),
+    ("de_inferno", -2087.0, 3870.0, 4.9, &[]),
+    ("de_mirage", -3230.0, 1713.0, 5.0, &[]),
+    (
+        "de_nuke",
+        -3453.0,
+        2887.0,
+        7.0,
+        &[("default", -495.0, 10000.0), ("lower", -10000.0, -495.0)],
+    ),
+    ("de_overpass", -4831.0, 1781.0, 5.2, &[]),
+    (
+        "de_train",
+        -2308.0,
+        2078.0,
+        4.082077,
+        &[("default", -50.0, 20000.0), ("lower", -5000.0, -50.0)],
+    ),
+    (
+        "de_vertigo",
+        -3168.0,
+        1762.0,
+        4.0,
+        &[("default", 11700.0, 20000.0), ("lower", -10000.0, 11700.0)],
+    ),
+];
+
+/// Overview data by map name.
+#[derive(Debug, Clone, Default)]
+pub struct MapRegistry {
+    maps: BTreeMap<String, MapMetadata>,
+}
+
+impl MapRegistry {
+    /// A registry of the official maps.
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        for &(name, pos_x, pos_y, scale, levels) in BUILTIN {
+            registry.insert(MapMetadata {
+                name: name.to_string(),
+                pos_x,
+                pos_y,
+                scale,
+                levels: levels
+                    .iter()
+                    .map(|&(name, altitude_min, altitude_max)| MapLevel {
+                        name: name.to_string(),
+                        altitude_min,
+                        altitude_max,
+                    })
+                    .collect(),
+            });
+        }
+        registry
+    }
+
+    /// The map called `name`, which may be a path like the map name of
+    /// workshop demos, e.g. `workshop/123456/de_nuke`.
+    pub fn get(&self, name: &str) -> Option<&MapMetadata> {
+        self.maps.get(base_name(name))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.maps.keys().map(String::as_str)
+    }
+
+    /// Adds `map`, returning the entry it replaced.
+    pub fn insert(&mut self, map: MapMetadata) -> Option<MapMetadata> {
+        self.maps.insert(map.name.clone(), map)
+    }
+
+    /// Adds the map of an overview file's content.
+    pub fn load_overview(&mut self, text: &str) -> Result<&MapMetadata, DataError> {
+        let map = MapMetadata::from_overview(text)?;
+        let name = map.name.clone();
+        self.insert(map);
+        Ok(&self.maps[&name])
+    }
+
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<&MapMetadata, DataError> {
+        let text = std::fs::read_to_string(path)?;
+        self.load_overview(&text)
+    }
+
+    /// Adds every `.txt` overview file in `dir` and returns how many were
+    /// loaded.
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<usize, DataError> {
+        load_dir(dir.as_ref(), "txt", |path| self.load_file(path).map(|_| ()))
+    }
+}
+
+/// `name` without the directories of workshop map paths.
+pub(crate) fn base_name(name: &str) -> &str {
+    name.rsplit(['/', '\\']).next().unwrap_or(name)
+}
+
+/// A value of a KeyValues file, as used by overview files.
+#[derive(Debug)]
+enum KeyValue {
+    Value(String),
+    Block(Vec<(String, KeyValue)>),
+}
+
+impl KeyValue {
+    fn block(&self) -> Option<&[(String, KeyValue)]> {
+        match self {
+            | KeyValue::Block(b) => Some(b),
+            | KeyValue::Value(_) => None,
+        }
+    }
+}
+
+/// Keys are case-insensitive.
+fn get<'a>(block: &'a [(String, KeyValue)], key: &str) -> Option<&'a KeyValue> {
+    block
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v)
+}
+
+fn value<'a>(block: &'a [(String, KeyValue)], key: &str) -> Option<&'a str> {
+    match get(block, key)? {
+        | KeyValue::Value(v) => Some(v.trim()),
+        | KeyValue::Block(_) => None,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Text(String),
+    Open,
+    Close,
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, DataError> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            | '{' => {
+                chars.next();
+                tokens.push(Token::Open);
+            },
+            | '}' => {
+                chars.next();
+                tokens.push(Token::Close);
+            },
+            | '"' => {
+                chars.next();
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        | Some('"') => break,
+                        | Some(c) => s.push(c),
+                        | None => return Err(DataError::Parse("unterminated string".into())),
+                    }
+                }
+                tokens.push(Token::Text(s));
+            },
+            | '/' if text_starts_comment(&chars) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            },
+            | c if c.is_whitespace() => {
+                chars.next();
+            },
+            | _ => {
+                let mut s = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '{' | '}' | '"') {
+                        break;
+                    }
+                    s.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Text(s));
+            },
+        }
+    }
+    Ok(tokens)
+}
+
+/// Whether the iterator, positioned at a `/`, is at `//`.
+fn text_starts_comment(chars: &std::iter::Peekable<std::str::Chars>) -> bool {
+    let mut ahead = chars.clone();
+    ahead.next();
+    ahead.peek() == Some(&'/')
+}
+
+fn parse_key_values(text: &str) -> Result<Vec<(String, KeyValue)>, DataError> {
+    let mut stack: Vec<(String, Vec<(String, KeyValue)>)> = vec![(String::new(), Vec::new())];
+    let mut key: Option<String> = None;
+    for token in tokenize(text)? {
+        match token {
+            | Token::Text(s) => match key.take() {
+                | Some(k) => stack
+                    .last_mut()
+                    .expect("the root is never popped")
+                    .1
+                    .push((k, KeyValue::Value(s))),
+                | None => key = Some(s),
+            },
+            | Token::Open => {
+                let k = key
+                    .take()
+                    .ok_or_else(|| DataError::Parse("block without a key".into()))?;
+                stack.push((k, Vec::new()));
+            },
+            | Token::Close => {
+                if stack.len() < 2 {
+                    return Err(DataError::Parse("unbalanced braces".into()));
+                }
+                let (k, block) = stack.pop().expect("checked above");
+                stack
+                    .last_mut()
+                    .expect("the root is never popped")
+                    .1
+                    .push((k, KeyValue::Block(block)));
+            },
+        }
+    }
+    if stack.len() != 1 {
+        return Err(DataError::Parse("unbalanced braces".into()));
+    }
+    Ok(stack.pop().expect("checked above").1)
+}
@@ -6,11 +6,14 @@ use crate::{sendtables2, stringtables};
 
 pub mod datatable;
 pub mod lumps;
+pub mod segments;
+pub mod stream;
 
 use prost::Message;
 use std::collections::HashMap;
 use std::io::Read;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
 
 /// Error type returned by [`Parser`] operations.
 #[derive(Debug)]
@@ -39,12 +42,19 @@ pub struct DemoHeader {
     pub signon_length: i32,
 }
 
+/// A Source 2 entity was created, updated or deleted. Created and updated
+/// entities can be looked up with [`GameState::entity`], deleted ones have
+/// already been removed.
 #[derive(Clone, Debug)]
 pub struct EntityEvent {
-    pub entity: crate::sendtables2::Entity,
+    pub handle: sendtables2::EntityHandle,
+    pub class: sendtables2::Class,
     pub op: crate::sendtables::EntityOp,
 }
 
+/// Only dispatched while a handler for it is registered, since it keeps a
+/// copy of the entity's properties alive.
+#[deprecated(note = "use `Parser::register_on_entity_created` or `EntityEvent`")]
 #[derive(Clone, Debug)]
 pub struct EntityCreated {
     pub entity: crate::sendtables2::Entity,
@@ -85,6 +95,86 @@ pub struct ParserConfig {
 
     /// Override the tick rate in Hz when no information is available in the demo.
     pub tick_rate_override: Option<f64>,
+
+    /// Skip decoding entities. Positions, equipment and other state read
+    /// from entities won't be available. Registering an entity handler, or
+    /// a handler for an event derived from entities such as
+    /// [`GrenadeProjectileThrow`](crate::events::GrenadeProjectileThrow),
+    /// turns decoding back on.
+    pub skip_entities: bool,
+
+    /// Only decode entities of these server classes, e.g. `CCSPlayerPawn`
+    /// and `CCSGameRulesProxy`. Updates of other entities are skipped before
+    /// their values are decoded. `None` decodes every class. Entities are
+    /// only decoded for Source 2 demos.
+    pub entity_classes: Option<Vec<String>>,
+
+    /// Skip string tables. Player names and steam ids are read from the
+    /// `userinfo` table and won't be available. Registering a string table
+    /// handler turns them back on. The `instancebaseline` table is still
+    /// read while entities are decoded, entities start from its values.
+    pub skip_string_tables: bool,
+
+    /// Skip user messages such as chat messages. Registering a user message
+    /// handler, or a handler for an event derived from user messages, turns
+    /// them back on.
+    pub skip_user_messages: bool,
+
+    /// Skip voice data. Registering a voice message handler turns it back on.
+    pub skip_voice_data: bool,
+}
+
+bitflags::bitflags! {
+    /// Optional parts of a demo that [`ParserConfig`] can skip.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Decoding: u8 {
+        const ENTITIES = 1 << 0;
+        const STRING_TABLES = 1 << 1;
+        const USER_MESSAGES = 1 << 2;
+        const VOICE_DATA = 1 << 3;
+    }
+}
+
+impl Decoding {
+    /// Parts that have to be decoded for a handler of `T`, an event or net
+    /// message type, to be called.
+    #[allow(deprecated)]
+    fn required_by<T: 'static>() -> Self {
+        use crate::events::{
+            ChatMessage, GrenadeProjectileBounce, GrenadeProjectileDestroy, GrenadeProjectileThrow,
+            HostageStateChanged, RankUpdate, StringTableCreated,
+        };
+        use std::any::TypeId;
+
+        let t = TypeId::of::<T>();
+        if t == TypeId::of::<EntityEvent>()
+            || t == TypeId::of::<EntityCreated>()
+            || t == TypeId::of::<proto_msg::CsvcMsgPacketEntities>()
+            || t == TypeId::of::<GrenadeProjectileThrow>()
+            || t == TypeId::of::<GrenadeProjectileBounce>()
+            || t == TypeId::of::<GrenadeProjectileDestroy>()
+            || t == TypeId::of::<HostageStateChanged>()
+        {
+            Self::ENTITIES
+        } else if t == TypeId::of::<StringTableUpdated>()
+            || t == TypeId::of::<StringTableCreated>()
+            || t == TypeId::of::<proto_msg::CsvcMsgCreateStringTable>()
+            || t == TypeId::of::<proto_msg::CsvcMsgUpdateStringTable>()
+        {
+            Self::STRING_TABLES
+        } else if t == TypeId::of::<ChatMessage>()
+            || t == TypeId::of::<RankUpdate>()
+            || t == TypeId::of::<proto_msg::CsvcMsgUserMessage>()
+        {
+            Self::USER_MESSAGES
+        } else if t == TypeId::of::<proto_msg::CsvcMsgVoiceData>()
+            || t == TypeId::of::<proto_msg::CsvcMsgVoiceInit>()
+        {
+            Self::VOICE_DATA
+        } else {
+            Self::empty()
+        }
+    }
 }
 
 impl Default for ParserConfig {
@@ -99,10 +189,23 @@ impl Default for ParserConfig {
             ignore_bad_encrypted_data: false,
             ignore_missing_decryption_key: false,
             tick_rate_override: None,
+            skip_entities: false,
+            entity_classes: None,
+            skip_string_tables: false,
+            skip_user_messages: false,
+            skip_voice_data: false,
         }
     }
 }
 
+/// Callback invoked synchronously with the game state after each frame.
+#[deprecated(note = "pass the closure to `Parser::register_on_frame_done` directly")]
+pub type FrameHook = Box<dyn FnMut(&GameState) + Send>;
+
+/// Callback invoked synchronously with an event and the game state it was
+/// applied to. See [`Parser::register_sync_event_handler`].
+type SyncHandler = Box<dyn FnMut(&dyn std::any::Any, &GameState) + Send>;
+
 /// Parser for CS:GO / CS2 demo files.
 pub struct Parser<R: Read> {
     bit_reader: BitReader<R>,
@@ -116,12 +219,39 @@ pub struct Parser<R: Read> {
     equipment_mapping: HashMap<String, crate::common::EquipmentType>,
     game_state: GameState,
     current_frame: i32,
+    /// Set once a `DEM_FullPacket` has been applied, see
+    /// [`Self::apply_full_packet`].
+    full_packet_applied: bool,
     cancelled: bool,
     game_events: crate::game_events::GameEventHandler,
     header: Option<DemoHeader>,
     config: ParserConfig,
     signon_skipped: bool,
     lump_size: u64,
+    sync_handlers: HashMap<std::any::TypeId, Vec<(HandlerIdentifier, SyncHandler)>>,
+    /// Sync handlers called for events of every type.
+    sync_any_handlers: Vec<(HandlerIdentifier, SyncHandler)>,
+    next_sync_handler: HandlerIdentifier,
+    /// Parts of the demo registered handlers need, see [`Decoding`].
+    required: AtomicU8,
+    /// Set once a handler for the deprecated [`EntityCreated`] is registered.
+    entity_created_handlers: AtomicBool,
+}
+
+#[cfg(feature = "mmap")]
+impl Parser<std::io::Cursor<memmap2::Mmap>> {
+    /// Opens the demo at `path` as a memory map instead of reading it, which
+    /// avoids copying the file into memory up front. Frame payloads are
+    /// still copied out of the map in bulk as they are parsed, there is no
+    /// zero-copy path.
+    ///
+    /// The file must not be modified while it is being parsed.
+    pub fn open_mmap(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the caller keeps the file unchanged while it is mapped.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self::new(std::io::Cursor::new(map)))
+    }
 }
 
 impl<R: Read> Parser<R> {
@@ -132,24 +262,32 @@ impl<R: Read> Parser<R> {
 
     /// Creates a new [`Parser`] from the given reader and configuration.
     pub fn with_config(reader: R, config: ParserConfig) -> Self {
+        let mut s2_tables = sendtables2::Parser::new();
+        s2_tables.set_entity_classes(config.entity_classes.clone());
         Self {
             bit_reader: BitReader::new_large(reader),
             event_dispatcher: EventDispatcher::with_capacity(config.msg_queue_size),
             msg_dispatcher: EventDispatcher::with_capacity(config.msg_queue_size),
             user_msg_dispatcher: EventDispatcher::with_capacity(config.msg_queue_size),
-            s2_tables: sendtables2::Parser::new(),
+            s2_tables,
             s1_tables: TablesParser::new(),
             string_tables: stringtables::StringTables::new(),
             server_classes: Vec::new(),
             equipment_mapping: HashMap::new(),
             game_state: GameState::default(),
             current_frame: 0,
+            full_packet_applied: false,
             cancelled: false,
             game_events: crate::game_events::GameEventHandler::new(),
             header: None,
             config,
             signon_skipped: false,
             lump_size: 0,
+            sync_handlers: HashMap::new(),
+            sync_any_handlers: Vec::new(),
+            next_sync_handler: 1,
+            required: AtomicU8::new(0),
+            entity_created_handlers: AtomicBool::new(false),
         }
     }
 
@@ -158,6 +296,8 @@ impl<R: Read> Parser<R> {
         E: Send + Sync + 'static,
         F: Fn(&E) + Send + Sync + 'static,
     {
+        self.require(Decoding::required_by::<E>());
+        self.note_entity_created_handler::<E>();
         self.event_dispatcher.register_handler::<E, F>(handler)
     }
 
@@ -166,6 +306,7 @@ impl<R: Read> Parser<R> {
         M: Send + Sync + 'static,
         F: Fn(&M) + Send + Sync + 'static,
     {
+        self.require(Decoding::required_by::<M>());
         self.msg_dispatcher.register_handler::<M, F>(handler)
     }
 
@@ -174,6 +315,7 @@ impl<R: Read> Parser<R> {
         M: Send + Sync + 'static,
         F: Fn(&M) + Send + Sync + 'static,
     {
+        self.require(Decoding::USER_MESSAGES);
         self.user_msg_dispatcher.register_handler::<M, F>(handler)
     }
 
@@ -181,28 +323,137 @@ impl<R: Read> Parser<R> {
     where
         F: Fn(&EntityEvent) + Send + Sync + 'static,
     {
-        self.event_dispatcher
-            .register_handler::<EntityEvent, F>(handler)
+        self.register_event_handler::<EntityEvent, F>(handler)
     }
 
-    pub fn register_on_entity_created<F>(&self, handler: F) -> HandlerIdentifier
+    /// Registers a callback that runs on the parsing thread for every entity
+    /// that is created. Remove it with
+    /// [`unregister_sync_handler`](Self::unregister_sync_handler).
+    pub fn register_on_entity_created<F>(&mut self, mut handler: F) -> HandlerIdentifier
     where
-        F: Fn(&crate::sendtables2::Entity) + Send + Sync + 'static,
+        F: FnMut(&crate::sendtables2::Entity) + Send + 'static,
     {
-        self.event_dispatcher
-            .register_handler::<EntityEvent, _>(move |ev| {
-                if ev.op.contains(crate::sendtables::EntityOp::CREATED) {
-                    handler(&ev.entity);
-                }
-            })
+        self.register_sync_event_handler::<EntityEvent, _>(move |ev, gs| {
+            if ev.op.contains(crate::sendtables::EntityOp::CREATED)
+                && let Some(entity) = gs.entity(ev.handle)
+            {
+                handler(entity);
+            }
+        })
     }
 
     pub fn register_on_string_table<F>(&self, handler: F) -> HandlerIdentifier
     where
         F: Fn(&crate::stringtables::StringTable) + Send + Sync + 'static,
     {
-        self.event_dispatcher
-            .register_handler::<StringTableUpdated, _>(move |ev| handler(&ev.table))
+        self.register_event_handler::<StringTableUpdated, _>(move |ev| handler(&ev.table))
+    }
+
+    /// Registers a callback that runs on the parsing thread for every event of
+    /// type `E`, after the [`GameState`] has been updated and before the event
+    /// is handed to the asynchronous event handlers. Unlike those handlers it
+    /// receives the current [`GameState`] and is guaranteed to have run for
+    /// every event once parsing returns.
+    pub fn register_sync_event_handler<E, F>(&mut self, mut handler: F) -> HandlerIdentifier
+    where
+        E: 'static,
+        F: FnMut(&E, &GameState) + Send + 'static,
+    {
+        self.require(Decoding::required_by::<E>());
+        self.note_entity_created_handler::<E>();
+        let id = self.next_sync_handler_id();
+        self.sync_handlers
+            .entry(std::any::TypeId::of::<E>())
+            .or_default()
+            .push((
+                id,
+                Box::new(move |ev, gs| {
+                    if let Some(ev) = ev.downcast_ref::<E>() {
+                        handler(ev, gs);
+                    }
+                }),
+            ));
+        id
+    }
+
+    /// Registers a callback that runs on the parsing thread for events of
+    /// every type, after the handlers registered for the event's type with
+    /// [`register_sync_event_handler`](Self::register_sync_event_handler).
+    /// Turns decoding of every part skipped by the [`ParserConfig`] back on.
+    pub fn register_sync_any_handler<F>(&mut self, handler: F) -> HandlerIdentifier
+    where
+        F: FnMut(&dyn std::any::Any, &GameState) + Send + 'static,
+    {
+        self.require(Decoding::all());
+        let id = self.next_sync_handler_id();
+        self.sync_any_handlers.push((id, Box::new(handler)));
+        id
+    }
+
+    /// Removes a handler registered with
+    /// [`register_sync_event_handler`](Self::register_sync_event_handler) or
+    /// [`register_sync_any_handler`](Self::register_sync_any_handler).
+    pub fn unregister_sync_handler(&mut self, id: HandlerIdentifier) {
+        for handlers in self.sync_handlers.values_mut() {
+            handlers.retain(|(h, _)| *h != id);
+        }
+        self.sync_any_handlers.retain(|(h, _)| *h != id);
+    }
+
+    fn next_sync_handler_id(&mut self) -> HandlerIdentifier {
+        let id = self.next_sync_handler;
+        self.next_sync_handler += 1;
+        id
+    }
+
+    /// Registers a callback that runs on the parsing thread after every
+    /// [`FrameDone`](crate::events::FrameDone). See
+    /// [`register_sync_event_handler`](Self::register_sync_event_handler).
+    pub fn register_on_frame_done<F>(&mut self, mut hook: F) -> HandlerIdentifier
+    where
+        F: FnMut(&GameState) + Send + 'static,
+    {
+        self.register_sync_event_handler::<crate::events::FrameDone, _>(move |_, gs| hook(gs))
+    }
+
+    /// Registers a [`TickSampler`](crate::ticks::TickSampler) recording player
+    /// state on every frame. Read the samples from the returned handle once
+    /// parsing is done.
+    pub fn register_tick_sampler(
+        &mut self,
+        config: crate::ticks::TickSamplerConfig,
+    ) -> Arc<Mutex<crate::ticks::TickSampler>> {
+        let sampler = Arc::new(Mutex::new(crate::ticks::TickSampler::new(config)));
+        let s = Arc::clone(&sampler);
+        self.register_on_frame_done(move |gs| s.lock().unwrap().sample(gs));
+        sampler
+    }
+
+    /// Parts of the demo that are decoded: everything not skipped by the
+    /// [`ParserConfig`] plus what the registered handlers require.
+    pub fn decoding(&self) -> Decoding {
+        let mut skipped = Decoding::empty();
+        skipped.set(Decoding::ENTITIES, self.config.skip_entities);
+        skipped.set(Decoding::STRING_TABLES, self.config.skip_string_tables);
+        skipped.set(Decoding::USER_MESSAGES, self.config.skip_user_messages);
+        skipped.set(Decoding::VOICE_DATA, self.config.skip_voice_data);
+        let required = Decoding::from_bits_truncate(self.required.load(Ordering::Relaxed));
+        Decoding::all().difference(skipped).union(required)
+    }
+
+    fn require(&self, parts: Decoding) {
+        self.required.fetch_or(parts.bits(), Ordering::Relaxed);
+    }
+
+    #[allow(deprecated)]
+    fn note_entity_created_handler<E: 'static>(&self) {
+        if std::any::TypeId::of::<E>() == std::any::TypeId::of::<EntityCreated>() {
+            self.entity_created_handlers.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn decodes(&self, part: Decoding) -> bool {
+        self.decoding().contains(part)
     }
 
     pub fn game_state(&self) -> &GameState {
@@ -213,10 +464,94 @@ impl<R: Read> Parser<R> {
         self.string_tables.get(name)
     }
 
+    pub(crate) fn config(&self) -> &ParserConfig {
+        &self.config
+    }
+
     fn game_state_mut(&mut self) -> &mut GameState {
         &mut self.game_state
     }
 
+    /// Hands the entries of the `instancebaseline` table to the Source 2
+    /// entity decoder. Entries are keyed by class id.
+    fn update_instance_baselines(&mut self, table: &crate::stringtables::StringTable) {
+        if table.name != "instancebaseline" {
+            return;
+        }
+        for entry in table.entries.values() {
+            if let Ok(class_id) = entry.value.parse() {
+                self.s2_tables
+                    .set_instance_baseline(class_id, entry.user_data.clone());
+            }
+        }
+    }
+
+    fn string_table_updated(&mut self, table: crate::stringtables::StringTable) {
+        if table.name.eq_ignore_ascii_case("userinfo") {
+            self.game_state_mut().apply_userinfo_table(&table);
+        }
+        if table.name == "ItemDefinitions" {
+            self.update_equipment_mapping_from_classes();
+        }
+        self.update_instance_baselines(&table);
+        self.dispatch_event(StringTableUpdated { table });
+    }
+
+    /// Applies the snapshot of a `DEM_FullPacket`: the contents of all
+    /// string tables, then a packet recreating every entity. A full packet
+    /// repeats the state built up by the frames before it, so only the
+    /// first one a parser reads is applied. That is where a parser starts
+    /// that skipped to a full packet, see [`segments`].
+    fn apply_full_packet(&mut self, msg: crate::proto::msgs2::CDemoFullPacket) {
+        self.full_packet_applied = true;
+        for snapshot in msg.string_table.iter().flat_map(|t| &t.tables) {
+            let name = snapshot.table_name();
+            let wanted = self.decodes(Decoding::STRING_TABLES)
+                || (self.decodes(Decoding::ENTITIES) && name == "instancebaseline");
+            if !wanted {
+                continue;
+            }
+            if let Some(t) = self.string_tables.apply_snapshot(snapshot) {
+                self.string_table_updated(t);
+            }
+        }
+        let data = msg.packet.and_then(|p| p.data).unwrap_or_default();
+        let mut slice = &data[..];
+        while let Some((msg_type, msg_buf)) = stringtables::next_message(&mut slice) {
+            self.handle_svc_message(msg_type, msg_buf);
+        }
+        self.game_state.full_packet_applied();
+    }
+
+    /// Keeps the `instancebaseline` table up to date while the other string
+    /// tables are skipped. Those still take up a table id, so that updates
+    /// find the right table.
+    fn update_baseline_table(&mut self, kind: proto_msg::SvcMessages, buf: &[u8]) {
+        let table = match kind {
+            | proto_msg::SvcMessages::SvcCreateStringTable => {
+                let Ok(msg) = proto_msg::CsvcMsgCreateStringTable::decode(buf) else {
+                    return;
+                };
+                if msg.name() == "instancebaseline" {
+                    self.string_tables.on_create_string_table(&msg)
+                } else {
+                    self.string_tables.skip_create_string_table();
+                    None
+                }
+            },
+            | proto_msg::SvcMessages::SvcUpdateStringTable => {
+                let Ok(msg) = proto_msg::CsvcMsgUpdateStringTable::decode(buf) else {
+                    return;
+                };
+                self.string_tables.on_update_string_table(&msg)
+            },
+            | _ => None,
+        };
+        if let Some(t) = table {
+            self.update_instance_baselines(&t);
+        }
+    }
+
     fn update_equipment_mapping_from_classes(&mut self) {
         let item_defs = self.string_tables.get("ItemDefinitions");
         self.equipment_mapping =
@@ -234,9 +569,135 @@ impl<R: Read> Parser<R> {
         E: Send + Sync + 'static,
     {
         self.game_state_mut().handle_event(&event);
+        if let Some(handlers) = self.sync_handlers.get_mut(&std::any::TypeId::of::<E>()) {
+            for (_, handler) in handlers {
+                handler(&event, &self.game_state);
+            }
+        }
+        for (_, handler) in &mut self.sync_any_handlers {
+            handler(&event, &self.game_state);
+        }
         self.event_dispatcher.dispatch(event);
     }
 
+    /// Stores `entity` in the [`GameState`], or removes it if `op` deletes
+    /// it, and dispatches the events a packet changing it would, including
+    /// the deletion of a different entity it replaces.
+    pub fn dispatch_entity(
+        &mut self,
+        entity: sendtables2::Entity,
+        op: crate::sendtables::EntityOp,
+    ) {
+        use crate::sendtables::EntityOp;
+
+        let handle = entity.handle();
+        let class = entity.class.clone();
+        if op.contains(EntityOp::DELETED) {
+            self.game_state.remove_entity(entity.index);
+        } else {
+            let replaced = self
+                .game_state
+                .entities
+                .by_index(entity.index)
+                .filter(|old| old.handle() != handle)
+                .map(|old| (old.handle(), old.class.clone()));
+            if let Some((old, old_class)) = replaced {
+                self.game_state.remove_entity(entity.index);
+                self.dispatch_entity_event(old, old_class, EntityOp::DELETED | EntityOp::LEFT);
+            }
+            self.game_state.add_entity(entity);
+        }
+        self.dispatch_entity_event(handle, class, op);
+    }
+
+    /// Dispatches the events for an entity created, updated or deleted by a
+    /// Source 2 packet, including the lifecycle events of grenade
+    /// projectiles. The entity has been written to the store already.
+    #[allow(deprecated)]
+    fn dispatch_entity_event(
+        &mut self,
+        handle: sendtables2::EntityHandle,
+        class: sendtables2::Class,
+        op: crate::sendtables::EntityOp,
+    ) {
+        use crate::sendtables::EntityOp;
+
+        let index = handle.index;
+        let before = self.game_state.grenade_projectiles.get(&index).cloned();
+        let hostage_before = self.game_state.hostages.get(&index).map(|h| h.status());
+        let created = op.contains(EntityOp::CREATED);
+        self.dispatch_event(EntityEvent { handle, class, op });
+        let wanted = created && self.entity_created_handlers.load(Ordering::Relaxed);
+        if let Some(entity) = self.game_state.entity(handle).filter(|_| wanted) {
+            let entity = entity.clone();
+            self.dispatch_event(EntityCreated { entity });
+        }
+
+        // Only projectiles have their positions tracked.
+        let position = if self.game_state.grenade_projectiles.contains_key(&index) {
+            self.game_state.entity(handle).and_then(|e| e.position())
+        } else {
+            None
+        };
+        if let Some(pos) = position {
+            let moved = self
+                .game_state
+                .grenade_projectiles
+                .get(&index)
+                .is_some_and(|g| g.last_position() != Some(&pos));
+            if moved {
+                let time = self.current_time();
+                let tick = self.game_state.ingame_tick;
+                self.game_state
+                    .track_grenade_position(index, pos, tick, time);
+            }
+        }
+        let after = self.game_state.grenade_projectiles.get(&index).cloned();
+        match (before, after) {
+            | (None, Some(g)) => self.dispatch_event(crate::events::GrenadeProjectileThrow {
+                projectile: Some(g),
+            }),
+            | (Some(b), Some(g)) if g.bounces > b.bounces => {
+                self.dispatch_event(crate::events::GrenadeProjectileBounce {
+                    bounce_nr: g.bounces,
+                    projectile: Some(g),
+                })
+            },
+            | (Some(b), None) => self.dispatch_event(crate::events::GrenadeProjectileDestroy {
+                projectile: Some(b),
+            }),
+            | _ => {},
+        }
+
+        let hostage = self.game_state.hostages.get(&index);
+        if let Some(h) = hostage.filter(|h| Some(h.status()) != hostage_before) {
+            self.dispatch_event(crate::events::HostageStateChanged {
+                old_state: hostage_before.map(|(state, _)| state).unwrap_or_default(),
+                new_state: h.state,
+                hostage: Some(h.clone()),
+            });
+        }
+    }
+
+    /// Source 1 demos only tell about hostages through game events. Moves
+    /// the hostage `index` to `state` following `leader`, unless its entity
+    /// says where it is, and dispatches the change like entity updates do.
+    pub(crate) fn hostage_game_event(
+        &mut self,
+        index: i32,
+        state: crate::common::HostageState,
+        leader: Option<crate::common::Player>,
+    ) {
+        let changed = self.game_state.hostage_game_event(index, state, leader);
+        if let Some((old_state, h)) = changed {
+            self.dispatch_event(crate::events::HostageStateChanged {
+                old_state,
+                new_state: h.state,
+                hostage: Some(h),
+            });
+        }
+    }
+
     pub fn dispatch_net_message<M>(&mut self, msg: M)
     where
         M: Send + Sync + 'static,
@@ -261,6 +722,12 @@ impl<R: Read> Parser<R> {
         &self.server_classes
     }
 
+    /// Classes of a Source 2 demo, ordered by class id. Source 1 demos use
+    /// [`server_classes`](Self::server_classes) instead.
+    pub fn source2_classes(&self) -> Vec<&crate::sendtables2::Class> {
+        self.s2_tables.classes()
+    }
+
     pub fn header(&self) -> Option<DemoHeader> {
         self.header.clone()
     }
@@ -371,10 +838,12 @@ impl<R: Read> Parser<R> {
             Ok(header)
         }));
 
-        match parse_res {
-            | Ok(res) => res,
-            | Err(_) => Err(ParserError::UnexpectedEndOfDemo),
-        }
+        let header = match parse_res {
+            | Ok(res) => res?,
+            | Err(_) => return Err(ParserError::UnexpectedEndOfDemo),
+        };
+        self.game_state.tick_rate = self.tick_rate();
+        Ok(header)
     }
 
     /// Parses the next frame of the demo. Returns `Ok(false)` if the demo
@@ -391,9 +860,7 @@ impl<R: Read> Parser<R> {
                 // lump data after the header which should be skipped before
                 // parsing frames.
                 if h.filestamp == "PBDEMS2" && self.lump_size > 0 {
-                    for _ in 0..self.lump_size {
-                        self.bit_reader.read_int(8);
-                    }
+                    self.bit_reader.skip_bytes(self.lump_size);
                 }
             }
             self.signon_skipped = true;
@@ -441,27 +908,20 @@ impl<R: Read> Parser<R> {
             // Console command
             | 4 => {
                 let len = self.bit_reader.read_signed_int(32) as u32;
-                for _ in 0..len {
-                    self.bit_reader.read_int(8);
-                }
+                self.bit_reader.skip_bytes(len as u64);
                 Ok(true)
             },
             // User command
             | 5 => {
                 self.bit_reader.read_int(32); // command number
                 let len = self.bit_reader.read_signed_int(32) as u32;
-                for _ in 0..len {
-                    self.bit_reader.read_int(8);
-                }
+                self.bit_reader.skip_bytes(len as u64);
                 Ok(true)
             },
             // Send tables
             | 6 => {
                 let len = self.bit_reader.read_signed_int(32) as usize;
-                let mut data = Vec::with_capacity(len);
-                for _ in 0..len {
-                    data.push(self.bit_reader.read_int(8) as u8);
-                }
+                let data = self.bit_reader.read_bytes(len);
 
                 if self.s1_tables.parse_packet(&data).is_ok() {
                     self.server_classes = self.s1_tables.server_classes().to_vec();
@@ -474,19 +934,18 @@ impl<R: Read> Parser<R> {
             // String tables
             | 9 => {
                 let len = self.bit_reader.read_signed_int(32) as usize;
-                let mut data = Vec::with_capacity(len);
-                for _ in 0..len {
-                    data.push(self.bit_reader.read_int(8) as u8);
+                if self.decodes(Decoding::STRING_TABLES) {
+                    let data = self.bit_reader.read_bytes(len);
+                    self.parse_stringtable_packet(&data);
+                } else {
+                    self.bit_reader.skip_bytes(len as u64);
                 }
-                self.parse_stringtable_packet(&data);
                 Ok(true)
             },
             // Custom data
             | 8 => {
                 let len = self.bit_reader.read_signed_int(32) as u32;
-                for _ in 0..len {
-                    self.bit_reader.read_int(8);
-                }
+                self.bit_reader.skip_bytes(len as u64);
                 Ok(true)
             },
             // Stop
@@ -494,9 +953,7 @@ impl<R: Read> Parser<R> {
             // Unhandled but length-prefixed commands
             | 10 | 11 | 12 | 13 | 14 | 15 | 16 | 17 | 18 | 19 => {
                 let len = self.bit_reader.read_signed_int(32) as u32;
-                for _ in 0..len {
-                    self.bit_reader.read_int(8);
-                }
+                self.bit_reader.skip_bytes(len as u64);
                 Ok(true)
             },
             | _ => Ok(true),
@@ -530,22 +987,19 @@ impl<R: Read> Parser<R> {
         const HEADER_BITS: u32 = (152 + 4 + 4) * 8;
         self.bit_reader.skip_bits(HEADER_BITS);
         let size = self.bit_reader.read_signed_int(32) as usize;
-
-        let mut data = Vec::with_capacity(size);
-        for _ in 0..size {
-            data.push(self.bit_reader.read_int(8) as u8);
-        }
-
-        use std::io::{Cursor, Read};
-
-        let mut cur = Cursor::new(&data);
-        while (cur.position() as usize) < size {
-            let cmd = Self::read_varint32_cursor(&mut cur)?;
-            let msg_size = Self::read_varint32_cursor(&mut cur)? as usize;
-            let mut msg_buf = vec![0u8; msg_size];
-            cur.read_exact(&mut msg_buf)
-                .map_err(|_| ParserError::UnexpectedEndOfDemo)?;
-            self.handle_svc_message(cmd, &msg_buf);
+        let data = self.bit_reader.read_bytes(size);
+
+        // Messages are handed on as sub-slices of the packet.
+        let mut rest = &data[..];
+        while !rest.is_empty() {
+            let cmd = Self::read_varint32_cursor(&mut rest)?;
+            let msg_size = Self::read_varint32_cursor(&mut rest)? as usize;
+            if msg_size > rest.len() {
+                return Err(ParserError::UnexpectedEndOfDemo);
+            }
+            let (msg_buf, tail) = rest.split_at(msg_size);
+            rest = tail;
+            self.handle_svc_message(cmd, msg_buf);
         }
 
         Ok(())
@@ -577,10 +1031,7 @@ impl<R: Read> Parser<R> {
         self.game_state.set_ingame_tick(tick as i32);
         let size = self.bit_reader.read_varint32();
 
-        let mut buf = Vec::with_capacity(size as usize);
-        for _ in 0..size {
-            buf.push(self.bit_reader.read_int(8) as u8);
-        }
+        let mut buf = self.bit_reader.read_bytes(size as usize);
 
         if compressed {
             buf = snap::raw::Decoder::new()
@@ -592,6 +1043,12 @@ impl<R: Read> Parser<R> {
             if self.s2_tables.parse_packet(&buf).is_ok() {
                 self.dispatch_event(crate::events::DataTablesParsed);
             }
+        } else if msg_type == 13
+            && let Ok(msg) = crate::proto::msgs2::CDemoFullPacket::decode(&buf[..])
+        {
+            if !self.full_packet_applied {
+                self.apply_full_packet(msg);
+            }
         } else {
             self.handle_svc_message(msg_type, &buf);
         }
@@ -652,8 +1109,14 @@ impl<R: Read> Parser<R> {
                         if let Ok(msg) = proto_msg::CcsUsrMsgSayText2::decode(&data[..]) {
                             self.dispatch_user_message(msg.clone());
                             let text = msg.params.get(1).cloned().unwrap_or_default();
+                            let sender = self
+                                .game_state
+                                .participants()
+                                .by_entity_id()
+                                .get(&msg.ent_idx.unwrap_or_default())
+                                .cloned();
                             self.dispatch_event(crate::events::ChatMessage {
-                                sender: None,
+                                sender,
                                 text,
                                 is_chat_all: msg.textallchat.unwrap_or_default(),
                             });
@@ -755,6 +1218,22 @@ impl<R: Read> Parser<R> {
 
     fn handle_svc_message(&mut self, msg_type: u32, buf: &[u8]) {
         if let Ok(kind) = proto_msg::SvcMessages::try_from(msg_type as i32) {
+            let part = match kind {
+                | proto_msg::SvcMessages::SvcPacketEntities => Decoding::ENTITIES,
+                | proto_msg::SvcMessages::SvcCreateStringTable
+                | proto_msg::SvcMessages::SvcUpdateStringTable => Decoding::STRING_TABLES,
+                | proto_msg::SvcMessages::SvcUserMessage => Decoding::USER_MESSAGES,
+                | proto_msg::SvcMessages::SvcVoiceInit | proto_msg::SvcMessages::SvcVoiceData => {
+                    Decoding::VOICE_DATA
+                },
+                | _ => Decoding::empty(),
+            };
+            if !self.decodes(part) {
+                if part == Decoding::STRING_TABLES && self.decodes(Decoding::ENTITIES) {
+                    self.update_baseline_table(kind, buf);
+                }
+                return;
+            }
             match kind {
                 | proto_msg::SvcMessages::SvcServerInfo => {
                     if let Ok(msg) = proto_msg::CsvcMsgServerInfo::decode(buf) {
@@ -785,13 +1264,7 @@ impl<R: Read> Parser<R> {
                             self.dispatch_event(crate::events::StringTableCreated {
                                 table_name: t.name.clone(),
                             });
-                            if t.name.eq_ignore_ascii_case("userinfo") {
-                                self.game_state_mut().apply_userinfo_table(&t);
-                            }
-                            if t.name == "ItemDefinitions" {
-                                self.update_equipment_mapping_from_classes();
-                            }
-                            self.dispatch_event(StringTableUpdated { table: t });
+                            self.string_table_updated(t);
                         }
                         self.dispatch_net_message(msg);
                     }
@@ -799,13 +1272,7 @@ impl<R: Read> Parser<R> {
                 | proto_msg::SvcMessages::SvcUpdateStringTable => {
                     if let Ok(msg) = proto_msg::CsvcMsgUpdateStringTable::decode(buf) {
                         if let Some(t) = self.string_tables.on_update_string_table(&msg) {
-                            if t.name.eq_ignore_ascii_case("userinfo") {
-                                self.game_state_mut().apply_userinfo_table(&t);
-                            }
-                            if t.name == "ItemDefinitions" {
-                                self.update_equipment_mapping_from_classes();
-                            }
-                            self.dispatch_event(StringTableUpdated { table: t });
+                            self.string_table_updated(t);
                         }
                         self.dispatch_net_message(msg);
                     }
@@ -875,15 +1342,17 @@ impl<R: Read> Parser<R> {
                 | proto_msg::SvcMessages::SvcPacketEntities => {
                     if let Ok(msg) = proto_msg::CsvcMsgPacketEntities::decode(buf) {
                         if self.header.as_ref().map(|h| h.filestamp.as_str()) == Some("PBDEMS2") {
-                            for (ent, op) in self.s2_tables.parse_packet_entities(&msg) {
-                                let ev = EntityEvent {
-                                    entity: ent.clone(),
-                                    op,
-                                };
-                                self.dispatch_event(ev.clone());
-                                if op.contains(crate::sendtables::EntityOp::CREATED) {
-                                    self.dispatch_event(EntityCreated { entity: ent });
-                                }
+                            let parsed = self
+                                .s2_tables
+                                .parse_packet_entities(&msg, &mut self.game_state.entities);
+                            for (handle, class, op) in parsed.events {
+                                self.dispatch_entity_event(handle, class, op);
+                            }
+                            if let Some(e) = parsed.error {
+                                self.dispatch_event(crate::events::ParserWarn {
+                                    message: format!("dropped rest of PacketEntities: {e}"),
+                                    r#type: crate::events::WarnType::InvalidPacketEntities,
+                                });
                             }
                         } else {
                             // TODO: implement PacketEntities parsing for Source 1 demos
@@ -0,0 +1,180 @@
+//! Splitting a demo into segments that can be parsed independently.
+//!
+//! Source 2 demos contain a `DEM_FullPacket` frame every few thousand
+//! ticks. A [`DemoIndex`] records where they are, and each [`Segment`] runs
+//! from one full packet to a later one. A segment's parser first replays
+//! the frames preceding the demo's first full packet, which set up the
+//! send tables, classes and string tables, and then continues at the
+//! segment's start. The full packet there restores the string tables, all
+//! entities and the rounds played. See
+//! [`crate::utils::parallel::run_segments`] for parsing the segments of a
+//! demo in parallel.
+//!
+//! The parser's frame counter restarts in every segment. Ticks read from
+//! the demo, e.g. [`GameState::ingame_tick`], are those of the full demo.
+//! State only game events build up, such as grenade records or the
+//! economy, covers the segment alone.
+//!
+//! [`GameState::ingame_tick`]: crate::game_state::GameState::ingame_tick
+
+use std::io::{Chain, Read};
+
+use super::stream::{frame_len, header_len, varint32};
+use super::{Parser, ParserConfig, ParserError};
+
+/// `EDemoCommands::DemFullPacket`.
+const DEM_FULL_PACKET: u32 = 13;
+/// Flag of compressed Source 2 frames.
+const DEM_IS_COMPRESSED: u32 = 64;
+/// A `DEM_Stop` frame, appended to every Source 2 segment so that parsing
+/// ends at its last frame.
+const STOP_FRAME: &[u8] = &[0, 0, 0];
+
+/// Reader of a segment: the demo's header and setup frames, followed by
+/// the segment's frames.
+pub type SegmentReader<'a> = Chain<Chain<&'a [u8], &'a [u8]>, &'static [u8]>;
+
+/// Position of a `DEM_FullPacket` frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FullPacket {
+    /// Byte offset of the frame in the demo.
+    pub offset: usize,
+    pub tick: i32,
+}
+
+/// A range of frames starting at a full packet, or at the first frame for
+/// the first segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Segment {
+    /// Position of the segment in the demo, starting at 0.
+    pub index: usize,
+    /// Byte offset of the first frame.
+    pub start: usize,
+    /// Byte offset after the last frame.
+    pub end: usize,
+    /// Tick of the first frame.
+    pub start_tick: i32,
+}
+
+/// Full packet positions of a demo.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DemoIndex {
+    pub source2: bool,
+    /// Byte offset of the first frame, after the header and lump data.
+    pub frames_start: usize,
+    /// Byte offset of the first full packet. Frames before it are replayed
+    /// by every segment.
+    pub setup_end: usize,
+    /// Number of frames before `setup_end`.
+    pub setup_frames: usize,
+    pub full_packets: Vec<FullPacket>,
+    /// Byte offset after the last complete frame.
+    pub end: usize,
+}
+
+impl DemoIndex {
+    /// Scans the frames of `data` without parsing them. Source 1 demos
+    /// have no full packets and form a single segment.
+    pub fn new(data: &[u8]) -> Result<Self, ParserError> {
+        let source2 = match data.get(..8) {
+            | Some(b"PBDEMS2\0") => true,
+            | Some(b"HL2DEMO\0") => false,
+            | Some(b"version ") => return Err(ParserError::GitLfsPointer),
+            | _ => return Err(ParserError::InvalidFileType),
+        };
+        let len = header_len(data, true).ok_or(ParserError::UnexpectedEndOfDemo)?;
+        let frames_start = len.table + len.lumps as usize;
+        if frames_start > data.len() {
+            return Err(ParserError::UnexpectedEndOfDemo);
+        }
+
+        let mut index = Self {
+            source2,
+            frames_start,
+            ..Default::default()
+        };
+        let mut offset = frames_start;
+        let mut frames = 0;
+        while let Some(n) = frame_len(source2, &data[offset..]) {
+            if let Some(tick) = full_packet_tick(source2, &data[offset..]) {
+                if index.full_packets.is_empty() {
+                    index.setup_frames = frames;
+                }
+                index.full_packets.push(FullPacket { offset, tick });
+            }
+            offset += n;
+            frames += 1;
+        }
+        index.end = offset;
+        index.setup_end = match index.full_packets.first() {
+            | Some(fp) => fp.offset,
+            | None => {
+                index.setup_frames = frames;
+                offset
+            },
+        };
+        Ok(index)
+    }
+
+    /// Splits the demo into at most `count` segments of roughly equal size.
+    /// Always returns at least one segment.
+    pub fn segments(&self, count: usize) -> Vec<Segment> {
+        let total = self.end - self.frames_start;
+        let mut starts = vec![(self.frames_start, 0)];
+        for i in 1..count.max(1) {
+            let target = self.frames_start + total * i / count;
+            let last = starts.last().unwrap().0;
+            let next = self.full_packets.iter().find(|fp| fp.offset >= target);
+            if let Some(fp) = next.filter(|fp| fp.offset > last) {
+                starts.push((fp.offset, fp.tick));
+            }
+        }
+        (0..starts.len())
+            .map(|i| Segment {
+                index: i,
+                start: starts[i].0,
+                end: starts.get(i + 1).map_or(self.end, |s| s.0),
+                start_tick: starts[i].1,
+            })
+            .collect()
+    }
+
+    /// The input of `segment`'s parser.
+    pub fn reader<'a>(&self, data: &'a [u8], segment: &Segment) -> SegmentReader<'a> {
+        let setup = if segment.index == 0 {
+            &data[..segment.start]
+        } else {
+            &data[..self.setup_end]
+        };
+        let stop = if self.source2 { STOP_FRAME } else { &[] };
+        setup.chain(&data[segment.start..segment.end]).chain(stop)
+    }
+
+    /// A parser positioned at the start of `segment`, with the demo's setup
+    /// frames parsed. Handlers registered on it only see the segment.
+    pub fn parser<'a>(
+        &self,
+        data: &'a [u8],
+        segment: &Segment,
+        config: ParserConfig,
+    ) -> Result<Parser<SegmentReader<'a>>, ParserError> {
+        let mut parser = Parser::with_config(self.reader(data, segment), config);
+        parser.parse_header()?;
+        if segment.index > 0 {
+            for _ in 0..self.setup_frames {
+                parser.parse_next_frame()?;
+            }
+        }
+        Ok(parser)
+    }
+}
+
+/// The tick of the frame at the start of `data` if it's a full packet.
+fn full_packet_tick(source2: bool, data: &[u8]) -> Option<i32> {
+    let (cmd, a) = varint32(data)?;
+    if !source2 || cmd & !DEM_IS_COMPRESSED != DEM_FULL_PACKET {
+        return None;
+    }
+    let (tick, _) = varint32(&data[a..])?;
+    Some(tick as i32)
+}
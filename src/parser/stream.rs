@@ -0,0 +1,361 @@
+//! Parsing a demo while it is still being received, e.g. downloaded in a
+//! browser.
+//!
+//! [`Parser`] reads from a blocking [`Read`] and can't resume a frame that
+//! ran out of input half way. [`DemoStream`] buffers the pushed chunks and
+//! only hands a frame to the parser once all of its bytes have arrived, so
+//! the input can be fed in pieces of any size without threads.
+
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+
+use super::lumps::LUMP_MAGIC;
+use super::{Parser, ParserConfig, ParserError};
+
+/// Size of the fixed demo header read by [`Parser::parse_header`].
+const HEADER_LEN: usize = 8 + 4 + 4 + 4 * 260 + 4 + 4 + 4 + 4;
+/// Size of a Source 1 frame's command, tick and player slot.
+const S1_FRAME_HEADER_LEN: usize = 6;
+/// `CommandInfo` and sequence numbers preceding a Source 1 packet.
+const S1_PACKET_INFO_LEN: usize = 152 + 4 + 4;
+/// Consumed bytes are dropped from the buffer once this many have piled up.
+const COMPACT_THRESHOLD: usize = 1024 * 1024;
+
+/// Result of [`DemoStream::parse_next_frame`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameStatus {
+    /// A frame was parsed.
+    Parsed,
+    /// The next frame hasn't been received completely yet.
+    Incomplete,
+    /// The demo has been parsed to the end.
+    Finished,
+}
+
+#[derive(Default)]
+struct Buffer {
+    data: Vec<u8>,
+    /// Stream offset of `data[0]`.
+    start: u64,
+    /// Stream offset of the next byte handed to the parser.
+    read: u64,
+}
+
+impl Buffer {
+    fn end(&self) -> u64 {
+        self.start + self.data.len() as u64
+    }
+
+    fn from(&self, offset: u64) -> &[u8] {
+        &self.data[(offset - self.start) as usize..]
+    }
+
+    /// Drops the bytes before `offset` if enough of them piled up.
+    fn discard_before(&mut self, offset: u64) {
+        let n = (offset - self.start) as usize;
+        if n >= COMPACT_THRESHOLD || (n > 0 && n * 2 >= self.data.len()) {
+            self.data.drain(..n);
+            self.start = offset;
+        }
+    }
+}
+
+/// The reader of a [`DemoStream`]'s parser. Yields the bytes pushed so far.
+pub struct ChunkReader {
+    buffer: Arc<Mutex<Buffer>>,
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let mut buffer = self.buffer.lock().unwrap();
+        let read = buffer.read;
+        let available = buffer.from(read);
+        let n = available.len().min(out.len());
+        out[..n].copy_from_slice(&available[..n]);
+        buffer.read += n as u64;
+        Ok(n)
+    }
+}
+
+enum State {
+    Header,
+    Frames {
+        source2: bool,
+        /// Lump data the parser skips before the first frame.
+        skip: u64,
+    },
+    Done,
+}
+
+/// A [`Parser`] fed with chunks of a demo as they arrive.
+///
+/// ```no_run
+/// use cs_demo_parser::parser::stream::{DemoStream, FrameStatus};
+///
+/// let mut stream = DemoStream::new();
+/// # let chunks: Vec<Vec<u8>> = Vec::new();
+/// for chunk in chunks {
+///     stream.push(&chunk);
+///     stream.parse_available()?;
+/// }
+/// stream.finish();
+/// assert_eq!(stream.parse_available()?, FrameStatus::Finished);
+/// # Ok::<(), cs_demo_parser::parser::ParserError>(())
+/// ```
+pub struct DemoStream {
+    parser: Parser<ChunkReader>,
+    buffer: Arc<Mutex<Buffer>>,
+    /// Stream offset up to which the input has been handed to the parser.
+    framed: u64,
+    state: State,
+    end_of_input: bool,
+    expected_size: Option<u64>,
+}
+
+impl Default for DemoStream {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemoStream {
+    /// Creates a stream parser using [`ParserConfig::default`].
+    pub fn new() -> Self {
+        Self::with_config(ParserConfig::default())
+    }
+
+    pub fn with_config(config: ParserConfig) -> Self {
+        let buffer = Arc::new(Mutex::new(Buffer::default()));
+        let reader = ChunkReader {
+            buffer: Arc::clone(&buffer),
+        };
+        Self {
+            parser: Parser::with_config(reader, config),
+            buffer,
+            framed: 0,
+            state: State::Header,
+            end_of_input: false,
+            expected_size: None,
+        }
+    }
+
+    /// The parser the frames are handed to. Handlers are registered on it
+    /// as usual.
+    pub fn parser(&self) -> &Parser<ChunkReader> {
+        &self.parser
+    }
+
+    pub fn parser_mut(&mut self) -> &mut Parser<ChunkReader> {
+        &mut self.parser
+    }
+
+    /// Sets the total size of the demo, e.g. from a `Content-Length`
+    /// header, so that [`progress`](Self::progress) can be computed before
+    /// the header has been parsed.
+    pub fn set_expected_size(&mut self, size: u64) {
+        self.expected_size = Some(size);
+    }
+
+    /// Appends the next chunk of the demo.
+    pub fn push(&mut self, chunk: &[u8]) {
+        let mut buffer = self.buffer.lock().unwrap();
+        let keep = buffer.read.min(self.framed);
+        buffer.discard_before(keep);
+        buffer.data.extend_from_slice(chunk);
+    }
+
+    /// Marks the end of the input. Remaining bytes that don't form a
+    /// complete frame are handed to the parser, which reports them as a
+    /// truncated demo.
+    pub fn finish(&mut self) {
+        self.end_of_input = true;
+    }
+
+    /// Number of bytes pushed so far.
+    pub fn bytes_received(&self) -> u64 {
+        self.buffer.lock().unwrap().end()
+    }
+
+    /// Number of bytes handed to the parser so far.
+    pub fn bytes_parsed(&self) -> u64 {
+        self.framed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// Fraction of the demo parsed so far, between 0 and 1. Based on the
+    /// expected size if set and on the frame count in the header otherwise.
+    pub fn progress(&self) -> f32 {
+        if self.is_finished() {
+            return 1.0;
+        }
+        match self.expected_size {
+            | Some(size) if size > 0 => (self.framed as f64 / size as f64).min(1.0) as f32,
+            | _ => self.parser.progress(),
+        }
+    }
+
+    /// Parses all frames that have been received completely.
+    pub fn parse_available(&mut self) -> Result<FrameStatus, ParserError> {
+        loop {
+            match self.parse_next_frame()? {
+                | FrameStatus::Parsed => {},
+                | status => return Ok(status),
+            }
+        }
+    }
+
+    /// Parses the next frame if it has been received completely. The
+    /// header is parsed along with the first frame.
+    pub fn parse_next_frame(&mut self) -> Result<FrameStatus, ParserError> {
+        let res = self.step();
+        if res.is_err() {
+            self.state = State::Done;
+        }
+        res
+    }
+
+    fn step(&mut self) -> Result<FrameStatus, ParserError> {
+        if let State::Header = self.state {
+            match self.header_len() {
+                | Some(len) => {
+                    let header = self.parser.parse_header()?;
+                    self.framed = len.table as u64;
+                    self.state = State::Frames {
+                        source2: header.filestamp == "PBDEMS2",
+                        skip: len.lumps,
+                    };
+                },
+                | None if self.end_of_input => {
+                    self.parser.parse_header()?;
+                    // The header parsed but the demo can't contain a frame.
+                    return Err(ParserError::UnexpectedEndOfDemo);
+                },
+                | None => return Ok(FrameStatus::Incomplete),
+            }
+        }
+
+        let State::Frames { source2, skip } = self.state else {
+            return Ok(FrameStatus::Finished);
+        };
+        let len = {
+            let buffer = self.buffer.lock().unwrap();
+            let available = buffer.from(self.framed);
+            let after_skip = available.get(skip as usize..).unwrap_or_default();
+            if after_skip.is_empty() && self.end_of_input {
+                self.state = State::Done;
+                return Ok(FrameStatus::Finished);
+            }
+            frame_len(source2, after_skip)
+        };
+        match len {
+            | Some(len) => self.framed += skip + len as u64,
+            | None if self.end_of_input => self.framed = self.bytes_received(),
+            | None => return Ok(FrameStatus::Incomplete),
+        }
+        self.state = State::Frames { source2, skip: 0 };
+
+        if self.parser.parse_next_frame()? {
+            Ok(FrameStatus::Parsed)
+        } else {
+            self.state = State::Done;
+            Ok(FrameStatus::Finished)
+        }
+    }
+
+    /// Length of the header and lump table, if received completely.
+    fn header_len(&self) -> Option<HeaderLen> {
+        let buffer = self.buffer.lock().unwrap();
+        header_len(buffer.from(0), self.end_of_input)
+    }
+}
+
+/// Length of the header and lump table, if received completely. Input
+/// that isn't a demo is reported as complete so that the parser can
+/// reject it right away.
+pub(super) fn header_len(data: &[u8], end_of_input: bool) -> Option<HeaderLen> {
+    let filestamp = data.get(..8)?;
+    if filestamp != b"HL2DEMO\0" && filestamp != b"PBDEMS2\0" {
+        return Some(HeaderLen::default());
+    }
+    // The parser peeks at the 4 bytes following the header.
+    let peek = match data.get(HEADER_LEN..HEADER_LEN + 4) {
+        | Some(peek) => peek,
+        | None if end_of_input && data.len() >= HEADER_LEN => &[],
+        | None => return None,
+    };
+    if filestamp != b"PBDEMS2\0" || peek != LUMP_MAGIC.to_le_bytes() {
+        return Some(HeaderLen {
+            table: HEADER_LEN,
+            lumps: 0,
+        });
+    }
+
+    let table = data.get(HEADER_LEN..)?;
+    let count = u32::from_le_bytes(table.get(4..8)?.try_into().unwrap()) as usize;
+    let entries = table.get(16..16 + count * 16)?;
+    let lumps = entries
+        .chunks_exact(16)
+        .map(|e| {
+            let offset = u32::from_le_bytes(e[0..4].try_into().unwrap()) as u64;
+            let length = u32::from_le_bytes(e[4..8].try_into().unwrap()) as u64;
+            offset + length
+        })
+        .max()
+        .unwrap_or(0);
+    Some(HeaderLen {
+        table: HEADER_LEN + 16 + count * 16,
+        lumps,
+    })
+}
+
+#[derive(Default)]
+pub(super) struct HeaderLen {
+    /// Header plus lump table.
+    pub(super) table: usize,
+    /// Lump data following the table.
+    pub(super) lumps: u64,
+}
+
+/// Length of the frame at the start of `data`, or `None` if `data` ends
+/// before the frame does. Mirrors `Parser::parse_frame_s1` and
+/// `Parser::parse_frame_s2`.
+pub(super) fn frame_len(source2: bool, data: &[u8]) -> Option<usize> {
+    if source2 {
+        let (_, a) = varint32(data)?;
+        let (_, b) = varint32(&data[a..])?;
+        let (size, c) = varint32(&data[a + b..])?;
+        let len = a + b + c + size as usize;
+        return (data.len() >= len).then_some(len);
+    }
+
+    let i32_at = |at: usize| {
+        data.get(at..at + 4)
+            .map(|b| i32::from_le_bytes(b.try_into().unwrap()) as u32 as usize)
+    };
+    let len = match *data.first()? {
+        | 1 | 2 => {
+            let at = S1_FRAME_HEADER_LEN + S1_PACKET_INFO_LEN;
+            at + 4 + i32_at(at)?
+        },
+        | 4 | 6 | 8..=19 => S1_FRAME_HEADER_LEN + 4 + i32_at(S1_FRAME_HEADER_LEN)?,
+        | 5 => S1_FRAME_HEADER_LEN + 8 + i32_at(S1_FRAME_HEADER_LEN + 4)?,
+        | _ => S1_FRAME_HEADER_LEN,
+    };
+    (data.len() >= len).then_some(len)
+}
+
+/// Decodes a varint the way `BitReader::read_varint32` does, returning the
+/// value and its length in bytes.
+pub(super) fn varint32(data: &[u8]) -> Option<(u32, usize)> {
+    let mut res = 0u32;
+    for (i, b) in data.iter().take(5).enumerate() {
+        res |= ((b & 0x7f) as u32) << (7 * i);
+        if b & 0x80 == 0 {
+            return Some((res, i + 1));
+        }
+    }
+    (data.len() >= 5).then_some((res, 5))
+}
@@ -0,0 +1,160 @@
+//! Callout names ("B Site", "Mid Doors") of world positions.
+//!
+//! CS:GO and CS2 send the place of each player in `m_szLastPlaceName`, see
+//! [`Player::place_name`]. Where the property is missing, and for positions
+//! that aren't players', e.g. grenade detonations, [`Places`] looks the
+//! position up in callout polygons loaded per map.
+//!
+//! With the `serde` feature polygons are loaded from JSON files:
+//!
+//! ```json
+//! {
+//!   "map": "de_mirage",
+//!   "callouts": [
+//!     { "name": "B Site", "polygon": [[-2200, 100], [-1900, 100], [-1900, 600], [-2200, 600]] },
+//!     { "name": "Underpass", "polygon": [[-900, -200], [-700, -200], [-700, 100], [-900, 100]], "z_max": -100 }
+//!   ]
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+#[cfg(feature = "serde")]
+use std::path::Path;
+
+use crate::common::Player;
+use crate::maps::base_name;
+use crate::sendtables::entity::Vector;
+#[cfg(feature = "serde")]
+use crate::utils::{DataError, load_dir};
+
+/// A named area of a map, a polygon in world x/y with optional height
+/// bounds.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Callout {
+    pub name: String,
+    /// Corners as world x and y.
+    pub polygon: Vec<[f64; 2]>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub z_min: Option<f64>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub z_max: Option<f64>,
+}
+
+impl Callout {
+    pub fn contains(&self, position: &Vector) -> bool {
+        if self.z_min.is_some_and(|min| position.z < min)
+            || self.z_max.is_some_and(|max| position.z > max)
+        {
+            return false;
+        }
+        let (x, y) = (position.x, position.y);
+        let mut inside = false;
+        for (i, a) in self.polygon.iter().enumerate() {
+            let b = self.polygon[(i + 1) % self.polygon.len()];
+            if (a[1] > y) != (b[1] > y) && x < a[0] + (y - a[1]) / (b[1] - a[1]) * (b[0] - a[0]) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+
+    /// Area of the polygon in square world units.
+    pub fn area(&self) -> f64 {
+        let twice: f64 = self
+            .polygon
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let b = self.polygon[(i + 1) % self.polygon.len()];
+                a[0] * b[1] - b[0] * a[1]
+            })
+            .sum();
+        twice.abs() / 2.0
+    }
+}
+
+/// The callouts of one map.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MapPlaces {
+    pub map: String,
+    pub callouts: Vec<Callout>,
+}
+
+impl MapPlaces {
+    /// The name of the callout containing `position`. Where callouts
+    /// overlap the smallest one wins, so e.g. "Jungle" can lie inside
+    /// "Mid".
+    pub fn place_at(&self, position: &Vector) -> Option<&str> {
+        self.callouts
+            .iter()
+            .filter(|c| c.contains(position))
+            .min_by(|a, b| a.area().total_cmp(&b.area()))
+            .map(|c| c.name.as_str())
+    }
+}
+
+/// Callouts by map name.
+#[derive(Debug, Clone, Default)]
+pub struct Places {
+    maps: BTreeMap<String, MapPlaces>,
+}
+
+impl Places {
+    /// The callouts of `map`, which may be a workshop path like
+    /// `workshop/123456/de_nuke`.
+    pub fn get(&self, map: &str) -> Option<&MapPlaces> {
+        self.maps.get(base_name(map))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.maps.keys().map(String::as_str)
+    }
+
+    /// Adds `places`, returning the callouts it replaced. Workshop paths
+    /// are stored under the map's base name, like [`get`](Self::get) looks
+    /// them up.
+    pub fn insert(&mut self, places: MapPlaces) -> Option<MapPlaces> {
+        self.maps.insert(base_name(&places.map).to_string(), places)
+    }
+
+    /// The callout at `position` on `map`.
+    pub fn place_at(&self, map: &str, position: &Vector) -> Option<&str> {
+        self.get(map)?.place_at(position)
+    }
+
+    /// The place of `player`: [`Player::place_name`] if the demo sends it,
+    /// else the callout at the player's position. `None` if the demo sent
+    /// neither.
+    pub fn place_of(&self, map: &str, player: &Player) -> Option<String> {
+        player.place_name().or_else(|| {
+            let position = Some(player.position()).filter(|_| player.has_position())?;
+            self.place_at(map, &position).map(String::from)
+        })
+    }
+
+    /// Adds the callouts of a JSON document.
+    #[cfg(feature = "serde")]
+    pub fn load_json(&mut self, json: &str) -> Result<&MapPlaces, DataError> {
+        let places: MapPlaces =
+            serde_json::from_str(json).map_err(|e| DataError::Parse(e.to_string()))?;
+        let map = base_name(&places.map).to_string();
+        self.insert(places);
+        Ok(&self.maps[&map])
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<&MapPlaces, DataError> {
+        let json = std::fs::read_to_string(path)?;
+        self.load_json(&json)
+    }
+
+    /// Adds every `.json` file in `dir` and returns how many were loaded.
+    #[cfg(feature = "serde")]
+    pub fn load_dir(&mut self, dir: impl AsRef<Path>) -> Result<usize, DataError> {
+        load_dir(dir.as_ref(), "json", |path| {
+            self.load_file(path).map(|_| ())
+        })
+    }
+}
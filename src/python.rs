@@ -0,0 +1,434 @@
+//! Python bindings, built with the `python` feature.
+//!
+//! ```python
+//! import cs_demo_parser
+//!
+//! demo = cs_demo_parser.DemoParser("match.dem")
+//! demo.on("Kill", lambda event, tick: print(tick, event["is_headshot"]))
+//! demo.parse()
+//! kills = pandas.DataFrame(demo.kills())
+//! ```
+//!
+//! Events and table rows are passed to Python as dicts with the same layout
+//! as their serde serialization. With the `parquet` feature enabled the
+//! tables can also be returned as `pyarrow.Table`s.
+
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde::Serialize;
+
+use crate::common::Team;
+use crate::export::{Dataset, DatasetCollector, DatasetConfig};
+use crate::parser::{Parser, ParserError};
+use crate::serialization::event_name;
+
+create_exception!(
+    cs_demo_parser,
+    DemoError,
+    PyException,
+    "The demo could not be parsed."
+);
+
+fn demo_error(e: ParserError) -> PyErr {
+    let msg = match e {
+        | ParserError::UnexpectedEndOfDemo => "demo is truncated or corrupt",
+        | ParserError::InvalidFileType => "not a demo file",
+        | ParserError::GitLfsPointer => "file is a Git LFS pointer",
+    };
+    DemoError::new_err(msg)
+}
+
+fn to_py(py: Python<'_>, value: &serde_json::Value) -> PyResult<PyObject> {
+    use serde_json::Value;
+
+    Ok(match value {
+        | Value::Null => py.None(),
+        | Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any().unbind(),
+        | Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                i.into_pyobject(py)?.into_any().unbind()
+            } else if let Some(u) = n.as_u64() {
+                u.into_pyobject(py)?.into_any().unbind()
+            } else {
+                n.as_f64()
+                    .unwrap_or(f64::NAN)
+                    .into_pyobject(py)?
+                    .into_any()
+                    .unbind()
+            }
+        },
+        | Value::String(s) => s.into_pyobject(py)?.into_any().unbind(),
+        | Value::Array(values) => {
+            let items = values
+                .iter()
+                .map(|v| to_py(py, v))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items)?.into_any().unbind()
+        },
+        | Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (k, v) in map {
+                dict.set_item(k, to_py(py, v)?)?;
+            }
+            dict.into_any().unbind()
+        },
+    })
+}
+
+/// Converts `value` through its serde representation.
+fn serialize<T: Serialize>(py: Python<'_>, value: &T) -> PyResult<PyObject> {
+    let value = serde_json::to_value(value).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    to_py(py, &value)
+}
+
+/// Everything the handlers registered on the parser share with the
+/// [`DemoParser`].
+#[derive(Default)]
+struct Shared {
+    /// First exception raised by a Python callback. Parsing stops once set.
+    error: Option<PyErr>,
+}
+
+/// A demo opened from a path or a bytes object.
+#[pyclass(module = "cs_demo_parser")]
+pub struct DemoParser {
+    parser: Mutex<Parser<Box<dyn Read + Send>>>,
+    collector: Arc<Mutex<DatasetCollector>>,
+    shared: Arc<Mutex<Shared>>,
+    dataset: Mutex<Option<Dataset>>,
+    /// Why parsing failed. The parser can't resume, so later calls raise
+    /// it again instead of returning partial tables.
+    failure: Mutex<Option<PyErr>>,
+    /// Bits of the `f32` progress, readable while another thread parses.
+    progress: AtomicU32,
+}
+
+impl DemoParser {
+    /// Locks the parser without blocking. Waiting for a thread that is
+    /// parsing while holding the GIL would deadlock once that thread runs a
+    /// callback.
+    fn lock(&self) -> PyResult<MutexGuard<'_, Parser<Box<dyn Read + Send>>>> {
+        self.parser
+            .try_lock()
+            .map_err(|_| PyRuntimeError::new_err("the demo is being parsed by another thread"))
+    }
+
+    fn dataset<T>(&self, py: Python<'_>, f: impl FnOnce(&Dataset) -> T) -> PyResult<T> {
+        self.parse(py)?;
+        let dataset = self.dataset.lock().unwrap();
+        Ok(f(dataset.as_ref().expect("set by parse")))
+    }
+
+    fn rows<T: Serialize>(py: Python<'_>, rows: &[T]) -> PyResult<PyObject> {
+        let rows = rows
+            .iter()
+            .map(|r| serialize(py, r))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(PyList::new(py, rows)?.into_any().unbind())
+    }
+
+    fn table(&self, py: Python<'_>, arrow: bool, table: Table) -> PyResult<PyObject> {
+        if arrow {
+            return self.arrow_table(py, table);
+        }
+        self.dataset(py, |d| match table {
+            | Table::Kills => Self::rows(py, &d.kills),
+            | Table::Grenades => Self::rows(py, &d.grenades),
+            | Table::Rounds => Self::rows(py, &d.rounds),
+            | Table::PlayerTicks => ticks(py, d),
+        })?
+    }
+
+    #[cfg(feature = "parquet")]
+    fn arrow_table(&self, py: Python<'_>, table: Table) -> PyResult<PyObject> {
+        use crate::export::arrow::{Table as ArrowTable, to_record_batch};
+        use arrow::pyarrow::ToPyArrow;
+
+        let table = match table {
+            | Table::Kills => ArrowTable::Kills,
+            | Table::Grenades => ArrowTable::Grenades,
+            | Table::Rounds => ArrowTable::Rounds,
+            | Table::PlayerTicks => ArrowTable::PlayerTicks,
+        };
+        let batch = self
+            .dataset(py, |d| to_record_batch(d, table))?
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let batch = batch.to_pyarrow(py)?;
+        let pyarrow = py.import("pyarrow")?;
+        let table = pyarrow
+            .getattr("Table")?
+            .call_method1("from_batches", (vec![batch],))?;
+        Ok(table.unbind())
+    }
+
+    #[cfg(not(feature = "parquet"))]
+    fn arrow_table(&self, _py: Python<'_>, _table: Table) -> PyResult<PyObject> {
+        Err(PyValueError::new_err(
+            "arrow tables need the `parquet` feature",
+        ))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Table {
+    Kills,
+    Grenades,
+    Rounds,
+    PlayerTicks,
+}
+
+/// The player-tick table as a dict of columns. Properties that weren't
+/// sampled are left out.
+fn ticks(py: Python<'_>, dataset: &Dataset) -> PyResult<PyObject> {
+    let t = &dataset.ticks;
+    let dict = PyDict::new(py);
+    macro_rules! columns {
+        ($($name:ident),* $(,)?) => {$(
+            if t.$name.len() == t.len() {
+                dict.set_item(stringify!($name), &t.$name)?;
+            }
+        )*};
+    }
+    columns!(tick, user_id, steam_id64, name);
+    dict.set_item("team", PyList::new(py, t.team.iter().map(|t| *t as u8))?)?;
+    columns!(
+        x,
+        y,
+        z,
+        velocity_x,
+        velocity_y,
+        velocity_z,
+        pitch,
+        yaw,
+        health,
+        armor,
+        has_helmet,
+        has_defuser
+    );
+    if t.active_weapon.len() == t.len() {
+        let weapons: Vec<&str> = t.active_weapon.iter().map(|w| w.as_str()).collect();
+        dict.set_item("active_weapon", weapons)?;
+    }
+    columns!(
+        money,
+        flash_duration,
+        is_alive,
+        is_scoped,
+        is_ducking,
+        is_airborne,
+        place_name
+    );
+    Ok(dict.into_any().unbind())
+}
+
+#[pymethods]
+impl DemoParser {
+    /// Opens the demo at `path`, or reads it from `data` if given.
+    /// `tick_interval` is the number of ingame ticks between two samples of
+    /// the player-tick table, `None` disables it.
+    #[new]
+    #[pyo3(signature = (path=None, *, data=None, tick_interval=Some(16)))]
+    fn new(
+        path: Option<&str>,
+        data: Option<Vec<u8>>,
+        tick_interval: Option<i32>,
+    ) -> PyResult<Self> {
+        let (reader, demo): (Box<dyn Read + Send>, String) = match (path, data) {
+            | (Some(path), None) => {
+                let demo = std::path::Path::new(path)
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                (Box::new(File::open(path)?), demo)
+            },
+            | (None, Some(data)) => (Box::new(Cursor::new(data)), String::new()),
+            | _ => return Err(PyValueError::new_err("expected either a path or data")),
+        };
+        let mut parser = Parser::new(reader);
+        let config = DatasetConfig {
+            tick_interval,
+            ..Default::default()
+        };
+        let collector = DatasetCollector::register(&mut parser, demo, config);
+        Ok(Self {
+            parser: Mutex::new(parser),
+            collector,
+            shared: Arc::default(),
+            dataset: Mutex::new(None),
+            failure: Mutex::new(None),
+            progress: AtomicU32::new(0),
+        })
+    }
+
+    /// The demo header as a dict.
+    fn header(&self, py: Python<'_>) -> PyResult<PyObject> {
+        let h = self.lock()?.parse_header().map_err(demo_error)?;
+        let dict = PyDict::new(py);
+        dict.set_item("filestamp", h.filestamp)?;
+        dict.set_item("protocol", h.protocol)?;
+        dict.set_item("network_protocol", h.network_protocol)?;
+        dict.set_item("server_name", h.server_name)?;
+        dict.set_item("client_name", h.client_name)?;
+        dict.set_item("map_name", h.map_name)?;
+        dict.set_item("game_directory", h.game_directory)?;
+        dict.set_item("playback_time", h.playback_time)?;
+        dict.set_item("playback_ticks", h.playback_ticks)?;
+        dict.set_item("playback_frames", h.playback_frames)?;
+        dict.set_item("signon_length", h.signon_length)?;
+        Ok(dict.into_any().unbind())
+    }
+
+    /// Calls `callback(event, tick)` for every event of type `event`, e.g.
+    /// `"Kill"` or `"RoundEnd"`. Must be called before parsing. An exception
+    /// raised by the callback stops parsing and is re-raised by `parse`.
+    fn on(&self, py: Python<'_>, event: &str, callback: PyObject) -> PyResult<()> {
+        let mut parser = self.lock()?;
+        let mut found = false;
+
+        macro_rules! register {
+            ($($ty:path),* $(,)?) => {$(
+                if event_name::<$ty>() == event {
+                    found = true;
+                    let callback = callback.clone_ref(py);
+                    let shared = Arc::clone(&self.shared);
+                    parser.register_sync_event_handler::<$ty, _>(move |ev, gs| {
+                        if shared.lock().unwrap().error.is_some() {
+                            return;
+                        }
+                        Python::with_gil(|py| {
+                            let res = serialize(py, ev)
+                                .and_then(|ev| callback.call1(py, (ev, gs.ingame_tick())));
+                            if let Err(e) = res {
+                                shared.lock().unwrap().error = Some(e);
+                            }
+                        });
+                    });
+                }
+            )*};
+        }
+        crate::events::for_each_event!(register);
+
+        if found {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "unknown event type `{event}`"
+            )))
+        }
+    }
+
+    /// Parses the demo until the end. The GIL is released while parsing
+    /// and only taken to run callbacks. Does nothing if the demo has already
+    /// been parsed, and raises the same error again if parsing failed.
+    fn parse(&self, py: Python<'_>) -> PyResult<()> {
+        let mut guard = self.lock()?;
+        if let Some(e) = self.failure.lock().unwrap().as_ref() {
+            return Err(e.clone_ref(py));
+        }
+        if self.dataset.lock().unwrap().is_some() {
+            return Ok(());
+        }
+        let parser: &mut Parser<_> = &mut guard;
+        let (shared, progress) = (&self.shared, &self.progress);
+        let res = py.allow_threads(|| {
+            loop {
+                if shared.lock().unwrap().error.is_some() {
+                    return Ok(());
+                }
+                let more = parser.parse_next_frame()?;
+                progress.store(parser.progress().to_bits(), Ordering::Relaxed);
+                if !more {
+                    return Ok(());
+                }
+            }
+        });
+        let callback_error = self.shared.lock().unwrap().error.take();
+        match callback_error.or_else(|| res.err().map(demo_error)) {
+            | Some(e) => {
+                *self.failure.lock().unwrap() = Some(e.clone_ref(py));
+                Err(e)
+            },
+            | None => {
+                *self.dataset.lock().unwrap() =
+                    Some(self.collector.lock().unwrap().take_dataset());
+                Ok(())
+            },
+        }
+    }
+
+    /// Map, build, tick rate, duration and players of the parsed demo.
+    fn info(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.parse(py)?;
+        let parser = self.lock()?;
+        let state = parser.game_state();
+        let header = parser.header().unwrap_or_default();
+
+        let players = PyList::empty(py);
+        for p in state.participants().all() {
+            if !matches!(p.team, Team::Terrorists | Team::CounterTerrorists) {
+                continue;
+            }
+            let player = PyDict::new(py);
+            player.set_item("name", &p.name)?;
+            player.set_item("steam_id64", p.steam_id64)?;
+            player.set_item("team", p.team as u8)?;
+            player.set_item("is_bot", p.is_bot)?;
+            players.append(player)?;
+        }
+
+        let dict = PyDict::new(py);
+        dict.set_item("map_name", state.map_name().unwrap_or(&header.map_name))?;
+        dict.set_item("build", state.server_version())?;
+        dict.set_item("tick_rate", parser.tick_rate())?;
+        dict.set_item("duration", parser.current_time().as_secs_f64())?;
+        dict.set_item("ticks", state.ingame_tick())?;
+        dict.set_item("rounds", state.total_rounds_played())?;
+        dict.set_item("players", players)?;
+        Ok(dict.into_any().unbind())
+    }
+
+    /// Fraction of the demo parsed so far, between 0 and 1. Can be polled
+    /// from another thread while `parse` runs.
+    fn progress(&self) -> f32 {
+        f32::from_bits(self.progress.load(Ordering::Relaxed))
+    }
+
+    /// All kills, as a list of dicts or a `pyarrow.Table`.
+    #[pyo3(signature = (*, arrow=false))]
+    fn kills(&self, py: Python<'_>, arrow: bool) -> PyResult<PyObject> {
+        self.table(py, arrow, Table::Kills)
+    }
+
+    /// All rounds, as a list of dicts or a `pyarrow.Table`.
+    #[pyo3(signature = (*, arrow=false))]
+    fn rounds(&self, py: Python<'_>, arrow: bool) -> PyResult<PyObject> {
+        self.table(py, arrow, Table::Rounds)
+    }
+
+    /// Grenade detonations and expirations, as a list of dicts or a
+    /// `pyarrow.Table`.
+    #[pyo3(signature = (*, arrow=false))]
+    fn grenades(&self, py: Python<'_>, arrow: bool) -> PyResult<PyObject> {
+        self.table(py, arrow, Table::Grenades)
+    }
+
+    /// Sampled player state, as a dict of columns or a `pyarrow.Table`.
+    #[pyo3(signature = (*, arrow=false))]
+    fn ticks(&self, py: Python<'_>, arrow: bool) -> PyResult<PyObject> {
+        self.table(py, arrow, Table::PlayerTicks)
+    }
+}
+
+#[pymodule]
+fn cs_demo_parser(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<DemoParser>()?;
+    m.add("DemoError", m.py().get_type::<DemoError>())?;
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use super::{Color, Point, Surface};
+
+/// An RGBA raster with straight (not premultiplied) alpha.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    pub fn new(width: usize, height: usize, background: Color) -> Self {
+        let pixels =
+            [background.r, background.g, background.b, background.a].repeat(width * height);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// A canvas of RGBA bytes, row by row. `None` if `pixels` doesn't hold
+    /// `width * height` pixels.
+    pub fn from_rgba(width: usize, height: usize, pixels: Vec<u8>) -> Option<Self> {
+        (pixels.len() == width * height * 4).then_some(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    pub fn into_pixels(self) -> Vec<u8> {
+        self.pixels
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Color> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let i = (y * self.width + x) * 4;
+        let p = &self.pixels[i..i + 4];
+        Some(Color::rgba(p[0], p[1], p[2], p[3]))
+    }
+
+    /// Draws `color` over the pixel, ignoring pixels outside the canvas.
+    pub fn blend(&mut self, x: i64, y: i64, color: Color) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height || color.a == 0 {
+            return;
+        }
+        let i = (y as usize * self.width + x as usize) * 4;
+        let p = &mut self.pixels[i..i + 4];
+        let src_a = color.a as f64 / 255.0;
+        let dst_a = p[3] as f64 / 255.0;
+        let out_a = src_a + dst_a * (1.0 - src_a);
+        if out_a <= 0.0 {
+            return;
+        }
+        for (c, src) in p[..3].iter_mut().zip([color.r, color.g, color.b]) {
+            let v = (src as f64 * src_a + *c as f64 * dst_a * (1.0 - src_a)) / out_a;
+            *c = v.round() as u8;
+        }
+        p[3] = (out_a * 255.0).round() as u8;
+    }
+
+    /// A copy scaled to `width` × `height` by nearest neighbour, e.g. to
+    /// fit a radar image to the output size.
+    pub fn resized(&self, width: usize, height: usize) -> Self {
+        let mut out = Self::new(width, height, Color::TRANSPARENT);
+        if self.width == 0 || self.height == 0 {
+            return out;
+        }
+        for y in 0..height {
+            let sy = y * self.height / height;
+            for x in 0..width {
+                let sx = x * self.width / width;
+                let src = (sy * self.width + sx) * 4;
+                let dst = (y * width + x) * 4;
+                out.pixels[dst..dst + 4].copy_from_slice(&self.pixels[src..src + 4]);
+            }
+        }
+        out
+    }
+
+    /// Draws `other` over this canvas, scaled to its size.
+    pub fn draw_image(&mut self, other: &Canvas) {
+        let scaled;
+        let other = if other.width == self.width && other.height == self.height {
+            other
+        } else {
+            scaled = other.resized(self.width, self.height);
+            &scaled
+        };
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if let Some(c) = other.pixel(x, y) {
+                    self.blend(x as i64, y as i64, c);
+                }
+            }
+        }
+    }
+
+    /// Pixel range covered by `min..=max` along an axis of `len` pixels.
+    fn span(min: f64, max: f64, len: usize) -> std::ops::Range<i64> {
+        min.floor().max(0.0) as i64..(max.ceil() as i64 + 1).min(len as i64)
+    }
+}
+
+/// Distance of `p` to the segment `a`-`b`.
+fn segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (cx, cy) = (a.0 + t * dx, a.1 + t * dy);
+    ((p.0 - cx).powi(2) + (p.1 - cy).powi(2)).sqrt()
+}
+
+impl Surface for Canvas {
+    fn width(&self) -> f64 {
+        self.width as f64
+    }
+
+    fn height(&self) -> f64 {
+        self.height as f64
+    }
+
+    fn circle(&mut self, center: Point, radius: f64, fill: Color) {
+        for y in Self::span(center.1 - radius, center.1 + radius, self.height) {
+            for x in Self::span(center.0 - radius, center.0 + radius, self.width) {
+                let (dx, dy) = (x as f64 + 0.5 - center.0, y as f64 + 0.5 - center.1);
+                if dx * dx + dy * dy <= radius * radius {
+                    self.blend(x, y, fill);
+                }
+            }
+        }
+    }
+
+    fn polyline(&mut self, points: &[Point], width: f64, stroke: Color) {
+        let half = (width / 2.0).max(0.5);
+        for (i, pair) in points.windows(2).enumerate() {
+            let (a, b) = (pair[0], pair[1]);
+            // The previous segment already drew the joint at `a`.
+            let previous = |p: Point| i > 0 && segment_distance(p, a, a) <= half;
+            for y in Self::span(a.1.min(b.1) - half, a.1.max(b.1) + half, self.height) {
+                for x in Self::span(a.0.min(b.0) - half, a.0.max(b.0) + half, self.width) {
+                    let p = (x as f64 + 0.5, y as f64 + 0.5);
+                    if segment_distance(p, a, b) <= half && !previous(p) {
+                        self.blend(x, y, stroke);
+                    }
+                }
+            }
+        }
+    }
+
+    fn polygon(&mut self, points: &[Point], fill: Color) {
+        if points.len() < 3 {
+            return;
+        }
+        let min_y = points.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+        let max_y = points.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+        let mut crossings = Vec::new();
+        for y in Self::span(min_y, max_y, self.height) {
+            let sy = y as f64 + 0.5;
+            crossings.clear();
+            for (i, a) in points.iter().enumerate() {
+                let b = points[(i + 1) % points.len()];
+                if (a.1 <= sy) != (b.1 <= sy) {
+                    crossings.push(a.0 + (sy - a.1) / (b.1 - a.1) * (b.0 - a.0));
+                }
+            }
+            crossings.sort_by(f64::total_cmp);
+            for pair in crossings.chunks_exact(2) {
+                for x in Self::span(pair[0], pair[1], self.width) {
+                    let sx = x as f64 + 0.5;
+                    if sx >= pair[0] && sx < pair[1] {
+                        self.blend(x, y, fill);
+                    }
+                }
+            }
+        }
+    }
+}
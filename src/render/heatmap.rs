@@ -0,0 +1,290 @@
+//! Gaussian kernel density heatmaps of world positions on radar images.
+//!
+//! ```no_run
+//! # use cs_demo_parser::parser::Parser;
+//! use cs_demo_parser::analyzer;
+//! use cs_demo_parser::maps::MapRegistry;
+//! use cs_demo_parser::render::heatmap::{Heatmap, HeatmapCollector, HeatmapKind, HeatmapOptions};
+//!
+//! # let mut parser = Parser::new(std::fs::File::open("demo.dem").unwrap());
+//! let collector = analyzer::run(&mut parser, HeatmapCollector::new(64)).unwrap();
+//!
+//! let maps = MapRegistry::builtin();
+//! let map = maps.get(parser.game_state().map_name().unwrap()).unwrap();
+//! let options = HeatmapOptions::default();
+//! let heatmap = Heatmap::new(map, collector.points(HeatmapKind::Deaths), &options);
+//! let image = heatmap.render(None, &options);
+//! ```
+
+use std::any::Any;
+
+use super::{Canvas, Color, ColorRamp};
+use crate::analyzer::Analyzer;
+use crate::common::Team;
+use crate::events;
+use crate::game_state::GameState;
+use crate::maps::{MapMetadata, RADAR_SIZE};
+use crate::sendtables::entity::Vector;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HeatmapKind {
+    /// Where killers stood.
+    Kills,
+    /// Where victims died.
+    Deaths,
+    /// Where living players were, sampled every few ticks.
+    Positions,
+    /// Where grenades detonated.
+    Grenades,
+}
+
+#[derive(Debug, Clone)]
+pub struct HeatmapOptions {
+    /// Size of the heatmap in pixels.
+    pub width: usize,
+    pub height: usize,
+    /// Standard deviation of the gaussian kernel in radar pixels.
+    pub sigma: f64,
+    pub ramp: ColorRamp,
+    /// Multiplies the alpha of the ramp's colours.
+    pub opacity: f64,
+    /// Only positions on this [level](MapMetadata::level) are counted, all
+    /// when `None`. Maps with a single level count every position.
+    pub level: Option<String>,
+}
+
+impl Default for HeatmapOptions {
+    fn default() -> Self {
+        Self {
+            width: RADAR_SIZE as usize,
+            height: RADAR_SIZE as usize,
+            sigma: 12.0,
+            ramp: ColorRamp::default(),
+            opacity: 0.8,
+            level: None,
+        }
+    }
+}
+
+/// A density grid normalized to `0..=1`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    width: usize,
+    height: usize,
+    values: Vec<f64>,
+}
+
+impl Heatmap {
+    /// Estimates the density of `positions` on the radar of `map`.
+    pub fn new<'a>(
+        map: &MapMetadata,
+        positions: impl IntoIterator<Item = &'a Vector>,
+        options: &HeatmapOptions,
+    ) -> Self {
+        let (width, height) = (options.width, options.height);
+        let mut values = vec![0.0; width * height];
+        let (sx, sy) = (width as f64 / RADAR_SIZE, height as f64 / RADAR_SIZE);
+        for position in positions {
+            if let (Some(level), Some(at)) = (&options.level, map.level(position.z))
+                && &at.name != level
+            {
+                continue;
+            }
+            let (x, y) = map.to_radar(position);
+            let (x, y) = ((x * sx).floor(), (y * sy).floor());
+            if x >= 0.0 && y >= 0.0 && (x as usize) < width && (y as usize) < height {
+                values[y as usize * width + x as usize] += 1.0;
+            }
+        }
+
+        let sigma = options.sigma * sx;
+        let kernel = gaussian_kernel(sigma);
+        let values = convolve(&values, width, height, &kernel, true);
+        let mut values = convolve(&values, width, height, &kernel, false);
+        let max = values.iter().copied().fold(0.0, f64::max);
+        if max > 0.0 {
+            values.iter_mut().for_each(|v| *v /= max);
+        }
+        Self {
+            width,
+            height,
+            values,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The density at a pixel, `0` outside the heatmap.
+    pub fn value(&self, x: usize, y: usize) -> f64 {
+        if x >= self.width || y >= self.height {
+            return 0.0;
+        }
+        self.values[y * self.width + x]
+    }
+
+    /// Draws the heatmap over `canvas`, scaled to its size.
+    pub fn draw(&self, canvas: &mut Canvas, options: &HeatmapOptions) {
+        use super::Surface;
+
+        let (w, h) = (canvas.width() as usize, canvas.height() as usize);
+        for y in 0..h {
+            for x in 0..w {
+                let v = self.value(x * self.width / w, y * self.height / h);
+                if v > 0.0 {
+                    let color = options.ramp.at(v).with_opacity(options.opacity);
+                    canvas.blend(x as i64, y as i64, color);
+                }
+            }
+        }
+    }
+
+    /// The heatmap over `background`, e.g. the map's radar image, or over a
+    /// transparent canvas.
+    pub fn render(&self, background: Option<&Canvas>, options: &HeatmapOptions) -> Canvas {
+        let mut canvas = Canvas::new(self.width, self.height, Color::TRANSPARENT);
+        if let Some(background) = background {
+            canvas.draw_image(background);
+        }
+        self.draw(&mut canvas, options);
+        canvas
+    }
+}
+
+/// Normalized weights of a gaussian kernel cut off at three sigmas.
+fn gaussian_kernel(sigma: f64) -> Vec<f64> {
+    if sigma <= 0.0 {
+        return vec![1.0];
+    }
+    let radius = (sigma * 3.0).ceil() as i64;
+    let kernel: Vec<f64> = (-radius..=radius)
+        .map(|i| (-((i * i) as f64) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f64 = kernel.iter().sum();
+    kernel.into_iter().map(|k| k / sum).collect()
+}
+
+/// One pass of a separable convolution, along rows if `horizontal`.
+fn convolve(
+    values: &[f64],
+    width: usize,
+    height: usize,
+    kernel: &[f64],
+    horizontal: bool,
+) -> Vec<f64> {
+    let radius = (kernel.len() / 2) as i64;
+    let mut out = vec![0.0; values.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let v = values[y * width + x];
+            if v == 0.0 {
+                continue;
+            }
+            for (k, weight) in kernel.iter().enumerate() {
+                let offset = k as i64 - radius;
+                let (tx, ty) = if horizontal {
+                    (x as i64 + offset, y as i64)
+                } else {
+                    (x as i64, y as i64 + offset)
+                };
+                if tx >= 0 && ty >= 0 && (tx as usize) < width && (ty as usize) < height {
+                    out[ty as usize * width + tx as usize] += v * weight;
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Collects the positions of every [`HeatmapKind`] from parser events, run
+/// it with [`analyzer::run`](crate::analyzer::run). Warmup is skipped.
+#[derive(Debug, Default)]
+pub struct HeatmapCollector {
+    kills: Vec<Vector>,
+    deaths: Vec<Vector>,
+    positions: Vec<Vector>,
+    grenades: Vec<Vector>,
+    position_interval: i32,
+    last_sample: Option<i32>,
+}
+
+impl HeatmapCollector {
+    /// Living players' positions are sampled every `position_interval`
+    /// ticks.
+    pub fn new(position_interval: i32) -> Self {
+        Self {
+            position_interval: position_interval.max(1),
+            ..Default::default()
+        }
+    }
+
+    pub fn points(&self, kind: HeatmapKind) -> &[Vector] {
+        match kind {
+            | HeatmapKind::Kills => &self.kills,
+            | HeatmapKind::Deaths => &self.deaths,
+            | HeatmapKind::Positions => &self.positions,
+            | HeatmapKind::Grenades => &self.grenades,
+        }
+    }
+
+    /// Records `event`, which has already been applied to `state`.
+    pub fn handle_event(&mut self, event: &dyn Any, state: &GameState) {
+        if state.is_warmup_period {
+            return;
+        }
+        let tick = state.ingame_tick();
+
+        if event.is::<events::FrameDone>() {
+            if self
+                .last_sample
+                .is_some_and(|last| tick < last + self.position_interval)
+            {
+                return;
+            }
+            self.last_sample = Some(tick);
+            let mut players: Vec<_> = state
+                .players_by_user_id
+                .values()
+                .filter(|p| p.is_alive())
+                .filter(|p| matches!(p.team, Team::Terrorists | Team::CounterTerrorists))
+                .collect();
+            players.sort_by_key(|p| p.user_id);
+            self.positions.extend(players.iter().map(|p| p.position()));
+        } else if let Some(kill) = event.downcast_ref::<events::Kill>() {
+            if let Some(killer) = &kill.killer {
+                self.kills.push(killer.position());
+            }
+            if let Some(victim) = &kill.victim {
+                self.deaths.push(victim.position());
+            }
+        } else if let Some(ev) = event.downcast_ref::<events::HeExplode>() {
+            self.grenades.push(ev.inner.position.clone());
+        } else if let Some(ev) = event.downcast_ref::<events::FlashExplode>() {
+            self.grenades.push(ev.inner.position.clone());
+        } else if let Some(ev) = event.downcast_ref::<events::SmokeStart>() {
+            self.grenades.push(ev.inner.position.clone());
+        } else if let Some(ev) = event.downcast_ref::<events::FireGrenadeStart>() {
+            self.grenades.push(ev.inner.position.clone());
+        } else if let Some(ev) = event.downcast_ref::<events::DecoyStart>() {
+            self.grenades.push(ev.inner.position.clone());
+        }
+    }
+}
+
+impl Analyzer for HeatmapCollector {
+    type Output = HeatmapCollector;
+
+    fn on_event(&mut self, event: &dyn Any, state: &GameState) {
+        self.handle_event(event, state);
+    }
+
+    fn finish(self, _: &GameState) -> HeatmapCollector {
+        self
+    }
+}
@@ -0,0 +1,143 @@
+//! CPU rendering of heatmaps and 2D round replays on radar images.
+//!
+//! World positions are placed with the overview data of [`crate::maps`].
+//! Everything is drawn onto a [`Surface`]: a [`Canvas`] is an RGBA raster,
+//! an [`Svg`] a vector image. With the `png` feature enabled canvases are
+//! read from and written to PNG files, see [`png`].
+//!
+//! [`heatmap`] turns kill, death, position or grenade positions into a
+//! gaussian kernel density coloured by a [`ColorRamp`]. [`replay`] records
+//! the players, bomb, smokes, infernos and grenade paths of a round every
+//! few ticks and draws each frame.
+
+mod canvas;
+pub mod heatmap;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod replay;
+mod svg;
+
+pub use canvas::Canvas;
+pub use svg::Svg;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const TRANSPARENT: Color = Color::rgba(0, 0, 0, 0);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b, a: 255 }
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// The colour with its alpha multiplied by `opacity`.
+    pub fn with_opacity(self, opacity: f64) -> Self {
+        Self {
+            a: (self.a as f64 * opacity.clamp(0.0, 1.0)).round() as u8,
+            ..self
+        }
+    }
+
+    /// Linear interpolation towards `other`, `t` is clamped to `0..=1`.
+    pub fn lerp(self, other: Color, t: f64) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Self {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+            a: mix(self.a, other.a),
+        }
+    }
+
+    /// `#rrggbb`, without alpha.
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+    }
+}
+
+/// Maps values in `0..=1` to colours by interpolating between stops.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ColorRamp {
+    stops: Vec<(f64, Color)>,
+}
+
+impl ColorRamp {
+    /// A ramp through `stops`, given as position and colour. Stops are
+    /// sorted by position; values outside them take the nearest stop.
+    pub fn new(mut stops: Vec<(f64, Color)>) -> Self {
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Self { stops }
+    }
+
+    pub fn stops(&self) -> &[(f64, Color)] {
+        &self.stops
+    }
+
+    pub fn at(&self, t: f64) -> Color {
+        let Some(&(first_pos, first)) = self.stops.first() else {
+            return Color::TRANSPARENT;
+        };
+        if t <= first_pos {
+            return first;
+        }
+        for pair in self.stops.windows(2) {
+            let ((a_pos, a), (b_pos, b)) = (pair[0], pair[1]);
+            if t <= b_pos {
+                let span = b_pos - a_pos;
+                return if span > 0.0 {
+                    a.lerp(b, (t - a_pos) / span)
+                } else {
+                    b
+                };
+            }
+        }
+        self.stops[self.stops.len() - 1].1
+    }
+}
+
+impl Default for ColorRamp {
+    /// Transparent through blue, green and yellow to red.
+    fn default() -> Self {
+        Self::new(vec![
+            (0.0, Color::rgba(0, 0, 255, 0)),
+            (0.15, Color::rgba(0, 0, 255, 160)),
+            (0.4, Color::rgba(0, 255, 0, 200)),
+            (0.7, Color::rgba(255, 255, 0, 230)),
+            (1.0, Color::rgb(255, 0, 0)),
+        ])
+    }
+}
+
+/// A point on a surface in pixels from the top left corner.
+pub type Point = (f64, f64);
+
+/// Something shapes can be drawn on. Coordinates are pixels of the
+/// surface.
+pub trait Surface {
+    fn width(&self) -> f64;
+
+    fn height(&self) -> f64;
+
+    fn circle(&mut self, center: Point, radius: f64, fill: Color);
+
+    fn polyline(&mut self, points: &[Point], width: f64, stroke: Color);
+
+    fn polygon(&mut self, points: &[Point], fill: Color);
+
+    /// Draws `text` next to `at`. Surfaces without fonts ignore it.
+    fn label(&mut self, _at: Point, _text: &str, _fill: Color) {}
+}
@@ -0,0 +1,67 @@
+//! Reading and writing [`Canvas`]es as PNG images.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::{Canvas, Surface};
+use crate::utils::DataError;
+
+impl From<png::DecodingError> for DataError {
+    fn from(e: png::DecodingError) -> Self {
+        DataError::Parse(e.to_string())
+    }
+}
+
+impl From<png::EncodingError> for DataError {
+    fn from(e: png::EncodingError) -> Self {
+        DataError::Parse(e.to_string())
+    }
+}
+
+/// Decodes a PNG image of any colour type into an RGBA canvas.
+pub fn decode(reader: impl Read) -> Result<Canvas, DataError> {
+    let mut decoder = png::Decoder::new(reader);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buf)?;
+    buf.truncate(info.buffer_size());
+
+    let rgba = match info.color_type {
+        | png::ColorType::Rgba => buf,
+        | png::ColorType::Rgb => buf
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        | png::ColorType::GrayscaleAlpha => buf
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        | png::ColorType::Grayscale => buf.iter().flat_map(|&g| [g, g, g, 255]).collect(),
+        | png::ColorType::Indexed => {
+            return Err(DataError::Parse("palette wasn't expanded".into()));
+        },
+    };
+    Canvas::from_rgba(info.width as usize, info.height as usize, rgba)
+        .ok_or_else(|| DataError::Parse("unexpected image size".into()))
+}
+
+/// Encodes `canvas` as an RGBA PNG image.
+pub fn encode(canvas: &Canvas, writer: impl Write) -> Result<(), DataError> {
+    let mut encoder = png::Encoder::new(writer, canvas.width() as u32, canvas.height() as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(canvas.pixels())?;
+    writer.finish()?;
+    Ok(())
+}
+
+pub fn read_file(path: impl AsRef<Path>) -> Result<Canvas, DataError> {
+    decode(BufReader::new(File::open(path)?))
+}
+
+pub fn write_file(canvas: &Canvas, path: impl AsRef<Path>) -> Result<(), DataError> {
+    encode(canvas, BufWriter::new(File::create(path)?))
+}
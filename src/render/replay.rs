@@ -0,0 +1,355 @@
+//! 2D replays of rounds: the players, bomb, smokes, infernos and grenades
+//! in flight, recorded every few ticks and drawn on a radar image.
+//!
+//! ```no_run
+//! # use cs_demo_parser::parser::Parser;
+//! use cs_demo_parser::analyzer;
+//! use cs_demo_parser::maps::MapRegistry;
+//! use cs_demo_parser::render::replay::{FrameStyle, ReplayOptions, ReplayRecorder};
+//!
+//! # let mut parser = Parser::new(std::fs::File::open("demo.dem").unwrap());
+//! let recorder = ReplayRecorder::new(ReplayOptions::default());
+//! let recorder = analyzer::run(&mut parser, recorder).unwrap();
+//!
+//! let maps = MapRegistry::builtin();
+//! let map = maps.get(parser.game_state().map_name().unwrap()).unwrap();
+//! for (i, frame) in recorder.round_frames(3).enumerate() {
+//!     let svg = frame.to_svg(map, &FrameStyle::default(), Some("de_mirage_radar.png"));
+//!     std::fs::write(format!("frame_{i:04}.svg"), svg).unwrap();
+//! }
+//! ```
+
+use std::any::Any;
+
+use super::{Color, Point, Surface, Svg};
+use crate::analyzer::Analyzer;
+use crate::common::{BombState, EquipmentType, Team};
+use crate::events;
+use crate::game_state::{GameState, PlayerSnapshot};
+use crate::maps::{MapMetadata, RADAR_SIZE};
+use crate::sendtables::entity::Vector;
+use crate::smokes::SMOKE_RADIUS;
+
+/// Radius of one fire of an inferno in world units.
+const FIRE_RADIUS: f64 = 30.0;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameGrenade {
+    pub entity_id: i32,
+    pub grenade_type: EquipmentType,
+    /// Positions since the throw, the last one is the current.
+    pub path: Vec<Vector>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameSmoke {
+    pub entity_id: i32,
+    pub position: Vector,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameInferno {
+    pub entity_id: i32,
+    pub flames: Vec<Vector>,
+    /// Convex hull of `flames`.
+    pub hull: Vec<Vector>,
+}
+
+/// What a round looked like at one tick.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RoundFrame {
+    pub round: i32,
+    pub tick: i32,
+    /// Players of both teams, sorted by user id.
+    pub players: Vec<PlayerSnapshot>,
+    pub bomb_position: Vector,
+    pub bomb_state: BombState,
+    pub bomb_carrier: Option<i32>,
+    pub smokes: Vec<FrameSmoke>,
+    pub infernos: Vec<FrameInferno>,
+    pub grenades: Vec<FrameGrenade>,
+}
+
+impl RoundFrame {
+    pub fn capture(state: &GameState) -> Self {
+        let tick = state.ingame_tick();
+        let mut players: Vec<PlayerSnapshot> = state
+            .players_by_user_id
+            .values()
+            .filter(|p| matches!(p.team, Team::Terrorists | Team::CounterTerrorists))
+            .map(PlayerSnapshot::from)
+            .collect();
+        players.sort_by_key(|p| p.user_id);
+
+        let smokes = state
+            .smokes()
+            .at(tick)
+            .map(|s| FrameSmoke {
+                entity_id: s.entity_id,
+                position: s.position.clone(),
+            })
+            .collect();
+
+        let mut infernos: Vec<FrameInferno> = state
+            .infernos
+            .iter()
+            .map(|(id, inferno)| FrameInferno {
+                entity_id: *id,
+                flames: inferno.flames.clone(),
+                hull: inferno.hull().to_vec(),
+            })
+            .collect();
+        infernos.sort_by_key(|i| i.entity_id);
+
+        let mut grenades: Vec<FrameGrenade> = state
+            .grenade_projectiles
+            .iter()
+            .map(|(id, g)| FrameGrenade {
+                entity_id: *id,
+                grenade_type: g
+                    .weapon_instance
+                    .as_ref()
+                    .map(|w| w.equipment_type)
+                    .unwrap_or_default(),
+                path: g.trajectory.clone(),
+            })
+            .collect();
+        grenades.sort_by_key(|g| g.entity_id);
+
+        Self {
+            round: state.total_rounds_played() + 1,
+            tick,
+            players,
+            bomb_position: state.bomb.position(),
+            bomb_state: state.bomb.state,
+            bomb_carrier: state.bomb.carrier.as_ref().map(|p| p.user_id),
+            smokes,
+            infernos,
+            grenades,
+        }
+    }
+
+    /// Draws the frame on `surface`, which shows the whole radar of `map`.
+    pub fn draw(&self, map: &MapMetadata, surface: &mut impl Surface, style: &FrameStyle) {
+        let scale = surface.width() / RADAR_SIZE;
+        let project = |v: &Vector| {
+            let (x, y) = map.to_radar(v);
+            (x * scale, y * scale)
+        };
+        let visible = |v: &Vector| match (&style.level, map.level(v.z)) {
+            | (Some(level), Some(at)) => &at.name == level,
+            | _ => true,
+        };
+
+        for smoke in self.smokes.iter().filter(|s| visible(&s.position)) {
+            let radius = SMOKE_RADIUS / map.scale * scale;
+            surface.circle(project(&smoke.position), radius, style.smoke);
+        }
+        for inferno in &self.infernos {
+            if inferno.flames.iter().all(|f| !visible(f)) {
+                continue;
+            }
+            if inferno.hull.len() >= 3 {
+                let hull: Vec<Point> = inferno.hull.iter().map(project).collect();
+                surface.polygon(&hull, style.fire);
+            } else {
+                for flame in &inferno.flames {
+                    surface.circle(project(flame), FIRE_RADIUS / map.scale * scale, style.fire);
+                }
+            }
+        }
+        for grenade in &self.grenades {
+            let path: Vec<Point> = grenade
+                .path
+                .iter()
+                .filter(|v| visible(v))
+                .map(project)
+                .collect();
+            surface.polyline(&path, style.line_width, style.grenade);
+            if let Some(&last) = path.last() {
+                surface.circle(last, style.player_radius / 2.0, style.grenade);
+            }
+        }
+
+        let radius = style.player_radius;
+        for player in self.players.iter().filter(|p| visible(&p.position)) {
+            let center = project(&player.position);
+            if !player.is_alive {
+                if let Some(dead) = style.dead {
+                    surface.circle(center, radius / 2.0, dead);
+                }
+                continue;
+            }
+            let color = match player.team {
+                | Team::CounterTerrorists => style.counter_terrorists,
+                | _ => style.terrorists,
+            };
+            let yaw = (player.yaw as f64).to_radians();
+            let tip = (
+                center.0 + yaw.cos() * radius * 2.0,
+                center.1 - yaw.sin() * radius * 2.0,
+            );
+            surface.polyline(&[center, tip], style.line_width, color);
+            surface.circle(center, radius, color);
+            if style.names {
+                surface.label(
+                    (center.0 + radius + 2.0, center.1 + 4.0),
+                    &player.name,
+                    Color::WHITE,
+                );
+            }
+        }
+
+        let bomb_visible = !matches!(self.bomb_state, BombState::Defused | BombState::Exploded);
+        if bomb_visible && visible(&self.bomb_position) {
+            surface.circle(project(&self.bomb_position), radius / 2.0, style.bomb);
+        }
+    }
+
+    /// The frame as an SVG document of `style.size` pixels, over the radar
+    /// image at `radar_href` if given.
+    pub fn to_svg(
+        &self,
+        map: &MapMetadata,
+        style: &FrameStyle,
+        radar_href: Option<&str>,
+    ) -> String {
+        let mut svg = Svg::new(style.size, style.size);
+        if let Some(href) = radar_href {
+            svg.image(href);
+        }
+        self.draw(map, &mut svg, style);
+        svg.finish()
+    }
+}
+
+/// Colours and sizes of the shapes of a [`RoundFrame`].
+#[derive(Debug, Clone)]
+pub struct FrameStyle {
+    /// Width and height of SVG documents in pixels.
+    pub size: f64,
+    /// Radius of player dots in pixels.
+    pub player_radius: f64,
+    pub line_width: f64,
+    pub counter_terrorists: Color,
+    pub terrorists: Color,
+    /// Colour of dead players, hidden when `None`.
+    pub dead: Option<Color>,
+    pub bomb: Color,
+    pub smoke: Color,
+    pub fire: Color,
+    pub grenade: Color,
+    /// Draw player names, on surfaces that support text.
+    pub names: bool,
+    /// Only things on this [level](MapMetadata::level) are drawn, all when
+    /// `None`.
+    pub level: Option<String>,
+}
+
+impl Default for FrameStyle {
+    fn default() -> Self {
+        Self {
+            size: RADAR_SIZE,
+            player_radius: 8.0,
+            line_width: 2.0,
+            counter_terrorists: Color::rgb(93, 121, 174),
+            terrorists: Color::rgb(222, 155, 53),
+            dead: Some(Color::rgba(128, 128, 128, 160)),
+            bomb: Color::rgb(255, 0, 0),
+            smoke: Color::rgba(200, 200, 200, 180),
+            fire: Color::rgba(255, 90, 0, 160),
+            grenade: Color::rgb(255, 255, 255),
+            names: true,
+            level: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayOptions {
+    /// Number of ingame ticks between two frames.
+    pub interval: i32,
+    /// Only record this round, all rounds when `None`.
+    pub round: Option<i32>,
+    /// Also record the freeze time at the start of a round.
+    pub include_freezetime: bool,
+}
+
+impl Default for ReplayOptions {
+    fn default() -> Self {
+        Self {
+            interval: 16,
+            round: None,
+            include_freezetime: false,
+        }
+    }
+}
+
+/// Records a [`RoundFrame`] every few ticks while parsing, run it with
+/// [`analyzer::run`](crate::analyzer::run). Warmup is skipped.
+#[derive(Debug, Default)]
+pub struct ReplayRecorder {
+    options: ReplayOptions,
+    frames: Vec<RoundFrame>,
+    last_frame: Option<i32>,
+}
+
+impl ReplayRecorder {
+    pub fn new(options: ReplayOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
+
+    pub fn frames(&self) -> &[RoundFrame] {
+        &self.frames
+    }
+
+    pub fn round_frames(&self, round: i32) -> impl Iterator<Item = &RoundFrame> {
+        self.frames.iter().filter(move |f| f.round == round)
+    }
+
+    pub fn take_frames(&mut self) -> Vec<RoundFrame> {
+        std::mem::take(&mut self.frames)
+    }
+
+    /// Records a frame on [`FrameDone`](events::FrameDone) if it's due.
+    pub fn handle_event(&mut self, event: &dyn Any, state: &GameState) {
+        if !event.is::<events::FrameDone>() || state.is_warmup_period {
+            return;
+        }
+        if state.is_freezetime && !self.options.include_freezetime {
+            return;
+        }
+        let round = state.total_rounds_played() + 1;
+        if self.options.round.is_some_and(|r| r != round) {
+            return;
+        }
+        let tick = state.ingame_tick();
+        if self
+            .last_frame
+            .is_some_and(|last| tick >= last && tick < last + self.options.interval.max(1))
+        {
+            return;
+        }
+        self.last_frame = Some(tick);
+        self.frames.push(RoundFrame::capture(state));
+    }
+}
+
+impl Analyzer for ReplayRecorder {
+    type Output = ReplayRecorder;
+
+    fn on_event(&mut self, event: &dyn Any, state: &GameState) {
+        self.handle_event(event, state);
+    }
+
+    fn finish(self, _: &GameState) -> ReplayRecorder {
+        self
+    }
+}
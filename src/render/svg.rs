@@ -0,0 +1,121 @@
+use std::fmt::Write;
+
+use super::{Color, Point, Surface};
+
+/// An SVG document drawn into a string.
+#[derive(Debug, Clone, Default)]
+pub struct Svg {
+    width: f64,
+    height: f64,
+    body: String,
+}
+
+impl Svg {
+    pub fn new(width: f64, height: f64) -> Self {
+        Self {
+            width,
+            height,
+            body: String::new(),
+        }
+    }
+
+    /// Draws the image at `href`, e.g. a radar image, over the whole
+    /// document.
+    pub fn image(&mut self, href: &str) {
+        let _ = writeln!(
+            self.body,
+            r#"<image href="{}" x="0" y="0" width="{}" height="{}"/>"#,
+            escape(href),
+            self.width,
+            self.height
+        );
+    }
+
+    /// The complete document.
+    pub fn finish(&self) -> String {
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}</svg>\n",
+            self.body,
+            w = self.width,
+            h = self.height
+        )
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn paint(attr: &str, color: Color) -> String {
+    if color.a == 255 {
+        format!(r#"{attr}="{}""#, color.hex())
+    } else {
+        format!(
+            r#"{attr}="{}" {attr}-opacity="{:.3}""#,
+            color.hex(),
+            color.a as f64 / 255.0
+        )
+    }
+}
+
+fn points(points: &[Point]) -> String {
+    points
+        .iter()
+        .map(|(x, y)| format!("{x:.1},{y:.1}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl Surface for Svg {
+    fn width(&self) -> f64 {
+        self.width
+    }
+
+    fn height(&self) -> f64 {
+        self.height
+    }
+
+    fn circle(&mut self, center: Point, radius: f64, fill: Color) {
+        let _ = writeln!(
+            self.body,
+            r#"<circle cx="{:.1}" cy="{:.1}" r="{:.1}" {}/>"#,
+            center.0,
+            center.1,
+            radius,
+            paint("fill", fill)
+        );
+    }
+
+    fn polyline(&mut self, line: &[Point], width: f64, stroke: Color) {
+        let _ = writeln!(
+            self.body,
+            r#"<polyline points="{}" fill="none" stroke-width="{:.1}" stroke-linecap="round" stroke-linejoin="round" {}/>"#,
+            points(line),
+            width,
+            paint("stroke", stroke)
+        );
+    }
+
+    fn polygon(&mut self, polygon: &[Point], fill: Color) {
+        let _ = writeln!(
+            self.body,
+            r#"<polygon points="{}" {}/>"#,
+            points(polygon),
+            paint("fill", fill)
+        );
+    }
+
+    fn label(&mut self, at: Point, text: &str, fill: Color) {
+        let _ = writeln!(
+            self.body,
+            r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="12" {}>{}</text>"#,
+            at.0,
+            at.1,
+            paint("fill", fill),
+            escape(text)
+        );
+    }
+}
@@ -6,6 +6,7 @@ use std::io::Read;
 use std::sync::Arc;
 
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector {
     pub x: f64,
     pub y: f64,
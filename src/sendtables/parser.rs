@@ -68,10 +68,7 @@ impl Parser {
                 panic!("Expected SendTable message");
             }
             let size = r.read_varint32() as usize;
-            let mut bytes = vec![0u8; size];
-            for b in &mut bytes {
-                *b = r.read_int(8) as u8;
-            }
+            let bytes = r.read_bytes(size);
             let st = CsvcMsgSendTable::decode(&bytes[..])?;
             if st.is_end.unwrap_or(false) {
                 break;
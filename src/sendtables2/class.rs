@@ -1,8 +1,13 @@
+use std::sync::Arc;
+
+use super::plan::DecodePlan;
 use super::serializer::Serializer;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Class {
     pub class_id: i32,
-    pub name: String,
-    pub serializer: Option<Serializer>,
+    pub name: Arc<str>,
+    pub serializer: Option<Arc<Serializer>>,
+    /// Compiled from `serializer` when the class is registered.
+    pub plan: Option<Arc<DecodePlan>>,
 }
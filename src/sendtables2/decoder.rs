@@ -0,0 +1,347 @@
+use super::field::Field;
+use super::field_type::FieldType;
+use super::reader::Reader;
+use crate::sendtables::entity::{PropertyValue, Vector};
+
+const QFF_ROUND_DOWN: u32 = 1 << 0;
+const QFF_ROUND_UP: u32 = 1 << 1;
+const QFF_ENCODE_ZERO: u32 = 1 << 2;
+const QFF_ENCODE_INTEGER: u32 = 1 << 3;
+
+/// Decodes floats packed into `bit_count` bits between `low` and `high`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantizedFloat {
+    low: f32,
+    high: f32,
+    high_low_mul: f32,
+    dec_mul: f32,
+    offset: f32,
+    bit_count: u32,
+    flags: u32,
+}
+
+impl QuantizedFloat {
+    pub fn new(bit_count: u32, flags: u32, low: Option<f32>, high: Option<f32>) -> Self {
+        let mut q = Self {
+            low: low.unwrap_or(0.0),
+            high: high.unwrap_or(1.0),
+            high_low_mul: 0.0,
+            dec_mul: 0.0,
+            offset: 0.0,
+            bit_count,
+            flags,
+        };
+        q.validate_flags();
+
+        let mut steps = 1u64 << q.bit_count;
+        if q.flags & QFF_ROUND_DOWN != 0 {
+            q.offset = (q.high - q.low) / steps as f32;
+            q.high -= q.offset;
+        } else if q.flags & QFF_ROUND_UP != 0 {
+            q.offset = (q.high - q.low) / steps as f32;
+            q.low += q.offset;
+        }
+
+        if q.flags & QFF_ENCODE_INTEGER != 0 {
+            let delta = (q.high - q.low).max(1.0);
+            let range = 1u64 << (delta as f64).log2().ceil() as u32;
+            let mut bit_count = q.bit_count;
+            while (1u64 << bit_count) <= range {
+                bit_count += 1;
+            }
+            if bit_count > q.bit_count {
+                q.bit_count = bit_count;
+                steps = 1 << bit_count;
+            }
+            q.offset = range as f32 / steps as f32;
+            q.high = q.low + range as f32 - q.offset;
+        }
+
+        q.assign_multipliers(steps);
+
+        if q.flags & QFF_ROUND_DOWN != 0 && q.quantize(q.low) == q.low {
+            q.flags &= !QFF_ROUND_DOWN;
+        }
+        if q.flags & QFF_ROUND_UP != 0 && q.quantize(q.high) == q.high {
+            q.flags &= !QFF_ROUND_UP;
+        }
+        if q.flags & QFF_ENCODE_ZERO != 0 && q.quantize(0.0) == 0.0 {
+            q.flags &= !QFF_ENCODE_ZERO;
+        }
+        q
+    }
+
+    fn validate_flags(&mut self) {
+        if self.flags == 0 {
+            return;
+        }
+        if (self.low == 0.0 && self.flags & QFF_ROUND_DOWN != 0)
+            || (self.high == 0.0 && self.flags & QFF_ROUND_UP != 0)
+        {
+            self.flags &= !QFF_ENCODE_ZERO;
+        }
+        if self.low == 0.0 && self.flags & QFF_ENCODE_ZERO != 0 {
+            self.flags |= QFF_ROUND_DOWN;
+            self.flags &= !QFF_ENCODE_ZERO;
+        }
+        if self.high == 0.0 && self.flags & QFF_ENCODE_ZERO != 0 {
+            self.flags |= QFF_ROUND_UP;
+            self.flags &= !QFF_ENCODE_ZERO;
+        }
+        if self.low > 0.0 || self.high < 0.0 {
+            self.flags &= !QFF_ENCODE_ZERO;
+        }
+        if self.flags & QFF_ENCODE_INTEGER != 0 {
+            self.flags &= !(QFF_ROUND_UP | QFF_ROUND_DOWN | QFF_ENCODE_ZERO);
+        }
+    }
+
+    fn assign_multipliers(&mut self, steps: u64) {
+        let range = self.high - self.low;
+        let high = if self.bit_count == 32 {
+            0xFFFF_FFFE_u32
+        } else {
+            (1u32 << self.bit_count) - 1
+        } as f32;
+        let mut mul = if range.abs() <= 0.0 {
+            high
+        } else {
+            high / range
+        };
+        if mul * range > high {
+            for m in [0.9999, 0.99, 0.9, 0.8, 0.7] {
+                mul = high / range * m;
+                if mul * range <= high {
+                    break;
+                }
+            }
+        }
+        self.high_low_mul = mul;
+        self.dec_mul = 1.0 / (steps - 1) as f32;
+    }
+
+    fn quantize(&self, value: f32) -> f32 {
+        if value < self.low {
+            return self.low;
+        }
+        if value > self.high {
+            return self.high;
+        }
+        let i = ((value - self.low) * self.high_low_mul) as u32;
+        self.low + (self.high - self.low) * (i as f32 * self.dec_mul)
+    }
+
+    pub fn decode(&self, r: &mut Reader) -> f32 {
+        if self.flags & QFF_ROUND_DOWN != 0 && r.read_boolean() {
+            return self.low;
+        }
+        if self.flags & QFF_ROUND_UP != 0 && r.read_boolean() {
+            return self.high;
+        }
+        if self.flags & QFF_ENCODE_ZERO != 0 && r.read_boolean() {
+            return 0.0;
+        }
+        let bits = r.read_bits(self.bit_count) as f32;
+        self.low + (self.high - self.low) * bits * self.dec_mul
+    }
+}
+
+/// How the value of a single property is encoded.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Decoder {
+    Bool,
+    Signed,
+    Signed64,
+    Unsigned,
+    Unsigned64,
+    Fixed64,
+    Component,
+    String,
+    NoScale,
+    Coord,
+    SimulationTime,
+    RuneTime,
+    Quantized(QuantizedFloat),
+    Vector(Box<Decoder>, usize),
+    Normal,
+    QAngle(u32),
+    QAnglePitchYaw(u32),
+    QAngleCoord,
+    QAnglePrecise,
+    GameModeRules,
+}
+
+impl Decoder {
+    /// Picks the decoder for a field of type `ty`. For collections `ty` is
+    /// the element type.
+    pub fn for_type(ty: &FieldType, field: &Field) -> Self {
+        match field.var_name.as_str() {
+            | "m_PredFloatVariables" | "m_OwnerOnlyPredNetFloatVariables" => {
+                return Decoder::NoScale;
+            },
+            | "m_PredVectorVariables" | "m_OwnerOnlyPredNetVectorVariables" => {
+                return Decoder::Vector(Box::new(Decoder::NoScale), 3);
+            },
+            | "m_pGameModeRules" => return Decoder::GameModeRules,
+            | _ => {},
+        }
+        let encoder = field.var_encoder.as_deref().unwrap_or_default();
+        match ty.base_type.as_str() {
+            | "bool" => Decoder::Bool,
+            | "char" | "CUtlString" | "CUtlSymbolLarge" => Decoder::String,
+            | "int8" | "int16" | "int32" => Decoder::Signed,
+            | "int64" => Decoder::Signed64,
+            | "uint64" | "CStrongHandle" => {
+                if encoder == "fixed64" {
+                    Decoder::Fixed64
+                } else {
+                    Decoder::Unsigned64
+                }
+            },
+            | "float32" | "CNetworkedQuantizedFloat" => Self::float(field),
+            | "GameTime_t" => Decoder::NoScale,
+            | "Vector" if encoder == "normal" => Decoder::Normal,
+            | "Vector" => Decoder::Vector(Box::new(Self::float(field)), 3),
+            | "Vector2D" => Decoder::Vector(Box::new(Self::float(field)), 2),
+            | "Vector4D" => Decoder::Vector(Box::new(Self::float(field)), 4),
+            | "QAngle" => {
+                let bits = field.bit_count.unwrap_or(0) as u32;
+                match encoder {
+                    | "qangle_pitch_yaw" => Decoder::QAnglePitchYaw(bits),
+                    | "qangle_precise" => Decoder::QAnglePrecise,
+                    | _ if bits != 0 => Decoder::QAngle(bits),
+                    | _ => Decoder::QAngleCoord,
+                }
+            },
+            | "CBodyComponent" | "CPhysicsComponent" | "CRenderComponent" => Decoder::Component,
+            | _ => Decoder::Unsigned,
+        }
+    }
+
+    fn float(field: &Field) -> Self {
+        match field.var_encoder.as_deref() {
+            | Some("coord") => return Decoder::Coord,
+            | Some("simtime") => return Decoder::SimulationTime,
+            | Some("runetime") => return Decoder::RuneTime,
+            | _ => {},
+        }
+        match field.bit_count {
+            | Some(bits) if bits > 0 && bits < 32 => Decoder::Quantized(QuantizedFloat::new(
+                bits as u32,
+                field.encode_flags.unwrap_or(0) as u32,
+                field.low_value,
+                field.high_value,
+            )),
+            | _ => Decoder::NoScale,
+        }
+    }
+
+    pub fn decode(&self, r: &mut Reader) -> PropertyValue {
+        let mut v = PropertyValue::default();
+        match self {
+            | Decoder::Bool => v.int_val = r.read_boolean() as i32,
+            | Decoder::Signed => v.int_val = r.read_var_int32(),
+            | Decoder::Signed64 => v.int64_val = r.read_var_int64(),
+            | Decoder::Unsigned => {
+                let x = r.read_var_uint32();
+                v.int_val = x as i32;
+                v.int64_val = x as i64;
+            },
+            | Decoder::Unsigned64 => v.int64_val = r.read_var_uint64() as i64,
+            | Decoder::Fixed64 => v.int64_val = r.read_bits_u64(64) as i64,
+            | Decoder::Component => v.int_val = r.read_bits(1) as i32,
+            | Decoder::GameModeRules => v.int_val = r.read_bits(7) as i32,
+            | Decoder::String => v.string_val = r.read_string(),
+            | Decoder::NoScale
+            | Decoder::Coord
+            | Decoder::SimulationTime
+            | Decoder::RuneTime
+            | Decoder::Quantized(_) => v.float_val = self.decode_float(r),
+            | Decoder::Vector(elem, n) => {
+                let mut xs = [0.0; 4];
+                for x in xs.iter_mut().take(*n) {
+                    *x = elem.decode_float(r);
+                }
+                v.vector_val = vector(&xs);
+                v.array_val = xs[..*n]
+                    .iter()
+                    .map(|&f| PropertyValue {
+                        float_val: f,
+                        ..Default::default()
+                    })
+                    .collect();
+            },
+            | Decoder::Normal => v.vector_val = vector(&r.read_3bit_normal()),
+            | Decoder::QAngle(bits) => {
+                let xs = [
+                    r.read_angle(*bits),
+                    r.read_angle(*bits),
+                    r.read_angle(*bits),
+                ];
+                v.vector_val = vector(&xs);
+            },
+            | Decoder::QAnglePitchYaw(bits) => {
+                v.vector_val = vector(&[r.read_angle(*bits), r.read_angle(*bits), 0.0]);
+            },
+            | Decoder::QAngleCoord | Decoder::QAnglePrecise => {
+                let present = [r.read_boolean(), r.read_boolean(), r.read_boolean()];
+                let mut xs = [0.0; 3];
+                for (x, present) in xs.iter_mut().zip(present) {
+                    if present {
+                        *x = if *self == Decoder::QAnglePrecise {
+                            r.read_coord_precise()
+                        } else {
+                            r.read_coord()
+                        };
+                    }
+                }
+                v.vector_val = vector(&xs);
+            },
+        }
+        v
+    }
+
+    fn decode_float(&self, r: &mut Reader) -> f32 {
+        match self {
+            | Decoder::Coord => r.read_coord(),
+            | Decoder::SimulationTime => r.read_var_uint32() as f32 * (1.0 / 30.0),
+            | Decoder::RuneTime => f32::from_bits(r.read_bits(4)),
+            | Decoder::Quantized(q) => q.decode(r),
+            | _ => r.read_float(),
+        }
+    }
+
+    /// Advances `r` past a value without decoding it. Cheaper than
+    /// [`decode`](Self::decode) for fixed size encodings.
+    pub fn skip(&self, r: &mut Reader) {
+        match self {
+            | Decoder::NoScale => r.skip_bits(32),
+            | Decoder::Fixed64 => r.skip_bits(64),
+            | Decoder::Component => r.skip_bits(1),
+            | Decoder::GameModeRules => r.skip_bits(7),
+            | Decoder::RuneTime => r.skip_bits(4),
+            | Decoder::Quantized(q) if q.flags == 0 => r.skip_bits(q.bit_count as usize),
+            | Decoder::Vector(elem, n) if **elem == Decoder::NoScale => r.skip_bits(32 * n),
+            | Decoder::QAngle(bits) => r.skip_bits(3 * *bits as usize),
+            | Decoder::QAnglePitchYaw(bits) => r.skip_bits(2 * *bits as usize),
+            | Decoder::Bool => r.skip_bits(1),
+            | Decoder::Signed | Decoder::Unsigned => {
+                r.read_var_uint32();
+            },
+            | Decoder::Signed64 | Decoder::Unsigned64 => {
+                r.read_var_uint64();
+            },
+            | _ => {
+                self.decode(r);
+            },
+        }
+    }
+}
+
+fn vector(xs: &[f32]) -> Vector {
+    Vector {
+        x: xs[0] as f64,
+        y: xs[1] as f64,
+        z: xs.get(2).copied().unwrap_or(0.0) as f64,
+    }
+}
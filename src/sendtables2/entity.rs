@@ -1,8 +1,161 @@
+use std::sync::{Arc, OnceLock};
+
 use super::class::Class;
+use super::plan::{DecodePlan, PathKey, PathMap};
+use super::reader::Reader;
+use super::store::EntityHandle;
+use crate::sendtables::entity::{PropertyValue, Vector};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct Entity {
     pub index: i32,
     pub serial: i32,
     pub class: Class,
+    pub properties: Properties,
+}
+
+impl Entity {
+    pub fn handle(&self) -> EntityHandle {
+        EntityHandle {
+            index: self.index,
+            serial: self.serial,
+        }
+    }
+
+    /// The current value of the property `name`, e.g. `m_iHealth` or
+    /// `CBodyComponent.m_cellX`. Decoded on first access.
+    pub fn property_value(&self, name: &str) -> Option<PropertyValue> {
+        let plan = self.class.plan.as_ref()?;
+        self.properties.get(plan, &plan.resolve(name)?)
+    }
+
+    /// The elements of the vector property `name`, e.g. `m_VoxelFrameData`,
+    /// up to the vector's current length. Elements that weren't received
+    /// are default values.
+    pub fn property_array(&self, name: &str) -> Vec<PropertyValue> {
+        let Some(plan) = self.class.plan.as_ref() else {
+            return Vec::new();
+        };
+        let Some(key) = plan.resolve(name) else {
+            return Vec::new();
+        };
+        let len = self
+            .properties
+            .get(plan, &key)
+            .map_or(0, |v| v.int_val.max(0));
+        (0..len)
+            .map(|i| {
+                key.push(i)
+                    .and_then(|k| self.properties.get(plan, &k))
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Position in world coordinates, combined from the cell and the offset
+    /// in the cell of `CBodyComponent`. `None` for entities without one.
+    pub fn position(&self) -> Option<Vector> {
+        const CELL_WIDTH: f64 = 512.0;
+        const MAX_COORD: f64 = 16384.0;
+        let coord = |axis: char| {
+            let cell = self.property_value(&format!("CBodyComponent.m_cell{axis}"))?;
+            let offset = self.property_value(&format!("CBodyComponent.m_vec{axis}"))?;
+            Some(cell.int_val as f64 * CELL_WIDTH - MAX_COORD + offset.float_val as f64)
+        };
+        Some(Vector {
+            x: coord('X')?,
+            y: coord('Y')?,
+            z: coord('Z')?,
+        })
+    }
+
+    /// All properties the entity received, ordered by field path. Decodes
+    /// every value not read yet.
+    pub fn property_values(&self) -> Vec<(String, PropertyValue)> {
+        let Some(plan) = &self.class.plan else {
+            return Vec::new();
+        };
+        let mut keys: Vec<&PathKey> = self.properties.values.keys().collect();
+        keys.sort();
+        keys.into_iter()
+            .filter_map(|key| {
+                let name = plan.property_name(key.as_slice())?;
+                Some((name, self.properties.get(plan, key)?))
+            })
+            .collect()
+    }
+}
+
+/// A property value that is decoded from the bytes of the update it was
+/// received in when it's first read.
+#[derive(Clone, Debug)]
+pub(super) struct LazyValue {
+    /// The bytes of the entity's update, shared by all values it set.
+    /// `None` for values decoded right away.
+    data: Option<Arc<[u8]>>,
+    /// Bit offset of the value in `data`.
+    bit: usize,
+    value: OnceLock<PropertyValue>,
+}
+
+impl LazyValue {
+    pub(super) fn new(data: Arc<[u8]>, bit: usize) -> Self {
+        Self {
+            data: Some(data),
+            bit,
+            value: OnceLock::new(),
+        }
+    }
+
+    /// A value that was decoded right away.
+    pub(super) fn decoded(value: PropertyValue) -> Self {
+        Self {
+            data: None,
+            bit: 0,
+            value: OnceLock::from(value),
+        }
+    }
+}
+
+/// Property values of an entity by field path.
+///
+/// Cloning is cheap: copies share their values with the store until the
+/// next update, which then has to copy them. Entity events therefore only
+/// carry a handle.
+#[derive(Clone, Debug, Default)]
+pub struct Properties {
+    values: Arc<PathMap<LazyValue>>,
+}
+
+impl Properties {
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Number of values that have been decoded so far.
+    pub fn decoded(&self) -> usize {
+        self.values
+            .values()
+            .filter(|v| v.value.get().is_some())
+            .count()
+    }
+
+    fn get(&self, plan: &DecodePlan, key: &PathKey) -> Option<PropertyValue> {
+        let lazy = self.values.get(key)?;
+        let value = lazy.value.get_or_init(|| {
+            let mut r = Reader::at(lazy.data.as_deref().unwrap_or_default(), lazy.bit);
+            plan.decoder(key.as_slice())
+                .map(|d| d.decode(&mut r))
+                .unwrap_or_default()
+        });
+        Some(value.clone())
+    }
+
+    pub(super) fn values_mut(&mut self) -> &mut PathMap<LazyValue> {
+        Arc::make_mut(&mut self.values)
+    }
 }
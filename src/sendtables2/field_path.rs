@@ -7,7 +7,7 @@ use super::reader::Reader;
 
 #[derive(Clone, Debug)]
 pub struct FieldPath {
-    pub path: [usize; 7],
+    pub path: [i32; 7],
     pub last: usize,
     pub done: bool,
 }
@@ -15,7 +15,7 @@ pub struct FieldPath {
 impl FieldPath {
     pub fn new() -> Self {
         FieldPath {
-            path: [-1isize as usize, 0, 0, 0, 0, 0, 0],
+            path: [-1, 0, 0, 0, 0, 0, 0],
             last: 0,
             done: false,
         }
@@ -120,17 +120,17 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
     FieldPathOp {
         weight: 10530,
         op: |r, fp| {
-            fp.path[fp.last] += r.read_bits(3) as usize + 2;
+            fp.path[fp.last] += r.read_bits(3) as i32 + 2;
             fp.last += 1;
-            fp.path[fp.last] = r.read_bits(3) as usize + 1;
+            fp.path[fp.last] = r.read_bits(3) as i32 + 1;
         },
     },
     FieldPathOp {
         weight: 251,
         op: |r, fp| {
-            fp.path[fp.last] += r.read_bits(4) as usize + 2;
+            fp.path[fp.last] += r.read_bits(4) as i32 + 2;
             fp.last += 1;
-            fp.path[fp.last] = r.read_bits(4) as usize + 1;
+            fp.path[fp.last] = r.read_bits(4) as i32 + 1;
         },
     },
     FieldPathOp {
@@ -146,9 +146,9 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
         weight: 0,
         op: |r, fp| {
             fp.last += 1;
-            fp.path[fp.last] = r.read_bits(5) as usize;
+            fp.path[fp.last] = r.read_bits(5) as i32;
             fp.last += 1;
-            fp.path[fp.last] = r.read_bits(5) as usize;
+            fp.path[fp.last] = r.read_bits(5) as i32;
         },
     },
     FieldPathOp {
@@ -166,11 +166,11 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
         weight: 0,
         op: |r, fp| {
             fp.last += 1;
-            fp.path[fp.last] = r.read_bits(5) as usize;
+            fp.path[fp.last] = r.read_bits(5) as i32;
             fp.last += 1;
-            fp.path[fp.last] = r.read_bits(5) as usize;
+            fp.path[fp.last] = r.read_bits(5) as i32;
             fp.last += 1;
-            fp.path[fp.last] = r.read_bits(5) as usize;
+            fp.path[fp.last] = r.read_bits(5) as i32;
         },
     },
     FieldPathOp {
@@ -188,9 +188,9 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
         op: |r, fp| {
             fp.path[fp.last] += 1;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
         },
     },
     FieldPathOp {
@@ -210,17 +210,17 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
         op: |r, fp| {
             fp.path[fp.last] += 1;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
         },
     },
     FieldPathOp {
         weight: 0,
         op: |r, fp| {
-            fp.path[fp.last] += r.read_ubit_var() as usize + 2;
+            fp.path[fp.last] += r.read_ubit_var() as i32 + 2;
             fp.last += 1;
             fp.path[fp.last] += r.read_ubit_var_field_path();
             fp.last += 1;
@@ -230,17 +230,17 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
     FieldPathOp {
         weight: 0,
         op: |r, fp| {
-            fp.path[fp.last] += r.read_ubit_var() as usize + 2;
+            fp.path[fp.last] += r.read_ubit_var() as i32 + 2;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
         },
     },
     FieldPathOp {
         weight: 0,
         op: |r, fp| {
-            fp.path[fp.last] += r.read_ubit_var() as usize + 2;
+            fp.path[fp.last] += r.read_ubit_var() as i32 + 2;
             fp.last += 1;
             fp.path[fp.last] += r.read_ubit_var_field_path();
             fp.last += 1;
@@ -252,13 +252,13 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
     FieldPathOp {
         weight: 0,
         op: |r, fp| {
-            fp.path[fp.last] += r.read_ubit_var() as usize + 2;
+            fp.path[fp.last] += r.read_ubit_var() as i32 + 2;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
             fp.last += 1;
-            fp.path[fp.last] += r.read_bits(5) as usize;
+            fp.path[fp.last] += r.read_bits(5) as i32;
         },
     },
     FieldPathOp {
@@ -277,7 +277,7 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
         op: |r, fp| {
             for i in 0..=fp.last {
                 if r.read_boolean() {
-                    fp.path[i] = (fp.path[i] as i32 + r.read_var_int32() + 1) as usize;
+                    fp.path[i] += r.read_var_int32() + 1;
                 }
             }
             let count = r.read_ubit_var();
@@ -319,37 +319,37 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
         weight: 300,
         op: |r, fp| {
             fp.pop(fp.last);
-            fp.path[0] += r.read_bits(3) as usize + 1;
+            fp.path[0] += r.read_bits(3) as i32 + 1;
         },
     },
     FieldPathOp {
         weight: 634,
         op: |r, fp| {
             fp.pop(fp.last);
-            fp.path[0] += r.read_bits(6) as usize + 1;
+            fp.path[0] += r.read_bits(6) as i32 + 1;
         },
     },
     FieldPathOp {
         weight: 0,
         op: |r, fp| {
-            fp.pop(r.read_ubit_var_field_path());
+            fp.pop(r.read_ubit_var_field_path() as usize);
             fp.path[fp.last] += 1;
         },
     },
     FieldPathOp {
         weight: 0,
         op: |r, fp| {
-            fp.pop(r.read_ubit_var_field_path());
-            fp.path[fp.last] = ((fp.path[fp.last] as i32) + r.read_var_int32()) as usize;
+            fp.pop(r.read_ubit_var_field_path() as usize);
+            fp.path[fp.last] += r.read_var_int32();
         },
     },
     FieldPathOp {
         weight: 1,
         op: |r, fp| {
-            fp.pop(r.read_ubit_var_field_path());
+            fp.pop(r.read_ubit_var_field_path() as usize);
             for i in 0..=fp.last {
                 if r.read_boolean() {
-                    fp.path[i] = ((fp.path[i] as i32) + r.read_var_int32()) as usize;
+                    fp.path[i] += r.read_var_int32();
                 }
             }
         },
@@ -359,7 +359,7 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
         op: |r, fp| {
             for i in 0..=fp.last {
                 if r.read_boolean() {
-                    fp.path[i] = ((fp.path[i] as i32) + r.read_var_int32()) as usize;
+                    fp.path[i] += r.read_var_int32();
                 }
             }
         },
@@ -378,7 +378,7 @@ static FIELD_PATH_TABLE: &[FieldPathOp] = &[
             for i in 0..=fp.last {
                 if r.read_boolean() {
                     let v = r.read_bits(4) as i32 - 7;
-                    fp.path[i] = ((fp.path[i] as i32) + v) as usize;
+                    fp.path[i] += v;
                 }
             }
         },
@@ -398,7 +398,7 @@ pub fn read_field_paths(r: &mut Reader, paths: &mut Vec<FieldPath>) -> usize {
     let mut fp = FieldPath::new();
     let mut node = &*HUFFMAN_TREE;
     let mut i = 0usize;
-    while !fp.done {
+    while !fp.done && !r.overflowed() {
         let next = if r.read_boolean() {
             node.right()
         } else {
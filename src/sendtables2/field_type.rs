@@ -8,6 +8,7 @@ pub struct FieldType {
 
 impl FieldType {
     pub fn new(mut name: &str) -> Self {
+        name = name.trim();
         let mut pointer = false;
         if name.ends_with('*') {
             pointer = true;
@@ -35,7 +36,7 @@ impl FieldType {
             None
         };
         Self {
-            base_type: name.to_string(),
+            base_type: name.trim().to_string(),
             generic_type,
             pointer,
             count,
@@ -1,33 +1,103 @@
 use crate::proto::msg::cs_demo_parser_rs as msg;
 use prost::Message;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
 
 mod class;
+mod decoder;
 mod entity;
 mod field;
 mod field_path;
 mod field_type;
 mod huffman;
+mod plan;
 pub mod proto;
 mod reader;
 mod serializer;
+mod store;
 
 pub use class::Class;
-pub use entity::Entity;
+pub use decoder::{Decoder, QuantizedFloat};
+pub use entity::{Entity, Properties};
 pub use field::Field;
 pub use field_path::FieldPath;
 pub use field_type::FieldType;
+pub use plan::{DecodePlan, PathKey};
 pub use serializer::Serializer;
+pub use store::{EntityHandle, EntityStore};
+
+use entity::LazyValue;
+
+/// Why the rest of a PacketEntities message couldn't be applied. The
+/// updates are packed without lengths, so decoding can't resume after one
+/// that fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PacketEntitiesError {
+    /// An entity was created with a class id no ClassInfo announced.
+    UnknownClass { index: i32, class_id: i32 },
+    /// The entity's class has no serializer to decode it with.
+    MissingPlan { index: i32, class: String },
+    /// An update for an entity that doesn't exist.
+    MissingEntity { index: i32 },
+    /// The update references a field its class doesn't have or runs past
+    /// the end of the message.
+    FieldMismatch { index: i32, class: String },
+}
+
+impl fmt::Display for PacketEntitiesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | PacketEntitiesError::UnknownClass { index, class_id } => {
+                write!(f, "entity {index} has unknown class id {class_id}")
+            },
+            | PacketEntitiesError::MissingPlan { index, class } => {
+                write!(f, "entity {index} of class {class} has no serializer")
+            },
+            | PacketEntitiesError::MissingEntity { index } => {
+                write!(f, "update for unknown entity {index}")
+            },
+            | PacketEntitiesError::FieldMismatch { index, class } => {
+                write!(f, "fields of entity {index} don't match class {class}")
+            },
+        }
+    }
+}
+
+impl std::error::Error for PacketEntitiesError {}
+
+/// The result of [`Parser::parse_packet_entities`].
+#[derive(Debug, Default)]
+pub struct PacketEntities {
+    /// The entities that changed, in the order of the message. Created and
+    /// updated entities are in the store, deleted ones have been removed.
+    pub events: Vec<(EntityHandle, Class, crate::sendtables::EntityOp)>,
+    /// Set if the updates after `events` were dropped.
+    pub error: Option<PacketEntitiesError>,
+}
 
 /// Minimal parser for Source2 send tables.
-#[derive(Default)]
 pub struct Parser {
     class_id_size: u32,
     serializers: HashMap<String, Serializer>,
+    plans: HashMap<String, Arc<DecodePlan>>,
     classes_by_id: HashMap<i32, Class>,
     classes_by_name: HashMap<String, Class>,
-    class_baselines: HashMap<i32, Vec<u8>>,
-    entities: HashMap<i32, Entity>,
+    class_baselines: HashMap<i32, Arc<[u8]>>,
+    /// `class_baselines` decoded on first use.
+    baseline_properties: HashMap<i32, Properties>,
+    field_paths: Vec<FieldPath>,
+    lazy: bool,
+    /// Names of the classes whose entities are decoded, `None` for all.
+    entity_classes: Option<HashSet<String>>,
+    /// Classes of the entities whose updates are skipped, by index.
+    skipped: HashMap<i32, Class>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Parser {
@@ -35,13 +105,37 @@ impl Parser {
         Self {
             class_id_size: 0,
             serializers: HashMap::new(),
+            plans: HashMap::new(),
             classes_by_id: HashMap::new(),
             classes_by_name: HashMap::new(),
             class_baselines: HashMap::new(),
-            entities: HashMap::new(),
+            baseline_properties: HashMap::new(),
+            field_paths: Vec::new(),
+            lazy: true,
+            entity_classes: None,
+            skipped: HashMap::new(),
         }
     }
 
+    /// Whether property values are only decoded when read, which is the
+    /// default. Otherwise every value is decoded as it's received.
+    pub fn set_lazy_properties(&mut self, lazy: bool) {
+        self.lazy = lazy;
+    }
+
+    /// Only decodes entities of the named classes. Updates of other entities
+    /// are skipped without decoding their values, and the entities are
+    /// neither stored nor reported.
+    pub fn set_entity_classes(&mut self, classes: Option<Vec<String>>) {
+        self.entity_classes = classes.map(|c| c.into_iter().collect());
+    }
+
+    fn decodes_class(&self, class: &Class) -> bool {
+        self.entity_classes
+            .as_ref()
+            .is_none_or(|c| c.contains(&*class.name))
+    }
+
     /// Handles CSVCMsg_ServerInfo and extracts the class id size.
     pub fn on_server_info(&mut self, msg: &msg::CsvcMsgServerInfo) {
         if let Some(max) = msg.max_classes {
@@ -109,6 +203,8 @@ impl Parser {
             }
             self.serializers.insert(name, ser);
         }
+        // Serializers may have been replaced.
+        self.plans.clear();
 
         Ok(())
     }
@@ -121,8 +217,11 @@ impl Parser {
         self.serializers.get(name)
     }
 
-    pub fn entity(&self, index: i32) -> Option<&Entity> {
-        self.entities.get(&index)
+    /// Returns all known classes ordered by class id.
+    pub fn classes(&self) -> Vec<&Class> {
+        let mut classes: Vec<&Class> = self.classes_by_id.values().collect();
+        classes.sort_by_key(|c| c.class_id);
+        classes
     }
 
     /// Handles CSVCMsg_ClassInfo and registers classes with their serializers.
@@ -130,11 +229,13 @@ impl Parser {
         for c in &msg.classes {
             let class_id = c.class_id.unwrap_or_default();
             let name = c.class_name.clone().unwrap_or_default();
-            let serializer = self.serializers.get(&name).cloned();
+            let serializer = self.serializers.get(&name).cloned().map(Arc::new);
+            let plan = DecodePlan::compile(&name, &self.serializers, &mut self.plans);
             let class = Class {
                 class_id,
-                name: name.clone(),
+                name: name.as_str().into(),
                 serializer,
+                plan,
             };
             self.classes_by_id.insert(class_id, class.clone());
             self.classes_by_name.insert(name, class);
@@ -143,51 +244,225 @@ impl Parser {
 
     /// Stores baseline data for a given class id.
     pub fn set_instance_baseline(&mut self, class_id: i32, data: Vec<u8>) {
-        self.class_baselines.insert(class_id, data);
+        self.class_baselines.insert(class_id, data.into());
+        self.baseline_properties.remove(&class_id);
+    }
+
+    /// The properties a new entity of `class` starts with.
+    fn baseline(&mut self, class: &Class) -> Properties {
+        if let Some(props) = self.baseline_properties.get(&class.class_id) {
+            return props.clone();
+        }
+        let mut props = Properties::default();
+        if let (Some(data), Some(plan)) = (self.class_baselines.get(&class.class_id), &class.plan) {
+            let mut r = reader::Reader::new(data);
+            let paths = &mut self.field_paths;
+            read_fields(&mut r, data, plan, &mut props, paths, self.lazy);
+        }
+        self.baseline_properties
+            .insert(class.class_id, props.clone());
+        props
     }
 
-    /// Parses a PacketEntities message and returns entity operations.
+    /// Applies a PacketEntities message to `entities` and returns the
+    /// entities that changed.
+    ///
+    /// Decoding stops at the first update that can't be decoded, e.g. one
+    /// referencing a field its class doesn't have, as the remaining data
+    /// can't be located. The reason is returned in
+    /// [`PacketEntities::error`].
     pub fn parse_packet_entities(
         &mut self,
         msg: &msg::CsvcMsgPacketEntities,
-    ) -> Vec<(Entity, crate::sendtables::EntityOp)> {
+        entities: &mut EntityStore,
+    ) -> PacketEntities {
         use crate::sendtables::EntityOp;
-        let mut events = Vec::new();
-        if let Some(data) = msg.entity_data.as_ref() {
-            let mut r = reader::Reader::new(data);
-            let mut index: i32 = -1;
-            let mut updates = msg.updated_entries.unwrap_or(0);
-            while updates > 0 {
-                let next = index + r.read_ubit_var() as i32 + 1;
-                index = next;
-                let cmd = r.read_bits(2);
-                if cmd & 0x01 == 0 {
-                    if cmd & 0x02 != 0 {
-                        let class_id = r.read_bits(self.class_id_size) as i32;
-                        let serial = r.read_bits(17) as i32;
-                        let _ = r.read_var_uint32();
-                        if let Some(class) = self.classes_by_id.get(&class_id) {
-                            let ent = Entity {
-                                index,
-                                serial,
-                                class: class.clone(),
-                            };
-                            self.entities.insert(index, ent.clone());
-                            events.push((ent, EntityOp::CREATED | EntityOp::ENTERED));
+        let mut out = PacketEntities::default();
+        let Some(data) = msg.entity_data.as_ref() else {
+            return out;
+        };
+        let mut r = reader::Reader::new(data);
+        let mut index: i32 = -1;
+        for _ in 0..msg.updated_entries.unwrap_or(0) {
+            index += r.read_ubit_var() as i32 + 1;
+            let cmd = r.read_bits(2);
+            if cmd & 0x01 == 0 {
+                if cmd & 0x02 != 0 {
+                    let class_id = r.read_bits(self.class_id_size) as i32;
+                    let serial = r.read_bits(17) as i32;
+                    let _ = r.read_var_uint32();
+                    let Some(class) = self.classes_by_id.get(&class_id).cloned() else {
+                        out.error = Some(PacketEntitiesError::UnknownClass { index, class_id });
+                        break;
+                    };
+                    // A different entity in the slot is replaced without a
+                    // delete of its own.
+                    if entities.by_index(index).is_some_and(|e| e.serial != serial)
+                        && let Some(old) = entities.remove(index)
+                    {
+                        out.events.push((
+                            old.handle(),
+                            old.class,
+                            EntityOp::DELETED | EntityOp::LEFT,
+                        ));
+                    }
+                    if !self.decodes_class(&class) {
+                        entities.remove(index);
+                        if let Err(e) = self.skip_entity_fields(&mut r, index, &class) {
+                            out.error = Some(e);
+                            break;
                         }
-                    } else if let Some(ent) = self.entities.get(&index).cloned() {
-                        events.push((ent, EntityOp::UPDATED));
+                        self.skipped.insert(index, class);
+                        continue;
+                    }
+                    self.skipped.remove(&index);
+                    let mut ent = Entity {
+                        index,
+                        serial,
+                        properties: self.baseline(&class),
+                        class,
+                    };
+                    if let Err(e) = self.read_entity_fields(&mut r, data, &mut ent) {
+                        out.error = Some(e);
+                        break;
                     }
-                } else if cmd & 0x02 != 0 {
-                    if let Some(ent) = self.entities.remove(&index) {
-                        events.push((ent, EntityOp::DELETED | EntityOp::LEFT));
+                    let class = ent.class.clone();
+                    let handle = entities.insert(ent);
+                    out.events
+                        .push((handle, class, EntityOp::CREATED | EntityOp::ENTERED));
+                } else if let Some(class) = self.skipped.get(&index).cloned() {
+                    if let Err(e) = self.skip_entity_fields(&mut r, index, &class) {
+                        out.error = Some(e);
+                        break;
                     }
+                } else {
+                    let Some(ent) = entities.by_index_mut(index) else {
+                        out.error = Some(PacketEntitiesError::MissingEntity { index });
+                        break;
+                    };
+                    if let Err(e) = self.read_entity_fields(&mut r, data, ent) {
+                        out.error = Some(e);
+                        break;
+                    }
+                    out.events
+                        .push((ent.handle(), ent.class.clone(), EntityOp::UPDATED));
+                }
+            } else if cmd & 0x02 != 0 {
+                if self.skipped.remove(&index).is_some() {
+                    continue;
+                }
+                if let Some(ent) = entities.remove(index) {
+                    out.events
+                        .push((ent.handle(), ent.class, EntityOp::DELETED | EntityOp::LEFT));
                 }
-                updates -= 1;
             }
         }
-        events
+        out
+    }
+
+    fn read_entity_fields(
+        &mut self,
+        r: &mut reader::Reader,
+        data: &[u8],
+        ent: &mut Entity,
+    ) -> Result<(), PacketEntitiesError> {
+        let Some(plan) = &ent.class.plan else {
+            return Err(PacketEntitiesError::MissingPlan {
+                index: ent.index,
+                class: ent.class.name.to_string(),
+            });
+        };
+        let paths = &mut self.field_paths;
+        if read_fields(r, data, plan, &mut ent.properties, paths, self.lazy) {
+            Ok(())
+        } else {
+            Err(PacketEntitiesError::FieldMismatch {
+                index: ent.index,
+                class: ent.class.name.to_string(),
+            })
+        }
+    }
+
+    fn skip_entity_fields(
+        &mut self,
+        r: &mut reader::Reader,
+        index: i32,
+        class: &Class,
+    ) -> Result<(), PacketEntitiesError> {
+        let Some(plan) = &class.plan else {
+            return Err(PacketEntitiesError::MissingPlan {
+                index,
+                class: class.name.to_string(),
+            });
+        };
+        if skip_fields(r, plan, &mut self.field_paths) {
+            Ok(())
+        } else {
+            Err(PacketEntitiesError::FieldMismatch {
+                index,
+                class: class.name.to_string(),
+            })
+        }
+    }
+}
+
+/// Reads the changed field paths of an entity and skips their values.
+/// Returns false if the data doesn't match `plan`.
+fn skip_fields(r: &mut reader::Reader, plan: &DecodePlan, paths: &mut Vec<FieldPath>) -> bool {
+    let n = field_path::read_field_paths(r, paths);
+    for fp in &paths[..n] {
+        let Some(decoder) = plan.decoder(PathKey::from(fp).as_slice()) else {
+            return false;
+        };
+        decoder.skip(r);
+    }
+    !r.overflowed()
+}
+
+/// Reads the changed field paths of an entity followed by their values.
+/// Values are only located unless `lazy` is false. Lazy values keep a copy
+/// of the bytes of this update rather than the whole message. Returns false
+/// if the data doesn't match `plan`.
+fn read_fields(
+    r: &mut reader::Reader,
+    data: &[u8],
+    plan: &DecodePlan,
+    props: &mut Properties,
+    paths: &mut Vec<FieldPath>,
+    lazy: bool,
+) -> bool {
+    let n = field_path::read_field_paths(r, paths);
+    let start = r.position() / 8 * 8;
+    let mut located = Vec::with_capacity(n);
+    for fp in &paths[..n] {
+        let key = PathKey::from(fp);
+        let Some(decoder) = plan.decoder(key.as_slice()) else {
+            return false;
+        };
+        let bit = r.position() - start;
+        if lazy {
+            decoder.skip(r);
+            located.push((key, bit, None));
+        } else {
+            located.push((key, bit, Some(decoder.decode(r))));
+        }
+    }
+    if r.overflowed() {
+        return false;
+    }
+
+    let bytes: Option<Arc<[u8]>> =
+        lazy.then(|| Arc::from(&data[start / 8..r.position().div_ceil(8)]));
+    let values = props.values_mut();
+    values.reserve(n);
+    for (key, bit, value) in located {
+        let value = match value {
+            | Some(value) => LazyValue::decoded(value),
+            | None => LazyValue::new(Arc::clone(bytes.as_ref().expect("set if lazy")), bit),
+        };
+        values.insert(key, value);
     }
+    true
 }
 
 fn read_var_uint32(slice: &mut &[u8]) -> u32 {
@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
+use std::sync::Arc;
+
+use super::decoder::Decoder;
+use super::field::Field;
+use super::field_path::FieldPath;
+use super::serializer::Serializer;
+
+/// Length prefix of variable sized arrays and tables.
+static LENGTH: Decoder = Decoder::Unsigned;
+/// Whether a pointer to a nested serializer is set.
+static POINTER: Decoder = Decoder::Bool;
+
+/// Nested serializers that are always fixed tables rather than vectors.
+const POINTER_TYPES: &[&str] = &[
+    "PhysicsRagdollPose_t",
+    "CBodyComponent",
+    "CEntityIdentity",
+    "CPhysicsComponent",
+    "CRenderComponent",
+    "CPlayerLocalData",
+];
+
+const VECTOR_TYPES: &[&str] = &[
+    "CUtlVector",
+    "CNetworkUtlVectorBase",
+    "C_NetworkUtlVectorBase",
+    "CUtlVectorEmbeddedNetworkVar",
+];
+
+/// Nested serializers deeper than this are assumed to be cyclic.
+const MAX_DEPTH: usize = 32;
+
+/// A field path used as map key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PathKey {
+    path: [i32; 7],
+    len: u8,
+}
+
+impl Hash for PathKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut h = self.len as u64;
+        for &i in self.as_slice() {
+            h = (h.rotate_left(5) ^ i as u32 as u64).wrapping_mul(0x517c_c1b7_2722_0a95);
+        }
+        state.write_u64(h);
+    }
+}
+
+/// Passes the hash computed by [`PathKey`] through. Hashing the paths of
+/// every received value with SipHash is a noticeable part of decoding.
+#[derive(Default)]
+pub struct PathHasher(u64);
+
+impl Hasher for PathHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0.rotate_left(5) ^ b as u64).wrapping_mul(0x517c_c1b7_2722_0a95);
+        }
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.0 = i;
+    }
+}
+
+pub type PathMap<V> = HashMap<PathKey, V, BuildHasherDefault<PathHasher>>;
+
+impl PathKey {
+    pub fn as_slice(&self) -> &[i32] {
+        &self.path[..self.len as usize]
+    }
+
+    pub(super) fn push(mut self, i: i32) -> Option<Self> {
+        *self.path.get_mut(self.len as usize)? = i;
+        self.len += 1;
+        Some(self)
+    }
+}
+
+impl From<&FieldPath> for PathKey {
+    fn from(fp: &FieldPath) -> Self {
+        let mut path = [0; 7];
+        let len = fp.last + 1;
+        path[..len].copy_from_slice(&fp.path[..len]);
+        Self {
+            path,
+            len: len as u8,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Kind {
+    Simple(Decoder),
+    FixedArray(Decoder),
+    VariableArray(Decoder),
+    Table(Arc<DecodePlan>),
+    VariableTable(Arc<DecodePlan>),
+}
+
+#[derive(Debug)]
+struct PlanField {
+    name: String,
+    kind: Kind,
+}
+
+/// A serializer compiled once into the decoder of every field path, so
+/// that entity updates don't need to look at field types and encoders.
+#[derive(Debug)]
+pub struct DecodePlan {
+    name: String,
+    fields: Vec<PlanField>,
+    by_name: HashMap<String, usize>,
+}
+
+impl DecodePlan {
+    /// Compiles the plan of `name` and the serializers nested in it,
+    /// reusing plans in `cache`.
+    pub(super) fn compile(
+        name: &str,
+        serializers: &HashMap<String, Serializer>,
+        cache: &mut HashMap<String, Arc<DecodePlan>>,
+    ) -> Option<Arc<DecodePlan>> {
+        Self::compile_nested(name, serializers, cache, 0)
+    }
+
+    fn compile_nested(
+        name: &str,
+        serializers: &HashMap<String, Serializer>,
+        cache: &mut HashMap<String, Arc<DecodePlan>>,
+        depth: usize,
+    ) -> Option<Arc<DecodePlan>> {
+        if let Some(plan) = cache.get(name) {
+            return Some(Arc::clone(plan));
+        }
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let serializer = serializers.get(name)?;
+        let mut fields = Vec::with_capacity(serializer.fields.len());
+        for f in &serializer.fields {
+            let kind = match &f.serializer_name {
+                | Some(child) => match Self::compile_nested(child, serializers, cache, depth + 1) {
+                    | Some(plan) if is_pointer(f) => Kind::Table(plan),
+                    | Some(plan) => Kind::VariableTable(plan),
+                    | None => Kind::Simple(LENGTH.clone()),
+                },
+                | None => field_kind(f),
+            };
+            fields.push(PlanField {
+                name: f.var_name.clone(),
+                kind,
+            });
+        }
+        let by_name = fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| (f.name.clone(), i))
+            .collect();
+        let plan = Arc::new(DecodePlan {
+            name: name.to_string(),
+            fields,
+            by_name,
+        });
+        cache.insert(name.to_string(), Arc::clone(&plan));
+        Some(plan)
+    }
+
+    /// Name of the serializer the plan was compiled from.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The decoder of the value at `path`.
+    pub fn decoder(&self, path: &[i32]) -> Option<&Decoder> {
+        let (&i, rest) = path.split_first()?;
+        let field = self.fields.get(usize::try_from(i).ok()?)?;
+        match &field.kind {
+            | Kind::Simple(d) | Kind::FixedArray(d) => Some(d),
+            | Kind::VariableArray(d) if !rest.is_empty() => Some(d),
+            | Kind::VariableArray(_) => Some(&LENGTH),
+            | Kind::Table(plan) if !rest.is_empty() => plan.decoder(rest),
+            | Kind::Table(_) => Some(&POINTER),
+            | Kind::VariableTable(plan) if rest.len() >= 2 => plan.decoder(&rest[1..]),
+            | Kind::VariableTable(_) => Some(&LENGTH),
+        }
+    }
+
+    /// Name of the property at `path`, e.g. `m_iHealth`,
+    /// `CBodyComponent.m_cellX` or `m_hMyWeapons.0002`. The length of a
+    /// vector has the vector's name.
+    pub fn property_name(&self, path: &[i32]) -> Option<String> {
+        let (&i, rest) = path.split_first()?;
+        let field = self.fields.get(usize::try_from(i).ok()?)?;
+        let name = field.name.clone();
+        match (&field.kind, rest) {
+            | (_, []) => Some(name),
+            | (Kind::FixedArray(_) | Kind::VariableArray(_), [j]) => Some(format!("{name}.{j:04}")),
+            | (Kind::Table(plan), _) => Some(format!("{name}.{}", plan.property_name(rest)?)),
+            | (Kind::VariableTable(plan), [j, rest @ ..]) if !rest.is_empty() => {
+                Some(format!("{name}.{j:04}.{}", plan.property_name(rest)?))
+            },
+            | _ => None,
+        }
+    }
+
+    /// Inverse of [`property_name`](Self::property_name).
+    pub fn resolve(&self, name: &str) -> Option<PathKey> {
+        let key = PathKey {
+            path: [0; 7],
+            len: 0,
+        };
+        let parts: Vec<&str> = name.split('.').collect();
+        self.resolve_into(key, &parts)
+    }
+
+    fn resolve_into(&self, key: PathKey, parts: &[&str]) -> Option<PathKey> {
+        let (first, rest) = parts.split_first()?;
+        let i = *self.by_name.get(*first)?;
+        let key = key.push(i as i32)?;
+        match (&self.fields[i].kind, rest) {
+            | (_, []) => Some(key),
+            | (Kind::FixedArray(_) | Kind::VariableArray(_), [j]) => key.push(j.parse().ok()?),
+            | (Kind::Table(plan), _) => plan.resolve_into(key, rest),
+            | (Kind::VariableTable(plan), [j, rest @ ..]) => {
+                plan.resolve_into(key.push(j.parse().ok()?)?, rest)
+            },
+            | _ => None,
+        }
+    }
+}
+
+fn is_pointer(f: &Field) -> bool {
+    f.field_type.pointer || POINTER_TYPES.contains(&f.field_type.base_type.as_str())
+}
+
+fn field_kind(f: &Field) -> Kind {
+    let ty = &f.field_type;
+    if ty.count > 0 && ty.base_type != "char" {
+        return Kind::FixedArray(Decoder::for_type(ty, f));
+    }
+    if VECTOR_TYPES.contains(&ty.base_type.as_str()) {
+        let elem = match &ty.generic_type {
+            | Some(generic) => Decoder::for_type(generic, f),
+            | None => LENGTH.clone(),
+        };
+        return Kind::VariableArray(elem);
+    }
+    Kind::Simple(Decoder::for_type(ty, f))
+}
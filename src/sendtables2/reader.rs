@@ -17,6 +17,28 @@ impl<'a> Reader<'a> {
         }
     }
 
+    /// Creates a reader positioned `bit` bits into `buf`.
+    pub fn at(buf: &'a [u8], bit: usize) -> Self {
+        let mut r = Self::new(buf);
+        r.pos = bit / 8;
+        let rem = (bit % 8) as u32;
+        if rem > 0 {
+            r.read_bits(rem);
+        }
+        r
+    }
+
+    /// Number of bits read so far.
+    pub fn position(&self) -> usize {
+        self.pos * 8 - self.bit_count as usize
+    }
+
+    /// Whether more bits were read than `buf` holds. Missing bits read as
+    /// zero.
+    pub fn overflowed(&self) -> bool {
+        self.position() > self.buf.len() * 8
+    }
+
     fn next_byte(&mut self) -> u8 {
         let b = self.buf.get(self.pos).copied().unwrap_or(0);
         self.pos += 1;
@@ -35,6 +57,23 @@ impl<'a> Reader<'a> {
         x
     }
 
+    pub fn read_bits_u64(&mut self, n: u32) -> u64 {
+        if n <= 32 {
+            return self.read_bits(n) as u64;
+        }
+        let low = self.read_bits(32) as u64;
+        low | (self.read_bits(n - 32) as u64) << 32
+    }
+
+    /// Skips `n` bits without decoding them.
+    pub fn skip_bits(&mut self, mut n: usize) {
+        let buffered = (self.bit_count as usize).min(n);
+        self.read_bits(buffered as u32);
+        n -= buffered;
+        self.pos += n / 8;
+        self.read_bits((n % 8) as u32);
+    }
+
     pub fn read_byte(&mut self) -> u8 {
         if self.bit_count == 0 {
             self.next_byte()
@@ -113,6 +152,77 @@ impl<'a> Reader<'a> {
         x
     }
 
+    /// Reads a null terminated string.
+    pub fn read_string(&mut self) -> String {
+        let mut buf = Vec::new();
+        loop {
+            let b = self.read_byte();
+            if b == 0 || self.overflowed() {
+                break;
+            }
+            buf.push(b);
+        }
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    pub fn read_float(&mut self) -> f32 {
+        f32::from_bits(self.read_bits(32))
+    }
+
+    pub fn read_coord(&mut self) -> f32 {
+        let mut int_val = self.read_bits(1);
+        let mut fract_val = self.read_bits(1);
+        if int_val == 0 && fract_val == 0 {
+            return 0.0;
+        }
+        let negative = self.read_boolean();
+        if int_val != 0 {
+            int_val = self.read_bits(14) + 1;
+        }
+        if fract_val != 0 {
+            fract_val = self.read_bits(5);
+        }
+        let value = int_val as f32 + fract_val as f32 * (1.0 / (1 << 5) as f32);
+        if negative { -value } else { value }
+    }
+
+    pub fn read_coord_precise(&mut self) -> f32 {
+        self.read_bits(20) as f32 * 360.0 / (1 << 20) as f32 - 180.0
+    }
+
+    pub fn read_angle(&mut self, n: u32) -> f32 {
+        self.read_bits(n) as f32 * 360.0 / (1u64 << n) as f32
+    }
+
+    pub fn read_normal(&mut self) -> f32 {
+        let negative = self.read_boolean();
+        let len = self.read_bits(11) as f32;
+        let value = len * (1.0 / ((1 << 11) as f32 - 1.0));
+        if negative { -value } else { value }
+    }
+
+    /// Reads a unit vector whose z component is implied by x and y.
+    pub fn read_3bit_normal(&mut self) -> [f32; 3] {
+        let mut ret = [0.0; 3];
+        let has_x = self.read_boolean();
+        let has_y = self.read_boolean();
+        if has_x {
+            ret[0] = self.read_normal();
+        }
+        if has_y {
+            ret[1] = self.read_normal();
+        }
+        let negative_z = self.read_boolean();
+        let sum = ret[0] * ret[0] + ret[1] * ret[1];
+        if sum < 1.0 {
+            ret[2] = (1.0 - sum).sqrt();
+        }
+        if negative_z {
+            ret[2] = -ret[2];
+        }
+        ret
+    }
+
     pub fn read_ubit_var_fp(&mut self) -> u32 {
         if self.read_boolean() {
             return self.read_bits(2);
@@ -129,7 +239,7 @@ impl<'a> Reader<'a> {
         self.read_bits(31)
     }
 
-    pub fn read_ubit_var_field_path(&mut self) -> usize {
-        self.read_ubit_var_fp() as usize
+    pub fn read_ubit_var_field_path(&mut self) -> i32 {
+        self.read_ubit_var_fp() as i32
     }
 }
@@ -0,0 +1,117 @@
+use super::entity::Entity;
+use crate::constants::{
+    ENTITY_HANDLE_INDEX_MASK_SOURCE2, ENTITY_HANDLE_SERIAL_NUMBER_BITS,
+    INVALID_ENTITY_HANDLE_SOURCE2, MAX_EDICT_BITS_SOURCE2,
+};
+
+/// Networked handles only carry the low bits of an entity's serial.
+const SERIAL_MASK: i32 = (1 << ENTITY_HANDLE_SERIAL_NUMBER_BITS) - 1;
+
+/// Refers to the entity in a slot as long as the slot isn't reused. Entity
+/// indices are recycled, the serial number tells the occupants apart.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EntityHandle {
+    pub index: i32,
+    pub serial: i32,
+}
+
+impl EntityHandle {
+    /// Decodes a networked handle such as `m_hOwnerEntity`.
+    pub fn from_raw(raw: u32) -> Option<Self> {
+        if raw == INVALID_ENTITY_HANDLE_SOURCE2 {
+            return None;
+        }
+        Some(Self {
+            index: (raw & ENTITY_HANDLE_INDEX_MASK_SOURCE2) as i32,
+            serial: (raw >> MAX_EDICT_BITS_SOURCE2) as i32,
+        })
+    }
+
+    fn matches(&self, entity: &Entity) -> bool {
+        entity.index == self.index && entity.serial & SERIAL_MASK == self.serial & SERIAL_MASK
+    }
+}
+
+/// The live entities of a demo, in slots indexed by entity index.
+///
+/// The store owns the only full copy of each entity. Game state refers to
+/// entities by [`EntityHandle`], which stops resolving once its slot is
+/// taken by a different entity.
+#[derive(Clone, Debug, Default)]
+pub struct EntityStore {
+    slots: Vec<Option<Entity>>,
+    len: usize,
+}
+
+impl EntityStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores `entity` in its slot, replacing the previous occupant. Take
+    /// that out with [`remove`](Self::remove) first to report its deletion.
+    pub fn insert(&mut self, entity: Entity) -> EntityHandle {
+        let handle = entity.handle();
+        let Ok(index) = usize::try_from(entity.index) else {
+            return handle;
+        };
+        if index >= self.slots.len() {
+            self.slots.resize_with(index + 1, || None);
+        }
+        if self.slots[index].replace(entity).is_none() {
+            self.len += 1;
+        }
+        handle
+    }
+
+    pub fn remove(&mut self, index: i32) -> Option<Entity> {
+        let entity = self.slot_mut(index)?.take()?;
+        self.len -= 1;
+        Some(entity)
+    }
+
+    /// The entity `handle` refers to, unless it has been deleted since.
+    pub fn get(&self, handle: EntityHandle) -> Option<&Entity> {
+        self.by_index(handle.index).filter(|e| handle.matches(e))
+    }
+
+    pub fn get_mut(&mut self, handle: EntityHandle) -> Option<&mut Entity> {
+        self.by_index_mut(handle.index)
+            .filter(|e| handle.matches(e))
+    }
+
+    /// The entity currently in slot `index`, whichever it is.
+    pub fn by_index(&self, index: i32) -> Option<&Entity> {
+        self.slots.get(usize::try_from(index).ok()?)?.as_ref()
+    }
+
+    pub fn by_index_mut(&mut self, index: i32) -> Option<&mut Entity> {
+        self.slot_mut(index)?.as_mut()
+    }
+
+    pub fn contains(&self, handle: EntityHandle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Entities ordered by index.
+    pub fn iter(&self) -> impl Iterator<Item = &Entity> {
+        self.slots.iter().flatten()
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.len = 0;
+    }
+
+    fn slot_mut(&mut self, index: i32) -> Option<&mut Option<Entity>> {
+        self.slots.get_mut(usize::try_from(index).ok()?)
+    }
+}
@@ -0,0 +1,189 @@
+//! Serde support for events, common types and game state snapshots.
+//!
+//! Players referenced from events and common types are serialized as a
+//! [`PlayerRef`] (user id, steam id and name) rather than the full
+//! [`Player`]; use [`GameState::snapshot`](crate::game_state::GameState::snapshot)
+//! for complete player state. [`EventStreamWriter`] writes the event stream of
+//! a parser as newline delimited JSON.
+
+use std::io::{self, Read, Write};
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::common::{Player, PlayerRef};
+use crate::parser::Parser;
+
+/// Serializes an `Option<Player>` as an `Option<PlayerRef>`. Deserialized
+/// players only have their user id, steam id and name set.
+pub mod player_ref {
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(player: &Option<Player>, s: S) -> Result<S::Ok, S::Error> {
+        player.as_ref().map(PlayerRef::from).serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Player>, D::Error> {
+        Ok(Option::<PlayerRef>::deserialize(d)?.map(Player::from))
+    }
+}
+
+/// Serializes the keys of a [`GenericGameEvent`](crate::events::GenericGameEvent)
+/// as a map of plain values, sorted by key name.
+pub mod game_event_keys {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::{BTreeMap, HashMap};
+
+    use crate::proto::msgs2::csvc_msg_game_event::KeyT;
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Bool(bool),
+        Int(i64),
+        UInt(u64),
+        Float(f64),
+        String(String),
+    }
+
+    fn value(key: &KeyT) -> Option<Value> {
+        if let Some(v) = &key.val_string {
+            Some(Value::String(v.clone()))
+        } else if let Some(v) = key.val_float {
+            Some(Value::Float(v as f64))
+        } else if let Some(v) = key.val_long.or(key.val_short).or(key.val_byte) {
+            Some(Value::Int(v as i64))
+        } else if let Some(v) = key.val_bool {
+            Some(Value::Bool(v))
+        } else {
+            key.val_uint64.map(Value::UInt)
+        }
+    }
+
+    fn key(value: Option<Value>) -> KeyT {
+        let mut key = KeyT::default();
+        match value {
+            | Some(Value::Bool(v)) => key.val_bool = Some(v),
+            | Some(Value::Int(v)) => match i32::try_from(v) {
+                | Ok(v) => key.val_long = Some(v),
+                | Err(_) => key.val_uint64 = Some(v as u64),
+            },
+            | Some(Value::UInt(v)) => key.val_uint64 = Some(v),
+            | Some(Value::Float(v)) => key.val_float = Some(v as f32),
+            | Some(Value::String(v)) => key.val_string = Some(v),
+            | None => {},
+        }
+        key
+    }
+
+    pub fn serialize<S: Serializer>(data: &HashMap<String, KeyT>, s: S) -> Result<S::Ok, S::Error> {
+        data.iter()
+            .map(|(k, v)| (k, value(v)))
+            .collect::<BTreeMap<_, _>>()
+            .serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<HashMap<String, KeyT>, D::Error> {
+        Ok(HashMap::<String, Option<Value>>::deserialize(d)?
+            .into_iter()
+            .map(|(k, v)| (k, key(v)))
+            .collect())
+    }
+}
+
+/// Name of an event type as written by [`EventStreamWriter`], e.g. `Kill`.
+pub fn event_name<E>() -> &'static str {
+    let name = std::any::type_name::<E>();
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+#[derive(Serialize)]
+struct Line<'a, E> {
+    tick: i32,
+    #[serde(rename = "type")]
+    kind: &'a str,
+    data: &'a E,
+}
+
+/// Writes events as newline delimited JSON, one
+/// `{"tick":…,"type":"Kill","data":{…}}` object per line.
+pub struct EventStreamWriter<W: Write> {
+    writer: W,
+    error: Option<io::Error>,
+    ticks: RangeInclusive<i32>,
+}
+
+impl<W: Write> EventStreamWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            error: None,
+            ticks: i32::MIN..=i32::MAX,
+        }
+    }
+
+    /// Limits the events received from a parser to those in `ticks`.
+    pub fn set_tick_range(&mut self, ticks: RangeInclusive<i32>) {
+        self.ticks = ticks;
+    }
+
+    pub fn write_event<E: Serialize>(&mut self, tick: i32, event: &E) -> io::Result<()> {
+        let line = Line {
+            tick,
+            kind: event_name::<E>(),
+            data: event,
+        };
+        serde_json::to_writer(&mut self.writer, &line)?;
+        self.writer.write_all(b"\n")
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// Returns the first error that occurred while writing events received
+    /// from a parser, see [`register`](Self::register).
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write + Send + 'static> EventStreamWriter<W> {
+    /// Registers a writer on `parser` that receives every event whose
+    /// [`event_name`] is accepted by `filter`. Writing stops at the first
+    /// error, which can be retrieved with [`take_error`](Self::take_error).
+    pub fn register<R: Read, F>(
+        parser: &mut Parser<R>,
+        writer: W,
+        filter: F,
+    ) -> Arc<Mutex<EventStreamWriter<W>>>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let stream = Arc::new(Mutex::new(Self::new(writer)));
+
+        macro_rules! register {
+            ($($ty:path),* $(,)?) => {$(
+                if filter(event_name::<$ty>()) {
+                    let s = Arc::clone(&stream);
+                    parser.register_sync_event_handler::<$ty, _>(move |ev, gs| {
+                        let mut s = s.lock().unwrap();
+                        if s.error.is_none() && s.ticks.contains(&gs.ingame_tick()) {
+                            if let Err(e) = s.write_event(gs.ingame_tick(), ev) {
+                                s.error = Some(e);
+                            }
+                        }
+                    });
+                }
+            )*};
+        }
+        crate::events::for_each_event!(register);
+        stream
+    }
+}
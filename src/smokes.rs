@@ -0,0 +1,410 @@
+//! Smoke volumes and their lifetime, so analyses can ask whether a point was
+//! inside smoke at a tick or whether a line of sight crossed one.
+//!
+//! CS2 smokes fill a grid of voxels that grows, gets cleared by HE grenades
+//! and shot through. It is read from the smoke projectile's
+//! `m_VoxelFrameData` whenever that changes. The layout of that data hasn't
+//! been confirmed against demos yet, so the voxels stay internal until it
+//! is. Without voxels, e.g. for Source 1 demos, a smoke is a cylinder of
+//! [`SMOKE_RADIUS`] around its detonation.
+
+use std::collections::HashMap;
+
+use crate::common::{Player, PlayerRef, Team};
+use crate::events::{GrenadeEvent, Kill};
+use crate::sendtables::entity::Vector;
+use crate::sendtables2::Entity;
+
+/// Radius in units of a smoke without voxels.
+pub const SMOKE_RADIUS: f64 = 144.0;
+
+/// Height in units of a smoke without voxels, above and below its
+/// detonation.
+pub const SMOKE_HEIGHT: f64 = 128.0;
+
+/// Number of voxels along each axis of a smoke's grid.
+pub(crate) const VOXEL_GRID_SIZE: usize = 20;
+
+/// Edge length of a voxel in units.
+pub(crate) const VOXEL_SIZE: f64 = 16.0;
+
+/// Height of a standing player's eyes above their position.
+pub const EYE_HEIGHT: f64 = 64.0;
+
+/// Occupancy of a cube of voxels.
+///
+/// The layout below is assumed rather than confirmed against demos, grids
+/// of real smokes may come out wrong until it is.
+///
+/// `m_VoxelFrameData` is read as a bitmask with one bit per voxel, the
+/// lowest bit first, x varying fastest and then y and z, over a cube of
+/// [`VOXEL_GRID_SIZE`]³ voxels of [`VOXEL_SIZE`] units centred on the
+/// detonation. Voxels missing from it are empty.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct VoxelGrid {
+    /// Corner of the grid with the lowest coordinates.
+    origin: Vector,
+    voxel_size: f64,
+    dims: [usize; 3],
+    bits: Vec<u8>,
+}
+
+impl VoxelGrid {
+    /// An empty grid of `dims` voxels.
+    fn new(origin: Vector, voxel_size: f64, dims: [usize; 3]) -> Self {
+        let len = (dims[0] * dims[1] * dims[2]).div_ceil(8);
+        Self {
+            origin,
+            voxel_size,
+            dims,
+            bits: vec![0; len],
+        }
+    }
+
+    /// Decodes `m_VoxelFrameData` of a smoke that detonated at `center`.
+    fn from_voxel_data(center: &Vector, data: &[u8]) -> Self {
+        let half = VOXEL_GRID_SIZE as f64 * VOXEL_SIZE / 2.0;
+        let origin = Vector {
+            x: center.x - half,
+            y: center.y - half,
+            z: center.z - half,
+        };
+        let mut grid = Self::new(origin, VOXEL_SIZE, [VOXEL_GRID_SIZE; 3]);
+        let len = grid.bits.len().min(data.len());
+        grid.bits[..len].copy_from_slice(&data[..len]);
+        grid
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> Option<usize> {
+        let [dx, dy, dz] = self.dims;
+        (x < dx && y < dy && z < dz).then(|| x + dx * (y + dy * z))
+    }
+
+    fn is_set(&self, x: usize, y: usize, z: usize) -> bool {
+        self.index(x, y, z)
+            .is_some_and(|i| self.bits[i / 8] & (1 << (i % 8)) != 0)
+    }
+
+    #[cfg(test)]
+    fn set(&mut self, x: usize, y: usize, z: usize, occupied: bool) {
+        let Some(i) = self.index(x, y, z) else {
+            return;
+        };
+        if occupied {
+            self.bits[i / 8] |= 1 << (i % 8);
+        } else {
+            self.bits[i / 8] &= !(1 << (i % 8));
+        }
+    }
+
+    /// Number of occupied voxels.
+    #[cfg(test)]
+    fn occupied(&self) -> usize {
+        self.bits.iter().map(|b| b.count_ones() as usize).sum()
+    }
+
+    /// Whether the voxel containing `point` is occupied.
+    fn contains(&self, point: &Vector) -> bool {
+        let cell = |p: f64, o: f64| {
+            let c = ((p - o) / self.voxel_size).floor();
+            (c >= 0.0).then_some(c as usize)
+        };
+        match (
+            cell(point.x, self.origin.x),
+            cell(point.y, self.origin.y),
+            cell(point.z, self.origin.z),
+        ) {
+            | (Some(x), Some(y), Some(z)) => self.is_set(x, y, z),
+            | _ => false,
+        }
+    }
+
+    /// Whether the segment from `from` to `to` passes an occupied voxel,
+    /// sampled every half voxel.
+    fn crosses(&self, from: &Vector, to: &Vector) -> bool {
+        let length = distance(from, to);
+        let steps = (length / (self.voxel_size / 2.0)).ceil().max(1.0) as usize;
+        (0..=steps).any(|i| self.contains(&lerp(from, to, i as f64 / steps as f64)))
+    }
+}
+
+/// The voxels of a smoke from `tick` until the next frame.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct VoxelFrame {
+    tick: i32,
+    grid: VoxelGrid,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmokeVolume {
+    pub entity_id: i32,
+    pub thrower: Option<PlayerRef>,
+    pub thrower_team: Team,
+    pub position: Vector,
+    /// `m_nSmokeEffectTickBegin` when available, else the tick of
+    /// `smokegrenade_detonate`.
+    pub start_tick: i32,
+    /// `None` while the smoke is up.
+    pub end_tick: Option<i32>,
+    /// Empty if the demo has no voxels.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    frames: Vec<VoxelFrame>,
+}
+
+impl SmokeVolume {
+    pub fn is_active(&self, tick: i32) -> bool {
+        tick >= self.start_tick && self.end_tick.is_none_or(|end| tick < end)
+    }
+
+    /// The voxels at `tick`, `None` before the first frame.
+    fn grid_at(&self, tick: i32) -> Option<&VoxelGrid> {
+        self.frames
+            .iter()
+            .rev()
+            .find(|f| f.tick <= tick)
+            .map(|f| &f.grid)
+    }
+
+    pub fn contains(&self, point: &Vector, tick: i32) -> bool {
+        if !self.is_active(tick) {
+            return false;
+        }
+        match self.grid_at(tick) {
+            | Some(grid) => grid.contains(point),
+            | None => {
+                (point.x - self.position.x).hypot(point.y - self.position.y) <= SMOKE_RADIUS
+                    && (point.z - self.position.z).abs() <= SMOKE_HEIGHT
+            },
+        }
+    }
+
+    /// Whether the segment from `from` to `to` passes through the smoke.
+    pub fn crosses(&self, from: &Vector, to: &Vector, tick: i32) -> bool {
+        if !self.is_active(tick) {
+            return false;
+        }
+        match self.grid_at(tick) {
+            | Some(grid) => grid.crosses(from, to),
+            | None => {
+                let steps = (distance(from, to) / (SMOKE_RADIUS / 4.0)).ceil().max(1.0) as usize;
+                (0..=steps).any(|i| self.contains(&lerp(from, to, i as f64 / steps as f64), tick))
+            },
+        }
+    }
+}
+
+/// All smokes of a demo, in the order they popped. Owned by
+/// [`crate::game_state::GameState`], see its `smokes` accessor.
+#[derive(Debug, Clone, Default)]
+pub struct Smokes {
+    volumes: Vec<SmokeVolume>,
+    /// Smokes that haven't expired by entity id.
+    active: HashMap<i32, usize>,
+}
+
+impl Smokes {
+    pub fn volumes(&self) -> &[SmokeVolume] {
+        &self.volumes
+    }
+
+    /// Smokes up at `tick`.
+    pub fn at(&self, tick: i32) -> impl Iterator<Item = &SmokeVolume> {
+        self.volumes.iter().filter(move |v| v.is_active(tick))
+    }
+
+    /// Whether `point` was inside any smoke at `tick`.
+    pub fn contains(&self, point: &Vector, tick: i32) -> bool {
+        self.at(tick).any(|v| v.contains(point, tick))
+    }
+
+    /// Whether the segment from `from` to `to` crossed any smoke at `tick`.
+    pub fn blocks(&self, from: &Vector, to: &Vector, tick: i32) -> bool {
+        self.at(tick).any(|v| v.crosses(from, to, tick))
+    }
+
+    /// Whether the line from the killer's eyes to the victim's crossed smoke.
+    /// Unlike [`Kill::through_smoke`] this works for every demo with smoke
+    /// positions, but doesn't know the victim's exact hitbox.
+    pub fn kill_through_smoke(&self, kill: &Kill, tick: i32) -> bool {
+        let (Some(killer), Some(victim)) = (&kill.killer, &kill.victim) else {
+            return false;
+        };
+        self.blocks(&eyes(killer), &eyes(victim), tick)
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.volumes.clear();
+        self.active.clear();
+    }
+
+    fn volume(&mut self, entity_id: i32, tick: i32) -> &mut SmokeVolume {
+        let index = *self.active.entry(entity_id).or_insert_with(|| {
+            self.volumes.push(SmokeVolume {
+                entity_id,
+                start_tick: tick,
+                ..Default::default()
+            });
+            self.volumes.len() - 1
+        });
+        &mut self.volumes[index]
+    }
+
+    /// Handles `SmokeStart`.
+    pub(crate) fn detonated(&mut self, tick: i32, ev: &GrenadeEvent) {
+        let v = self.volume(ev.grenade_entity_id, tick);
+        if let Some(thrower) = &ev.thrower {
+            v.thrower = Some(PlayerRef::from(thrower));
+            v.thrower_team = thrower.team;
+        }
+        if v.frames.is_empty() {
+            v.position = ev.position.clone();
+        }
+    }
+
+    /// Reads the state of a CS2 smoke projectile once it has popped.
+    pub(crate) fn entity_updated(&mut self, tick: i32, entity: &Entity, thrower: Option<&Player>) {
+        let popped = entity.property_value("m_bDidSmokeEffect");
+        if !popped.is_some_and(|v| v.bool_val()) {
+            return;
+        }
+        let start_tick = entity
+            .property_value("m_nSmokeEffectTickBegin")
+            .map_or(tick, |v| v.int_val);
+        let position = entity
+            .property_value("m_vSmokeDetonationPos")
+            .map(|v| v.vector_val)
+            .or_else(|| entity.position());
+        // The projectile outlives its smoke. A volume started by `SmokeStart`
+        // may not have the entity's start tick, but it expired after it.
+        let expired = !self.active.contains_key(&entity.index)
+            && self.volumes.iter().any(|v| {
+                v.entity_id == entity.index && v.end_tick.is_some_and(|end| start_tick < end)
+            });
+        if expired {
+            return;
+        }
+        let data = voxel_data(entity);
+
+        let v = self.volume(entity.index, start_tick);
+        v.start_tick = start_tick;
+        if v.thrower.is_none() {
+            v.thrower = thrower.map(PlayerRef::from);
+            v.thrower_team = thrower.map(|p| p.team).unwrap_or_default();
+        }
+        if let Some(position) = position {
+            v.position = position;
+        }
+        if data.is_empty() {
+            return;
+        }
+        let grid = VoxelGrid::from_voxel_data(&v.position, &data);
+        match v.frames.last_mut() {
+            | Some(last) if last.grid == grid => {},
+            | Some(last) if last.tick == tick => last.grid = grid,
+            | _ => v.frames.push(VoxelFrame { tick, grid }),
+        }
+    }
+
+    /// Handles `SmokeExpired` and the removal of the smoke projectile.
+    pub(crate) fn expired(&mut self, tick: i32, entity_id: i32) {
+        if let Some(index) = self.active.remove(&entity_id) {
+            self.volumes[index].end_tick = Some(tick);
+        }
+    }
+}
+
+fn voxel_data(entity: &Entity) -> Vec<u8> {
+    entity
+        .property_array("m_VoxelFrameData")
+        .iter()
+        .map(|v| v.int_val as u8)
+        .collect()
+}
+
+fn eyes(player: &Player) -> Vector {
+    let mut position = player.position();
+    position.z += EYE_HEIGHT;
+    position
+}
+
+fn distance(a: &Vector, b: &Vector) -> f64 {
+    ((b.x - a.x).powi(2) + (b.y - a.y).powi(2) + (b.z - a.z).powi(2)).sqrt()
+}
+
+fn lerp(a: &Vector, b: &Vector, t: f64) -> Vector {
+    Vector {
+        x: a.x + (b.x - a.x) * t,
+        y: a.y + (b.y - a.y) * t,
+        z: a.z + (b.z - a.z) * t,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vector(x: f64, y: f64, z: f64) -> Vector {
+        Vector { x, y, z }
+    }
+
+    #[test]
+    fn decodes_voxel_data() {
+        // Only the voxel at the lowest corner and the ninth one along x.
+        let mut data = vec![0u8; 1000];
+        data[0] = 0b0000_0001;
+        data[1] = 0b0000_0010;
+        let center = vector(0.0, 0.0, 0.0);
+        let grid = VoxelGrid::from_voxel_data(&center, &data);
+        let half = VOXEL_GRID_SIZE as f64 * VOXEL_SIZE / 2.0;
+        assert_eq!(vector(-half, -half, -half), grid.origin);
+        assert_eq!(2, grid.occupied());
+        assert!(grid.is_set(0, 0, 0));
+        assert!(grid.is_set(9, 0, 0));
+        assert!(!grid.is_set(8, 0, 0));
+        assert!(grid.contains(&vector(-half + 1.0, -half + 1.0, -half + 1.0)));
+        assert!(!grid.contains(&vector(0.0, 0.0, 0.0)));
+        assert!(!grid.contains(&vector(-half - 1.0, -half, -half)));
+
+        // Short data leaves the rest empty.
+        let grid = VoxelGrid::from_voxel_data(&center, &[0xff]);
+        assert_eq!(8, grid.occupied());
+    }
+
+    #[test]
+    fn queries_voxel_frames_over_time() {
+        let mut wall = VoxelGrid::new(vector(0.0, 0.0, 0.0), 10.0, [10, 10, 10]);
+        for y in 0..10 {
+            for z in 0..10 {
+                wall.set(5, y, z, true);
+            }
+        }
+        let mut cleared = wall.clone();
+        cleared.set(5, 5, 5, false);
+        let volume = SmokeVolume {
+            start_tick: 100,
+            end_tick: Some(200),
+            frames: vec![
+                VoxelFrame {
+                    tick: 100,
+                    grid: wall,
+                },
+                VoxelFrame {
+                    tick: 150,
+                    grid: cleared,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let from = vector(5.0, 55.0, 55.0);
+        let to = vector(95.0, 55.0, 55.0);
+        assert!(!volume.crosses(&from, &to, 99));
+        assert!(volume.crosses(&from, &to, 120));
+        // An HE cleared a hole in the wall.
+        assert!(!volume.crosses(&from, &to, 150));
+        assert!(volume.crosses(&vector(5.0, 15.0, 55.0), &vector(95.0, 15.0, 55.0), 150));
+        assert!(volume.contains(&vector(55.0, 15.0, 15.0), 199));
+        assert!(!volume.contains(&vector(55.0, 15.0, 15.0), 200));
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 
 use crate::proto::msg::cs_demo_parser_rs as msg;
+use crate::proto::msgs2::c_demo_string_tables;
 use prost::Message;
 
 #[derive(Debug, Default, Clone)]
@@ -22,6 +23,8 @@ pub struct StringTable {
 pub struct StringTables {
     tables: HashMap<i32, StringTable>,
     name_to_id: HashMap<String, i32>,
+    /// Id of the next table, tables are numbered in order of creation.
+    next_id: i32,
 }
 
 impl StringTables {
@@ -37,7 +40,8 @@ impl StringTables {
         &mut self,
         msg: &msg::CsvcMsgCreateStringTable,
     ) -> Option<StringTable> {
-        let id = self.tables.len() as i32;
+        let id = self.next_id;
+        self.next_id += 1;
         let mut table = StringTable {
             name: msg.name.clone().unwrap_or_default(),
             max_entries: msg.max_entries.unwrap_or_default(),
@@ -53,6 +57,34 @@ impl StringTables {
         Some(table)
     }
 
+    /// Gives the next table id to a table that isn't parsed.
+    pub fn skip_create_string_table(&mut self) {
+        self.next_id += 1;
+    }
+
+    /// Replaces the entries of a table with those of a `DEM_FullPacket`.
+    /// Tables that weren't created before are ignored.
+    pub fn apply_snapshot(
+        &mut self,
+        snapshot: &c_demo_string_tables::TableT,
+    ) -> Option<StringTable> {
+        let id = self.name_to_id.get(snapshot.table_name())?;
+        let table = self.tables.get_mut(id)?;
+        table.entries = snapshot
+            .items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let entry = StringTableEntry {
+                    value: item.str().to_string(),
+                    user_data: item.data().to_vec(),
+                };
+                (i as i32, entry)
+            })
+            .collect();
+        Some(table.clone())
+    }
+
     pub fn on_update_string_table(
         &mut self,
         msg: &msg::CsvcMsgUpdateStringTable,
@@ -141,17 +173,19 @@ fn parse_s1_entries(table: &mut StringTable, num: i32, data: &[u8]) {
         if r.read_bit() {
             if table.user_data_fixed_size {
                 let bytes = (table.user_data_size_bits as u32 + 7) / 8;
-                for _ in 0..bytes {
-                    user_data.push(r.read_int(8) as u8);
-                }
+                user_data = r.read_bytes(bytes as usize);
             } else {
                 let len = r.read_int(14) as usize;
-                for _ in 0..len {
-                    user_data.push(r.read_int(8) as u8);
-                }
+                user_data = r.read_bytes(len);
             }
         }
-        table.entries.insert(idx, StringTableEntry { value: key, user_data });
+        table.entries.insert(
+            idx,
+            StringTableEntry {
+                value: key,
+                user_data,
+            },
+        );
     }
 }
 
@@ -173,14 +207,7 @@ impl StringTables {
     pub fn parse_packet(&mut self, data: &[u8]) -> Vec<StringTable> {
         let mut updates = Vec::new();
         let mut slice = data;
-        while !slice.is_empty() {
-            let msg_id = read_var_uint32(&mut slice);
-            let size = read_var_uint32(&mut slice) as usize;
-            if slice.len() < size {
-                break;
-            }
-            let (msg_buf, rest) = slice.split_at(size);
-            slice = rest;
+        while let Some((msg_id, msg_buf)) = next_message(&mut slice) {
             if let Ok(t) = msg::SvcMessages::try_from(msg_id as i32) {
                 if let Some(tbl) = self.parse_svc_message(t, msg_buf) {
                     updates.push(tbl);
@@ -191,6 +218,21 @@ impl StringTables {
     }
 }
 
+/// Splits the next message, its type and content, off a packet.
+pub(crate) fn next_message<'a>(slice: &mut &'a [u8]) -> Option<(u32, &'a [u8])> {
+    if slice.is_empty() {
+        return None;
+    }
+    let msg_id = read_var_uint32(slice);
+    let size = read_var_uint32(slice) as usize;
+    if slice.len() < size {
+        return None;
+    }
+    let (msg_buf, rest) = slice.split_at(size);
+    *slice = rest;
+    Some((msg_id, msg_buf))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
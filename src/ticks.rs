@@ -0,0 +1,224 @@
+use bitflags::bitflags;
+
+use crate::common::{EquipmentType, Player, Team};
+use crate::game_state::GameState;
+
+bitflags! {
+    /// Player properties recorded by a [`TickSampler`]. Tick, user id,
+    /// steam id, name and team are always recorded.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct TickProperties: u32 {
+        const POSITION = 1 << 0;
+        const VELOCITY = 1 << 1;
+        const EYE_ANGLES = 1 << 2;
+        const HEALTH = 1 << 3;
+        const ARMOR = 1 << 4;
+        const HAS_HELMET = 1 << 5;
+        const HAS_DEFUSER = 1 << 6;
+        const ACTIVE_WEAPON = 1 << 7;
+        const MONEY = 1 << 8;
+        const FLASH_DURATION = 1 << 9;
+        const IS_ALIVE = 1 << 10;
+        const IS_SCOPED = 1 << 11;
+        const IS_DUCKING = 1 << 12;
+        const IS_AIRBORNE = 1 << 13;
+        const PLACE_NAME = 1 << 14;
+    }
+}
+
+impl Default for TickProperties {
+    fn default() -> Self {
+        Self::all()
+    }
+}
+
+/// Configuration for a [`TickSampler`].
+#[derive(Debug, Clone)]
+pub struct TickSamplerConfig {
+    /// Number of ingame ticks between two samples. `1` samples every tick.
+    pub interval: i32,
+    pub properties: TickProperties,
+    /// Also sample spectators and unassigned players.
+    pub include_spectators: bool,
+}
+
+impl Default for TickSamplerConfig {
+    fn default() -> Self {
+        Self {
+            interval: 1,
+            properties: TickProperties::default(),
+            include_spectators: false,
+        }
+    }
+}
+
+/// Columnar per-tick player state. Every column holds one entry per row;
+/// columns of properties that weren't selected stay empty.
+#[derive(Debug, Clone, Default)]
+pub struct TickBuffer {
+    pub tick: Vec<i32>,
+    pub user_id: Vec<i32>,
+    pub steam_id64: Vec<u64>,
+    pub name: Vec<String>,
+    pub team: Vec<Team>,
+    pub x: Vec<f64>,
+    pub y: Vec<f64>,
+    pub z: Vec<f64>,
+    pub velocity_x: Vec<f64>,
+    pub velocity_y: Vec<f64>,
+    pub velocity_z: Vec<f64>,
+    pub pitch: Vec<f32>,
+    pub yaw: Vec<f32>,
+    pub health: Vec<i32>,
+    pub armor: Vec<i32>,
+    pub has_helmet: Vec<bool>,
+    pub has_defuser: Vec<bool>,
+    pub active_weapon: Vec<EquipmentType>,
+    pub money: Vec<i32>,
+    pub flash_duration: Vec<f32>,
+    pub is_alive: Vec<bool>,
+    pub is_scoped: Vec<bool>,
+    pub is_ducking: Vec<bool>,
+    pub is_airborne: Vec<bool>,
+    pub place_name: Vec<String>,
+}
+
+impl TickBuffer {
+    pub fn len(&self) -> usize {
+        self.tick.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tick.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    fn push(&mut self, tick: i32, p: &Player, props: TickProperties) {
+        self.tick.push(tick);
+        self.user_id.push(p.user_id);
+        self.steam_id64.push(p.steam_id64);
+        self.name.push(p.name.clone());
+        self.team.push(p.team);
+        if props.contains(TickProperties::POSITION) {
+            let pos = p.position();
+            self.x.push(pos.x);
+            self.y.push(pos.y);
+            self.z.push(pos.z);
+        }
+        if props.contains(TickProperties::VELOCITY) {
+            let vel = p.velocity();
+            self.velocity_x.push(vel.x);
+            self.velocity_y.push(vel.y);
+            self.velocity_z.push(vel.z);
+        }
+        if props.contains(TickProperties::EYE_ANGLES) {
+            self.pitch.push(p.view_direction_y());
+            self.yaw.push(p.view_direction_x());
+        }
+        if props.contains(TickProperties::HEALTH) {
+            self.health.push(p.health());
+        }
+        if props.contains(TickProperties::ARMOR) {
+            self.armor.push(p.armor());
+        }
+        if props.contains(TickProperties::HAS_HELMET) {
+            self.has_helmet.push(p.has_helmet());
+        }
+        if props.contains(TickProperties::HAS_DEFUSER) {
+            self.has_defuser.push(p.has_defuse_kit());
+        }
+        if props.contains(TickProperties::ACTIVE_WEAPON) {
+            self.active_weapon.push(
+                p.active_weapon()
+                    .map(|w| w.equipment_type)
+                    .unwrap_or_default(),
+            );
+        }
+        if props.contains(TickProperties::MONEY) {
+            self.money.push(p.money());
+        }
+        if props.contains(TickProperties::FLASH_DURATION) {
+            self.flash_duration.push(p.flash_duration);
+        }
+        if props.contains(TickProperties::IS_ALIVE) {
+            self.is_alive.push(p.is_alive());
+        }
+        if props.contains(TickProperties::IS_SCOPED) {
+            self.is_scoped.push(p.is_scoped());
+        }
+        if props.contains(TickProperties::IS_DUCKING) {
+            self.is_ducking.push(p.is_ducking());
+        }
+        if props.contains(TickProperties::IS_AIRBORNE) {
+            self.is_airborne.push(p.is_airborne());
+        }
+        if props.contains(TickProperties::PLACE_NAME) {
+            self.place_name.push(p.place_name().unwrap_or_default());
+        }
+    }
+}
+
+/// Samples the state of all players into a [`TickBuffer`].
+///
+/// Register it with [`Parser::register_tick_sampler`](crate::parser::Parser::register_tick_sampler)
+/// to sample on every [`FrameDone`](crate::events::FrameDone).
+#[derive(Debug, Clone, Default)]
+pub struct TickSampler {
+    config: TickSamplerConfig,
+    buffer: TickBuffer,
+    last_sampled_tick: Option<i32>,
+}
+
+impl TickSampler {
+    pub fn new(config: TickSamplerConfig) -> Self {
+        Self {
+            config,
+            buffer: TickBuffer::default(),
+            last_sampled_tick: None,
+        }
+    }
+
+    pub fn config(&self) -> &TickSamplerConfig {
+        &self.config
+    }
+
+    pub fn buffer(&self) -> &TickBuffer {
+        &self.buffer
+    }
+
+    /// Returns the samples collected so far and starts a new buffer.
+    pub fn take_buffer(&mut self) -> TickBuffer {
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Records one row per player if at least `interval` ticks passed since
+    /// the last sample.
+    pub fn sample(&mut self, state: &GameState) {
+        let tick = state.ingame_tick();
+        let interval = self.config.interval.max(1);
+        if self
+            .last_sampled_tick
+            .is_some_and(|last| tick - last < interval)
+        {
+            return;
+        }
+        self.last_sampled_tick = Some(tick);
+
+        let participants = state.participants();
+        let mut players: Vec<&Player> = participants
+            .connected()
+            .into_iter()
+            .filter(|p| {
+                self.config.include_spectators
+                    || matches!(p.team, Team::Terrorists | Team::CounterTerrorists)
+            })
+            .collect();
+        players.sort_by_key(|p| p.user_id);
+        for p in players {
+            self.buffer.push(tick, p, self.config.properties);
+        }
+    }
+}
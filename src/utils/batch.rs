@@ -0,0 +1,279 @@
+//! Running an analysis over many demos.
+//!
+//! [`run_batch`] parses every demo on a bounded pool of threads and
+//! reports the outcome of each one separately. A demo that can't be read,
+//! fails to parse or makes the analysis panic doesn't affect the others.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use rayon::prelude::*;
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::parser::{Parser, ParserConfig, ParserError};
+
+/// Why a demo of a batch has no result.
+#[derive(Debug)]
+pub enum BatchError {
+    /// The demo couldn't be opened or hashed.
+    Io(io::Error),
+    Parser(ParserError),
+    /// The analysis panicked, with the panic message.
+    Panic(String),
+    /// The demo's content hash is in [`BatchOptions::skip`].
+    AlreadyProcessed(ContentHash),
+    /// The batch was cancelled before the demo was started.
+    Cancelled,
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | BatchError::Io(e) => write!(f, "io error: {e}"),
+            | BatchError::Parser(e) => write!(f, "parser error: {e:?}"),
+            | BatchError::Panic(msg) => write!(f, "panicked: {msg}"),
+            | BatchError::AlreadyProcessed(hash) => write!(f, "already processed ({hash})"),
+            | BatchError::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+impl From<io::Error> for BatchError {
+    fn from(e: io::Error) -> Self {
+        BatchError::Io(e)
+    }
+}
+
+impl From<ParserError> for BatchError {
+    fn from(e: ParserError) -> Self {
+        BatchError::Parser(e)
+    }
+}
+
+/// XXH3 hash of a demo file's content, printed as 32 hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ContentHash(pub u128);
+
+impl ContentHash {
+    pub fn of_file(path: &Path) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let mut hasher = Xxh3::new();
+        let mut buf = vec![0; 1 << 20];
+        loop {
+            match file.read(&mut buf)? {
+                | 0 => break,
+                | n => hasher.update(&buf[..n]),
+            }
+        }
+        Ok(Self(hasher.digest128()))
+    }
+}
+
+impl fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+impl FromStr for ContentHash {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u128::from_str_radix(s, 16).map(Self)
+    }
+}
+
+/// Content hashes of the demos that have been analyzed, shared between
+/// batches. Persist [`hashes`](Self::hashes) to skip demos across runs.
+#[derive(Debug, Clone, Default)]
+pub struct Processed {
+    hashes: Arc<Mutex<HashSet<ContentHash>>>,
+}
+
+impl Processed {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn contains(&self, hash: ContentHash) -> bool {
+        self.hashes.lock().unwrap().contains(&hash)
+    }
+
+    pub fn insert(&self, hash: ContentHash) {
+        self.hashes.lock().unwrap().insert(hash);
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All hashes, sorted.
+    pub fn hashes(&self) -> Vec<ContentHash> {
+        let mut hashes: Vec<ContentHash> = self.hashes.lock().unwrap().iter().copied().collect();
+        hashes.sort();
+        hashes
+    }
+}
+
+impl FromIterator<ContentHash> for Processed {
+    fn from_iter<I: IntoIterator<Item = ContentHash>>(iter: I) -> Self {
+        Self {
+            hashes: Arc::new(Mutex::new(iter.into_iter().collect())),
+        }
+    }
+}
+
+/// Stops a running batch. Demos that are being parsed already finish,
+/// the remaining ones fail with [`BatchError::Cancelled`].
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Reported after each demo of a batch, see [`BatchOptions::progress`].
+#[derive(Debug)]
+pub struct Progress<'a> {
+    pub path: &'a Path,
+    /// Why the demo failed, `None` if it was analyzed.
+    pub error: Option<&'a BatchError>,
+    /// Number of demos done so far, including this one.
+    pub completed: usize,
+    pub total: usize,
+}
+
+pub type ProgressCallback = Arc<dyn Fn(&Progress<'_>) + Send + Sync>;
+
+/// Options of [`run_batch`].
+#[derive(Clone, Default)]
+pub struct BatchOptions {
+    /// Number of demos parsed at the same time. `None` uses one thread per
+    /// CPU.
+    pub threads: Option<usize>,
+
+    /// Configuration of every demo's parser.
+    pub config: ParserConfig,
+
+    /// Skip demos whose content hash is in the set, and add the hashes of
+    /// demos analyzed successfully. Hashing reads each demo once more.
+    pub skip: Option<Processed>,
+
+    pub cancel: Option<CancelToken>,
+
+    /// Called from the worker threads after each demo.
+    pub progress: Option<ProgressCallback>,
+}
+
+/// Runs `analyze` for every demo in `paths` and returns the results in the
+/// order of `paths`.
+///
+/// `analyze` receives a parser for the demo and does the parsing itself,
+/// e.g. by registering handlers and calling [`Parser::parse_to_end`].
+/// Errors it returns and panics are reported for the demo at hand.
+pub fn run_batch<I, P, T, F>(
+    paths: I,
+    options: &BatchOptions,
+    analyze: F,
+) -> Vec<(PathBuf, Result<T, BatchError>)>
+where
+    I: IntoIterator<Item = P>,
+    P: Into<PathBuf>,
+    T: Send,
+    F: Fn(&mut Parser<File>, &Path) -> Result<T, ParserError> + Send + Sync,
+{
+    let paths: Vec<PathBuf> = paths.into_iter().map(Into::into).collect();
+    let total = paths.len();
+    let completed = AtomicUsize::new(0);
+
+    let run = || {
+        paths
+            .into_par_iter()
+            .map(|path| {
+                let res = run_one(&path, options, &analyze);
+                if let Some(progress) = &options.progress {
+                    progress(&Progress {
+                        path: &path,
+                        error: res.as_ref().err(),
+                        completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                        total,
+                    });
+                }
+                (path, res)
+            })
+            .collect()
+    };
+    let pool = options
+        .threads
+        .and_then(|n| rayon::ThreadPoolBuilder::new().num_threads(n).build().ok());
+    match pool {
+        | Some(pool) => pool.install(run),
+        | None => run(),
+    }
+}
+
+fn run_one<T, F>(path: &Path, options: &BatchOptions, analyze: &F) -> Result<T, BatchError>
+where
+    F: Fn(&mut Parser<File>, &Path) -> Result<T, ParserError>,
+{
+    if options
+        .cancel
+        .as_ref()
+        .is_some_and(CancelToken::is_cancelled)
+    {
+        return Err(BatchError::Cancelled);
+    }
+    let hash = match &options.skip {
+        | Some(skip) => {
+            let hash = ContentHash::of_file(path)?;
+            if skip.contains(hash) {
+                return Err(BatchError::AlreadyProcessed(hash));
+            }
+            Some(hash)
+        },
+        | None => None,
+    };
+
+    let file = File::open(path)?;
+    let mut parser = Parser::with_config(file, options.config.clone());
+    let res = catch_unwind(AssertUnwindSafe(|| analyze(&mut parser, path)))
+        .map_err(|payload| BatchError::Panic(panic_message(payload.as_ref())))?;
+    let value = res?;
+    if let (Some(skip), Some(hash)) = (&options.skip, hash) {
+        skip.insert(hash);
+    }
+    Ok(value)
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+/// Error of loading or saving the data files used alongside demos: map
+/// overviews, callouts and radar images.
+#[derive(Debug)]
+pub enum DataError {
+    Io(io::Error),
+    /// The data is malformed, or an image couldn't be encoded.
+    Parse(String),
+    /// A required key is missing.
+    Missing(&'static str),
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | DataError::Io(e) => write!(f, "io error: {e}"),
+            | DataError::Parse(msg) => write!(f, "invalid data: {msg}"),
+            | DataError::Missing(key) => write!(f, "missing {key}"),
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+impl From<io::Error> for DataError {
+    fn from(e: io::Error) -> Self {
+        DataError::Io(e)
+    }
+}
+
+/// Calls `load` for every file in `dir` with the given extension and
+/// returns how many were loaded.
+pub(crate) fn load_dir(
+    dir: &Path,
+    extension: &str,
+    mut load: impl FnMut(&Path) -> Result<(), DataError>,
+) -> Result<usize, DataError> {
+    let mut loaded = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|e| e == extension) {
+            load(&path)?;
+            loaded += 1;
+        }
+    }
+    Ok(loaded)
+}
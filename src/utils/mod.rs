@@ -1,6 +1,11 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+mod data;
 pub mod net_encryption;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod parallel;
 mod steamid;
 
+pub use data::DataError;
+pub(crate) use data::load_dir;
 pub use steamid::*;
@@ -1,16 +1,23 @@
 #![cfg(not(target_arch = "wasm32"))]
 
 use rayon::prelude::*;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::File;
+use std::hash::Hash;
 use std::path::{Path, PathBuf};
 
-use crate::parser::Parser;
+use crate::parser::segments::{DemoIndex, Segment, SegmentReader};
+use crate::parser::{Parser, ParserConfig, ParserError};
 
 /// Runs the given function for every demo path in parallel.
 ///
 /// The closure receives a mutable [`Parser`] for the demo file and the path
 /// to the file. Any parsing work (such as calling [`Parser::parse_to_end`])
 /// is left to the closure.
+///
+/// Panics if a demo can't be opened. [`run_batch`](super::batch::run_batch)
+/// reports such errors per demo instead.
+#[deprecated(note = "use utils::batch::run_batch, which reports errors per demo")]
 pub fn run<I, P, F>(paths: I, func: F)
 where
     I: IntoParallelIterator<Item = P>,
@@ -24,3 +31,96 @@ where
         func(&mut parser, &path);
     });
 }
+
+/// Results of a demo segment that can be combined with those of the
+/// segment following it. See [`run_segments`].
+pub trait Merge {
+    /// Appends `later`, the results of the next segment, to `self`.
+    fn merge(&mut self, later: Self);
+}
+
+impl<T> Merge for Vec<T> {
+    fn merge(&mut self, later: Self) {
+        self.extend(later);
+    }
+}
+
+impl<K: Eq + Hash, V: Merge> Merge for HashMap<K, V> {
+    fn merge(&mut self, later: Self) {
+        for (k, v) in later {
+            match self.get_mut(&k) {
+                | Some(cur) => cur.merge(v),
+                | None => {
+                    self.insert(k, v);
+                },
+            }
+        }
+    }
+}
+
+impl<K: Ord, V: Merge> Merge for BTreeMap<K, V> {
+    fn merge(&mut self, later: Self) {
+        for (k, v) in later {
+            match self.get_mut(&k) {
+                | Some(cur) => cur.merge(v),
+                | None => {
+                    self.insert(k, v);
+                },
+            }
+        }
+    }
+}
+
+impl<A: Merge, B: Merge> Merge for (A, B) {
+    fn merge(&mut self, later: Self) {
+        self.0.merge(later.0);
+        self.1.merge(later.1);
+    }
+}
+
+macro_rules! merge_by_adding {
+    ($($t:ty),*) => {
+        $(impl Merge for $t {
+            fn merge(&mut self, later: Self) {
+                *self += later;
+            }
+        })*
+    };
+}
+
+merge_by_adding!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64);
+
+/// Parses a single demo in parallel, split at its full packets into up to
+/// one segment per thread of the rayon pool.
+///
+/// The closure receives a parser positioned at the segment's start, see
+/// [`DemoIndex::parser`], and parses it like [`run`]'s closure does. Its
+/// results are merged in segment order, so the outcome doesn't depend on
+/// which segment finishes first. Events collected with their ticks come out
+/// sorted the same way a sequential parse would deliver them. Collect them
+/// with sync handlers, see [`Parser::register_sync_event_handler`], which
+/// have all run by the time parsing returns.
+///
+/// The first segment that fails to parse fails the whole demo.
+pub fn run_segments<T, F>(data: &[u8], config: &ParserConfig, func: F) -> Result<T, ParserError>
+where
+    T: Merge + Send,
+    F: Fn(&mut Parser<SegmentReader<'_>>, &Segment) -> Result<T, ParserError> + Send + Sync,
+{
+    let index = DemoIndex::new(data)?;
+    let segments = index.segments(rayon::current_num_threads());
+    let results: Vec<Result<T, ParserError>> = segments
+        .par_iter()
+        .map(|segment| {
+            let mut parser = index.parser(data, segment, config.clone())?;
+            func(&mut parser, segment)
+        })
+        .collect();
+
+    let mut results = results.into_iter();
+    let mut merged = results.next().expect("a demo has at least one segment")?;
+    for res in results {
+        merged.merge(res?);
+    }
+    Ok(merged)
+}
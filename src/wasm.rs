@@ -0,0 +1,218 @@
+//! WebAssembly bindings, built with the `wasm` feature.
+//!
+//! ```js
+//! import init, { WasmParser } from "./pkg/cs_demo_parser.js";
+//!
+//! await init();
+//! const response = await fetch("match.dem");
+//! const parser = new WasmParser(Number(response.headers.get("Content-Length")));
+//! parser.on("Kill", (event, tick) => console.log(tick, event.is_headshot));
+//! for await (const chunk of response.body) {
+//!     parser.push(chunk);
+//!     progressBar.value = parser.progress();
+//! }
+//! parser.finish();
+//! console.table(parser.scoreboard());
+//! ```
+//!
+//! Frames are parsed as soon as they have been received completely, see
+//! [`DemoStream`]. Events and summary rows are passed to JavaScript as plain
+//! objects with the same layout as their serde serialization.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use js_sys::Function;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::export::{DatasetCollector, DatasetConfig};
+use crate::parser::ParserError;
+use crate::parser::stream::{DemoStream, FrameStatus};
+use crate::serialization::event_name;
+
+fn demo_error(e: ParserError) -> JsValue {
+    let msg = match e {
+        | ParserError::UnexpectedEndOfDemo => "demo is truncated or corrupt",
+        | ParserError::InvalidFileType => "not a demo file",
+        | ParserError::GitLfsPointer => "file is a Git LFS pointer",
+    };
+    JsError::new(msg).into()
+}
+
+/// Converts `value` through its serde representation. Maps become plain
+/// objects and 64-bit integers numbers.
+fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(Into::into)
+}
+
+/// An event waiting to be passed to the JavaScript callbacks.
+struct Pending {
+    event: &'static str,
+    tick: i32,
+    payload: serde_json::Value,
+}
+
+/// A demo parsed from chunks pushed by JavaScript.
+#[wasm_bindgen]
+pub struct WasmParser {
+    stream: DemoStream,
+    collector: Arc<Mutex<DatasetCollector>>,
+    /// Events recorded by the parser's handlers. JavaScript functions can't
+    /// be moved into the handlers, so they are called after each frame.
+    pending: Arc<Mutex<Vec<Pending>>>,
+    callbacks: HashMap<&'static str, Vec<Function>>,
+}
+
+impl WasmParser {
+    fn parse_available(&mut self) -> Result<(), JsValue> {
+        loop {
+            let status = self.stream.parse_next_frame().map_err(demo_error);
+            self.flush()?;
+            match status? {
+                | FrameStatus::Parsed => {},
+                | FrameStatus::Incomplete | FrameStatus::Finished => return Ok(()),
+            }
+        }
+    }
+
+    /// Passes the recorded events to their callbacks.
+    fn flush(&mut self) -> Result<(), JsValue> {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        for p in pending {
+            let payload = to_js(&p.payload)?;
+            for callback in &self.callbacks[p.event] {
+                callback.call2(&JsValue::NULL, &payload, &JsValue::from(p.tick))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl WasmParser {
+    /// Creates a parser. `expected_size` is the size of the demo in bytes,
+    /// if known, and makes [`progress`](Self::progress) more accurate.
+    #[wasm_bindgen(constructor)]
+    pub fn new(expected_size: Option<f64>) -> WasmParser {
+        console_error_panic_hook::set_once();
+        let mut stream = DemoStream::new();
+        if let Some(size) = expected_size {
+            stream.set_expected_size(size as u64);
+        }
+        let config = DatasetConfig {
+            tick_interval: None,
+            ..Default::default()
+        };
+        let collector = DatasetCollector::register(stream.parser_mut(), "", config);
+        Self {
+            stream,
+            collector,
+            pending: Arc::default(),
+            callbacks: HashMap::new(),
+        }
+    }
+
+    /// Calls `callback(event, tick)` for every event of type `event`, e.g.
+    /// `"Kill"` or `"RoundEnd"`, parsed from now on. Throws for unknown
+    /// event types.
+    pub fn on(&mut self, event: &str, callback: Function) -> Result<(), JsValue> {
+        let mut name = None;
+
+        macro_rules! register {
+            ($($ty:path),* $(,)?) => {$(
+                if event_name::<$ty>() == event {
+                    name = Some(event_name::<$ty>());
+                    if !self.callbacks.contains_key(event) {
+                        let pending = Arc::clone(&self.pending);
+                        self.stream
+                            .parser_mut()
+                            .register_sync_event_handler::<$ty, _>(move |ev, gs| {
+                                if let Ok(payload) = serde_json::to_value(ev) {
+                                    pending.lock().unwrap().push(Pending {
+                                        event: event_name::<$ty>(),
+                                        tick: gs.ingame_tick(),
+                                        payload,
+                                    });
+                                }
+                            });
+                    }
+                }
+            )*};
+        }
+        crate::events::for_each_event!(register);
+
+        let Some(name) = name else {
+            return Err(JsError::new(&format!("unknown event type `{event}`")).into());
+        };
+        self.callbacks.entry(name).or_default().push(callback);
+        Ok(())
+    }
+
+    /// Appends the next chunk of the demo and parses every frame received
+    /// completely. Throws if the demo is invalid or a callback throws;
+    /// events of the current frame that weren't passed on yet are dropped.
+    pub fn push(&mut self, chunk: &[u8]) -> Result<(), JsValue> {
+        self.stream.push(chunk);
+        self.parse_available()
+    }
+
+    /// Parses the rest of the demo once all chunks have been pushed. Throws
+    /// if the demo is truncated.
+    pub fn finish(&mut self) -> Result<(), JsValue> {
+        self.stream.finish();
+        self.parse_available()
+    }
+
+    /// Fraction of the demo parsed so far, between 0 and 1.
+    pub fn progress(&self) -> f32 {
+        self.stream.progress()
+    }
+
+    #[wasm_bindgen(js_name = isFinished)]
+    pub fn is_finished(&self) -> bool {
+        self.stream.is_finished()
+    }
+
+    /// The demo header, or `undefined` until it has been received.
+    pub fn header(&self) -> Result<JsValue, JsValue> {
+        let Some(h) = self.stream.parser().header() else {
+            return Ok(JsValue::UNDEFINED);
+        };
+        to_js(&serde_json::json!({
+            "filestamp": h.filestamp,
+            "protocol": h.protocol,
+            "network_protocol": h.network_protocol,
+            "server_name": h.server_name,
+            "client_name": h.client_name,
+            "map_name": h.map_name,
+            "game_directory": h.game_directory,
+            "playback_time": h.playback_time,
+            "playback_ticks": h.playback_ticks,
+            "playback_frames": h.playback_frames,
+            "signon_length": h.signon_length,
+        }))
+    }
+
+    /// Kills parsed so far.
+    pub fn kills(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.collector.lock().unwrap().dataset().kills)
+    }
+
+    /// Rounds parsed so far.
+    pub fn rounds(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.collector.lock().unwrap().dataset().rounds)
+    }
+
+    /// Per-player statistics of the rounds parsed so far.
+    pub fn scoreboard(&self) -> Result<JsValue, JsValue> {
+        let collector = self.collector.lock().unwrap();
+        to_js(
+            &collector
+                .dataset()
+                .scoreboard(self.stream.parser().game_state()),
+        )
+    }
+}
@@ -0,0 +1,121 @@
+use cs_demo_parser::analyzer::{self, Analyzer, RoundCount};
+use cs_demo_parser::events::{FrameDone, RoundEnd, RoundEndReason};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::parser::{DemoHeader, Parser};
+use std::any::Any;
+
+/// Records which hooks were called.
+#[derive(Default)]
+struct Log {
+    map: String,
+    events: usize,
+    frames: Vec<i32>,
+}
+
+impl Analyzer for Log {
+    type Output = (String, usize, Vec<i32>);
+
+    fn on_header(&mut self, header: &DemoHeader) {
+        self.map = header.map_name.clone();
+    }
+
+    fn on_event(&mut self, _: &dyn Any, _: &GameState) {
+        self.events += 1;
+    }
+
+    fn on_frame(&mut self, state: &GameState) {
+        self.frames.push(state.ingame_tick());
+    }
+
+    fn finish(self, _: &GameState) -> Self::Output {
+        (self.map, self.events, self.frames)
+    }
+}
+
+/// A Source 2 demo on `de_test` with `frames` empty frames.
+fn source2_demo(frames: u32) -> Vec<u8> {
+    let mut data = b"PBDEMS2\0".to_vec();
+    data.resize(16 + 2 * 260, 0);
+    data.extend(b"de_test");
+    data.resize(1072, 0);
+    for tick in 0..frames {
+        data.extend([1, tick as u8 + 10, 0]);
+    }
+    data.extend([0, 0, 0]);
+    data
+}
+
+fn round_end() -> RoundEnd {
+    RoundEnd {
+        message: String::new(),
+        reason: RoundEndReason::TargetBombed,
+        winner: Default::default(),
+        winner_state: None,
+        loser_state: None,
+    }
+}
+
+#[test]
+fn runs_analyzers_in_one_pass() {
+    let demo = source2_demo(3);
+    let mut parser = Parser::new(&demo[..]);
+    let ((map, events, frames), rounds) =
+        analyzer::run(&mut parser, (Log::default(), RoundCount::default())).unwrap();
+    assert_eq!("de_test", map);
+    assert_eq!(vec![10, 11, 12], frames);
+    assert!(events >= 3);
+    assert_eq!(0, rounds);
+}
+
+#[test]
+fn combinators_forward_every_hook() {
+    let state = GameState::default();
+    let mut analyzers = (
+        vec![RoundCount::default(), RoundCount::default()],
+        Log::default(),
+    );
+    analyzers.on_round_end(&round_end(), &state);
+    analyzers.on_event(&FrameDone, &state);
+    analyzers.on_frame(&state);
+    let (rounds, (_, events, frames)) = analyzers.finish(&state);
+    assert_eq!(vec![1, 1], rounds);
+    assert_eq!(1, events);
+    assert_eq!(1, frames.len());
+}
+
+#[test]
+fn round_end_events_reach_on_round_end() {
+    let mut parser = Parser::new(&[][..]);
+    let count = std::sync::Arc::new(std::sync::Mutex::new(RoundCount::default()));
+    let c = count.clone();
+    parser.register_sync_any_handler(move |ev, state| {
+        if let Some(round) = ev.downcast_ref::<RoundEnd>() {
+            c.lock().unwrap().on_round_end(round, state);
+        }
+    });
+    parser.dispatch_event(round_end());
+    parser.dispatch_event(FrameDone);
+    let count = std::mem::take(&mut *count.lock().unwrap());
+    assert_eq!(1, count.finish(parser.game_state()));
+}
+
+#[test]
+fn run_unregisters_its_hook() {
+    let demo = source2_demo(2);
+    let mut parser = Parser::new(&demo[..]);
+    let frames = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let f = frames.clone();
+    let id = parser.register_sync_any_handler(move |ev, _| {
+        if ev.is::<FrameDone>() {
+            f.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    });
+    let (_, events, _) = analyzer::run(&mut parser, Log::default()).unwrap();
+    assert_eq!(2, frames.load(std::sync::atomic::Ordering::SeqCst));
+
+    // Neither the analyzer's hook nor an unregistered handler run anymore.
+    parser.unregister_sync_handler(id);
+    parser.dispatch_event(FrameDone);
+    assert_eq!(2, frames.load(std::sync::atomic::Ordering::SeqCst));
+    assert!(events >= 2);
+}
@@ -0,0 +1,120 @@
+use cs_demo_parser::parser::ParserError;
+use cs_demo_parser::utils::batch::{
+    BatchError, BatchOptions, CancelToken, ContentHash, Processed, run_batch,
+};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// A directory with an empty Source 2 demo `ok.dem`, a file that isn't a
+/// demo `bad.dem` and a copy of `ok.dem`.
+fn demo_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cs-demo-batch-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let mut demo = b"PBDEMS2\0".to_vec();
+    demo.resize(1072, 0);
+    demo.extend([0, 0, 0]);
+    std::fs::write(dir.join("ok.dem"), &demo).unwrap();
+    std::fs::write(dir.join("copy.dem"), &demo).unwrap();
+    std::fs::write(dir.join("bad.dem"), b"not a demo").unwrap();
+    dir
+}
+
+#[test]
+fn reports_errors_per_demo() {
+    let dir = demo_dir("errors");
+    let paths = vec![
+        dir.join("ok.dem"),
+        dir.join("bad.dem"),
+        dir.join("missing.dem"),
+        dir.join("copy.dem"),
+    ];
+    let results = run_batch(paths.clone(), &BatchOptions::default(), |parser, path| {
+        if path.ends_with("copy.dem") {
+            panic!("analysis failed");
+        }
+        Ok(parser.parse_header()?.filestamp)
+    });
+
+    assert_eq!(
+        paths,
+        results.iter().map(|(p, _)| p.clone()).collect::<Vec<_>>()
+    );
+    assert_eq!("PBDEMS2", results[0].1.as_ref().unwrap());
+    assert!(matches!(
+        results[1].1,
+        Err(BatchError::Parser(ParserError::InvalidFileType))
+    ));
+    assert!(matches!(results[2].1, Err(BatchError::Io(_))));
+    assert!(matches!(&results[3].1, Err(BatchError::Panic(msg)) if msg == "analysis failed"));
+}
+
+#[test]
+fn skips_processed_demos() {
+    let dir = demo_dir("skip");
+    let options = BatchOptions {
+        threads: Some(1),
+        skip: Some(Processed::new()),
+        ..Default::default()
+    };
+    let paths = [
+        dir.join("ok.dem"),
+        dir.join("copy.dem"),
+        dir.join("bad.dem"),
+    ];
+    let results = run_batch(paths, &options, |parser, _| {
+        parser.parse_to_end()?;
+        Ok(())
+    });
+
+    // The copy has the same content and is skipped whichever comes first.
+    let skipped = results
+        .iter()
+        .filter(|(_, r)| matches!(r, Err(BatchError::AlreadyProcessed(_))))
+        .count();
+    assert_eq!(1, skipped);
+    let processed = options.skip.as_ref().unwrap();
+    assert_eq!(1, processed.len());
+    let hash = ContentHash::of_file(&dir.join("ok.dem")).unwrap();
+    assert!(processed.contains(hash));
+    assert_eq!(Ok(hash), hash.to_string().parse());
+}
+
+#[test]
+fn reports_progress_and_cancels() {
+    let dir = demo_dir("cancel");
+    let cancel = CancelToken::new();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let s = seen.clone();
+    let c = cancel.clone();
+    let options = BatchOptions {
+        threads: Some(1),
+        cancel: Some(cancel),
+        progress: Some(Arc::new(move |p| {
+            s.lock()
+                .unwrap()
+                .push((p.completed, p.total, p.error.is_some()));
+            c.cancel();
+        })),
+        ..Default::default()
+    };
+    let paths = [
+        dir.join("ok.dem"),
+        dir.join("copy.dem"),
+        dir.join("bad.dem"),
+    ];
+    let results = run_batch(paths, &options, |parser, _| {
+        parser.parse_to_end()?;
+        Ok(())
+    });
+
+    let done = results.iter().filter(|(_, r)| r.is_ok()).count();
+    let cancelled = results
+        .iter()
+        .filter(|(_, r)| matches!(r, Err(BatchError::Cancelled)))
+        .count();
+    assert_eq!(1, done);
+    assert_eq!(2, cancelled);
+    let seen = seen.lock().unwrap();
+    assert_eq!(3, seen.len());
+    assert_eq!((3, 3), (seen[2].0, seen[2].1));
+}
@@ -85,3 +85,31 @@ fn test_ubit_int() {
         assert_eq!(r.read_ubit_int(), val);
     }
 }
+
+#[test]
+fn read_bytes_aligned_and_unaligned() {
+    let data: Vec<u8> = (0..=255).collect();
+    let mut r = BitReader::new_small(&data[..]);
+    assert_eq!(vec![0, 1, 2], r.read_bytes(3));
+
+    // Shifted by 4 bits, each byte combines two nibbles.
+    r.read_int(4);
+    assert_eq!(vec![0x40, 0x50], r.read_bytes(2));
+    r.read_int(4);
+
+    let mut buf = [0u8; 600];
+    r.read_bytes_into(&mut buf[..250]);
+    assert_eq!(data[6..256], buf[..250]);
+}
+
+#[test]
+fn skip_bytes_and_bits() {
+    let data: Vec<u8> = (0..=255).collect();
+    let mut r = BitReader::new_small(&data[..]);
+    r.skip_bytes(100);
+    assert_eq!(100, r.read_int(8));
+    r.skip_bits(4);
+    r.skip_bytes(1);
+    r.skip_bits(4);
+    assert_eq!(103, r.read_int(8));
+}
@@ -1,5 +1,15 @@
-use cs_demo_parser::common::{Bomb, Player};
+use cs_demo_parser::common::{Bomb, BombState, Player, Team};
+use cs_demo_parser::events::{self, Bombsite};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::parser::{Parser, ParserConfig};
+use cs_demo_parser::proto::msg;
 use cs_demo_parser::sendtables::entity::Vector;
+use std::io::Cursor;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use support::{Demo, Op, Value};
+
+mod support;
 
 #[test]
 fn bomb_position() {
@@ -29,3 +39,259 @@ fn bomb_position() {
     bomb.carrier = Some(carrier);
     assert_eq!(bomb.position(), bomb.carrier.as_ref().unwrap().position());
 }
+
+fn t(user_id: i32, x: f64) -> Player {
+    Player {
+        user_id,
+        team: Team::Terrorists,
+        last_alive_position: Vector { x, y: 0.0, z: 0.0 },
+        ..Default::default()
+    }
+}
+
+fn bomb_event(player: &Player, site: Bombsite) -> events::BombEvent {
+    events::BombEvent {
+        player: Some(player.clone()),
+        site,
+    }
+}
+
+#[test]
+fn tracks_carriers_plant_and_defuse() {
+    let mut gs = GameState {
+        tick_rate: 64.0,
+        ..Default::default()
+    };
+    gs.rules
+        .con_vars
+        .insert("mp_c4timer".to_string(), "40".to_string());
+    let a = t(1, 100.0);
+    let b = t(2, 200.0);
+    let ct = Player {
+        user_id: 3,
+        team: Team::CounterTerrorists,
+        ..Default::default()
+    };
+
+    gs.handle_event(&events::RoundStart::default());
+    gs.set_ingame_tick(640);
+    gs.handle_event(&events::BombPickup {
+        player: Some(a.clone()),
+    });
+    gs.set_ingame_tick(700);
+    gs.handle_event(&events::BombDropped {
+        player: Some(a.clone()),
+        entity_id: 0,
+    });
+    assert_eq!(BombState::Dropped, gs.bomb.state);
+    assert_eq!(100.0, gs.bomb.position().x);
+    gs.set_ingame_tick(800);
+    gs.handle_event(&events::BombPickup {
+        player: Some(b.clone()),
+    });
+    gs.set_ingame_tick(1000);
+    gs.handle_event(&events::BombPlantBegin {
+        inner: bomb_event(&b, Bombsite::B),
+    });
+    gs.handle_event(&events::BombPlantAborted {
+        player: Some(b.clone()),
+    });
+    gs.set_ingame_tick(1100);
+    gs.handle_event(&events::BombPlantBegin {
+        inner: bomb_event(&b, Bombsite::B),
+    });
+    assert_eq!(BombState::Planting, gs.bomb.state);
+    gs.set_ingame_tick(1280);
+    gs.handle_event(&events::BombPlanted {
+        inner: bomb_event(&b, Bombsite::Unknown),
+    });
+    assert_eq!(Some(40.0), gs.bomb.time_remaining(gs.game_time()));
+
+    // Ten seconds after the plant: not enough time left without a kit.
+    gs.set_ingame_tick(1920);
+    gs.handle_event(&events::BombDefuseStart {
+        player: Some(ct.clone()),
+        has_kit: false,
+    });
+    gs.handle_event(&events::BombDefuseAborted {
+        player: Some(ct.clone()),
+    });
+    gs.set_ingame_tick(3200);
+    gs.handle_event(&events::BombDefuseStart {
+        player: Some(ct.clone()),
+        has_kit: true,
+    });
+    gs.set_ingame_tick(3520);
+    gs.handle_event(&events::BombDefused {
+        inner: bomb_event(&ct, Bombsite::Unknown),
+    });
+
+    assert_eq!(BombState::Defused, gs.bomb.state);
+    let round = &gs.bomb.rounds()[0];
+    assert_eq!(
+        vec![(1, 640, Some(700)), (2, 800, Some(1280))],
+        round
+            .carriers
+            .iter()
+            .map(|c| (c.player.user_id, c.start_tick, c.end_tick))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(100.0, round.drops[0].position.x);
+    assert_eq!(200.0, round.pickups[1].position.x);
+    assert_eq!(1, round.aborted_plants());
+    assert_eq!(Bombsite::B, round.plants[1].site);
+    assert!(round.plants[1].planted);
+
+    assert_eq!(1, round.aborted_defuses());
+    assert_eq!(Some(30.0), round.defuses[0].time_remaining);
+    assert_eq!(Some(true), round.defuses[0].in_time());
+    assert!(round.defuses[1].has_kit);
+    assert_eq!(Some(10.0), round.defuses[1].time_remaining);
+
+    let planted = round.planted.as_ref().unwrap();
+    assert_eq!(Bombsite::B, planted.site);
+    assert_eq!(Some(2), planted.planter.as_ref().map(|p| p.user_id));
+    assert_eq!(Some(40.0), planted.timer);
+    assert!(planted.defused);
+    assert_eq!(Some(5.0), planted.time_remaining_at_defuse);
+    assert_eq!(None, planted.explode_tick);
+}
+
+#[test]
+fn starts_a_bomb_round_per_round() {
+    let mut gs = GameState::default();
+    let a = t(1, 0.0);
+    gs.handle_event(&events::RoundStart::default());
+    gs.handle_event(&events::BombPickup {
+        player: Some(a.clone()),
+    });
+    gs.handle_event(&events::BombPlanted {
+        inner: bomb_event(&a, Bombsite::A),
+    });
+    gs.set_ingame_tick(500);
+    gs.handle_event(&events::BombExplode {
+        inner: bomb_event(&a, Bombsite::A),
+    });
+    assert_eq!(BombState::Exploded, gs.bomb.state);
+    assert_eq!(Some(500), gs.bomb.planted().and_then(|p| p.explode_tick));
+    // Without mp_c4timer or the entity the timer is unknown.
+    assert_eq!(None, gs.bomb.time_remaining(gs.game_time()));
+
+    gs.handle_event(&events::RoundEnd {
+        message: String::new(),
+        reason: events::RoundEndReason::TargetBombed,
+        winner: 2,
+        winner_state: None,
+        loser_state: None,
+    });
+    gs.handle_event(&events::RoundStart::default());
+    assert_eq!(BombState::Carried, gs.bomb.state);
+    assert!(gs.bomb.planted().is_none());
+    assert_eq!(
+        vec![1, 2],
+        gs.bomb.rounds().iter().map(|r| r.round).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn warns_about_unknown_bombsites() {
+    let warnings = |ignore: bool| {
+        let mut parser = Parser::with_config(
+            Cursor::new(Vec::<u8>::new()),
+            ParserConfig {
+                ignore_bombsite_index_not_found: ignore,
+                ..Default::default()
+            },
+        );
+        let count = Arc::new(AtomicUsize::new(0));
+        let c = count.clone();
+        parser.register_sync_event_handler::<events::ParserWarn, _>(move |w, _| {
+            if matches!(w.r#type, events::WarnType::BombsiteUnknown) {
+                c.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        parser.on_game_event_list(&msg::CsvcMsgGameEventList {
+            descriptors: vec![msg::csvc_msg_game_event_list::DescriptorT {
+                eventid: Some(1),
+                name: Some("bomb_planted".into()),
+                keys: vec![msg::csvc_msg_game_event_list::KeyT {
+                    r#type: None,
+                    name: Some("site".into()),
+                }],
+            }],
+        });
+        parser.on_game_event(&msg::CsvcMsgGameEvent {
+            event_name: None,
+            eventid: Some(1),
+            keys: vec![msg::csvc_msg_game_event::KeyT {
+                r#type: None,
+                val_string: None,
+                val_float: None,
+                val_long: Some(154),
+                val_short: None,
+                val_byte: None,
+                val_bool: None,
+                val_uint64: None,
+                val_wstring: None,
+            }],
+            passthrough: None,
+        });
+        count.load(Ordering::SeqCst)
+    };
+    assert_eq!(1, warnings(false));
+    assert_eq!(0, warnings(true));
+}
+
+#[test]
+fn resolves_the_defuser_through_its_pawn() {
+    let controller = [
+        ("m_iTeamNum", Value::Int(3)),
+        ("m_hPlayerPawn", Value::Uint(1 << 14 | 5)),
+    ];
+    let pawn = [("m_iHealth", Value::Int(100))];
+    // The handle points at the defuser's pawn, not at its controller.
+    let planted = [
+        ("m_nBombSite", Value::Int(1)),
+        ("m_hBombDefuser", Value::Uint(1 << 14 | 5)),
+        ("m_bBombDefused", Value::Bool(false)),
+    ];
+    let mut demo = Demo::new(
+        &[
+            ("CCSPlayerController", &controller),
+            ("CCSPlayerPawn", &pawn),
+            ("CPlantedC4", &planted),
+        ],
+        &[],
+    );
+    demo.entities(
+        1,
+        &[
+            Op::Create {
+                class_id: 0,
+                index: 1,
+                serial: 1,
+                values: &controller,
+            },
+            Op::Create {
+                class_id: 1,
+                index: 5,
+                serial: 1,
+                values: &pawn,
+            },
+            Op::Create {
+                class_id: 2,
+                index: 60,
+                serial: 1,
+                values: &planted,
+            },
+        ],
+    );
+    let data = demo.finish();
+    let mut parser = Parser::new(&data[..]);
+    parser.parse_to_end().unwrap();
+
+    let planted = parser.game_state().bomb().planted().unwrap();
+    assert_eq!(Some(60), planted.entity_id);
+    assert_eq!(Bombsite::B, planted.site);
+    assert_eq!(Some(1), planted.defuser.as_ref().map(|p| p.user_id));
+}
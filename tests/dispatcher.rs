@@ -16,3 +16,20 @@ fn test_dispatcher_event() {
     thread::sleep(std::time::Duration::from_millis(10));
     assert_eq!(1, count.load(Ordering::SeqCst));
 }
+
+#[test]
+fn test_inline_dispatcher_runs_handlers_immediately() {
+    let disp = EventDispatcher::inline();
+    let count = Arc::new(AtomicUsize::new(0));
+    let c = count.clone();
+    let id = disp.register_handler::<u32, _>(move |v| {
+        c.fetch_add(*v as usize, Ordering::SeqCst);
+    });
+    disp.dispatch(2u32);
+    disp.dispatch("ignored");
+    assert_eq!(2, count.load(Ordering::SeqCst));
+
+    disp.unregister_handler(id);
+    disp.dispatch(2u32);
+    assert_eq!(2, count.load(Ordering::SeqCst));
+}
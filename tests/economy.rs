@@ -0,0 +1,268 @@
+use cs_demo_parser::common::{Equipment, EquipmentType, Player, Team};
+use cs_demo_parser::economy::{BuyThresholds, BuyType};
+use cs_demo_parser::events;
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::parser::Parser;
+use cs_demo_parser::sendtables::entity::{Entity, FlattenedPropEntry, Property, PropertyValue};
+use cs_demo_parser::sendtables::propdecoder::SendTableProperty;
+use cs_demo_parser::sendtables::serverclass::ServerClass;
+use std::sync::Arc;
+use support::{Demo, Op, Value};
+
+mod support;
+
+fn make_entity(props: Vec<(&str, i32)>) -> Entity {
+    let props = props
+        .iter()
+        .map(|(name, val)| Property {
+            entry: FlattenedPropEntry {
+                name: name.to_string(),
+                prop: SendTableProperty::default(),
+                array_element_prop: None,
+            },
+            value: PropertyValue {
+                int_val: *val,
+                ..Default::default()
+            },
+        })
+        .collect();
+    Entity {
+        id: 0,
+        serial_num: 0,
+        server_class: Arc::new(ServerClass::default()),
+        props,
+    }
+}
+
+fn add_player(gs: &mut GameState, user_id: i32, team: Team, money: i32) {
+    gs.players_by_user_id.insert(
+        user_id,
+        Player {
+            user_id,
+            team,
+            entity: Some(make_entity(vec![
+                ("m_iAccount", money),
+                ("m_unFreezetimeEndEquipmentValue", 0),
+            ])),
+            ..Default::default()
+        },
+    );
+}
+
+fn set_prop(gs: &mut GameState, user_id: i32, name: &str, val: i32) {
+    let ent = gs
+        .players_by_user_id
+        .get_mut(&user_id)
+        .and_then(|p| p.entity.as_mut())
+        .unwrap();
+    ent.props
+        .iter_mut()
+        .find(|p| p.entry.name == name)
+        .unwrap()
+        .value
+        .int_val = val;
+}
+
+fn pickup(
+    gs: &GameState,
+    user_id: i32,
+    equipment_type: EquipmentType,
+    silent: bool,
+) -> events::ItemPickup {
+    events::ItemPickup {
+        player: gs.players_by_user_id.get(&user_id).cloned(),
+        weapon: Some(Equipment {
+            equipment_type,
+            ..Default::default()
+        }),
+        silent,
+    }
+}
+
+#[test]
+fn classify_buy_types() {
+    let t = BuyThresholds::default();
+    assert_eq!(BuyType::Pistol, t.classify(true, 5000));
+    assert_eq!(BuyType::Eco, t.classify(false, 400));
+    assert_eq!(BuyType::SemiEco, t.classify(false, 1500));
+    assert_eq!(BuyType::Force, t.classify(false, 3000));
+    assert_eq!(BuyType::Full, t.classify(false, 5200));
+}
+
+#[test]
+fn tracks_round_economy() {
+    let mut gs = GameState::default();
+    add_player(&mut gs, 1, Team::Terrorists, 800);
+    add_player(&mut gs, 2, Team::CounterTerrorists, 800);
+
+    gs.handle_event(&events::MatchStart);
+    gs.set_ingame_tick(100);
+    gs.handle_event(&events::RoundStart::default());
+
+    gs.handle_event(&pickup(&gs, 1, EquipmentType::Tec9, false));
+    gs.handle_event(&pickup(&gs, 2, EquipmentType::Knife, true));
+    set_prop(&mut gs, 1, "m_iAccount", 100);
+    gs.handle_event(&events::FrameDone);
+    // A weapon dropped by a teammate costs nothing.
+    gs.handle_event(&pickup(&gs, 2, EquipmentType::Ak47, false));
+    gs.handle_event(&events::FrameDone);
+
+    set_prop(&mut gs, 1, "m_unFreezetimeEndEquipmentValue", 700);
+    set_prop(&mut gs, 2, "m_unFreezetimeEndEquipmentValue", 200);
+    gs.handle_event(&events::RoundFreezetimeEnd);
+
+    set_prop(&mut gs, 1, "m_iAccount", 400);
+    gs.handle_event(&events::FrameDone);
+    gs.set_ingame_tick(500);
+    gs.handle_event(&events::RoundEnd {
+        message: String::new(),
+        reason: events::RoundEndReason::TerroristsWin,
+        winner: Team::Terrorists as u8,
+        winner_state: None,
+        loser_state: None,
+    });
+
+    let econ = gs.economy();
+    let round = econ.current_round().unwrap();
+    assert_eq!(1, round.round);
+    assert!(round.is_pistol_round);
+    assert_eq!(Team::Terrorists, round.winner);
+    assert_eq!(500, round.end_tick);
+
+    let p1 = round.player(1).unwrap();
+    assert_eq!(800, p1.start_money);
+    assert_eq!(700, p1.money_spent);
+    assert_eq!(300, p1.cash_earned);
+    assert_eq!(700, p1.freezetime_end_equipment_value);
+    assert_eq!(1, p1.purchases.len());
+    assert_eq!(EquipmentType::Tec9, p1.purchases[0].equipment_type);
+    assert!(round.player(2).unwrap().purchases.is_empty());
+
+    assert_eq!(BuyType::Pistol, round.terrorists.buy_type);
+    assert_eq!(700, round.terrorists.money_spent);
+
+    gs.handle_event(&events::RoundStart::default());
+    gs.handle_event(&events::RoundFreezetimeEnd);
+    let round = gs.economy().current_round().unwrap();
+    assert_eq!(2, round.round);
+    assert!(!round.is_pistol_round);
+    assert_eq!(0, round.terrorists.loss_streak);
+    assert_eq!(1, round.counter_terrorists.loss_streak);
+    assert_eq!(1900, round.counter_terrorists.loss_bonus());
+    assert_eq!(BuyType::Eco, round.counter_terrorists.buy_type);
+    assert_eq!(2, gs.economy().rounds().len());
+}
+
+#[test]
+fn ignores_half_time_money_reset() {
+    let mut gs = GameState::default();
+    add_player(&mut gs, 1, Team::Terrorists, 800);
+    gs.handle_event(&events::MatchStart);
+    gs.handle_event(&events::RoundStart::default());
+
+    set_prop(&mut gs, 1, "m_iAccount", 12000);
+    gs.handle_event(&events::FrameDone);
+    gs.handle_event(&events::GameHalfEnded);
+    set_prop(&mut gs, 1, "m_iAccount", 800);
+    gs.handle_event(&events::FrameDone);
+    let round = gs.economy().current_round().unwrap();
+    assert_eq!(0, round.player(1).unwrap().money_spent);
+    assert_eq!(11200, round.player(1).unwrap().cash_earned);
+
+    gs.handle_event(&events::RoundStart::default());
+    set_prop(&mut gs, 1, "m_iAccount", 600);
+    gs.handle_event(&events::FrameDone);
+    let round = gs.economy().current_round().unwrap();
+    assert!(round.is_pistol_round);
+    assert_eq!(800, round.player(1).unwrap().start_money);
+    assert_eq!(200, round.player(1).unwrap().money_spent);
+}
+
+#[test]
+fn prefers_purchase_events() {
+    let mut gs = GameState::default();
+    add_player(&mut gs, 1, Team::CounterTerrorists, 800);
+    gs.handle_event(&events::MatchStart);
+    gs.handle_event(&events::RoundStart::default());
+
+    gs.handle_event(&events::ItemPurchase {
+        player: gs.players_by_user_id.get(&1).cloned(),
+        weapon: Some(Equipment {
+            equipment_type: EquipmentType::Kevlar,
+            ..Default::default()
+        }),
+    });
+    gs.handle_event(&pickup(&gs, 1, EquipmentType::Kevlar, false));
+    gs.handle_event(&pickup(&gs, 1, EquipmentType::Deagle, false));
+    set_prop(&mut gs, 1, "m_iAccount", 150);
+    gs.handle_event(&events::FrameDone);
+
+    let purchases = &gs
+        .economy()
+        .current_round()
+        .unwrap()
+        .player(1)
+        .unwrap()
+        .purchases;
+    assert_eq!(1, purchases.len());
+    assert_eq!(EquipmentType::Kevlar, purchases[0].equipment_type);
+}
+
+#[test]
+fn overtime_halves_are_not_pistol_rounds() {
+    let mut gs = GameState::default();
+    add_player(&mut gs, 1, Team::Terrorists, 800);
+    gs.handle_event(&events::MatchStart);
+    gs.handle_event(&events::RoundStart::default());
+    gs.handle_event(&events::GameHalfEnded);
+    gs.handle_event(&events::RoundStart::default());
+    assert!(gs.economy().current_round().unwrap().is_pistol_round);
+
+    gs.overtime_count = 1;
+    gs.handle_event(&events::TeamSideSwitch);
+    gs.handle_event(&events::RoundStart::default());
+    assert!(!gs.economy().current_round().unwrap().is_pistol_round);
+}
+
+fn controller(team: i32, money: i32) -> Vec<(&'static str, Value)> {
+    vec![
+        ("m_iTeamNum", Value::Int(team)),
+        ("m_hPlayerPawn", Value::Uint(1 << 14 | 5)),
+        ("m_pInGameMoneyServices.m_iAccount", Value::Int(money)),
+    ]
+}
+
+#[test]
+fn reads_money_of_parsed_controllers() {
+    let mut demo = Demo::new(
+        &[("CCSPlayerController", &controller(2, 0))],
+        &[("round_start", &[])],
+    );
+    demo.entities(
+        1,
+        &[Op::Create {
+            class_id: 0,
+            index: 1,
+            serial: 1,
+            values: &controller(2, 4000),
+        }],
+    );
+    demo.event(2, "round_start", &[]);
+    demo.entities(
+        3,
+        &[Op::Update {
+            index: 1,
+            values: &controller(2, 900),
+        }],
+    );
+    let data = demo.finish();
+    let mut parser = Parser::new(&data[..]);
+    parser.parse_to_end().unwrap();
+
+    let gs = parser.game_state();
+    let player = &gs.players_by_user_id[&1];
+    assert_eq!((Team::Terrorists, 900), (player.team, player.money()));
+    let round = gs.economy().current_round().unwrap();
+    let economy = round.player(1).unwrap();
+    assert_eq!((4000, 3100), (economy.start_money, economy.money_spent));
+}
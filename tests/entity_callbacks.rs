@@ -1,4 +1,4 @@
-use cs_demo_parser::parser::{EntityEvent, Parser};
+use cs_demo_parser::parser::Parser;
 use cs_demo_parser::sendtables::EntityOp;
 use cs_demo_parser::sendtables2::{Class, Entity};
 use std::io::Cursor;
@@ -29,21 +29,17 @@ fn test_entity_handlers() {
             class_id: 0,
             name: "Test".into(),
             serializer: None,
+            ..Default::default()
         },
+        ..Default::default()
     };
 
-    p.dispatch_event(EntityEvent {
-        entity: ent.clone(),
-        op: EntityOp::CREATED,
-    });
-    p.dispatch_event(EntityEvent {
-        entity: ent.clone(),
-        op: EntityOp::UPDATED,
-    });
+    p.dispatch_entity(ent.clone(), EntityOp::CREATED);
+    p.dispatch_entity(ent.clone(), EntityOp::UPDATED);
 
     thread::sleep(std::time::Duration::from_millis(10));
 
     assert_eq!(2, on_entity.load(Ordering::SeqCst));
     assert_eq!(1, on_created.load(Ordering::SeqCst));
-    assert!(p.game_state().entities().get(&1).is_some());
+    assert!(p.game_state().entities().by_index(1).is_some());
 }
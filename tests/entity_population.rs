@@ -13,15 +13,19 @@ fn weapon_entities_are_tracked() {
         class_id: 1,
         name: "CWeaponAK47".into(),
         serializer: None,
+        ..Default::default()
     };
     let ent = Entity {
         index: 10,
         serial: 1,
         class,
+        ..Default::default()
     };
 
+    gs.add_entity(ent.clone());
     gs.handle_event(&EntityEvent {
-        entity: ent.clone(),
+        handle: ent.handle(),
+        class: ent.class.clone(),
         op: EntityOp::CREATED,
     });
     assert_eq!(1, gs.weapons.len());
@@ -31,7 +35,8 @@ fn weapon_entities_are_tracked() {
     );
 
     gs.handle_event(&EntityEvent {
-        entity: ent,
+        handle: ent.handle(),
+        class: ent.class.clone(),
         op: EntityOp::DELETED,
     });
     assert!(gs.weapons.is_empty());
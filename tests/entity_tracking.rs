@@ -2,6 +2,10 @@ use cs_demo_parser::parser::{EntityEvent, Parser};
 use cs_demo_parser::sendtables::EntityOp;
 use cs_demo_parser::sendtables2::{Class, Entity};
 use std::io::Cursor;
+use std::sync::{Arc, Mutex};
+use support::{Demo, Op, Value};
+
+mod support;
 
 #[test]
 fn test_projectile_and_dropped_weapon_tracking() {
@@ -11,41 +15,100 @@ fn test_projectile_and_dropped_weapon_tracking() {
         class_id: 1,
         name: "CGrenadeProjectile".into(),
         serializer: None,
+        ..Default::default()
     };
     let projectile = Entity {
         index: 1,
         serial: 1,
         class: proj_class,
+        ..Default::default()
     };
-    p.dispatch_event(EntityEvent {
-        entity: projectile.clone(),
-        op: EntityOp::CREATED,
-    });
+    p.dispatch_entity(projectile.clone(), EntityOp::CREATED);
     assert!(p.game_state().projectile_owners().contains_key(&1));
-    p.dispatch_event(EntityEvent {
-        entity: projectile,
-        op: EntityOp::DELETED,
-    });
+    p.dispatch_entity(projectile, EntityOp::DELETED);
     assert!(!p.game_state().projectile_owners().contains_key(&1));
 
     let drop_class = Class {
         class_id: 2,
         name: "CDroppedWeapon".into(),
         serializer: None,
+        ..Default::default()
     };
     let dropped = Entity {
         index: 2,
         serial: 1,
         class: drop_class,
+        ..Default::default()
     };
-    p.dispatch_event(EntityEvent {
-        entity: dropped.clone(),
-        op: EntityOp::CREATED,
-    });
+    p.dispatch_entity(dropped.clone(), EntityOp::CREATED);
     assert!(p.game_state().dropped_weapons().contains_key(&2));
-    p.dispatch_event(EntityEvent {
-        entity: dropped,
-        op: EntityOp::DELETED,
-    });
+    p.dispatch_entity(dropped, EntityOp::DELETED);
     assert!(!p.game_state().dropped_weapons().contains_key(&2));
 }
+
+#[test]
+fn entities_replaced_in_their_slot_are_dropped() {
+    let hostage = [("m_nHostageState", Value::Int(0))];
+    let flashbang = [("m_nBounces", Value::Int(0))];
+    let prop = [("m_iHealth", Value::Int(100))];
+    let mut demo = Demo::new(
+        &[
+            ("CHostage", &hostage),
+            ("CFlashbangProjectile", &flashbang),
+            ("CDynamicProp", &prop),
+        ],
+        &[],
+    );
+    demo.entities(
+        1,
+        &[
+            Op::Create {
+                class_id: 1,
+                index: 40,
+                serial: 1,
+                values: &flashbang,
+            },
+            Op::Create {
+                class_id: 0,
+                index: 70,
+                serial: 1,
+                values: &hostage,
+            },
+        ],
+    );
+    // Both slots are taken by new entities without a delete first.
+    demo.entities(
+        2,
+        &[
+            Op::Create {
+                class_id: 1,
+                index: 40,
+                serial: 2,
+                values: &flashbang,
+            },
+            Op::Create {
+                class_id: 2,
+                index: 70,
+                serial: 2,
+                values: &prop,
+            },
+        ],
+    );
+    let data = demo.finish();
+    let mut parser = Parser::new(&data[..]);
+    let deleted = Arc::new(Mutex::new(Vec::new()));
+    let d = Arc::clone(&deleted);
+    parser.register_sync_event_handler::<EntityEvent, _>(move |ev, _| {
+        if ev.op.contains(EntityOp::DELETED) {
+            d.lock().unwrap().push((ev.handle.index, ev.handle.serial));
+        }
+    });
+    parser.parse_to_end().unwrap();
+
+    assert_eq!(vec![(40, 1), (70, 1)], *deleted.lock().unwrap());
+    let gs = parser.game_state();
+    assert!(gs.hostages.is_empty());
+    assert_eq!(1, gs.grenade_projectiles().len());
+    let projectile = &gs.grenade_projectiles()[&40];
+    assert_eq!(Some(2), projectile.entity.map(|h| h.serial));
+}
@@ -0,0 +1,287 @@
+use cs_demo_parser::common::{Equipment, EquipmentType, Player, Team};
+use cs_demo_parser::events;
+use cs_demo_parser::export::{DatasetCollector, DatasetConfig};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::parser::Parser;
+use cs_demo_parser::proto::msg;
+use std::io::Cursor;
+
+fn player(user_id: i32, steam_id64: u64, team: Team) -> Player {
+    Player {
+        user_id,
+        steam_id64,
+        name: format!("player{user_id}"),
+        team,
+        is_connected: true,
+        ..Default::default()
+    }
+}
+
+fn key(name: &str) -> msg::csvc_msg_game_event_list::KeyT {
+    msg::csvc_msg_game_event_list::KeyT {
+        r#type: None,
+        name: Some(name.into()),
+    }
+}
+
+fn value() -> msg::csvc_msg_game_event::KeyT {
+    msg::csvc_msg_game_event::KeyT {
+        r#type: None,
+        val_string: None,
+        val_float: None,
+        val_long: None,
+        val_short: None,
+        val_byte: None,
+        val_bool: None,
+        val_uint64: None,
+        val_wstring: None,
+    }
+}
+
+#[test]
+fn collects_rows_from_parser_events() {
+    let mut parser = Parser::new(Cursor::new(Vec::<u8>::new()));
+    let collector = DatasetCollector::register(
+        &mut parser,
+        "match",
+        DatasetConfig {
+            tick_interval: None,
+            ..Default::default()
+        },
+    );
+
+    parser.on_game_event_list(&msg::CsvcMsgGameEventList {
+        descriptors: vec![
+            msg::csvc_msg_game_event_list::DescriptorT {
+                eventid: Some(1),
+                name: Some("round_start".into()),
+                keys: vec![],
+            },
+            msg::csvc_msg_game_event_list::DescriptorT {
+                eventid: Some(2),
+                name: Some("player_death".into()),
+                keys: vec![key("weapon"), key("headshot"), key("penetrated")],
+            },
+            msg::csvc_msg_game_event_list::DescriptorT {
+                eventid: Some(3),
+                name: Some("round_end".into()),
+                keys: vec![key("winner"), key("reason"), key("message")],
+            },
+        ],
+    });
+    let event = |id, keys| msg::CsvcMsgGameEvent {
+        event_name: None,
+        eventid: Some(id),
+        keys,
+        passthrough: None,
+    };
+    parser.on_game_event(&event(1, vec![]));
+    parser.on_game_event(&event(
+        2,
+        vec![
+            msg::csvc_msg_game_event::KeyT {
+                val_string: Some("ak47".into()),
+                ..value()
+            },
+            msg::csvc_msg_game_event::KeyT {
+                val_bool: Some(true),
+                ..value()
+            },
+            msg::csvc_msg_game_event::KeyT {
+                val_short: Some(1),
+                ..value()
+            },
+        ],
+    ));
+    parser.on_game_event(&event(
+        3,
+        vec![
+            msg::csvc_msg_game_event::KeyT {
+                val_byte: Some(2),
+                ..value()
+            },
+            msg::csvc_msg_game_event::KeyT {
+                val_byte: Some(9),
+                ..value()
+            },
+            msg::csvc_msg_game_event::KeyT {
+                val_string: Some("#SFUI_Notice_Terrorists_Win".into()),
+                ..value()
+            },
+        ],
+    ));
+
+    let dataset = collector.lock().unwrap().take_dataset();
+    assert_eq!("match", dataset.demo);
+    assert_eq!(1, dataset.kills.len());
+    let kill = &dataset.kills[0];
+    assert_eq!(1, kill.round);
+    assert_eq!("ak47", kill.weapon);
+    assert!(kill.is_headshot);
+    assert_eq!(1, kill.penetrated_objects);
+    assert_eq!(None, kill.killer_steam_id64);
+
+    assert_eq!(1, dataset.rounds.len());
+    let round = &dataset.rounds[0];
+    assert_eq!(1, round.round);
+    assert_eq!(Team::Terrorists as u8, round.winner);
+    assert_eq!(events::RoundEndReason::TerroristsWin as u8, round.reason);
+    assert_eq!("#SFUI_Notice_Terrorists_Win", round.message);
+    assert!(dataset.ticks.is_empty());
+}
+
+#[test]
+fn collects_player_columns() {
+    let mut gs = GameState::default();
+    let attacker = player(1, 76561198000000001, Team::Terrorists);
+    let victim = player(2, 76561198000000002, Team::CounterTerrorists);
+    gs.set_ingame_tick(640);
+
+    let mut collector = DatasetCollector::new("match", DatasetConfig::default());
+    collector.handle_event(
+        &events::PlayerHurt {
+            player: Some(victim.clone()),
+            attacker: Some(attacker.clone()),
+            health: 73,
+            armor: 90,
+            weapon: Some(Equipment {
+                equipment_type: EquipmentType::Glock,
+                original_string: "glock".into(),
+                ..Default::default()
+            }),
+            weapon_string: "glock".into(),
+            health_damage: 27,
+            armor_damage: 10,
+            health_damage_taken: 27,
+            armor_damage_taken: 10,
+            hit_group: events::HitGroup::Head,
+        },
+        &gs,
+    );
+    collector.handle_event(
+        &events::HeExplode {
+            inner: events::GrenadeEvent {
+                grenade_type: EquipmentType::He,
+                thrower: Some(attacker),
+                grenade_entity_id: 120,
+                ..Default::default()
+            },
+        },
+        &gs,
+    );
+    collector.handle_event(
+        &events::ChatMessage {
+            sender: Some(victim),
+            text: "gg".into(),
+            is_chat_all: true,
+        },
+        &gs,
+    );
+
+    let dataset = collector.dataset();
+    let damage = &dataset.damages[0];
+    assert_eq!(640, damage.tick);
+    assert_eq!(Some(76561198000000001), damage.attacker_steam_id64);
+    assert_eq!(Some(Team::CounterTerrorists as u8), damage.victim_team);
+    assert_eq!(events::HitGroup::Head as u8, damage.hit_group);
+
+    let grenade = &dataset.grenades[0];
+    assert_eq!("he_explode", grenade.event);
+    assert_eq!("HE Grenade", grenade.grenade_type);
+    assert_eq!(Some("player1".to_string()), grenade.thrower_name);
+
+    assert_eq!("gg", dataset.chat[0].text);
+    assert_eq!(Some(76561198000000002), dataset.chat[0].sender_steam_id64);
+}
+
+#[test]
+fn builds_scoreboard() {
+    let gs = GameState::default();
+    let t = player(1, 76561198000000001, Team::Terrorists);
+    let ct = player(2, 76561198000000002, Team::CounterTerrorists);
+    let mut collector = DatasetCollector::new("match", DatasetConfig::default());
+
+    let kill = |killer: &Player, victim: &Player, is_headshot| events::Kill {
+        weapon: None,
+        victim: Some(victim.clone()),
+        killer: Some(killer.clone()),
+        assister: None,
+        penetrated_objects: 0,
+        is_headshot,
+        assisted_flash: false,
+        attacker_blind: false,
+        no_scope: false,
+        through_smoke: false,
+        distance: 0.0,
+    };
+    collector.handle_event(&kill(&t, &ct, true), &gs);
+    collector.handle_event(&kill(&ct, &ct, false), &gs);
+    collector.handle_event(
+        &events::PlayerHurt {
+            player: Some(ct.clone()),
+            attacker: Some(t.clone()),
+            health: 0,
+            armor: 0,
+            weapon: None,
+            weapon_string: String::new(),
+            health_damage: 120,
+            armor_damage: 0,
+            health_damage_taken: 100,
+            armor_damage_taken: 0,
+            hit_group: events::HitGroup::Head,
+        },
+        &gs,
+    );
+
+    let rows = collector.dataset().scoreboard(&gs);
+    assert_eq!(2, rows.len());
+    assert_eq!(76561198000000001, rows[0].steam_id64);
+    assert_eq!(
+        (1, 0, 1),
+        (rows[0].kills, rows[0].deaths, rows[0].headshot_kills)
+    );
+    assert_eq!(100, rows[0].damage);
+    assert_eq!(100.0, rows[0].adr);
+    // The suicide counts as a death but not as a kill.
+    assert_eq!((0, 2), (rows[1].kills, rows[1].deaths));
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn writes_parquet_tables() {
+    use cs_demo_parser::export::arrow::{Table, to_record_batch, write_parquet};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let mut collector = DatasetCollector::new("match", DatasetConfig::default());
+    let mut gs = GameState::default();
+    gs.players_by_user_id
+        .insert(1, player(1, 76561198000000001, Team::Terrorists));
+    gs.set_ingame_tick(64);
+    collector.handle_event(&events::FrameDone, &gs);
+    collector.handle_event(
+        &events::ChatMessage {
+            sender: None,
+            text: "glhf".into(),
+            is_chat_all: true,
+        },
+        &gs,
+    );
+    let dataset = collector.take_dataset();
+
+    let ticks = to_record_batch(&dataset, Table::PlayerTicks).unwrap();
+    assert_eq!(1, ticks.num_rows());
+    assert_eq!(Table::PlayerTicks.schema(), ticks.schema());
+
+    let out = std::env::temp_dir().join(format!("cs-demo-export-{}", std::process::id()));
+    let written = write_parquet(&dataset, &out).unwrap();
+    assert_eq!(Table::ALL.len(), written.len());
+
+    let file = std::fs::File::open(out.join("chat").join("match.parquet")).unwrap();
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .unwrap()
+        .build()
+        .unwrap();
+    let rows: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+    assert_eq!(1, rows);
+    std::fs::remove_dir_all(out).unwrap();
+}
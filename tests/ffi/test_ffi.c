@@ -0,0 +1,151 @@
+/*
+ * Exercises the C ABI. Build the library with the `ffi` feature and link
+ * against it:
+ *
+ *     cargo build --features ffi
+ *     cc -Wall -Wextra -Iinclude tests/ffi/test_ffi.c -Ltarget/debug \
+ *         -lcs_demo_parser -o target/test_ffi
+ *     LD_LIBRARY_PATH=target/debug target/test_ffi [demo.dem]
+ *
+ * Without arguments only the error handling is checked. With a demo path
+ * the demo is parsed and its kills and players are printed.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "cs_demo_parser.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,        \
+                    __LINE__, #cond);                                      \
+            failures++;                                                    \
+        }                                                                  \
+    } while (0)
+
+struct counts {
+    int kills;
+    int rounds;
+};
+
+static void on_kill(const CsDemoKill *kill, int32_t tick, void *user_data)
+{
+    struct counts *counts = user_data;
+    counts->kills++;
+    printf("%7d  %-20s %-16s %-20s%s\n", tick,
+           kill->killer ? kill->killer->name : "world", kill->weapon_name,
+           kill->victim ? kill->victim->name : "?",
+           kill->is_headshot ? "  (headshot)" : "");
+}
+
+static void on_round_end(const CsDemoRoundEnd *round, int32_t tick,
+                         void *user_data)
+{
+    struct counts *counts = user_data;
+    counts->rounds++;
+    printf("%7d  round %d won by team %d: %s\n", tick, counts->rounds,
+           round->winner, round->message);
+}
+
+static void check_errors(void)
+{
+    static const char lfs[] =
+        "version https://git-lfs.github.com/spec/v1\n"
+        "oid sha256:0000\nsize 1\n";
+    static const char truncated[] = "HL2DEMO\0abc";
+    CsDemoParser *parser = NULL;
+    CsDemoHeader header;
+    CsDemoPlayer player;
+    size_t count = 1;
+
+    CHECK(cs_demo_parser_open_path(NULL, &parser) ==
+          CS_DEMO_ERROR_NULL_POINTER);
+    CHECK(cs_demo_parser_open_path("does/not/exist.dem", &parser) ==
+          CS_DEMO_ERROR_IO);
+    CHECK(strcmp(cs_demo_error_message(CS_DEMO_ERROR_IO),
+                 "could not read the demo") == 0);
+
+    CHECK(cs_demo_parser_open_buffer((const uint8_t *)lfs, sizeof(lfs) - 1,
+                                     &parser) == CS_DEMO_ERROR_OK);
+    CHECK(cs_demo_parser_header(parser, &header) ==
+          CS_DEMO_ERROR_GIT_LFS_POINTER);
+    cs_demo_parser_free(parser);
+
+    CHECK(cs_demo_parser_open_buffer((const uint8_t *)truncated,
+                                     sizeof(truncated) - 1,
+                                     &parser) == CS_DEMO_ERROR_OK);
+    CHECK(cs_demo_parser_on_kill(parser, NULL, NULL) ==
+          CS_DEMO_ERROR_NULL_POINTER);
+    CHECK(cs_demo_parser_parse_to_end(parser) ==
+          CS_DEMO_ERROR_UNEXPECTED_END_OF_DEMO);
+    CHECK(cs_demo_parser_players(parser, NULL, 0, &count) ==
+          CS_DEMO_ERROR_OK);
+    CHECK(count == 0);
+    CHECK(cs_demo_parser_player_by_user_id(parser, 1, &player) ==
+          CS_DEMO_ERROR_NOT_FOUND);
+    cs_demo_parser_free(parser);
+
+    cs_demo_parser_free(NULL);
+}
+
+static int parse_demo(const char *path)
+{
+    struct counts counts = {0, 0};
+    CsDemoParser *parser = NULL;
+    CsDemoHeader header;
+    CsDemoPlayer *players;
+    size_t count = 0;
+    CsDemoError err;
+
+    err = cs_demo_parser_open_path(path, &parser);
+    if (err != CS_DEMO_ERROR_OK) {
+        fprintf(stderr, "%s: %s\n", path, cs_demo_error_message(err));
+        return 1;
+    }
+    err = cs_demo_parser_header(parser, &header);
+    if (err != CS_DEMO_ERROR_OK) {
+        fprintf(stderr, "%s: %s\n", path, cs_demo_error_message(err));
+        cs_demo_parser_free(parser);
+        return 1;
+    }
+    printf("%s on %s\n", header.filestamp, header.map_name);
+
+    cs_demo_parser_on_kill(parser, on_kill, &counts);
+    cs_demo_parser_on_round_end(parser, on_round_end, &counts);
+    err = cs_demo_parser_parse_to_end(parser);
+    if (err != CS_DEMO_ERROR_OK)
+        fprintf(stderr, "%s: %s\n", path, cs_demo_error_message(err));
+
+    cs_demo_parser_players(parser, NULL, 0, &count);
+    players = calloc(count, sizeof(*players));
+    if (players && cs_demo_parser_players(parser, players, count, &count) ==
+                       CS_DEMO_ERROR_OK) {
+        for (size_t i = 0; i < count; i++)
+            printf("player %3d  team %d  %s\n", players[i].user_id,
+                   players[i].team, players[i].name);
+    }
+    free(players);
+
+    printf("%d kills in %d rounds\n", counts.kills, counts.rounds);
+    cs_demo_parser_free(parser);
+    return err == CS_DEMO_ERROR_OK ? 0 : 1;
+}
+
+int main(int argc, char **argv)
+{
+    int res = 0;
+
+    check_errors();
+    if (argc > 1)
+        res = parse_demo(argv[1]);
+    if (failures) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return 1;
+    }
+    return res;
+}
@@ -0,0 +1,177 @@
+use cs_demo_parser::common::{EquipmentType, Player, Team, new_grenade_projectile};
+use cs_demo_parser::events::{self, GrenadeEvent};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::grenades::Effect;
+use cs_demo_parser::sendtables2::EntityHandle;
+use support::Value;
+
+mod support;
+
+fn player(user_id: i32, team: Team) -> Player {
+    Player {
+        user_id,
+        entity_id: user_id,
+        name: format!("player{user_id}"),
+        team,
+        ..Default::default()
+    }
+}
+
+fn explode(gs: &mut GameState, thrower: &Player, entity_id: i32) {
+    gs.handle_event(&events::FlashExplode {
+        inner: GrenadeEvent {
+            grenade_type: EquipmentType::Flash,
+            grenade: None,
+            position: Default::default(),
+            thrower: Some(thrower.clone()),
+            grenade_entity_id: entity_id,
+        },
+    });
+}
+
+fn blind(gs: &mut GameState, victim: &Player, attacker: &Player, entity_id: i32, duration: f32) {
+    let mut projectile = new_grenade_projectile();
+    projectile.entity = Some(EntityHandle {
+        index: entity_id,
+        serial: 1,
+    });
+    gs.handle_event(&events::PlayerFlashed {
+        player: Some(victim.clone()),
+        attacker: Some(attacker.clone()),
+        projectile: Some(projectile),
+        flash_duration: duration,
+    });
+}
+
+fn kill(gs: &mut GameState, victim: &Player, killer: &Player, assister: Option<&Player>) {
+    gs.handle_event(&events::Kill {
+        weapon: None,
+        victim: Some(victim.clone()),
+        killer: Some(killer.clone()),
+        assister: assister.cloned(),
+        penetrated_objects: 0,
+        is_headshot: false,
+        assisted_flash: assister.is_some(),
+        attacker_blind: false,
+        no_scope: false,
+        through_smoke: false,
+        distance: 0.0,
+    });
+}
+
+#[test]
+fn attributes_victims_of_simultaneous_flashes() {
+    let mut gs = GameState::default();
+    let a = player(1, Team::Terrorists);
+    let b = player(2, Team::Terrorists);
+    let mate = player(3, Team::Terrorists);
+    let ct1 = player(4, Team::CounterTerrorists);
+    let ct2 = player(5, Team::CounterTerrorists);
+    gs.players_by_user_id.insert(ct1.user_id, ct1.clone());
+
+    gs.set_ingame_tick(100);
+    // Victims of the first flash arrive before its detonation, those of
+    // the second one after.
+    blind(&mut gs, &ct1, &a, 40, 3.0);
+    blind(&mut gs, &mate, &a, 40, 1.5);
+    explode(&mut gs, &a, 40);
+    explode(&mut gs, &b, 41);
+    blind(&mut gs, &ct2, &b, 41, 2.0);
+    gs.handle_event(&events::FrameDone);
+
+    let results = gs.flashes().results();
+    assert_eq!(2, results.len());
+    assert_eq!(Some(1), results[0].thrower.as_ref().map(|t| t.user_id));
+    assert_eq!(
+        vec![(4, false, 3.0), (3, true, 1.5)],
+        results[0]
+            .victims
+            .iter()
+            .map(|v| (v.player.user_id, v.teammate, v.duration))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(3.0, results[0].enemy_blind_time());
+    assert_eq!(1, results[1].victims.len());
+    assert_eq!(5, results[1].victims[0].player.user_id);
+
+    let victim = &gs.players_by_user_id[&4];
+    assert_eq!((3.0, 100), (victim.flash_duration, victim.flash_tick));
+}
+
+#[test]
+fn records_kills_while_blind_and_stats() {
+    let mut gs = GameState::default();
+    let flasher = player(1, Team::Terrorists);
+    let entry = player(2, Team::Terrorists);
+    let ct1 = player(3, Team::CounterTerrorists);
+    let ct2 = player(4, Team::CounterTerrorists);
+    gs.tick_rate = 64.0;
+
+    gs.set_ingame_tick(1000);
+    explode(&mut gs, &flasher, 40);
+    blind(&mut gs, &ct1, &flasher, 40, 2.0);
+    blind(&mut gs, &ct2, &flasher, 40, 0.5);
+    blind(&mut gs, &entry, &flasher, 40, 1.0);
+
+    // One second later only the first victim is still blind.
+    gs.set_ingame_tick(1064);
+    kill(&mut gs, &ct2, &entry, None);
+    kill(&mut gs, &ct1, &entry, Some(&flasher));
+
+    let result = &gs.flashes().results()[0];
+    assert!(result.victims[1].killed_while_blind.is_none());
+    let blind_kill = result.victims[0].killed_while_blind.as_ref().unwrap();
+    assert_eq!(1064, blind_kill.tick);
+    assert_eq!(Some(2), blind_kill.killer.as_ref().map(|k| k.user_id));
+    assert!(blind_kill.flash_assist);
+
+    let stats = gs.flashes().player_stats();
+    assert_eq!(1, stats.len());
+    let s = &stats[0];
+    assert_eq!(
+        (1, 2, 1),
+        (s.flashes, s.enemies_flashed, s.teammates_flashed)
+    );
+    assert_eq!((1, 1), (s.blind_kills, s.flash_assists));
+    assert_eq!(2.5, s.enemy_blind_time);
+    assert!((s.team_flash_rate() - 1.0 / 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn drops_unmatched_victims() {
+    let mut gs = GameState::default();
+    let a = player(1, Team::Terrorists);
+    let ct = player(2, Team::CounterTerrorists);
+    gs.set_ingame_tick(10);
+    blind(&mut gs, &ct, &a, 40, 3.0);
+    gs.handle_event(&events::FrameDone);
+    gs.set_ingame_tick(11);
+    gs.handle_event(&events::FrameDone);
+    explode(&mut gs, &a, 40);
+    assert!(gs.flashes().results()[0].victims.is_empty());
+}
+
+#[test]
+fn reads_the_duration_from_the_victims_pawn() {
+    let mut gs = GameState::default();
+    let t = player(1, Team::Terrorists);
+    let mut ct = player(2, Team::CounterTerrorists);
+    let pawn = support::entity(
+        "CCSPlayerPawn",
+        2,
+        1,
+        &[("m_flFlashDuration", Value::Float(4.5))],
+    );
+    ct.pawn = Some(pawn.handle());
+    gs.add_entity(pawn);
+    gs.players_by_user_id.insert(ct.user_id, ct.clone());
+
+    gs.set_ingame_tick(100);
+    explode(&mut gs, &t, 40);
+    blind(&mut gs, &ct, &t, 40, 2.0);
+
+    assert_eq!(4.5, gs.flashes().results()[0].victims[0].duration);
+    let record = &gs.grenades().records()[0];
+    assert_eq!(Effect::Flashed { duration: 4.5 }, record.affected[0].effect);
+    assert_eq!(4.5, gs.players_by_user_id[&2].flash_duration);
+}
@@ -1,4 +1,4 @@
-use cs_demo_parser::parser::{EntityEvent, Parser};
+use cs_demo_parser::parser::Parser;
 use cs_demo_parser::sendtables::EntityOp;
 use cs_demo_parser::sendtables2::{Class, Entity};
 use std::io::Cursor;
@@ -11,44 +11,36 @@ fn track_active_grenades_and_infernos() {
         class_id: 1,
         name: "CSmokeGrenadeProjectile".into(),
         serializer: None,
+        ..Default::default()
     };
     let grenade = Entity {
         index: 1,
         serial: 1,
         class: g_class,
+        ..Default::default()
     };
-    parser.dispatch_event(EntityEvent {
-        entity: grenade.clone(),
-        op: EntityOp::CREATED,
-    });
+    parser.dispatch_entity(grenade.clone(), EntityOp::CREATED);
     assert_eq!(1, parser.game_state().grenade_projectiles().len());
 
-    parser.dispatch_event(EntityEvent {
-        entity: grenade,
-        op: EntityOp::DELETED,
-    });
+    parser.dispatch_entity(grenade, EntityOp::DELETED);
     assert_eq!(0, parser.game_state().grenade_projectiles().len());
 
     let i_class = Class {
         class_id: 2,
         name: "CInferno".into(),
         serializer: None,
+        ..Default::default()
     };
     let inferno = Entity {
         index: 2,
         serial: 1,
         class: i_class,
+        ..Default::default()
     };
-    parser.dispatch_event(EntityEvent {
-        entity: inferno.clone(),
-        op: EntityOp::CREATED,
-    });
+    parser.dispatch_entity(inferno.clone(), EntityOp::CREATED);
     assert_eq!(1, parser.game_state().infernos().len());
 
-    parser.dispatch_event(EntityEvent {
-        entity: inferno,
-        op: EntityOp::DELETED,
-    });
+    parser.dispatch_entity(inferno, EntityOp::DELETED);
     assert_eq!(0, parser.game_state().infernos().len());
 }
 
@@ -0,0 +1,473 @@
+use cs_demo_parser::common::{
+    Equipment, EquipmentType, GrenadeProjectile, Player, Team, new_grenade_projectile,
+};
+use cs_demo_parser::events::{
+    self, GrenadeEvent, GrenadeProjectileBounce, GrenadeProjectileDestroy, GrenadeProjectileThrow,
+    HitGroup,
+};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::grenades::Effect;
+use cs_demo_parser::parser::{EntityEvent, Parser};
+use cs_demo_parser::sendtables::EntityOp;
+use cs_demo_parser::sendtables::entity::{
+    Entity, FlattenedPropEntry, Property, PropertyValue, Vector,
+};
+use cs_demo_parser::sendtables::propdecoder::SendTableProperty;
+use cs_demo_parser::sendtables::serverclass::ServerClass;
+use cs_demo_parser::sendtables2::EntityHandle;
+use std::sync::Arc;
+use std::time::Duration;
+use support::{Demo, Op, Value};
+
+mod support;
+
+fn vector(x: f64, y: f64, z: f64) -> Vector {
+    Vector { x, y, z }
+}
+
+/// A player at `position` looking at `pitch` and `yaw`.
+fn player(user_id: i32, team: Team, position: Vector, pitch: f32, yaw: f32) -> Player {
+    let prop = |name: &str, float_val: f32| Property {
+        entry: FlattenedPropEntry {
+            name: name.to_string(),
+            prop: SendTableProperty::default(),
+            array_element_prop: None,
+        },
+        value: PropertyValue {
+            float_val,
+            ..Default::default()
+        },
+    };
+    Player {
+        user_id,
+        entity_id: user_id,
+        name: format!("player{user_id}"),
+        team,
+        last_alive_position: position,
+        entity: Some(Entity {
+            id: user_id,
+            serial_num: 0,
+            server_class: Arc::new(ServerClass::default()),
+            props: vec![
+                prop("m_angEyeAngles[0]", pitch),
+                prop("m_angEyeAngles[1]", yaw),
+            ],
+        }),
+        ..Default::default()
+    }
+}
+
+fn weapon(equipment_type: EquipmentType) -> Option<Equipment> {
+    Some(Equipment {
+        equipment_type,
+        ..Default::default()
+    })
+}
+
+fn projectile(index: i32, thrower: &Player, grenade_type: EquipmentType) -> GrenadeProjectile {
+    let mut g = new_grenade_projectile();
+    g.entity = Some(EntityHandle { index, serial: 1 });
+    g.weapon_instance = weapon(grenade_type);
+    g.thrower = Some(thrower.clone());
+    g
+}
+
+fn grenade_event(
+    grenade_type: EquipmentType,
+    thrower: &Player,
+    entity_id: i32,
+    position: Vector,
+) -> GrenadeEvent {
+    GrenadeEvent {
+        grenade_type,
+        grenade: None,
+        position,
+        thrower: Some(thrower.clone()),
+        grenade_entity_id: entity_id,
+    }
+}
+
+fn hurt(
+    player: &Player,
+    attacker: &Player,
+    weapon_type: EquipmentType,
+    damage: i32,
+) -> events::PlayerHurt {
+    events::PlayerHurt {
+        player: Some(player.clone()),
+        attacker: Some(attacker.clone()),
+        health: 100 - damage,
+        armor: 100,
+        weapon: weapon(weapon_type),
+        weapon_string: String::new(),
+        health_damage: damage,
+        armor_damage: 0,
+        health_damage_taken: damage,
+        armor_damage_taken: 0,
+        hit_group: HitGroup::Generic,
+    }
+}
+
+#[test]
+fn records_source2_flashbang_lifecycle() {
+    let mut gs = GameState::default();
+    let thrower = player(1, Team::Terrorists, vector(100.0, 200.0, 64.0), -12.5, 90.0);
+    let enemy = player(2, Team::CounterTerrorists, vector(0.0, 0.0, 0.0), 0.0, 0.0);
+
+    gs.set_ingame_tick(100);
+    gs.handle_event(&events::WeaponFire {
+        shooter: Some(thrower.clone()),
+        weapon: weapon(EquipmentType::Flash),
+    });
+    gs.set_ingame_tick(101);
+    let mut g = projectile(40, &thrower, EquipmentType::Flash);
+    g.track_position(vector(100.0, 200.0, 70.0), 101, Duration::ZERO);
+    gs.handle_event(&GrenadeProjectileThrow {
+        projectile: Some(g.clone()),
+    });
+    gs.set_ingame_tick(120);
+    g.track_position(vector(300.0, 200.0, 10.0), 120, Duration::ZERO);
+    g.bounces = 1;
+    gs.handle_event(&GrenadeProjectileBounce {
+        projectile: Some(g.clone()),
+        bounce_nr: 1,
+    });
+    gs.set_ingame_tick(150);
+    gs.handle_event(&events::PlayerFlashed {
+        player: Some(enemy.clone()),
+        attacker: Some(thrower.clone()),
+        projectile: Some(g.clone()),
+        flash_duration: 2.5,
+    });
+    gs.handle_event(&events::FlashExplode {
+        inner: grenade_event(
+            EquipmentType::Flash,
+            &thrower,
+            40,
+            vector(350.0, 210.0, 20.0),
+        ),
+    });
+    gs.set_ingame_tick(151);
+    gs.handle_event(&GrenadeProjectileDestroy {
+        projectile: Some(g),
+    });
+
+    let records = gs.grenades().records();
+    assert_eq!(1, records.len());
+    let r = &records[0];
+    assert_eq!(EquipmentType::Flash, r.grenade_type);
+    assert_eq!(1, r.round);
+    assert_eq!(Some(1), r.thrower.as_ref().map(|t| t.user_id));
+    assert_eq!(Team::Terrorists, r.thrower_team);
+    let release = r.release.as_ref().unwrap();
+    assert_eq!(100, release.tick);
+    assert_eq!(vector(100.0, 200.0, 64.0), release.position);
+    assert_eq!((-12.5, 90.0), (release.pitch, release.yaw));
+    assert_eq!(40, r.entity_id);
+    assert_eq!(2, r.trajectory.len());
+    assert_eq!(1, r.bounces.len());
+    assert_eq!(120, r.bounces[0].tick);
+    assert_eq!(vector(300.0, 200.0, 10.0), r.bounces[0].position);
+    let detonation = r.detonation.as_ref().unwrap();
+    assert_eq!(150, detonation.tick);
+    assert_eq!(vector(350.0, 210.0, 20.0), detonation.position);
+    assert_eq!(Some(151), r.destroy_tick);
+    assert_eq!(1, r.affected.len());
+    assert_eq!(2, r.affected[0].player.user_id);
+    assert_eq!(Effect::Flashed { duration: 2.5 }, r.affected[0].effect);
+    assert_eq!(0, gs.grenades().in_flight().count());
+}
+
+#[test]
+fn matches_detonations_by_thrower_without_projectiles() {
+    let mut gs = GameState::default();
+    let a = player(1, Team::CounterTerrorists, vector(0.0, 0.0, 0.0), 0.0, 0.0);
+    let b = player(2, Team::CounterTerrorists, vector(10.0, 0.0, 0.0), 0.0, 0.0);
+    let victim = player(3, Team::Terrorists, vector(0.0, 0.0, 0.0), 0.0, 0.0);
+
+    for (tick, thrower, grenade_type) in [
+        (10, &a, EquipmentType::He),
+        (11, &b, EquipmentType::He),
+        (12, &a, EquipmentType::Incendiary),
+        (13, &a, EquipmentType::He),
+    ] {
+        gs.set_ingame_tick(tick);
+        gs.handle_event(&events::WeaponFire {
+            shooter: Some(thrower.clone()),
+            weapon: weapon(grenade_type),
+        });
+    }
+    assert_eq!(4, gs.grenades().in_flight().count());
+
+    // Entity ids of Source 1 events don't match anything, the oldest
+    // grenade of the thrower detonates first.
+    gs.set_ingame_tick(50);
+    gs.handle_event(&events::HeExplode {
+        inner: grenade_event(EquipmentType::He, &a, 7, vector(1.0, 1.0, 1.0)),
+    });
+    gs.handle_event(&hurt(&victim, &a, EquipmentType::He, 40));
+    gs.set_ingame_tick(60);
+    gs.handle_event(&events::FireGrenadeStart {
+        inner: grenade_event(EquipmentType::Incendiary, &a, 8, vector(2.0, 2.0, 2.0)),
+    });
+    gs.handle_event(&hurt(&victim, &a, EquipmentType::Incendiary, 8));
+    gs.handle_event(&hurt(&victim, &a, EquipmentType::Incendiary, 7));
+
+    let records = gs.grenades().records();
+    assert_eq!(Some(50), records[0].detonation.as_ref().map(|d| d.tick));
+    assert!(records[1].detonation.is_none());
+    assert_eq!(Some(60), records[2].detonation.as_ref().map(|d| d.tick));
+    assert!(records[3].detonation.is_none());
+    assert_eq!(
+        Effect::Damaged {
+            health: 40,
+            armor: 0
+        },
+        records[0].affected[0].effect
+    );
+    assert_eq!(1, records[2].affected.len());
+    assert_eq!(
+        Effect::Damaged {
+            health: 15,
+            armor: 0
+        },
+        records[2].affected[0].effect
+    );
+}
+
+#[test]
+fn detonations_without_throw_start_a_record() {
+    let mut gs = GameState::default();
+    let thrower = player(1, Team::Terrorists, vector(0.0, 0.0, 0.0), 0.0, 0.0);
+    gs.handle_event(&events::RoundEnd {
+        message: String::new(),
+        reason: events::RoundEndReason::TargetBombed,
+        winner: Default::default(),
+        winner_state: None,
+        loser_state: None,
+    });
+    gs.handle_event(&events::WeaponFire {
+        shooter: Some(thrower.clone()),
+        weapon: weapon(EquipmentType::Ak47),
+    });
+    gs.handle_event(&events::SmokeStart {
+        inner: grenade_event(EquipmentType::Smoke, &thrower, 12, vector(5.0, 5.0, 0.0)),
+    });
+
+    let records = gs.grenades().records();
+    assert_eq!(1, records.len());
+    assert_eq!(EquipmentType::Smoke, records[0].grenade_type);
+    assert_eq!(2, records[0].round);
+    assert_eq!(12, records[0].entity_id);
+    assert!(records[0].release.is_none());
+    assert_eq!(1, gs.grenades().round(2).count());
+
+    gs.handle_event(&events::MatchStart);
+    assert!(gs.grenades().records().is_empty());
+}
+
+/// Throws a molotov whose projectile ends at `(500, 500, 0)`, then spawns
+/// its inferno, entity 31, and sends the inferno's `inferno_startburn`
+/// before or after it. Source 2 `inferno_startburn` events have no thrower.
+fn burn_molotov(startburn_first: bool) -> GameState {
+    let mut gs = GameState::default();
+    let thrower = player(1, Team::Terrorists, vector(0.0, 0.0, 64.0), 0.0, 0.0);
+    let other = player(2, Team::Terrorists, vector(0.0, 0.0, 64.0), 0.0, 0.0);
+    let victim = player(
+        3,
+        Team::CounterTerrorists,
+        vector(500.0, 500.0, 0.0),
+        0.0,
+        0.0,
+    );
+    gs.players_by_entity_id.insert(1, thrower.clone());
+
+    for (tick, p, entity_id) in [(100, &other, 29), (101, &thrower, 30)] {
+        gs.set_ingame_tick(tick);
+        gs.handle_event(&events::WeaponFire {
+            shooter: Some(p.clone()),
+            weapon: weapon(EquipmentType::Molotov),
+        });
+        let mut g = projectile(entity_id, p, EquipmentType::Molotov);
+        g.track_position(vector(0.0, 0.0, 70.0), tick, Duration::ZERO);
+        gs.handle_event(&GrenadeProjectileThrow {
+            projectile: Some(g),
+        });
+    }
+    gs.set_ingame_tick(140);
+    let mut g = projectile(30, &thrower, EquipmentType::Molotov);
+    g.track_position(vector(500.0, 500.0, 0.0), 140, Duration::ZERO);
+    gs.handle_event(&GrenadeProjectileDestroy {
+        projectile: Some(g),
+    });
+
+    let startburn = events::FireGrenadeStart {
+        inner: GrenadeEvent {
+            grenade_type: EquipmentType::Incendiary,
+            grenade: None,
+            position: vector(500.0, 500.0, 0.0),
+            thrower: None,
+            grenade_entity_id: 31,
+        },
+    };
+    if startburn_first {
+        gs.handle_event(&startburn);
+    }
+    let inferno = support::entity(
+        "CInferno",
+        31,
+        1,
+        &[
+            ("m_hOwnerEntity", Value::Uint((1 << 14) | 1)),
+            ("CBodyComponent.m_cellX", Value::Uint(32)),
+            ("CBodyComponent.m_cellY", Value::Uint(32)),
+            ("CBodyComponent.m_cellZ", Value::Uint(32)),
+            ("CBodyComponent.m_vecX", Value::Float(500.0)),
+            ("CBodyComponent.m_vecY", Value::Float(500.0)),
+            ("CBodyComponent.m_vecZ", Value::Float(0.0)),
+        ],
+    );
+    let ev = EntityEvent {
+        handle: inferno.handle(),
+        class: inferno.class.clone(),
+        op: EntityOp::CREATED,
+    };
+    gs.add_entity(inferno);
+    gs.handle_event(&ev);
+    if !startburn_first {
+        gs.handle_event(&startburn);
+    }
+    gs.handle_event(&hurt(&victim, &thrower, EquipmentType::Incendiary, 10));
+    gs
+}
+
+#[test]
+fn links_source2_infernos_to_their_molotov() {
+    for startburn_first in [false, true] {
+        let gs = burn_molotov(startburn_first);
+        let records = gs.grenades().records();
+        assert_eq!(2, records.len(), "startburn first: {startburn_first}");
+        let molotov = &records[1];
+        assert_eq!(30, molotov.entity_id);
+        assert_eq!(31, molotov.inferno_id);
+        assert_eq!(Some(140), molotov.detonation.as_ref().map(|d| d.tick));
+        assert!(molotov.release.is_some());
+        assert_eq!(
+            Effect::Damaged {
+                health: 10,
+                armor: 0
+            },
+            molotov.affected[0].effect
+        );
+        assert!(records[0].detonation.is_none());
+        assert_eq!(0, records[0].inferno_id);
+    }
+}
+
+fn controller() -> Vec<(&'static str, Value)> {
+    vec![
+        ("m_iTeamNum", Value::Int(3)),
+        ("m_hPlayerPawn", Value::Uint(1 << 14 | 5)),
+    ]
+}
+
+fn pawn(x: f32) -> Vec<(&'static str, Value)> {
+    vec![
+        ("m_angEyeAngles", Value::Vector([-12.5, 45.0, 0.0])),
+        ("m_bIsWalking", Value::Bool(true)),
+        ("m_hGroundEntity", Value::Uint(1 << 14)),
+        ("m_pMovementServices.m_bDucked", Value::Bool(true)),
+        ("CBodyComponent.m_cellX", Value::Uint(32)),
+        ("CBodyComponent.m_cellY", Value::Uint(32)),
+        ("CBodyComponent.m_cellZ", Value::Uint(32)),
+        ("CBodyComponent.m_vecX", Value::Float(x)),
+        ("CBodyComponent.m_vecY", Value::Float(0.0)),
+        ("CBodyComponent.m_vecZ", Value::Float(0.0)),
+    ]
+}
+
+fn flashbang() -> Vec<(&'static str, Value)> {
+    vec![
+        ("m_hThrower", Value::Uint(1 << 14 | 5)),
+        ("m_nBounces", Value::Int(0)),
+        ("CBodyComponent.m_cellX", Value::Uint(32)),
+        ("CBodyComponent.m_cellY", Value::Uint(32)),
+        ("CBodyComponent.m_cellZ", Value::Uint(32)),
+        ("CBodyComponent.m_vecX", Value::Float(100.0)),
+        ("CBodyComponent.m_vecY", Value::Float(0.0)),
+        ("CBodyComponent.m_vecZ", Value::Float(64.0)),
+    ]
+}
+
+#[test]
+fn captures_the_release_of_parsed_throws() {
+    let mut demo = Demo::new(
+        &[
+            ("CCSPlayerController", &controller()),
+            ("CCSPlayerPawn", &pawn(0.0)),
+            ("CFlashbangProjectile", &flashbang()),
+        ],
+        &[("weapon_fire", &["userid", "weapon"])],
+    );
+    let (controller, standing, moved, flashbang) =
+        (controller(), pawn(0.0), pawn(4.0), flashbang());
+    demo.entities(
+        1,
+        &[
+            Op::Create {
+                class_id: 0,
+                index: 1,
+                serial: 1,
+                values: &controller,
+            },
+            Op::Create {
+                class_id: 1,
+                index: 5,
+                serial: 1,
+                values: &standing,
+            },
+        ],
+    );
+    demo.entities(
+        2,
+        &[Op::Update {
+            index: 5,
+            values: &moved,
+        }],
+    );
+    demo.event(
+        2,
+        "weapon_fire",
+        &[Value::Int(1), Value::Str("weapon_flashbang")],
+    );
+    demo.entities(
+        3,
+        &[Op::Create {
+            class_id: 2,
+            index: 40,
+            serial: 1,
+            values: &flashbang,
+        }],
+    );
+    let data = demo.finish();
+    let mut parser = Parser::new(&data[..]);
+    parser.parse_to_end().unwrap();
+
+    // The projectile's thrower is the pawn, which joins the throw of its
+    // player instead of starting a record of its own.
+    let records = parser.game_state().grenades().records();
+    assert_eq!(1, records.len());
+    let r = &records[0];
+    assert_eq!(EquipmentType::Flash, r.grenade_type);
+    assert_eq!(Some(1), r.thrower.as_ref().map(|t| t.user_id));
+    assert_eq!(Team::CounterTerrorists, r.thrower_team);
+    assert_eq!(40, r.entity_id);
+    let release = r.release.as_ref().unwrap();
+    assert_eq!((2, -12.5, 45.0), (release.tick, release.pitch, release.yaw));
+    assert_eq!(
+        (true, true, false),
+        (release.walking, release.ducking, release.airborne)
+    );
+    // 4 units in one tick at 64 ticks per second.
+    assert_eq!(vector(256.0, 0.0, 0.0), release.velocity);
+}
@@ -0,0 +1,302 @@
+use cs_demo_parser::common::{Hostage, HostageState, Player, RescueZone, Team};
+use cs_demo_parser::events::{self, RoundEndReason};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::parser::Parser;
+use cs_demo_parser::proto::msg;
+use cs_demo_parser::sendtables::entity::Vector;
+use std::io::Cursor;
+use support::{Demo, Op, Value};
+
+mod support;
+
+fn vector(x: f64, y: f64, z: f64) -> Vector {
+    Vector { x, y, z }
+}
+
+fn ct(user_id: i32) -> Player {
+    Player {
+        user_id,
+        team: Team::CounterTerrorists,
+        ..Default::default()
+    }
+}
+
+fn hostage(entity_id: i32, state: HostageState, leader: Option<&Player>) -> Hostage {
+    Hostage {
+        entity_id,
+        state,
+        leader: leader.cloned(),
+        last_position: vector(10.0, 10.0, 0.0),
+        health: 100,
+        ..Default::default()
+    }
+}
+
+fn change(gs: &mut GameState, old_state: HostageState, h: Hostage) {
+    gs.handle_event(&events::HostageStateChanged {
+        old_state,
+        new_state: h.state,
+        hostage: Some(h),
+    });
+}
+
+#[test]
+fn maps_states_and_zones() {
+    assert_eq!(HostageState::BeingCarried, HostageState::from(3));
+    assert_eq!(HostageState::Dead, HostageState::from(7));
+    assert_eq!(HostageState::Idle, HostageState::from(42));
+
+    let a = ct(1);
+    let h = hostage(70, HostageState::FollowingPlayer, Some(&a));
+    assert_eq!((HostageState::FollowingPlayer, Some(1)), h.status());
+    assert!(!h.is_rescued());
+
+    let zone = RescueZone {
+        entity_id: 5,
+        min: vector(0.0, 0.0, -10.0),
+        max: vector(100.0, 50.0, 10.0),
+    };
+    assert!(zone.contains(&vector(50.0, 50.0, 0.0)));
+    assert!(!zone.contains(&vector(50.0, 51.0, 0.0)));
+}
+
+#[test]
+fn records_rescues_and_round_outcome() {
+    let mut gs = GameState::default();
+    let a = ct(1);
+    let b = ct(2);
+    gs.hostages
+        .insert(70, hostage(70, HostageState::Idle, None));
+    gs.rescue_zones.insert(
+        5,
+        RescueZone {
+            entity_id: 5,
+            min: vector(0.0, 0.0, -10.0),
+            max: vector(100.0, 100.0, 10.0),
+        },
+    );
+
+    gs.handle_event(&events::RoundStart::default());
+    gs.set_ingame_tick(500);
+    change(
+        &mut gs,
+        HostageState::Idle,
+        hostage(70, HostageState::GettingPickedUp, Some(&a)),
+    );
+    gs.set_ingame_tick(600);
+    change(
+        &mut gs,
+        HostageState::GettingPickedUp,
+        hostage(70, HostageState::BeingCarried, Some(&a)),
+    );
+    // Handed over to a teammate.
+    gs.set_ingame_tick(700);
+    change(
+        &mut gs,
+        HostageState::BeingCarried,
+        hostage(70, HostageState::BeingCarried, Some(&b)),
+    );
+    gs.set_ingame_tick(900);
+    gs.handle_event(&events::HostageRescued {
+        player: Some(b.clone()),
+        hostage: Some(hostage(70, HostageState::Rescued, None)),
+    });
+    gs.handle_event(&events::HostageKilled {
+        killer: Some(a.clone()),
+        hostage: Some(hostage(71, HostageState::Dead, None)),
+    });
+    gs.handle_event(&events::RoundEnd {
+        message: String::new(),
+        reason: RoundEndReason::HostagesRescued,
+        winner: 3,
+        winner_state: None,
+        loser_state: None,
+    });
+
+    let round = gs.hostage_rounds().round(1).unwrap();
+    assert_eq!(Some(500), round.first_reached_tick);
+    assert_eq!(
+        vec![Some(1), Some(1), Some(2)],
+        round
+            .changes
+            .iter()
+            .map(|c| c.leader.as_ref().map(|l| l.user_id))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(1, round.rescues.len());
+    assert_eq!(Some(2), round.rescues[0].player.as_ref().map(|p| p.user_id));
+    assert_eq!(Some(5), round.rescues[0].zone);
+    assert_eq!(71, round.deaths[0].hostage_id);
+    assert!(round.decided_by_hostages());
+    assert_eq!(Team::CounterTerrorists, round.winner);
+
+    // No hostages, no hostage round.
+    let mut gs = GameState::default();
+    gs.handle_event(&events::RoundStart::default());
+    assert!(gs.hostage_rounds().rounds().is_empty());
+}
+
+#[test]
+fn resolves_hostages_of_game_events() {
+    let mut parser = Parser::new(Cursor::new(Vec::<u8>::new()));
+    parser.on_game_event_list(&msg::CsvcMsgGameEventList {
+        descriptors: vec![msg::csvc_msg_game_event_list::DescriptorT {
+            eventid: Some(1),
+            name: Some("hostage_rescued".into()),
+            keys: vec![
+                msg::csvc_msg_game_event_list::KeyT {
+                    r#type: None,
+                    name: Some("userid".into()),
+                },
+                msg::csvc_msg_game_event_list::KeyT {
+                    r#type: None,
+                    name: Some("hostage".into()),
+                },
+            ],
+        }],
+    });
+    let long = |v: i32| msg::csvc_msg_game_event::KeyT {
+        r#type: None,
+        val_string: None,
+        val_float: None,
+        val_long: Some(v),
+        val_short: None,
+        val_byte: None,
+        val_bool: None,
+        val_uint64: None,
+        val_wstring: None,
+    };
+    parser.on_game_event(&msg::CsvcMsgGameEvent {
+        event_name: None,
+        eventid: Some(1),
+        keys: vec![long(3), long(88)],
+        passthrough: None,
+    });
+
+    let rounds = parser.game_state().hostage_rounds().rounds();
+    assert_eq!(1, rounds.len());
+    assert_eq!(88, rounds[0].rescues[0].hostage_id);
+    assert_eq!(None, rounds[0].rescues[0].zone);
+}
+
+#[test]
+fn tracks_source1_hostages_from_game_events() {
+    let mut parser = Parser::new(Cursor::new(Vec::<u8>::new()));
+    let key = |name: &str| msg::csvc_msg_game_event_list::KeyT {
+        r#type: None,
+        name: Some(name.into()),
+    };
+    let descriptor =
+        |id: i32, name: &str, keys: &[&str]| msg::csvc_msg_game_event_list::DescriptorT {
+            eventid: Some(id),
+            name: Some(name.into()),
+            keys: keys.iter().map(|k| key(k)).collect(),
+        };
+    parser.on_game_event_list(&msg::CsvcMsgGameEventList {
+        descriptors: vec![
+            descriptor(1, "round_start", &[]),
+            descriptor(2, "round_end", &["winner", "reason"]),
+            descriptor(3, "hostage_follows", &["userid", "hostage"]),
+            descriptor(4, "hostage_rescued", &["userid", "hostage"]),
+        ],
+    });
+    let long = |v: i32| msg::csvc_msg_game_event::KeyT {
+        val_long: Some(v),
+        ..Default::default()
+    };
+    let mut event = |id: i32, keys: &[i32]| {
+        parser.on_game_event(&msg::CsvcMsgGameEvent {
+            event_name: None,
+            eventid: Some(id),
+            keys: keys.iter().map(|&v| long(v)).collect(),
+            passthrough: None,
+        })
+    };
+    // A round won by elimination, before any hostage was touched.
+    event(1, &[]);
+    event(2, &[2, 9]);
+    event(1, &[]);
+    event(3, &[3, 88]);
+    event(4, &[3, 88]);
+    event(2, &[3, 11]);
+
+    let rounds = parser.game_state().hostage_rounds().rounds();
+    assert_eq!(
+        vec![1, 2],
+        rounds.iter().map(|r| r.round).collect::<Vec<_>>()
+    );
+    assert_eq!(Some(RoundEndReason::TerroristsWin), rounds[0].end_reason);
+    assert!(rounds[0].changes.is_empty());
+    assert_eq!(
+        vec![
+            (HostageState::Idle, HostageState::FollowingPlayer),
+            (HostageState::FollowingPlayer, HostageState::Rescued),
+        ],
+        rounds[1]
+            .changes
+            .iter()
+            .map(|c| (c.old_state, c.new_state))
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(88, rounds[1].rescues[0].hostage_id);
+    assert!(rounds[1].decided_by_hostages());
+    let hostage = &parser.game_state().hostages[&88];
+    assert!(hostage.is_rescued());
+    assert_eq!(None, hostage.handle);
+}
+
+#[test]
+fn resolves_hostage_leaders_through_their_pawn() {
+    let controller = [
+        ("m_iTeamNum", Value::Int(3)),
+        ("m_hPlayerPawn", Value::Uint(1 << 14 | 5)),
+    ];
+    let pawn = [("m_iHealth", Value::Int(100))];
+    // `m_leader` is the handle of the leader's pawn, not of its controller.
+    let hostage = [
+        ("m_nHostageState", Value::Int(4)),
+        ("m_leader", Value::Uint(1 << 14 | 5)),
+        ("m_iHealth", Value::Int(100)),
+    ];
+    let mut demo = Demo::new(
+        &[
+            ("CCSPlayerController", &controller),
+            ("CCSPlayerPawn", &pawn),
+            ("CHostage", &hostage),
+        ],
+        &[],
+    );
+    demo.entities(
+        1,
+        &[
+            Op::Create {
+                class_id: 0,
+                index: 1,
+                serial: 1,
+                values: &controller,
+            },
+            Op::Create {
+                class_id: 1,
+                index: 5,
+                serial: 1,
+                values: &pawn,
+            },
+            Op::Create {
+                class_id: 2,
+                index: 70,
+                serial: 1,
+                values: &hostage,
+            },
+        ],
+    );
+    let data = demo.finish();
+    let mut parser = Parser::new(&data[..]);
+    parser.parse_to_end().unwrap();
+
+    let h = &parser.game_state().hostages[&70];
+    assert_eq!((HostageState::FollowingPlayer, Some(1)), h.status());
+    assert_eq!(
+        Some(Team::CounterTerrorists),
+        h.leader.as_ref().map(|p| p.team)
+    );
+}
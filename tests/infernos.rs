@@ -0,0 +1,239 @@
+use cs_demo_parser::common::{EquipmentType, Fire, Inferno, Player, Team, polygon_area};
+use cs_demo_parser::events::{self, GrenadeEvent, HitGroup};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::sendtables::entity::Vector;
+
+fn vector(x: f64, y: f64) -> Vector {
+    Vector { x, y, z: 0.0 }
+}
+
+fn player(user_id: i32, team: Team, position: Vector) -> Player {
+    Player {
+        user_id,
+        entity_id: user_id,
+        name: format!("player{user_id}"),
+        team,
+        last_alive_position: position,
+        ..Default::default()
+    }
+}
+
+fn grenade(
+    grenade_type: EquipmentType,
+    thrower: &Player,
+    entity_id: i32,
+    position: Vector,
+) -> GrenadeEvent {
+    GrenadeEvent {
+        grenade_type,
+        grenade: None,
+        position,
+        thrower: Some(thrower.clone()),
+        grenade_entity_id: entity_id,
+    }
+}
+
+fn burn(gs: &mut GameState, victim: &Player, attacker: &Player, damage: i32) {
+    gs.handle_event(&events::PlayerHurt {
+        player: Some(victim.clone()),
+        attacker: Some(attacker.clone()),
+        health: 100 - damage,
+        armor: 100,
+        weapon: None,
+        weapon_string: "inferno".to_string(),
+        health_damage: damage,
+        armor_damage: 0,
+        health_damage_taken: damage,
+        armor_damage_taken: 0,
+        hit_group: HitGroup::Generic,
+    });
+}
+
+fn fire(x: f64, y: f64, burning: bool) -> Fire {
+    Fire {
+        position: vector(x, y),
+        burning,
+    }
+}
+
+#[test]
+fn computes_area_of_burning_fires() {
+    assert_eq!(
+        100.0,
+        polygon_area(&[
+            vector(0.0, 0.0),
+            vector(10.0, 0.0),
+            vector(10.0, 10.0),
+            vector(0.0, 10.0)
+        ])
+    );
+
+    let mut inferno = Inferno::default();
+    let fires = vec![
+        fire(0.0, 0.0, true),
+        fire(100.0, 0.0, true),
+        fire(100.0, 100.0, true),
+        fire(0.0, 100.0, false),
+    ];
+    assert!(inferno.set_fires(fires.clone()));
+    assert!(!inferno.set_fires(fires));
+    assert_eq!(3, inferno.flames.len());
+    assert_eq!(5000.0, inferno.area());
+
+    assert!(inferno.set_fires(vec![fire(0.0, 0.0, true)]));
+    assert_eq!(0.0, inferno.area());
+}
+
+#[test]
+fn attributes_fire_damage_and_smoke_extinguishing() {
+    let mut gs = GameState::default();
+    let t = player(1, Team::Terrorists, vector(0.0, 0.0));
+    let ct = player(2, Team::CounterTerrorists, vector(1000.0, 0.0));
+    let ct_mate = player(3, Team::CounterTerrorists, vector(1010.0, 0.0));
+
+    gs.set_ingame_tick(100);
+    gs.handle_event(&events::FireGrenadeStart {
+        inner: grenade(EquipmentType::Molotov, &t, 50, vector(0.0, 0.0)),
+    });
+    gs.handle_event(&events::FireGrenadeStart {
+        inner: grenade(EquipmentType::Incendiary, &ct, 60, vector(1000.0, 0.0)),
+    });
+    // Both of the terrorist's infernos burn, the damage goes to the one
+    // closest to the victim.
+    gs.handle_event(&events::FireGrenadeStart {
+        inner: grenade(EquipmentType::Molotov, &t, 70, vector(2000.0, 0.0)),
+    });
+    let mut near = Inferno::default();
+    near.set_fires(vec![fire(990.0, 0.0, true)]);
+    gs.infernos.insert(70, near);
+
+    gs.set_ingame_tick(150);
+    burn(&mut gs, &ct, &t, 8);
+    burn(&mut gs, &ct, &t, 5);
+    burn(&mut gs, &ct_mate, &ct, 4);
+    assert_eq!(3, gs.fire_timelines().burning().count());
+
+    gs.set_ingame_tick(200);
+    gs.handle_event(&events::SmokeStart {
+        inner: grenade(EquipmentType::Smoke, &ct, 80, vector(50.0, 50.0)),
+    });
+    gs.handle_event(&events::FireGrenadeExpired {
+        inner: grenade(EquipmentType::Molotov, &t, 50, vector(0.0, 0.0)),
+    });
+
+    let timelines = gs.fire_timelines().timelines();
+    assert_eq!(3, timelines.len());
+    let smoked = &timelines[0];
+    assert!(smoked.extinguished_by_smoke);
+    assert_eq!(Some(100), smoked.duration());
+    assert!(smoked.damage.is_empty());
+
+    let own = &timelines[1];
+    assert!(!own.extinguished_by_smoke);
+    assert_eq!(None, own.duration());
+    assert_eq!((0, 4), (own.enemy_damage(), own.team_damage()));
+
+    let near = &timelines[2];
+    assert_eq!(Some(1), near.thrower.as_ref().map(|p| p.user_id));
+    assert_eq!(1, near.damage.len());
+    assert_eq!(13, near.damage[0].health);
+    assert_eq!((13, 0), (near.enemy_damage(), near.team_damage()));
+}
+
+#[test]
+fn marks_fires_started_in_smoke_and_late_damage() {
+    let mut gs = GameState::default();
+    let t = player(1, Team::Terrorists, vector(0.0, 0.0));
+    let ct = player(2, Team::CounterTerrorists, vector(0.0, 0.0));
+
+    gs.handle_event(&events::SmokeStart {
+        inner: grenade(EquipmentType::Smoke, &ct, 80, vector(100.0, 0.0)),
+    });
+    gs.handle_event(&events::FireGrenadeStart {
+        inner: grenade(EquipmentType::Molotov, &t, 50, vector(0.0, 0.0)),
+    });
+    gs.handle_event(&events::SmokeExpired {
+        inner: grenade(EquipmentType::Smoke, &ct, 80, vector(100.0, 0.0)),
+    });
+    gs.handle_event(&events::FireGrenadeStart {
+        inner: grenade(EquipmentType::Molotov, &t, 51, vector(0.0, 0.0)),
+    });
+    gs.handle_event(&events::FireGrenadeExpired {
+        inner: grenade(EquipmentType::Molotov, &t, 51, vector(0.0, 0.0)),
+    });
+    gs.handle_event(&events::FireGrenadeExpired {
+        inner: grenade(EquipmentType::Molotov, &t, 50, vector(0.0, 0.0)),
+    });
+    // Reported after both fires went out.
+    burn(&mut gs, &ct, &t, 3);
+
+    let timelines = gs.fire_timelines().timelines();
+    assert!(timelines[0].extinguished_by_smoke);
+    assert!(!timelines[1].extinguished_by_smoke);
+    assert_eq!(0, gs.fire_timelines().burning().count());
+    assert_eq!(3, timelines[1].enemy_damage());
+}
+
+#[test]
+fn claims_fires_without_thrower_for_the_first_attacker() {
+    let mut gs = GameState::default();
+    let t1 = player(1, Team::Terrorists, vector(0.0, 0.0));
+    let t2 = player(2, Team::Terrorists, vector(0.0, 0.0));
+    let ct = player(3, Team::CounterTerrorists, vector(480.0, 0.0));
+
+    // Source 1 inferno_startburn has no userid.
+    for (entity_id, x) in [(60, 0.0), (61, 500.0)] {
+        gs.handle_event(&events::FireGrenadeStart {
+            inner: GrenadeEvent {
+                thrower: None,
+                ..grenade(EquipmentType::Incendiary, &t1, entity_id, vector(x, 0.0))
+            },
+        });
+    }
+    burn(&mut gs, &ct, &t1, 8);
+    burn(&mut gs, &ct, &t2, 5);
+    burn(&mut gs, &ct, &t1, 2);
+
+    let timelines = gs.fire_timelines().timelines();
+    assert_eq!(Some(2), timelines[0].thrower.as_ref().map(|p| p.user_id));
+    assert_eq!(Some(1), timelines[1].thrower.as_ref().map(|p| p.user_id));
+    assert_eq!(
+        (10, 5),
+        (timelines[1].enemy_damage(), timelines[0].enemy_damage())
+    );
+    assert_eq!(Team::Terrorists, timelines[1].thrower_team);
+}
+
+#[test]
+fn keeps_damage_of_a_later_fire_off_an_earlier_one() {
+    let mut gs = GameState::default();
+    let t = player(1, Team::Terrorists, vector(0.0, 0.0));
+    let ct = player(2, Team::CounterTerrorists, vector(0.0, 0.0));
+
+    // Two molotovs by the same player, the second thrown after the first
+    // went out. Source 1 inferno_startburn has no userid.
+    for (start, end, entity_id) in [(100, 500, 60), (800, 1200, 61)] {
+        gs.ingame_tick = start;
+        gs.handle_event(&events::FireGrenadeStart {
+            inner: GrenadeEvent {
+                thrower: None,
+                ..grenade(EquipmentType::Molotov, &t, entity_id, vector(0.0, 0.0))
+            },
+        });
+        gs.ingame_tick = start + 10;
+        burn(&mut gs, &ct, &t, 7);
+        gs.ingame_tick = end;
+        gs.handle_event(&events::FireGrenadeExpired {
+            inner: grenade(EquipmentType::Molotov, &t, entity_id, vector(0.0, 0.0)),
+        });
+        // Reported in the tick the fire went out.
+        burn(&mut gs, &ct, &t, 1);
+    }
+
+    let timelines = gs.fire_timelines().timelines();
+    assert_eq!(2, timelines.len());
+    for timeline in timelines {
+        assert_eq!(Some(1), timeline.thrower.as_ref().map(|p| p.user_id));
+        assert_eq!(8, timeline.enemy_damage());
+    }
+}
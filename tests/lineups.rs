@@ -0,0 +1,317 @@
+use cs_demo_parser::common::{EquipmentType, PlayerRef, Team};
+use cs_demo_parser::grenades::{AffectedPlayer, Effect, GrenadePoint, GrenadeRecord, Release};
+use cs_demo_parser::lineups::{LineupOptions, Lineups, TargetArea, Technique};
+use cs_demo_parser::parser::Parser;
+use cs_demo_parser::sendtables::entity::Vector;
+use cs_demo_parser::utils::parallel::Merge;
+use std::collections::BTreeMap;
+use support::{Demo, Op, Value};
+
+mod support;
+
+fn vector(x: f64, y: f64, z: f64) -> Vector {
+    Vector { x, y, z }
+}
+
+fn release(x: f64, y: f64, pitch: f32, yaw: f32) -> Release {
+    Release {
+        position: vector(x, y, 0.0),
+        pitch,
+        yaw,
+        ..Default::default()
+    }
+}
+
+fn record(grenade_type: EquipmentType, release: Release, landing: Vector) -> GrenadeRecord {
+    GrenadeRecord {
+        grenade_type,
+        thrower_team: Team::Terrorists,
+        release: Some(release),
+        detonation: Some(GrenadePoint {
+            tick: 0,
+            position: landing,
+        }),
+        ..Default::default()
+    }
+}
+
+fn affected(user_id: i32, team: Team, effect: Effect) -> AffectedPlayer {
+    AffectedPlayer {
+        player: PlayerRef {
+            user_id,
+            ..Default::default()
+        },
+        team,
+        tick: 0,
+        effect,
+    }
+}
+
+#[test]
+fn classifies_techniques() {
+    let options = LineupOptions::default();
+    let moving = |x: f64, y: f64| Release {
+        velocity: vector(x, y, 0.0),
+        ..Default::default()
+    };
+    assert_eq!(Technique::Stand, Technique::of(&moving(0.0, 0.0), &options));
+    assert_eq!(
+        Technique::Crouch,
+        Technique::of(
+            &Release {
+                ducking: true,
+                ..Default::default()
+            },
+            &options
+        )
+    );
+    assert_eq!(
+        Technique::Walk,
+        Technique::of(&moving(80.0, 80.0), &options)
+    );
+    assert_eq!(
+        Technique::Run,
+        Technique::of(&moving(200.0, 100.0), &options)
+    );
+    assert_eq!(
+        Technique::Walk,
+        Technique::of(
+            &Release {
+                walking: true,
+                ..moving(200.0, 0.0)
+            },
+            &options
+        )
+    );
+    assert_eq!(
+        Technique::Jump,
+        Technique::of(
+            &Release {
+                airborne: true,
+                ..moving(200.0, 0.0)
+            },
+            &options
+        )
+    );
+}
+
+#[test]
+fn clusters_throws_into_lineups() {
+    let smoke = vector(1000.0, 500.0, 0.0);
+    let mut flash = record(
+        EquipmentType::Flash,
+        release(0.0, 0.0, -30.0, 0.0),
+        vector(-500.0, 0.0, 0.0),
+    );
+    flash.affected = vec![
+        affected(
+            2,
+            Team::CounterTerrorists,
+            Effect::Flashed { duration: 3.0 },
+        ),
+        affected(3, Team::Terrorists, Effect::Flashed { duration: 1.0 }),
+    ];
+    let records = vec![
+        // The same smoke three times, yaw wrapping around 180 degrees.
+        record(
+            EquipmentType::Smoke,
+            release(10.0, 10.0, -45.0, 179.8),
+            smoke.clone(),
+        ),
+        record(
+            EquipmentType::Smoke,
+            release(14.0, 10.0, -45.2, -179.8),
+            vector(1020.0, 480.0, 0.0),
+        ),
+        record(
+            EquipmentType::Smoke,
+            release(12.0, 10.0, -44.9, 180.0),
+            vector(1400.0, 500.0, 0.0),
+        ),
+        // Different view angle.
+        record(
+            EquipmentType::Smoke,
+            release(10.0, 10.0, -20.0, 180.0),
+            smoke.clone(),
+        ),
+        flash,
+        // Not detonated.
+        GrenadeRecord {
+            detonation: None,
+            ..record(
+                EquipmentType::Smoke,
+                release(10.0, 10.0, -45.0, 180.0),
+                smoke,
+            )
+        },
+    ];
+
+    let mut lineups = Lineups::new();
+    lineups.add("de_test", &records);
+    assert_eq!(5, lineups.throws("de_test").len());
+
+    let options = LineupOptions {
+        targets: BTreeMap::from([(
+            "de_test".to_string(),
+            vec![TargetArea {
+                name: "window".to_string(),
+                center: vector(1000.0, 500.0, 0.0),
+                radius: 100.0,
+            }],
+        )]),
+        ..Default::default()
+    };
+    let found = lineups.lineups("de_test", &options);
+    assert_eq!(4, found.len());
+
+    let best = &found[0];
+    assert_eq!(EquipmentType::Smoke, best.grenade_type);
+    assert_eq!(Technique::Stand, best.technique);
+    assert_eq!(2, best.throws);
+    assert_eq!(vector(12.0, 10.0, 0.0), best.position);
+    assert!((best.yaw.abs() - 180.0).abs() < 0.01);
+    assert_eq!(Some("window"), best.target.as_deref());
+    assert_eq!(2, best.on_target);
+    assert!(
+        best.console_command()
+            .starts_with("setpos 12.00 10.00 0.00;setang -45.10")
+    );
+
+    let flash = found
+        .iter()
+        .find(|l| l.grenade_type == EquipmentType::Flash)
+        .unwrap();
+    assert_eq!((1, 1), (flash.enemies_flashed, flash.teammates_flashed));
+    assert_eq!(None, flash.target);
+    assert_eq!(0.0, flash.on_target_rate());
+
+    let options = LineupOptions {
+        min_throws: 2,
+        ..Default::default()
+    };
+    assert_eq!(1, lineups.lineups("de_test", &options).len());
+    assert!(lineups.lineups("de_other", &options).is_empty());
+}
+
+#[test]
+fn merges_demos() {
+    let smoke = record(
+        EquipmentType::Smoke,
+        release(0.0, 0.0, 0.0, 0.0),
+        vector(0.0, 0.0, 0.0),
+    );
+    let mut a = Lineups::new();
+    a.add("de_test", [&smoke]);
+    let mut b = Lineups::new();
+    b.add("de_test", [&smoke]);
+    b.add("de_other", [&smoke]);
+    a.merge(b);
+    assert_eq!(vec!["de_other", "de_test"], a.maps().collect::<Vec<_>>());
+    let found = a.lineups("de_test", &LineupOptions::default());
+    assert_eq!(1, found.len());
+    assert_eq!(2, found[0].throws);
+}
+
+#[test]
+fn builds_lineups_from_parsed_throws() {
+    let controller = [
+        ("m_iTeamNum", Value::Int(2)),
+        ("m_hPlayerPawn", Value::Uint(1 << 14 | 5)),
+    ];
+    let pawn = [
+        ("m_angEyeAngles", Value::Vector([-30.0, 135.0, 0.0])),
+        ("m_bIsWalking", Value::Bool(false)),
+        ("m_hGroundEntity", Value::Uint(1 << 14)),
+        ("m_pMovementServices.m_bDucked", Value::Bool(true)),
+        ("CBodyComponent.m_cellX", Value::Uint(32)),
+        ("CBodyComponent.m_cellY", Value::Uint(32)),
+        ("CBodyComponent.m_cellZ", Value::Uint(32)),
+        ("CBodyComponent.m_vecX", Value::Float(10.0)),
+        ("CBodyComponent.m_vecY", Value::Float(20.0)),
+        ("CBodyComponent.m_vecZ", Value::Float(0.0)),
+    ];
+    let smoke = [
+        ("m_hThrower", Value::Uint(1 << 14 | 5)),
+        ("m_nBounces", Value::Int(0)),
+        ("CBodyComponent.m_cellX", Value::Uint(32)),
+        ("CBodyComponent.m_cellY", Value::Uint(32)),
+        ("CBodyComponent.m_cellZ", Value::Uint(32)),
+        ("CBodyComponent.m_vecX", Value::Float(10.0)),
+        ("CBodyComponent.m_vecY", Value::Float(20.0)),
+        ("CBodyComponent.m_vecZ", Value::Float(64.0)),
+    ];
+    let mut demo = Demo::new(
+        &[
+            ("CCSPlayerController", &controller),
+            ("CCSPlayerPawn", &pawn),
+            ("CSmokeGrenadeProjectile", &smoke),
+        ],
+        &[
+            ("weapon_fire", &["userid", "weapon"]),
+            (
+                "smokegrenade_detonate",
+                &["userid", "entityid", "x", "y", "z"],
+            ),
+        ],
+    );
+    demo.entities(
+        1,
+        &[
+            Op::Create {
+                class_id: 0,
+                index: 1,
+                serial: 1,
+                values: &controller,
+            },
+            Op::Create {
+                class_id: 1,
+                index: 5,
+                serial: 1,
+                values: &pawn,
+            },
+        ],
+    );
+    demo.event(
+        2,
+        "weapon_fire",
+        &[Value::Int(1), Value::Str("weapon_smokegrenade")],
+    );
+    demo.entities(
+        3,
+        &[Op::Create {
+            class_id: 2,
+            index: 40,
+            serial: 1,
+            values: &smoke,
+        }],
+    );
+    demo.event(
+        100,
+        "smokegrenade_detonate",
+        &[
+            Value::Int(1),
+            Value::Int(40),
+            Value::Float(500.0),
+            Value::Float(-250.0),
+            Value::Float(8.0),
+        ],
+    );
+    let data = demo.finish();
+    let mut parser = Parser::new(&data[..]);
+    parser.parse_to_end().unwrap();
+
+    let mut lineups = Lineups::default();
+    lineups.add("de_mirage", parser.game_state().grenades().records());
+    let lineups = lineups.lineups("de_mirage", &LineupOptions::default());
+    assert_eq!(1, lineups.len());
+    let lineup = &lineups[0];
+    assert_eq!(EquipmentType::Smoke, lineup.grenade_type);
+    assert_eq!(Team::Terrorists, lineup.team);
+    // The pawn stood still and ducked, looking up and to the left.
+    assert_eq!(Technique::Crouch, lineup.technique);
+    assert_eq!(vector(500.0, -250.0, 8.0), lineup.landing);
+    assert_eq!(
+        "setpos 10.00 20.00 0.00;setang -30.00 135.00 0",
+        lineup.console_command()
+    );
+}
@@ -0,0 +1,97 @@
+use cs_demo_parser::maps::{MapMetadata, MapRegistry};
+use cs_demo_parser::sendtables::entity::Vector;
+use cs_demo_parser::utils::DataError;
+use std::path::Path;
+
+const METADATA: &str = "docs/examples/_assets/metadata";
+
+#[test]
+fn builtin_matches_overview_files() {
+    let builtin = MapRegistry::builtin();
+    for name in ["de_nuke", "de_vertigo", "de_train", "de_dust2", "de_mirage"] {
+        let text =
+            std::fs::read_to_string(Path::new(METADATA).join(format!("{name}.txt"))).unwrap();
+        let parsed = MapMetadata::from_overview(&text).unwrap();
+        assert_eq!(builtin.get(name), Some(&parsed), "{name}");
+    }
+    assert!(matches!(
+        MapMetadata::from_overview("\"de_x\" { \"pos_x\" \"1\" }"),
+        Err(DataError::Missing("pos_y"))
+    ));
+    assert!(matches!(
+        MapMetadata::from_overview("\"de_x\" { \"pos_x\" \"1\""),
+        Err(DataError::Parse(_))
+    ));
+}
+
+#[test]
+fn converts_between_world_and_radar() {
+    let maps = MapRegistry::builtin();
+    let dust2 = maps.get("workshop/125438255/de_dust2").unwrap();
+    assert_eq!(
+        (0.0, 0.0),
+        dust2.to_radar(&Vector {
+            x: -2476.0,
+            y: 3239.0,
+            z: 0.0
+        })
+    );
+    let (x, y) = dust2.to_radar(&Vector {
+        x: -276.0,
+        y: 1039.0,
+        z: 100.0,
+    });
+    assert!((x - 500.0).abs() < 1e-9 && (y - 500.0).abs() < 1e-9);
+    let back = dust2.to_world(x, y);
+    assert!((back.x + 276.0).abs() < 1e-9 && (back.y - 1039.0).abs() < 1e-9);
+    assert!(maps.get("de_unknown").is_none());
+}
+
+#[test]
+fn detects_levels_and_loads_overrides() {
+    let mut maps = MapRegistry::builtin();
+    let nuke = maps.get("de_nuke").unwrap();
+    assert_eq!("default", nuke.level(-400.0).unwrap().name);
+    assert_eq!("lower", nuke.level(-700.0).unwrap().name);
+    assert_eq!("default", nuke.level(50000.0).unwrap().name);
+    assert_eq!(
+        "lower",
+        maps.get("de_vertigo").unwrap().level(11500.0).unwrap().name
+    );
+    assert!(maps.get("de_mirage").unwrap().level(0.0).is_none());
+
+    let custom = maps
+        .load_overview(
+            r#"// custom radar
+            "de_mirage"
+            {
+                "pos_x" "-3000" // moved
+                "pos_y" "2000"
+                "scale" "4.5"
+            }"#,
+        )
+        .unwrap();
+    assert_eq!(-3000.0, custom.pos_x);
+    assert_eq!(4.5, maps.get("de_mirage").unwrap().scale);
+
+    let mut loaded = MapRegistry::default();
+    assert_eq!(16, loaded.load_dir(METADATA).unwrap());
+    assert!(loaded.get("de_overpass").is_some());
+}
+
+#[test]
+fn keeps_slashes_of_unquoted_tokens() {
+    let mut maps = MapRegistry::default();
+    let map = maps
+        .load_overview(
+            "/maps/de_slash // workshop copy
+            {
+                material /overviews/de_slash
+                pos_x -100 pos_y 200 scale 2
+            }",
+        )
+        .unwrap();
+    assert_eq!("/maps/de_slash", map.name);
+    assert_eq!(-100.0, map.pos_x);
+    assert_eq!(2.0, map.scale);
+}
@@ -23,3 +23,153 @@ fn ignore_missing_decryption_key_flag() {
     };
     assert!(cfg.ignore_missing_decryption_key);
 }
+
+mod selective_decoding {
+    use cs_demo_parser::events::{ChatMessage, GrenadeProjectileThrow, HostageStateChanged};
+    use cs_demo_parser::parser::{Decoding, Parser, ParserConfig};
+    use cs_demo_parser::proto::msg::cs_demo_parser_rs as proto_msg;
+    use prost::Message;
+
+    fn skip_all() -> ParserConfig {
+        ParserConfig {
+            skip_entities: true,
+            skip_string_tables: true,
+            skip_user_messages: true,
+            skip_voice_data: true,
+            ..Default::default()
+        }
+    }
+
+    /// A Source 2 demo with a frame for each of `msgs`.
+    fn demo(msgs: &[(proto_msg::SvcMessages, Vec<u8>)]) -> Vec<u8> {
+        let mut data = b"PBDEMS2\0".to_vec();
+        data.resize(1072, 0);
+        for (kind, msg) in msgs {
+            data.extend([*kind as u8, 0]);
+            data.push(msg.len() as u8);
+            data.extend(msg);
+        }
+        data.extend([0, 0, 0]);
+        data
+    }
+
+    fn create_table(name: &str) -> (proto_msg::SvcMessages, Vec<u8>) {
+        let msg = proto_msg::CsvcMsgCreateStringTable {
+            name: Some(name.into()),
+            max_entries: Some(8),
+            ..Default::default()
+        };
+        (
+            proto_msg::SvcMessages::SvcCreateStringTable,
+            msg.encode_to_vec(),
+        )
+    }
+
+    /// Updates table `id` with an entry keyed `key` in the next slot.
+    fn update_table(id: i32, key: &str) -> (proto_msg::SvcMessages, Vec<u8>) {
+        // No dictionary, next index, has a key that isn't in the history,
+        // the key, no user data.
+        let mut bits = vec![false, true, true, false];
+        for b in key.bytes().chain([0]) {
+            bits.extend((0..8).map(|i| b >> i & 1 == 1));
+        }
+        bits.push(false);
+        let mut string_data = vec![0u8; bits.len().div_ceil(8)];
+        for (i, _) in bits.iter().enumerate().filter(|(_, b)| **b) {
+            string_data[i / 8] |= 1 << (i % 8);
+        }
+        let msg = proto_msg::CsvcMsgUpdateStringTable {
+            table_id: Some(id),
+            num_changed_entries: Some(1),
+            string_data: Some(string_data),
+        };
+        (
+            proto_msg::SvcMessages::SvcUpdateStringTable,
+            msg.encode_to_vec(),
+        )
+    }
+
+    /// A Source 2 demo with a single frame creating the `downloadables`
+    /// string table.
+    fn demo_with_string_table() -> Vec<u8> {
+        demo(&[create_table("downloadables")])
+    }
+
+    #[test]
+    fn decodes_everything_by_default() {
+        let parser = Parser::new(&[][..]);
+        assert_eq!(Decoding::all(), parser.decoding());
+    }
+
+    #[test]
+    fn handlers_enable_what_they_need() {
+        let mut parser = Parser::with_config(&[][..], skip_all());
+        assert_eq!(Decoding::empty(), parser.decoding());
+
+        parser.register_on_entity(|_| {});
+        assert_eq!(Decoding::ENTITIES, parser.decoding());
+
+        parser.register_sync_event_handler::<ChatMessage, _>(|_, _| {});
+        parser.register_net_message_handler::<proto_msg::CsvcMsgVoiceData, _>(|_| {});
+        assert_eq!(
+            Decoding::ENTITIES | Decoding::USER_MESSAGES | Decoding::VOICE_DATA,
+            parser.decoding()
+        );
+
+        parser.register_on_string_table(|_| {});
+        assert_eq!(Decoding::all(), parser.decoding());
+    }
+
+    #[test]
+    fn handlers_of_entity_events_enable_entities() {
+        let parser = Parser::with_config(&[][..], skip_all());
+        parser.register_event_handler::<GrenadeProjectileThrow, _>(|_| {});
+        assert_eq!(Decoding::ENTITIES, parser.decoding());
+
+        let mut parser = Parser::with_config(&[][..], skip_all());
+        parser.register_sync_event_handler::<HostageStateChanged, _>(|_, _| {});
+        assert_eq!(Decoding::ENTITIES, parser.decoding());
+    }
+
+    #[test]
+    fn any_handlers_enable_everything() {
+        let mut parser = Parser::with_config(&[][..], skip_all());
+        parser.register_sync_any_handler(|_, _| {});
+        assert_eq!(Decoding::all(), parser.decoding());
+    }
+
+    #[test]
+    fn skips_string_tables() {
+        let demo = demo_with_string_table();
+
+        let mut parser = Parser::new(&demo[..]);
+        parser.parse_to_end().unwrap();
+        assert!(parser.string_table("downloadables").is_some());
+
+        let mut parser = Parser::with_config(&demo[..], skip_all());
+        parser.parse_to_end().unwrap();
+        assert!(parser.string_table("downloadables").is_none());
+    }
+
+    #[test]
+    fn keeps_baselines_while_decoding_entities() {
+        let demo = demo(&[
+            create_table("downloadables"),
+            create_table("instancebaseline"),
+            update_table(1, "7"),
+        ]);
+        let cfg = ParserConfig {
+            skip_string_tables: true,
+            ..Default::default()
+        };
+        let mut parser = Parser::with_config(&demo[..], cfg);
+        parser.parse_to_end().unwrap();
+        assert!(parser.string_table("downloadables").is_none());
+        let baselines = parser.string_table("instancebaseline").unwrap();
+        assert_eq!("7", baselines.entries[&0].value);
+
+        let mut parser = Parser::with_config(&demo[..], skip_all());
+        parser.parse_to_end().unwrap();
+        assert!(parser.string_table("instancebaseline").is_none());
+    }
+}
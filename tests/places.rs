@@ -0,0 +1,268 @@
+use cs_demo_parser::common::Player;
+use cs_demo_parser::events;
+use cs_demo_parser::export::{DatasetCollector, DatasetConfig};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::parser::EntityEvent;
+use cs_demo_parser::places::{Callout, MapPlaces, Places};
+use cs_demo_parser::sendtables::EntityOp;
+use cs_demo_parser::sendtables::entity::{
+    Entity, FlattenedPropEntry, Property, PropertyValue, Vector,
+};
+use cs_demo_parser::sendtables::propdecoder::SendTableProperty;
+use cs_demo_parser::sendtables::serverclass::ServerClass;
+use cs_demo_parser::sendtables2::EntityHandle;
+use std::sync::Arc;
+use support::Value;
+
+mod support;
+
+fn vector(x: f64, y: f64, z: f64) -> Vector {
+    Vector { x, y, z }
+}
+
+fn square(name: &str, min: f64, max: f64) -> Callout {
+    Callout {
+        name: name.into(),
+        polygon: vec![[min, min], [max, min], [max, max], [min, max]],
+        ..Default::default()
+    }
+}
+
+fn mirage() -> Places {
+    let mut places = Places::default();
+    places.insert(MapPlaces {
+        map: "de_mirage".into(),
+        callouts: vec![
+            square("Mid", 0.0, 1000.0),
+            square("Jungle", 100.0, 200.0),
+            Callout {
+                z_max: Some(-100.0),
+                ..square("Underpass", 500.0, 600.0)
+            },
+        ],
+    });
+    places
+}
+
+fn player_in(place: Option<&str>, position: Vector) -> Player {
+    let entity = place.map(|place| Entity {
+        id: 0,
+        serial_num: 0,
+        server_class: Arc::new(ServerClass::default()),
+        props: vec![Property {
+            entry: FlattenedPropEntry {
+                name: "m_szLastPlaceName".into(),
+                prop: SendTableProperty::default(),
+                array_element_prop: None,
+            },
+            value: PropertyValue {
+                string_val: place.into(),
+                ..Default::default()
+            },
+        }],
+    });
+    Player {
+        user_id: 1,
+        entity,
+        pawn: Some(EntityHandle {
+            index: 5,
+            serial: 1,
+        }),
+        last_alive_position: position,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn finds_smallest_callout_containing_position() {
+    let places = mirage();
+    assert_eq!(
+        Some("Jungle"),
+        places.place_at("de_mirage", &vector(150.0, 150.0, 0.0))
+    );
+    assert_eq!(
+        Some("Mid"),
+        places.place_at("de_mirage", &vector(300.0, 150.0, 0.0))
+    );
+    // Underpass only below z -100.
+    assert_eq!(
+        Some("Underpass"),
+        places.place_at("de_mirage", &vector(550.0, 550.0, -200.0))
+    );
+    assert_eq!(
+        Some("Mid"),
+        places.place_at("de_mirage", &vector(550.0, 550.0, 0.0))
+    );
+    assert_eq!(None, places.place_at("de_mirage", &vector(-5.0, 5.0, 0.0)));
+    assert_eq!(
+        Some("Mid"),
+        places.place_at("workshop/1/de_mirage", &vector(5.0, 5.0, 0.0))
+    );
+    assert_eq!(None, places.place_at("de_nuke", &vector(5.0, 5.0, 0.0)));
+    assert_eq!(10000.0, square("Jungle", 100.0, 200.0).area());
+}
+
+#[test]
+fn prefers_the_place_name_of_players() {
+    let places = mirage();
+    let sent = player_in(Some("BombsiteB"), vector(150.0, 150.0, 0.0));
+    assert_eq!(Some("BombsiteB".into()), sent.place_name());
+    assert_eq!(
+        Some("BombsiteB".into()),
+        places.place_of("de_mirage", &sent)
+    );
+
+    let missing = player_in(None, vector(150.0, 150.0, 0.0));
+    assert_eq!(None, missing.place_name());
+    assert_eq!(
+        Some("Jungle".into()),
+        places.place_of("de_mirage", &missing)
+    );
+    assert_eq!(
+        None,
+        player_in(Some(""), vector(0.0, 0.0, 0.0)).place_name()
+    );
+    // Without a position there is nothing to look up.
+    let unknown = Player {
+        pawn: None,
+        ..player_in(None, vector(150.0, 150.0, 0.0))
+    };
+    assert_eq!(None, places.place_of("de_mirage", &unknown));
+
+    // Kills and grenades of the dataset get places.
+    let mut gs = GameState::default();
+    gs.match_info.map = Some("de_mirage".into());
+    let mut collector = DatasetCollector::new(
+        "match",
+        DatasetConfig {
+            tick_interval: None,
+            places: Some(Arc::new(places)),
+            ..Default::default()
+        },
+    );
+    collector.handle_event(
+        &events::Kill {
+            weapon: None,
+            victim: Some(missing),
+            killer: Some(sent),
+            assister: None,
+            penetrated_objects: 0,
+            is_headshot: false,
+            assisted_flash: false,
+            attacker_blind: false,
+            no_scope: false,
+            through_smoke: false,
+            distance: 0.0,
+        },
+        &gs,
+    );
+    collector.handle_event(
+        &events::HeExplode {
+            inner: events::GrenadeEvent {
+                grenade_type: Default::default(),
+                grenade: None,
+                position: vector(300.0, 300.0, 0.0),
+                thrower: None,
+                grenade_entity_id: 9,
+            },
+        },
+        &gs,
+    );
+    let dataset = collector.dataset();
+    assert_eq!(Some("BombsiteB"), dataset.kills[0].killer_place.as_deref());
+    assert_eq!(Some("Jungle"), dataset.kills[0].victim_place.as_deref());
+    assert_eq!(Some("Mid"), dataset.grenades[0].place.as_deref());
+}
+
+#[test]
+fn reads_places_of_source2_pawns() {
+    fn update(gs: &mut GameState, entity: cs_demo_parser::sendtables2::Entity) {
+        let ev = EntityEvent {
+            handle: entity.handle(),
+            class: entity.class.clone(),
+            op: EntityOp::CREATED,
+        };
+        gs.add_entity(entity);
+        gs.handle_event(&ev);
+    }
+
+    let mut gs = GameState::default();
+    let pawn_handle = (3 << 14) | 5;
+    update(
+        &mut gs,
+        support::entity(
+            "CCSPlayerController",
+            1,
+            1,
+            &[("m_hPlayerPawn", Value::Uint(pawn_handle))],
+        ),
+    );
+    let player = &gs.players_by_entity_id[&1];
+    assert_eq!(None, player.place_name());
+    assert!(player.has_position());
+
+    update(
+        &mut gs,
+        support::entity(
+            "CCSPlayerPawn",
+            5,
+            3,
+            &[
+                ("m_szLastPlaceName", Value::Str("BombsiteB")),
+                ("CBodyComponent.m_cellX", Value::Uint(32)),
+                ("CBodyComponent.m_cellY", Value::Uint(33)),
+                ("CBodyComponent.m_cellZ", Value::Uint(32)),
+                ("CBodyComponent.m_vecX", Value::Float(100.0)),
+                ("CBodyComponent.m_vecY", Value::Float(50.0)),
+                ("CBodyComponent.m_vecZ", Value::Float(8.0)),
+            ],
+        ),
+    );
+    let player = &gs.players_by_entity_id[&1];
+    assert_eq!(Some("BombsiteB".into()), player.place_name());
+    assert_eq!(vector(100.0, 562.0, 8.0), player.position());
+    let by_user_id = &gs.players_by_user_id[&player.user_id];
+    assert_eq!(Some("BombsiteB".into()), by_user_id.place_name());
+    // The pawn isn't a player of its own.
+    assert_eq!(1, gs.players_by_entity_id.len());
+    assert_eq!(1, gs.players_by_user_id.len());
+}
+
+#[test]
+fn stores_workshop_maps_by_base_name() {
+    let mut places = Places::default();
+    places.insert(MapPlaces {
+        map: "workshop/123/de_nuke".into(),
+        callouts: vec![square("Outside", 0.0, 10.0)],
+    });
+    assert_eq!(vec!["de_nuke"], places.names().collect::<Vec<_>>());
+    assert_eq!(
+        Some("Outside"),
+        places.place_at("de_nuke", &vector(5.0, 5.0, 0.0))
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn loads_callouts_from_json() {
+    let mut places = Places::default();
+    let loaded = places
+        .load_json(
+            r#"{
+                "map": "de_nuke",
+                "callouts": [
+                    { "name": "Ramp", "polygon": [[0, 0], [10, 0], [10, 10]], "z_min": -700 },
+                    { "name": "Outside", "polygon": [[0, 0], [50, 0], [50, 50], [0, 50]] }
+                ]
+            }"#,
+        )
+        .unwrap();
+    assert_eq!(2, loaded.callouts.len());
+    assert_eq!(Some(-700.0), loaded.callouts[0].z_min);
+    assert_eq!(None, loaded.callouts[1].z_max);
+    assert_eq!(
+        Some("Ramp"),
+        places.place_at("de_nuke", &vector(8.0, 2.0, 0.0))
+    );
+    assert!(places.load_json("{\"map\": 1}").is_err());
+}
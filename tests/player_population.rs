@@ -43,16 +43,20 @@ fn player_entity_updates() {
     let mut gs = GameState::default();
     let class = Class {
         class_id: 1,
-        name: "CCSPlayerPawn".into(),
+        name: "CCSPlayerController".into(),
         serializer: None,
+        ..Default::default()
     };
     let ent = Entity {
         index: 2,
         serial: 1,
         class,
+        ..Default::default()
     };
+    gs.add_entity(ent.clone());
     gs.handle_event(&EntityEvent {
-        entity: ent.clone(),
+        handle: ent.handle(),
+        class: ent.class.clone(),
         op: EntityOp::CREATED,
     });
     assert!(gs.players_by_entity_id.contains_key(&2));
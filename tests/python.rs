@@ -0,0 +1,92 @@
+#![cfg(feature = "python")]
+
+use cs_demo_parser::python::DemoParser;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PyList};
+
+fn varint(out: &mut Vec<u8>, mut v: u32) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+/// A Source 2 demo whose frames carry no messages the parser acts on.
+fn source2_demo(frames: usize) -> Vec<u8> {
+    let mut data = b"PBDEMS2\0".to_vec();
+    data.resize(1072, 0);
+    for tick in 0..frames {
+        varint(&mut data, 1);
+        varint(&mut data, tick as u32);
+        varint(&mut data, 4);
+        data.extend([0xab; 4]);
+    }
+    // Stop
+    data.extend([0, 0, 0]);
+    data
+}
+
+fn open<'py>(py: Python<'py>, data: &[u8]) -> Bound<'py, PyAny> {
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("data", PyBytes::new(py, data)).unwrap();
+    py.get_type::<DemoParser>().call((), Some(&kwargs)).unwrap()
+}
+
+#[test]
+fn parses_demos_and_converts_rows() {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let demo = open(py, &source2_demo(3));
+        let frames = PyList::empty(py);
+        let record = py
+            .eval(
+                c"lambda frames: lambda event, tick: frames.append((event, tick))",
+                None,
+                None,
+            )
+            .unwrap()
+            .call1((&frames,))
+            .unwrap();
+        demo.call_method1("on", ("FrameDone", record)).unwrap();
+        demo.call_method0("parse").unwrap();
+        assert_eq!(3, frames.len());
+        let (event, tick): (Bound<'_, PyAny>, i32) = frames.get_item(2).unwrap().extract().unwrap();
+        assert!(event.is_none() || event.is_instance_of::<PyDict>());
+        assert_eq!(2, tick);
+
+        let kills = demo.call_method0("kills").unwrap();
+        assert_eq!(0, kills.downcast::<PyList>().unwrap().len());
+        let ticks = demo.call_method0("ticks").unwrap();
+        assert!(
+            ticks
+                .downcast::<PyDict>()
+                .unwrap()
+                .contains("tick")
+                .unwrap()
+        );
+
+        assert!(demo.call_method1("on", ("Nope",)).is_err());
+    });
+}
+
+#[test]
+fn failed_parses_are_not_cached() {
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let demo = source2_demo(3);
+        let truncated = open(py, &demo[..demo.len() - 6]);
+        let err = truncated.call_method0("parse").unwrap_err();
+        assert!(err.to_string().contains("truncated"));
+        // Later calls fail the same way instead of returning partial tables.
+        assert!(truncated.call_method0("parse").is_err());
+        assert!(truncated.call_method0("kills").is_err());
+
+        let callbacks = open(py, &demo);
+        let fail = py.eval(c"lambda event, tick: 1 / 0", None, None).unwrap();
+        callbacks.call_method1("on", ("FrameDone", fail)).unwrap();
+        let err = callbacks.call_method0("parse").unwrap_err();
+        assert!(err.is_instance_of::<pyo3::exceptions::PyZeroDivisionError>(py));
+        assert!(callbacks.call_method0("rounds").is_err());
+    });
+}
@@ -0,0 +1,175 @@
+use cs_demo_parser::analyzer;
+use cs_demo_parser::common::Team;
+use cs_demo_parser::events;
+use cs_demo_parser::game_state::{GameState, PlayerSnapshot};
+use cs_demo_parser::maps::MapRegistry;
+use cs_demo_parser::parser::Parser;
+use cs_demo_parser::render::heatmap::{Heatmap, HeatmapCollector, HeatmapKind, HeatmapOptions};
+use cs_demo_parser::render::replay::{
+    FrameSmoke, FrameStyle, ReplayOptions, ReplayRecorder, RoundFrame,
+};
+use cs_demo_parser::render::{Canvas, Color, ColorRamp, Surface};
+use cs_demo_parser::sendtables::entity::Vector;
+use support::{Demo, Value};
+
+mod support;
+
+#[test]
+fn heatmap_peaks_at_positions() {
+    let maps = MapRegistry::builtin();
+    let dust2 = maps.get("de_dust2").unwrap();
+    let options = HeatmapOptions {
+        width: 128,
+        height: 128,
+        ..Default::default()
+    };
+    // Radar pixel (512, 512) is pixel (64, 64) of the heatmap.
+    let hot = dust2.to_world(512.0, 512.0);
+    let cold = dust2.to_world(256.0, 256.0);
+    let positions = [hot.clone(), hot.clone(), hot.clone(), cold];
+    let heatmap = Heatmap::new(dust2, &positions, &options);
+    assert_eq!(1.0, heatmap.value(64, 64));
+    assert!((heatmap.value(32, 32) - 1.0 / 3.0).abs() < 1e-9);
+    assert!(heatmap.value(64, 68) < 1.0 && heatmap.value(64, 68) > 0.0);
+    assert_eq!(0.0, heatmap.value(100, 10));
+
+    let image = heatmap.render(None, &options);
+    assert_eq!(Some(Color::rgba(255, 0, 0, 204)), image.pixel(64, 64));
+    assert_eq!(Some(Color::TRANSPARENT), image.pixel(100, 10));
+
+    // Only the upper level of de_nuke.
+    let nuke = maps.get("de_nuke").unwrap();
+    let mut lower = nuke.to_world(512.0, 512.0);
+    lower.z = -700.0;
+    let options = HeatmapOptions {
+        level: Some("default".into()),
+        ..options
+    };
+    assert_eq!(0.0, Heatmap::new(nuke, [&lower], &options).value(64, 64));
+
+    let ramp = ColorRamp::new(vec![(1.0, Color::WHITE), (0.0, Color::BLACK)]);
+    assert_eq!(Color::BLACK, ramp.at(-1.0));
+    assert_eq!(Color::rgb(128, 128, 128), ramp.at(0.5));
+    assert_eq!(Color::WHITE, ramp.at(2.0));
+}
+
+#[test]
+fn canvas_draws_shapes() {
+    let mut canvas = Canvas::new(20, 20, Color::BLACK);
+    canvas.circle((5.0, 5.0), 3.0, Color::WHITE);
+    assert_eq!(Some(Color::WHITE), canvas.pixel(5, 5));
+    assert_eq!(Some(Color::BLACK), canvas.pixel(9, 5));
+
+    canvas.polygon(
+        &[(10.0, 10.0), (20.0, 10.0), (20.0, 20.0)],
+        Color::rgb(255, 0, 0),
+    );
+    assert_eq!(Some(Color::rgb(255, 0, 0)), canvas.pixel(18, 12));
+    assert_eq!(Some(Color::BLACK), canvas.pixel(11, 18));
+
+    // Translucent strokes are blended once, also at joints.
+    let half = Color::rgba(0, 0, 255, 128);
+    canvas.polyline(&[(0.0, 15.5), (5.0, 15.5), (5.0, 19.0)], 1.0, half);
+    assert_eq!(Some(Color::rgb(0, 0, 128)), canvas.pixel(2, 15));
+    assert_eq!(Some(Color::rgb(0, 0, 128)), canvas.pixel(4, 15));
+    assert_eq!(Some(Color::rgb(0, 0, 128)), canvas.pixel(4, 17));
+
+    let radar = Canvas::new(2, 2, Color::rgb(0, 255, 0));
+    let mut big = Canvas::new(8, 8, Color::TRANSPARENT);
+    big.draw_image(&radar);
+    assert_eq!(Some(Color::rgb(0, 255, 0)), big.pixel(7, 7));
+}
+
+#[test]
+fn draws_and_records_round_frames() {
+    let maps = MapRegistry::builtin();
+    let mirage = maps.get("de_mirage").unwrap();
+    let frame = RoundFrame {
+        round: 2,
+        tick: 640,
+        players: vec![
+            PlayerSnapshot {
+                user_id: 1,
+                name: "<ct>".into(),
+                team: Team::CounterTerrorists,
+                is_alive: true,
+                position: mirage.to_world(100.0, 100.0),
+                ..Default::default()
+            },
+            PlayerSnapshot {
+                user_id: 2,
+                name: "t".into(),
+                team: Team::Terrorists,
+                is_alive: false,
+                position: mirage.to_world(900.0, 900.0),
+                ..Default::default()
+            },
+        ],
+        smokes: vec![FrameSmoke {
+            entity_id: 40,
+            position: mirage.to_world(500.0, 500.0),
+        }],
+        ..Default::default()
+    };
+    let style = FrameStyle::default();
+
+    let svg = frame.to_svg(mirage, &style, Some("de_mirage_radar.png"));
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains(r#"<image href="de_mirage_radar.png""#));
+    assert!(svg.contains(r##"<circle cx="100.0" cy="100.0" r="8.0" fill="#5d79ae"/>"##));
+    assert!(svg.contains("&lt;ct&gt;</text>"));
+    assert!(svg.contains(r#"<circle cx="500.0" cy="500.0" r="28.8""#));
+
+    let mut canvas = Canvas::new(256, 256, Color::BLACK);
+    frame.draw(mirage, &mut canvas, &style);
+    assert_eq!(Some(style.counter_terrorists), canvas.pixel(25, 25));
+    assert_ne!(Some(Color::BLACK), canvas.pixel(225, 225));
+
+    let mut recorder = ReplayRecorder::new(ReplayOptions::default());
+    let mut gs = GameState::default();
+    for tick in [0, 8, 16, 20, 40] {
+        gs.set_ingame_tick(tick);
+        recorder.handle_event(&events::FrameDone, &gs);
+    }
+    let ticks: Vec<i32> = recorder.round_frames(1).map(|f| f.tick).collect();
+    assert_eq!(vec![0, 16, 40], ticks);
+}
+
+#[test]
+fn collectors_run_as_analyzers() {
+    let mut demo = Demo::new(
+        &[(
+            "CCSGameRulesProxy",
+            &[("m_pGameRules.m_bWarmupPeriod", Value::Bool(false))],
+        )],
+        &[(
+            "smokegrenade_detonate",
+            &["userid", "entityid", "x", "y", "z"],
+        )],
+    );
+    let detonation = [
+        Value::Int(1),
+        Value::Int(40),
+        Value::Float(100.0),
+        Value::Float(-50.0),
+        Value::Float(8.0),
+    ];
+    demo.event(4, "smokegrenade_detonate", &detonation);
+    let data = demo.finish();
+
+    let mut parser = Parser::new(&data[..]);
+    let analyzers = (
+        HeatmapCollector::new(64),
+        ReplayRecorder::new(ReplayOptions::default()),
+    );
+    let (heatmap, replay) = analyzer::run(&mut parser, analyzers).unwrap();
+    assert_eq!(
+        &[Vector {
+            x: 100.0,
+            y: -50.0,
+            z: 8.0
+        }],
+        heatmap.points(HeatmapKind::Grenades)
+    );
+    assert!(!replay.frames().is_empty());
+}
@@ -0,0 +1,360 @@
+use cs_demo_parser::events::FrameDone;
+use cs_demo_parser::parser::segments::{DemoIndex, SegmentReader};
+use cs_demo_parser::parser::{Parser, ParserConfig, ParserError};
+use cs_demo_parser::proto::msg::{self, SvcMessages, csvc_msg_game_event_list};
+use cs_demo_parser::proto::msgs2::c_demo_string_tables::{ItemsT, TableT};
+use cs_demo_parser::proto::msgs2::{CDemoFullPacket, CDemoPacket, CDemoStringTables};
+use cs_demo_parser::utils::parallel::{self, Merge};
+use prost::Message;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use support::{Op, Value};
+
+mod support;
+
+const FULL_PACKET: u32 = 13;
+const SEND_TABLES: u32 = 4;
+
+fn varint(out: &mut Vec<u8>, mut v: u32) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+/// A Source 2 demo with two setup frames followed by `packets` full
+/// packets, each followed by three regular frames.
+fn source2_demo(packets: u32) -> Vec<u8> {
+    let mut data = b"PBDEMS2\0".to_vec();
+    data.resize(1072, 0);
+    let mut frame = |cmd: u32, tick: u32| {
+        varint(&mut data, cmd);
+        varint(&mut data, tick);
+        varint(&mut data, 4);
+        data.extend([0xff; 4]);
+    };
+    frame(1, 0);
+    frame(1, 0);
+    for p in 0..packets {
+        let tick = 1 + p * 10;
+        frame(FULL_PACKET, tick);
+        for t in 1..4 {
+            frame(1, tick + t);
+        }
+    }
+    data.extend([0, 0, 0]);
+    data
+}
+
+/// Ticks of the frames the parser reports as done.
+fn frame_ticks(parser: &mut Parser<SegmentReader<'_>>) -> Result<Vec<i32>, ParserError> {
+    let ticks = Arc::new(Mutex::new(Vec::new()));
+    let t = ticks.clone();
+    parser.register_sync_event_handler::<FrameDone, _>(move |_, state| {
+        t.lock().unwrap().push(state.ingame_tick());
+    });
+    parser.parse_to_end()?;
+    Ok(std::mem::take(&mut *ticks.lock().unwrap()))
+}
+
+fn sequential_ticks(data: &[u8]) -> Vec<i32> {
+    let ticks = Arc::new(Mutex::new(Vec::new()));
+    let t = ticks.clone();
+    let mut parser = Parser::new(data);
+    parser.register_sync_event_handler::<FrameDone, _>(move |_, state| {
+        t.lock().unwrap().push(state.ingame_tick());
+    });
+    parser.parse_to_end().unwrap();
+    std::mem::take(&mut *ticks.lock().unwrap())
+}
+
+#[test]
+fn indexes_full_packets() {
+    let demo = source2_demo(4);
+    let index = DemoIndex::new(&demo).unwrap();
+    assert_eq!(1072, index.frames_start);
+    assert_eq!(2, index.setup_frames);
+    assert_eq!(
+        vec![1, 11, 21, 31],
+        index
+            .full_packets
+            .iter()
+            .map(|fp| fp.tick)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(index.full_packets[0].offset, index.setup_end);
+
+    let segments = index.segments(3);
+    assert_eq!(3, segments.len());
+    assert_eq!(index.frames_start, segments[0].start);
+    for pair in segments.windows(2) {
+        assert_eq!(pair[0].end, pair[1].start);
+    }
+    assert_eq!(index.end, segments.last().unwrap().end);
+    assert!(segments[1..].iter().all(|s| s.start_tick % 10 == 1));
+
+    // There can't be more segments than full packets allow.
+    assert_eq!(5, index.segments(100).len());
+    assert_eq!(1, index.segments(0).len());
+}
+
+#[test]
+fn segments_reproduce_a_sequential_parse() {
+    let demo = source2_demo(6);
+    let index = DemoIndex::new(&demo).unwrap();
+    let mut merged: Vec<i32> = Vec::new();
+    for segment in index.segments(4) {
+        let mut parser = index
+            .parser(&demo, &segment, ParserConfig::default())
+            .unwrap();
+        let ticks = frame_ticks(&mut parser).unwrap();
+        if segment.index > 0 {
+            assert_eq!(segment.start_tick, ticks[0]);
+        }
+        merged.merge(ticks);
+    }
+    assert_eq!(sequential_ticks(&demo), merged);
+}
+
+#[test]
+fn run_segments_merges_in_order() {
+    let demo = source2_demo(8);
+    let ticks = parallel::run_segments(&demo, &ParserConfig::default(), |parser, _| {
+        frame_ticks(parser)
+    })
+    .unwrap();
+    assert_eq!(sequential_ticks(&demo), ticks);
+}
+
+fn team(num: i32, score: i32) -> Vec<(&'static str, Value)> {
+    vec![
+        ("m_iTeamNum", Value::Int(num)),
+        ("m_iScore", Value::Int(score)),
+    ]
+}
+
+fn rules(rounds: i32) -> Vec<(&'static str, Value)> {
+    vec![("m_pGameRules.m_totalRoundsPlayed", Value::Int(rounds))]
+}
+
+/// Creates the two teams and the game rules with the given scores and
+/// rounds played.
+fn create_entities(t: i32, ct: i32, rounds: i32) -> msg::CsvcMsgPacketEntities {
+    let (t, ct, rules) = (team(2, t), team(3, ct), rules(rounds));
+    support::packet_entities(
+        2,
+        &[
+            Op::Create {
+                class_id: 0,
+                index: 1,
+                serial: 1,
+                values: &t,
+            },
+            Op::Create {
+                class_id: 0,
+                index: 2,
+                serial: 1,
+                values: &ct,
+            },
+            Op::Create {
+                class_id: 1,
+                index: 3,
+                serial: 1,
+                values: &rules,
+            },
+        ],
+    )
+}
+
+/// Updates a team's score and the rounds played.
+fn update_entities(index: i32, num: i32, score: i32, rounds: i32) -> msg::CsvcMsgPacketEntities {
+    let (team, rules) = (team(num, score), rules(rounds));
+    support::packet_entities(
+        2,
+        &[
+            Op::Update {
+                index,
+                values: &team,
+            },
+            Op::Update {
+                index: 3,
+                values: &rules,
+            },
+        ],
+    )
+}
+
+/// A full packet with the `downloadables` table holding `strings`.
+fn full_packet(strings: &[&str], entities: msg::CsvcMsgPacketEntities) -> Vec<u8> {
+    let table = TableT {
+        table_name: Some("downloadables".into()),
+        items: strings
+            .iter()
+            .map(|s| ItemsT {
+                str: Some(s.to_string()),
+                data: None,
+            })
+            .collect(),
+        ..Default::default()
+    };
+    let mut data = Vec::new();
+    let entities = entities.encode_to_vec();
+    varint(&mut data, SvcMessages::SvcPacketEntities as u32);
+    varint(&mut data, entities.len() as u32);
+    data.extend(entities);
+    CDemoFullPacket {
+        string_table: Some(CDemoStringTables {
+            tables: vec![table],
+        }),
+        packet: Some(CDemoPacket { data: Some(data) }),
+    }
+    .encode_to_vec()
+}
+
+/// A Source 2 demo in which the terrorists and counter-terrorists each
+/// win a round, with a full packet before each round.
+fn demo_with_rounds() -> Vec<u8> {
+    let classes = support::classes(&[("CCSTeam", &team(2, 0)), ("CCSGameRulesProxy", &rules(0))]);
+    let list = msg::CsvcMsgGameEventList {
+        descriptors: vec![csvc_msg_game_event_list::DescriptorT {
+            eventid: Some(1),
+            name: Some("round_end".into()),
+            keys: vec![],
+        }],
+    };
+    let round_end = msg::CsvcMsgGameEvent {
+        eventid: Some(1),
+        ..Default::default()
+    };
+    let table = msg::CsvcMsgCreateStringTable {
+        name: Some("downloadables".into()),
+        max_entries: Some(8),
+        ..Default::default()
+    };
+
+    let mut data = b"PBDEMS2\0".to_vec();
+    data.resize(1072, 0);
+    let mut frame = |cmd: u32, tick: u32, msg: Vec<u8>| {
+        varint(&mut data, cmd);
+        varint(&mut data, tick);
+        varint(&mut data, msg.len() as u32);
+        data.extend(msg);
+    };
+    let svc = |kind: SvcMessages| kind as u32;
+    frame(
+        svc(SvcMessages::SvcServerInfo),
+        0,
+        classes.server_info.encode_to_vec(),
+    );
+    frame(SEND_TABLES, 0, classes.serializers);
+    frame(
+        svc(SvcMessages::SvcClassInfo),
+        0,
+        classes.class_info.encode_to_vec(),
+    );
+    frame(
+        svc(SvcMessages::SvcCreateStringTable),
+        0,
+        table.encode_to_vec(),
+    );
+    frame(svc(SvcMessages::SvcGameEventList), 0, list.encode_to_vec());
+
+    frame(
+        FULL_PACKET,
+        1,
+        full_packet(&["a"], create_entities(0, 0, 0)),
+    );
+    let entities = update_entities(2, 3, 1, 1);
+    frame(
+        svc(SvcMessages::SvcPacketEntities),
+        3,
+        entities.encode_to_vec(),
+    );
+    frame(svc(SvcMessages::SvcGameEvent), 3, round_end.encode_to_vec());
+
+    frame(
+        FULL_PACKET,
+        11,
+        full_packet(&["a"], create_entities(0, 1, 1)),
+    );
+    let entities = update_entities(1, 2, 1, 2);
+    frame(
+        svc(SvcMessages::SvcPacketEntities),
+        12,
+        entities.encode_to_vec(),
+    );
+    frame(
+        svc(SvcMessages::SvcGameEvent),
+        12,
+        round_end.encode_to_vec(),
+    );
+    data.extend([0, 0, 0]);
+    data
+}
+
+/// The state a parser ends with: rounds, scores, entities and the
+/// `downloadables` string table.
+fn final_state<R: std::io::Read>(
+    parser: &mut Parser<R>,
+) -> (i32, i32, i32, Vec<String>, Vec<String>) {
+    parser.parse_to_end().unwrap();
+    let state = parser.game_state();
+    let mut entities: Vec<String> = state
+        .entities()
+        .iter()
+        .map(|e| format!("{} {} {:?}", e.index, e.class.name, e.property_values()))
+        .collect();
+    entities.sort();
+    let table = parser.string_table("downloadables").unwrap();
+    let strings = (0..table.entries.len() as i32)
+        .map(|i| table.entries[&i].value.clone())
+        .collect();
+    (
+        state.total_rounds_played(),
+        state.team_terrorists().score,
+        state.team_counter_terrorists().score,
+        entities,
+        strings,
+    )
+}
+
+#[test]
+fn segments_start_from_the_full_packet() {
+    let demo = demo_with_rounds();
+    let sequential = final_state(&mut Parser::new(&demo[..]));
+    assert_eq!(2, sequential.0);
+    assert_eq!((1, 1), (sequential.1, sequential.2));
+    assert_eq!(3, sequential.3.len());
+    assert_eq!(vec!["a".to_string()], sequential.4);
+
+    let index = DemoIndex::new(&demo).unwrap();
+    // One segment per full packet, the last starts at tick 11.
+    let segments = index.segments(8);
+    assert_eq!(3, segments.len());
+    assert_eq!(11, segments[2].start_tick);
+    let mut parser = index
+        .parser(&demo, &segments[2], ParserConfig::default())
+        .unwrap();
+    assert_eq!(sequential, final_state(&mut parser));
+}
+
+#[test]
+fn rejects_other_files() {
+    assert!(matches!(
+        DemoIndex::new(b"version https://git-lfs.github.com/spec/v1\n"),
+        Err(ParserError::GitLfsPointer)
+    ));
+    assert!(matches!(
+        DemoIndex::new(b"not a demo"),
+        Err(ParserError::InvalidFileType)
+    ));
+}
+
+#[test]
+fn merges_stats_by_key() {
+    let mut kills: HashMap<&str, (u32, Vec<i32>)> = HashMap::from([("a", (1, vec![10]))]);
+    kills.merge(HashMap::from([("a", (2, vec![20])), ("b", (1, vec![30]))]));
+    assert_eq!((3, vec![10, 20]), kills["a"]);
+    assert_eq!((1, vec![30]), kills["b"]);
+}
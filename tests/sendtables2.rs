@@ -1,9 +1,10 @@
 use cs_demo_parser::proto::msg::csvc_msg_class_info::ClassT;
 use cs_demo_parser::proto::msg::{CsvcMsgClassInfo, CsvcMsgPacketEntities};
-use cs_demo_parser::sendtables2::Parser;
+use cs_demo_parser::sendtables::EntityOp;
 use cs_demo_parser::sendtables2::proto::{
     CsvcMsgFlattenedSerializer, ProtoFlattenedSerializerFieldT, ProtoFlattenedSerializerT,
 };
+use cs_demo_parser::sendtables2::{EntityHandle, EntityStore, PacketEntitiesError, Parser};
 use prost::Message;
 
 fn encode_var(mut value: u32) -> Vec<u8> {
@@ -16,6 +17,13 @@ fn encode_var(mut value: u32) -> Vec<u8> {
     out
 }
 
+// Huffman codes of the field path ops used below.
+const PLUS_ONE: &str = "0";
+const PLUS_TWO: &str = "1110";
+const PUSH_ZERO: &str = "110110001101";
+const POP_ALL_BUT_ONE_PLUS_ONE: &str = "110011";
+const FINISH: &str = "10";
+
 struct BitWriter {
     buf: Vec<u8>,
     bit_val: u64,
@@ -64,6 +72,17 @@ impl BitWriter {
         self.write_bits(value, 6);
     }
 
+    /// Writes a field path op given as the bits of its Huffman code.
+    fn write_code(&mut self, code: &str) {
+        for c in code.chars() {
+            self.write_bits((c == '1') as u32, 1);
+        }
+    }
+
+    fn write_signed(&mut self, value: i32) {
+        self.write_var(((value << 1) ^ (value >> 31)) as u32);
+    }
+
     fn into_bytes(mut self) -> Vec<u8> {
         if self.bit_count > 0 {
             self.buf.push(self.bit_val as u8);
@@ -122,6 +141,7 @@ fn test_parse_flattened_serializer() {
 
 #[test]
 fn test_class_info_and_entities() {
+    let mut entities = EntityStore::new();
     let mut p = Parser::new();
     p.on_server_info(&cs_demo_parser::proto::msg::CsvcMsgServerInfo {
         max_classes: Some(1),
@@ -154,7 +174,7 @@ fn test_class_info_and_entities() {
         }],
     };
     p.on_class_info(&class_msg);
-    assert!(p.entity(0).is_none());
+    assert!(entities.by_index(0).is_none());
 
     // baseline (unused in this test but call for coverage)
     p.set_instance_baseline(0, vec![]);
@@ -166,6 +186,7 @@ fn test_class_info_and_entities() {
     w.write_bits(0, p.class_id_size()); // class id
     w.write_bits(1, 17); // serial
     w.write_var(0); // length
+    w.write_code(FINISH); // no fields
     let data = w.into_bytes();
     let pe_msg = CsvcMsgPacketEntities {
         max_entries: Some(1),
@@ -176,12 +197,413 @@ fn test_class_info_and_entities() {
         delta_from: Some(0),
         entity_data: Some(data),
     };
-    let events = p.parse_packet_entities(&pe_msg);
+    let events = p.parse_packet_entities(&pe_msg, &mut entities).events;
     assert_eq!(events.len(), 1);
-    assert!(
-        events[0]
-            .1
-            .contains(cs_demo_parser::sendtables::EntityOp::CREATED)
+    assert!(events[0].2.contains(EntityOp::CREATED));
+    assert!(entities.get(events[0].0).is_some());
+}
+
+/// A parser knowing the single class `Test` with the given `(type, name)`
+/// fields and the serializers of its nested tables.
+fn parser_with_class(fields: &[(&str, &str)], nested: &[(&str, &[(&str, &str)])]) -> Parser {
+    let mut symbols: Vec<String> = vec!["Test".into()];
+    let mut sym = |s: &str| {
+        symbols.iter().position(|x| x == s).unwrap_or_else(|| {
+            symbols.push(s.into());
+            symbols.len() - 1
+        }) as i32
+    };
+    let mut all_fields = Vec::new();
+    let mut serializers = Vec::new();
+    let tables = std::iter::once(("Test", fields)).chain(nested.iter().copied());
+    for (name, fields) in tables {
+        let mut fields_index = Vec::new();
+        for &(ty, var) in fields {
+            let child = nested.iter().find(|(n, _)| ty.trim_end_matches('*') == *n);
+            fields_index.push(all_fields.len() as i32);
+            all_fields.push(ProtoFlattenedSerializerFieldT {
+                var_type_sym: Some(sym(ty)),
+                var_name_sym: Some(sym(var)),
+                bit_count: (ty == "float32").then_some(8),
+                low_value: Some(0.0),
+                high_value: Some(100.0),
+                encode_flags: None,
+                field_serializer_name_sym: child.map(|(n, _)| sym(n)),
+                field_serializer_version: None,
+                send_node_sym: None,
+                var_encoder_sym: None,
+                var_serializer_sym: None,
+            });
+        }
+        serializers.push(ProtoFlattenedSerializerT {
+            serializer_name_sym: Some(sym(name)),
+            serializer_version: Some(0),
+            fields_index,
+        });
+    }
+    let msg = CsvcMsgFlattenedSerializer {
+        serializers,
+        symbols,
+        fields: all_fields,
+    };
+
+    let mut p = Parser::new();
+    p.on_server_info(&cs_demo_parser::proto::msg::CsvcMsgServerInfo {
+        max_classes: Some(1),
+        ..Default::default()
+    });
+    let mut buf = Vec::new();
+    msg.encode(&mut buf).unwrap();
+    let mut data = encode_var(buf.len() as u32);
+    data.extend(buf);
+    p.parse_packet(&data).unwrap();
+    p.on_class_info(&CsvcMsgClassInfo {
+        create_on_client: Some(false),
+        classes: vec![ClassT {
+            class_id: Some(0),
+            data_table_name: None,
+            class_name: Some("Test".into()),
+        }],
+    });
+    p
+}
+
+fn packet_entities(updates: i32, w: BitWriter) -> CsvcMsgPacketEntities {
+    CsvcMsgPacketEntities {
+        max_entries: Some(1),
+        updated_entries: Some(updates),
+        is_delta: Some(false),
+        update_baseline: Some(false),
+        baseline: Some(0),
+        delta_from: Some(0),
+        entity_data: Some(w.into_bytes()),
+    }
+}
+
+fn create_header(w: &mut BitWriter, p: &Parser) {
+    w.write_ubit_var(0);
+    w.write_bits(2, 2);
+    w.write_bits(0, p.class_id_size());
+    w.write_bits(1, 17);
+    w.write_var(0);
+}
+
+#[test]
+fn test_entity_properties_are_decoded_lazily() {
+    let mut entities = EntityStore::new();
+    let mut p = parser_with_class(
+        &[
+            ("int32", "m_iHealth"),
+            ("bool", "m_bAlive"),
+            ("float32", "m_flStamina"),
+            ("CUtlString", "m_szName"),
+        ],
+        &[],
+    );
+
+    let mut w = BitWriter::new();
+    create_header(&mut w, &p);
+    for _ in 0..4 {
+        w.write_code(PLUS_ONE);
+    }
+    w.write_code(FINISH);
+    w.write_signed(100);
+    w.write_bits(1, 1);
+    w.write_bits(255, 8);
+    for b in b"ak\0" {
+        w.write_byte(*b);
+    }
+    // A second entity following the first one must be found.
+    w.write_ubit_var(0);
+    w.write_bits(2, 2);
+    w.write_bits(0, p.class_id_size());
+    w.write_bits(2, 17);
+    w.write_var(0);
+    w.write_code(PLUS_TWO);
+    w.write_code(FINISH);
+    w.write_bits(0, 1);
+    let events = p
+        .parse_packet_entities(&packet_entities(2, w), &mut entities)
+        .events;
+    assert_eq!(2, events.len());
+
+    let ent = entities.by_index(0).unwrap();
+    assert_eq!(4, ent.properties.len());
+    assert_eq!(0, ent.properties.decoded());
+    assert_eq!(100, ent.property_value("m_iHealth").unwrap().int_val);
+    assert_eq!(1, ent.properties.decoded());
+    assert!(ent.property_value("m_bAlive").unwrap().bool_val());
+    let stamina = ent.property_value("m_flStamina").unwrap().float_val;
+    assert!((stamina - 100.0).abs() < 1e-3);
+    assert_eq!("ak", ent.property_value("m_szName").unwrap().string_val);
+    assert!(ent.property_value("m_iMissing").is_none());
+
+    let other = entities.by_index(1).unwrap();
+    assert_eq!(2, other.serial);
+    assert!(!other.property_value("m_bAlive").unwrap().bool_val());
+    assert!(other.property_value("m_iHealth").is_none());
+}
+
+#[test]
+fn test_entity_updates_keep_earlier_values() {
+    let mut entities = EntityStore::new();
+    let mut p = parser_with_class(&[("int32", "m_iHealth"), ("bool", "m_bAlive")], &[]);
+    p.set_lazy_properties(false);
+
+    let mut w = BitWriter::new();
+    create_header(&mut w, &p);
+    w.write_code(PLUS_ONE);
+    w.write_code(PLUS_ONE);
+    w.write_code(FINISH);
+    w.write_signed(100);
+    w.write_bits(1, 1);
+    let created = p
+        .parse_packet_entities(&packet_entities(1, w), &mut entities)
+        .events;
+    let earlier = entities.get(created[0].0).unwrap().clone();
+    assert_eq!(2, earlier.properties.decoded());
+
+    let mut w = BitWriter::new();
+    w.write_ubit_var(0);
+    w.write_bits(0, 2); // update
+    w.write_code(PLUS_ONE);
+    w.write_code(FINISH);
+    w.write_signed(-5);
+    let updated = p
+        .parse_packet_entities(&packet_entities(1, w), &mut entities)
+        .events;
+    assert!(updated[0].2.contains(EntityOp::UPDATED));
+
+    let ent = entities.by_index(0).unwrap();
+    assert_eq!(-5, ent.property_value("m_iHealth").unwrap().int_val);
+    assert!(ent.property_value("m_bAlive").unwrap().bool_val());
+    // Copies taken earlier keep their values.
+    assert_eq!(100, earlier.property_value("m_iHealth").unwrap().int_val);
+}
+
+#[test]
+fn test_nested_tables_and_vectors() {
+    let mut entities = EntityStore::new();
+    let mut p = parser_with_class(
+        &[
+            ("CUtlVector< int32 >", "m_list"),
+            ("CBodyComponent", "CBodyComponent"),
+        ],
+        &[(
+            "CBodyComponent",
+            &[("uint32", "m_cellX"), ("uint32", "m_cellY")],
+        )],
+    );
+
+    let mut w = BitWriter::new();
+    create_header(&mut w, &p);
+    w.write_code(PLUS_ONE); // m_list
+    w.write_code(PUSH_ZERO); // m_list.0000
+    w.write_code(PLUS_ONE); // m_list.0001
+    w.write_code(POP_ALL_BUT_ONE_PLUS_ONE); // CBodyComponent
+    w.write_code(PUSH_ZERO); // CBodyComponent.m_cellX
+    w.write_code(PLUS_ONE); // CBodyComponent.m_cellY
+    w.write_code(FINISH);
+    w.write_var(2);
+    w.write_signed(7);
+    w.write_signed(-7);
+    w.write_bits(1, 1);
+    w.write_var(32);
+    w.write_var(300);
+    p.parse_packet_entities(&packet_entities(1, w), &mut entities);
+
+    let ent = entities.by_index(0).unwrap();
+    assert_eq!(2, ent.property_value("m_list").unwrap().int_val);
+    assert_eq!(7, ent.property_value("m_list.0000").unwrap().int_val);
+    assert_eq!(-7, ent.property_value("m_list.0001").unwrap().int_val);
+    assert_eq!(
+        300,
+        ent.property_value("CBodyComponent.m_cellY")
+            .unwrap()
+            .int_val
+    );
+    let names: Vec<String> = ent.property_values().into_iter().map(|(n, _)| n).collect();
+    assert_eq!(
+        vec![
+            "m_list",
+            "m_list.0000",
+            "m_list.0001",
+            "CBodyComponent",
+            "CBodyComponent.m_cellX",
+            "CBodyComponent.m_cellY",
+        ],
+        names
+    );
+}
+
+#[test]
+fn test_unknown_field_paths_stop_decoding() {
+    let mut entities = EntityStore::new();
+    let mut p = parser_with_class(&[("int32", "m_iHealth")], &[]);
+    let mut w = BitWriter::new();
+    create_header(&mut w, &p);
+    w.write_code(PLUS_TWO); // there is no second field
+    w.write_code(FINISH);
+    w.write_signed(1);
+    let parsed = p.parse_packet_entities(&packet_entities(1, w), &mut entities);
+    assert!(parsed.events.is_empty());
+    assert_eq!(
+        Some(PacketEntitiesError::FieldMismatch {
+            index: 0,
+            class: "Test".into()
+        }),
+        parsed.error
+    );
+    assert!(entities.by_index(0).is_none());
+
+    // Updates of entities that don't exist.
+    let mut w = BitWriter::new();
+    w.write_ubit_var(4);
+    w.write_bits(0, 2);
+    let parsed = p.parse_packet_entities(&packet_entities(1, w), &mut entities);
+    assert_eq!(
+        Some(PacketEntitiesError::MissingEntity { index: 4 }),
+        parsed.error
+    );
+}
+
+#[test]
+fn test_entities_of_other_classes_are_skipped() {
+    let mut entities = EntityStore::new();
+    let mut p = parser_with_class(&[("int32", "m_iHealth")], &[]);
+    p.set_entity_classes(Some(vec!["CCSPlayerPawn".into()]));
+
+    let mut w = BitWriter::new();
+    create_header(&mut w, &p);
+    w.write_code(PLUS_ONE);
+    w.write_code(FINISH);
+    w.write_signed(100);
+    // The entity following the skipped one must still be found.
+    w.write_ubit_var(0);
+    w.write_bits(2, 2);
+    w.write_bits(0, p.class_id_size());
+    w.write_bits(2, 17);
+    w.write_var(0);
+    w.write_code(FINISH);
+    let parsed = p.parse_packet_entities(&packet_entities(2, w), &mut entities);
+    assert!(parsed.events.is_empty());
+    assert_eq!(None, parsed.error);
+    assert!(entities.is_empty());
+
+    // Updates and deletions of skipped entities aren't errors.
+    let mut w = BitWriter::new();
+    w.write_ubit_var(0);
+    w.write_bits(0, 2);
+    w.write_code(PLUS_ONE);
+    w.write_code(FINISH);
+    w.write_signed(-5);
+    w.write_ubit_var(0);
+    w.write_bits(3, 2);
+    let parsed = p.parse_packet_entities(&packet_entities(2, w), &mut entities);
+    assert!(parsed.events.is_empty());
+    assert_eq!(None, parsed.error);
+}
+
+#[test]
+fn test_handles_of_deleted_entities_stop_resolving() {
+    let mut entities = EntityStore::new();
+    let mut p = parser_with_class(&[("int32", "m_iHealth")], &[]);
+    let mut w = BitWriter::new();
+    create_header(&mut w, &p);
+    w.write_code(FINISH);
+    let created = p
+        .parse_packet_entities(&packet_entities(1, w), &mut entities)
+        .events;
+    let old = created[0].0;
+    assert!(entities.contains(old));
+
+    // Delete entity 0, then create a new one with another serial in its slot.
+    let mut w = BitWriter::new();
+    w.write_ubit_var(0);
+    w.write_bits(3, 2);
+    let deleted = p
+        .parse_packet_entities(&packet_entities(1, w), &mut entities)
+        .events;
+    assert!(deleted[0].2.contains(EntityOp::DELETED));
+    assert!(entities.get(old).is_none());
+
+    let mut w = BitWriter::new();
+    w.write_ubit_var(0);
+    w.write_bits(2, 2);
+    w.write_bits(0, p.class_id_size());
+    w.write_bits(2, 17);
+    w.write_var(0);
+    w.write_code(FINISH);
+    p.parse_packet_entities(&packet_entities(1, w), &mut entities);
+
+    assert!(entities.get(old).is_none());
+    let new = EntityHandle {
+        index: 0,
+        serial: 2,
+    };
+    assert_eq!(2, entities.get(new).unwrap().serial);
+    assert_eq!(1, entities.len());
+    assert!(entities.remove(0).is_some());
+    assert!(entities.is_empty());
+}
+
+#[test]
+fn test_replaced_entities_are_deleted() {
+    let mut entities = EntityStore::new();
+    let mut p = parser_with_class(&[("int32", "m_iHealth")], &[]);
+    let mut w = BitWriter::new();
+    create_header(&mut w, &p);
+    w.write_code(FINISH);
+    let old = p
+        .parse_packet_entities(&packet_entities(1, w), &mut entities)
+        .events[0]
+        .0;
+
+    // Another serial takes the slot without a delete.
+    let class_id_size = p.class_id_size();
+    let create = |serial| {
+        let mut w = BitWriter::new();
+        w.write_ubit_var(0);
+        w.write_bits(2, 2);
+        w.write_bits(0, class_id_size);
+        w.write_bits(serial, 17);
+        w.write_var(0);
+        w.write_code(FINISH);
+        packet_entities(1, w)
+    };
+    let events = p.parse_packet_entities(&create(2), &mut entities).events;
+    let ops: Vec<_> = events.iter().map(|(h, _, op)| (*h, *op)).collect();
+    let new = EntityHandle {
+        index: 0,
+        serial: 2,
+    };
+    assert_eq!(
+        vec![
+            (old, EntityOp::DELETED | EntityOp::LEFT),
+            (new, EntityOp::CREATED | EntityOp::ENTERED),
+        ],
+        ops
+    );
+    assert_eq!(1, entities.len());
+
+    // The same entity created again, e.g. by a full packet, isn't deleted.
+    let events = p.parse_packet_entities(&create(2), &mut entities).events;
+    assert_eq!(1, events.len());
+    assert_eq!(
+        (new, EntityOp::CREATED | EntityOp::ENTERED),
+        (events[0].0, events[0].2)
+    );
+}
+
+#[test]
+fn test_entity_handle_from_raw() {
+    let raw = (5 << 14) | 42;
+    assert_eq!(
+        Some(EntityHandle {
+            index: 42,
+            serial: 5
+        }),
+        EntityHandle::from_raw(raw)
     );
-    assert!(p.entity(0).is_some());
+    assert_eq!(None, EntityHandle::from_raw((1 << 24) - 1));
 }
@@ -0,0 +1,103 @@
+#![cfg(feature = "serde")]
+
+use cs_demo_parser::common::{Equipment, EquipmentType, Player, Team};
+use cs_demo_parser::events;
+use cs_demo_parser::game_state::{GameState, GameStateSnapshot};
+use cs_demo_parser::parser::Parser;
+use cs_demo_parser::serialization::EventStreamWriter;
+use std::io::Cursor;
+use std::sync::Arc;
+
+fn player(user_id: i32, steam_id64: u64) -> Player {
+    Player {
+        user_id,
+        steam_id64,
+        name: format!("player{user_id}"),
+        team: Team::Terrorists,
+        flash_duration: 2.5,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn events_reference_players_compactly() {
+    let kill = events::Kill {
+        weapon: Some(Equipment {
+            equipment_type: EquipmentType::Ak47,
+            original_string: "ak47".into(),
+            ..Default::default()
+        }),
+        victim: Some(player(2, 76561198000000002)),
+        killer: Some(player(1, 76561198000000001)),
+        assister: None,
+        penetrated_objects: 0,
+        is_headshot: true,
+        assisted_flash: false,
+        attacker_blind: false,
+        no_scope: false,
+        through_smoke: false,
+        distance: 12.5,
+    };
+
+    let json = serde_json::to_value(&kill).unwrap();
+    assert_eq!(
+        serde_json::json!({
+            "user_id": 1,
+            "steam_id64": 76561198000000001u64,
+            "name": "player1",
+        }),
+        json["killer"]
+    );
+    assert!(json["assister"].is_null());
+    assert_eq!("Ak47", json["weapon"]["equipment_type"]);
+
+    let back: events::Kill = serde_json::from_value(json).unwrap();
+    let victim = back.victim.unwrap();
+    assert_eq!(76561198000000002, victim.steam_id64);
+    assert_eq!(Team::Unassigned, victim.team);
+    assert!(back.is_headshot);
+}
+
+#[test]
+fn snapshot_roundtrip() {
+    let mut gs = GameState::default();
+    gs.players_by_user_id
+        .insert(1, player(1, 76561198000000001));
+    gs.set_ingame_tick(128);
+
+    let snapshot = gs.snapshot();
+    assert_eq!(128, snapshot.tick);
+    assert_eq!(1, snapshot.players.len());
+    assert_eq!(2.5, snapshot.players[0].flash_duration);
+
+    let json = serde_json::to_string(&snapshot).unwrap();
+    let back: GameStateSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(snapshot, back);
+}
+
+#[test]
+fn writes_ndjson_event_stream() {
+    let mut parser = Parser::new(Cursor::new(Vec::<u8>::new()));
+    let stream = EventStreamWriter::register(&mut parser, Vec::new(), |name| name != "FrameDone");
+    parser.dispatch_event(events::FrameDone);
+    parser.dispatch_event(events::MatchStart);
+    parser.dispatch_event(events::ChatMessage {
+        sender: None,
+        text: "gg".into(),
+        is_chat_all: true,
+    });
+
+    drop(parser);
+    let mut stream = Arc::try_unwrap(stream).ok().unwrap().into_inner().unwrap();
+    assert!(stream.take_error().is_none());
+    let out = String::from_utf8(stream.into_inner()).unwrap();
+    let lines: Vec<serde_json::Value> = out
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(2, lines.len());
+    assert_eq!("MatchStart", lines[0]["type"]);
+    assert_eq!("ChatMessage", lines[1]["type"]);
+    assert_eq!("gg", lines[1]["data"]["text"]);
+    assert_eq!(0, lines[1]["tick"]);
+}
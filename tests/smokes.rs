@@ -0,0 +1,152 @@
+use cs_demo_parser::common::{EquipmentType, Player, Team};
+use cs_demo_parser::events::{self, GrenadeEvent};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::parser::EntityEvent;
+use cs_demo_parser::sendtables::EntityOp;
+use cs_demo_parser::sendtables::entity::Vector;
+use support::Value;
+
+mod support;
+
+fn vector(x: f64, y: f64, z: f64) -> Vector {
+    Vector { x, y, z }
+}
+
+fn smoke_event(entity_id: i32, position: Vector) -> GrenadeEvent {
+    GrenadeEvent {
+        grenade_type: EquipmentType::Smoke,
+        grenade: None,
+        position,
+        thrower: None,
+        grenade_entity_id: entity_id,
+    }
+}
+
+fn player(user_id: i32, position: Vector) -> Player {
+    Player {
+        user_id,
+        team: Team::Terrorists,
+        last_alive_position: position,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reads_voxels_of_smoke_projectiles() {
+    let mut voxels = vec![0u8; 1000];
+    voxels[0] = 0b0000_0001;
+    voxels[999] = 0b1000_0000;
+    let smoke = support::entity(
+        "CSmokeGrenadeProjectile",
+        40,
+        1,
+        &[
+            ("CBodyComponent.m_cellX", Value::Uint(32)),
+            ("CBodyComponent.m_cellY", Value::Uint(32)),
+            ("CBodyComponent.m_cellZ", Value::Uint(32)),
+            ("CBodyComponent.m_vecX", Value::Float(100.0)),
+            ("CBodyComponent.m_vecY", Value::Float(0.0)),
+            ("CBodyComponent.m_vecZ", Value::Float(0.0)),
+            ("m_bDidSmokeEffect", Value::Bool(true)),
+            ("m_nSmokeEffectTickBegin", Value::Int(90)),
+            ("m_VoxelFrameData", Value::Bytes(voxels)),
+        ],
+    );
+    let ev = EntityEvent {
+        handle: smoke.handle(),
+        class: smoke.class.clone(),
+        op: EntityOp::CREATED,
+    };
+    let mut gs = GameState::default();
+    gs.set_ingame_tick(100);
+    gs.add_entity(smoke);
+    gs.handle_event(&ev);
+
+    let volume = &gs.smokes().volumes()[0];
+    assert_eq!(90, volume.start_tick);
+    assert_eq!(vector(100.0, 0.0, 0.0), volume.position);
+    // Only the corner voxels of the grid around the detonation are
+    // occupied, 20 voxels of 16 units along each axis.
+    assert!(volume.contains(&vector(-55.0, -155.0, -155.0), 100));
+    assert!(volume.contains(&vector(255.0, 155.0, 155.0), 100));
+    assert!(!volume.contains(&vector(100.0, 0.0, 0.0), 100));
+}
+
+#[test]
+fn tracks_smokes_without_voxels() {
+    let mut gs = GameState::default();
+    gs.set_ingame_tick(100);
+    gs.handle_event(&events::SmokeStart {
+        inner: smoke_event(40, vector(0.0, 0.0, 0.0)),
+    });
+
+    let killer = player(1, vector(-500.0, 0.0, 0.0));
+    let victim = player(2, vector(500.0, 0.0, 0.0));
+    let kill = events::Kill {
+        weapon: None,
+        victim: Some(victim),
+        killer: Some(killer),
+        assister: None,
+        penetrated_objects: 0,
+        is_headshot: false,
+        assisted_flash: false,
+        attacker_blind: false,
+        no_scope: false,
+        through_smoke: true,
+        distance: 1000.0,
+    };
+    let smokes = gs.smokes();
+    assert!(smokes.contains(&vector(100.0, 0.0, 50.0), 120));
+    assert!(!smokes.contains(&vector(200.0, 0.0, 50.0), 120));
+    assert!(!smokes.contains(&vector(100.0, 0.0, 50.0), 99));
+    assert!(smokes.kill_through_smoke(&kill, 120));
+    assert!(!smokes.blocks(&vector(-500.0, 300.0, 0.0), &vector(500.0, 300.0, 0.0), 120));
+
+    gs.set_ingame_tick(1250);
+    gs.handle_event(&events::SmokeExpired {
+        inner: smoke_event(40, vector(0.0, 0.0, 0.0)),
+    });
+    let smokes = gs.smokes();
+    assert_eq!(Some(1250), smokes.volumes()[0].end_tick);
+    assert!(!smokes.kill_through_smoke(&kill, 1300));
+    assert_eq!(0, smokes.at(1300).count());
+}
+
+#[test]
+fn keeps_expired_smokes_of_game_events_expired() {
+    let smoke = support::entity(
+        "CSmokeGrenadeProjectile",
+        40,
+        1,
+        &[
+            ("m_bDidSmokeEffect", Value::Bool(true)),
+            ("m_nSmokeEffectTickBegin", Value::Int(98)),
+        ],
+    );
+    let ev = EntityEvent {
+        handle: smoke.handle(),
+        class: smoke.class.clone(),
+        op: EntityOp::CREATED,
+    };
+    let mut gs = GameState::default();
+    gs.set_ingame_tick(100);
+    gs.handle_event(&events::SmokeStart {
+        inner: smoke_event(40, vector(0.0, 0.0, 0.0)),
+    });
+    gs.set_ingame_tick(1250);
+    gs.handle_event(&events::SmokeExpired {
+        inner: smoke_event(40, vector(0.0, 0.0, 0.0)),
+    });
+    // The projectile is only updated after the smoke expired.
+    gs.set_ingame_tick(1260);
+    gs.add_entity(smoke);
+    gs.handle_event(&ev);
+
+    let volumes = gs.smokes().volumes();
+    assert_eq!(1, volumes.len());
+    assert_eq!(
+        (100, Some(1250)),
+        (volumes[0].start_tick, volumes[0].end_tick)
+    );
+    assert_eq!(0, gs.smokes().at(1300).count());
+}
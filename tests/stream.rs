@@ -0,0 +1,103 @@
+use cs_demo_parser::events::FrameDone;
+use cs_demo_parser::parser::ParserError;
+use cs_demo_parser::parser::stream::{DemoStream, FrameStatus};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+fn varint(out: &mut Vec<u8>, mut v: u32) {
+    while v >= 0x80 {
+        out.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    out.push(v as u8);
+}
+
+/// A Source 2 demo whose frames carry no messages the parser acts on.
+fn source2_demo(payloads: &[usize]) -> Vec<u8> {
+    let mut data = b"PBDEMS2\0".to_vec();
+    data.resize(1072, 0);
+    for (tick, &len) in payloads.iter().enumerate() {
+        varint(&mut data, 1);
+        varint(&mut data, tick as u32);
+        varint(&mut data, len as u32);
+        data.extend(std::iter::repeat_n(0xab, len));
+    }
+    // Stop
+    data.extend([0, 0, 0]);
+    data
+}
+
+fn counting(stream: &mut DemoStream) -> Arc<AtomicUsize> {
+    let frames = Arc::new(AtomicUsize::new(0));
+    let f = frames.clone();
+    stream
+        .parser_mut()
+        .register_sync_event_handler::<FrameDone, _>(move |_, _| {
+            f.fetch_add(1, Ordering::SeqCst);
+        });
+    frames
+}
+
+#[test]
+fn parses_frames_as_they_arrive() {
+    let demo = source2_demo(&[10, 200 * 1024, 0, 300]);
+    let mut stream = DemoStream::new();
+    stream.set_expected_size(demo.len() as u64);
+    let frames = counting(&mut stream);
+
+    let mut statuses = Vec::new();
+    for chunk in demo.chunks(4096) {
+        stream.push(chunk);
+        statuses.push(stream.parse_available().unwrap());
+        assert!(stream.bytes_parsed() <= stream.bytes_received());
+    }
+    assert_eq!(Some(&FrameStatus::Finished), statuses.last());
+    assert!(
+        statuses[..statuses.len() - 1]
+            .iter()
+            .all(|s| *s == FrameStatus::Incomplete)
+    );
+    assert_eq!(4, frames.load(Ordering::SeqCst));
+    assert_eq!(1.0, stream.progress());
+    assert_eq!("PBDEMS2", stream.parser().header().unwrap().filestamp);
+}
+
+#[test]
+fn waits_for_incomplete_frames() {
+    let demo = source2_demo(&[1000]);
+    let mut stream = DemoStream::new();
+    let frames = counting(&mut stream);
+
+    stream.push(&demo[..1072 + 500]);
+    assert_eq!(FrameStatus::Incomplete, stream.parse_next_frame().unwrap());
+    assert_eq!(0, frames.load(Ordering::SeqCst));
+
+    stream.push(&demo[1072 + 500..]);
+    assert_eq!(FrameStatus::Parsed, stream.parse_next_frame().unwrap());
+    assert_eq!(FrameStatus::Finished, stream.parse_next_frame().unwrap());
+    assert_eq!(1, frames.load(Ordering::SeqCst));
+    assert!(stream.is_finished());
+}
+
+#[test]
+fn truncated_input_is_an_error() {
+    let demo = source2_demo(&[1000]);
+    let mut stream = DemoStream::new();
+    stream.push(&demo[..1200]);
+    assert_eq!(FrameStatus::Incomplete, stream.parse_available().unwrap());
+    stream.finish();
+    assert!(matches!(
+        stream.parse_available(),
+        Err(ParserError::UnexpectedEndOfDemo)
+    ));
+}
+
+#[test]
+fn rejects_non_demos_early() {
+    let mut stream = DemoStream::new();
+    stream.push(b"version https://git-lfs.github.com/spec/v1\n");
+    assert!(matches!(
+        stream.parse_available(),
+        Err(ParserError::GitLfsPointer)
+    ));
+}
@@ -0,0 +1,483 @@
+//! Source 2 entities for tests, built by encoding property values and
+//! decoding them with the entity decoder demos go through.
+
+#![allow(dead_code)]
+
+use cs_demo_parser::proto::msg::csvc_msg_class_info::ClassT;
+use cs_demo_parser::proto::msg::{
+    CsvcMsgClassInfo, CsvcMsgGameEvent, CsvcMsgGameEventList, CsvcMsgPacketEntities,
+    CsvcMsgServerInfo, SvcMessages, csvc_msg_game_event, csvc_msg_game_event_list,
+};
+use cs_demo_parser::sendtables2::proto::{
+    CsvcMsgFlattenedSerializer, ProtoFlattenedSerializerFieldT, ProtoFlattenedSerializerT,
+};
+use cs_demo_parser::sendtables2::{Entity, EntityStore, Parser};
+use prost::Message;
+
+/// A property value and the field type it's sent as.
+#[derive(Clone, Debug)]
+pub enum Value {
+    Int(i32),
+    /// Also used for entity handles such as `m_hOwnerEntity`.
+    Uint(u32),
+    Float(f32),
+    Bool(bool),
+    Str(&'static str),
+    /// A `Vector`, also used for angles such as `m_angEyeAngles`.
+    Vector([f32; 3]),
+    /// A `CUtlVector< uint8 >`, e.g. `m_VoxelFrameData`.
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    fn field_type(&self) -> String {
+        match self {
+            | Value::Int(_) => "int32".into(),
+            | Value::Uint(_) => "uint32".into(),
+            | Value::Float(_) => "float32".into(),
+            | Value::Bool(_) => "bool".into(),
+            | Value::Str(_) => "CUtlString".into(),
+            | Value::Vector(_) => "Vector".into(),
+            | Value::Bytes(_) => "CUtlVector< uint8 >".into(),
+        }
+    }
+}
+
+// Huffman codes of the field path ops used below.
+const PLUS_ONE: &str = "0";
+const PUSH_ZERO: &str = "110110001101";
+const POP_ALL_BUT_ONE_PLUS_ONE: &str = "110011";
+const FINISH: &str = "10";
+
+#[derive(Default)]
+struct BitWriter {
+    buf: Vec<u8>,
+    bit_val: u64,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, mut value: u32, mut n: u32) {
+        while n > 0 {
+            let take = (8 - self.bit_count).min(n);
+            let mask = (1u32 << take) - 1;
+            self.bit_val |= ((value & mask) as u64) << self.bit_count;
+            self.bit_count += take;
+            value >>= take;
+            n -= take;
+            if self.bit_count == 8 {
+                self.buf.push(self.bit_val as u8);
+                self.bit_val = 0;
+                self.bit_count = 0;
+            }
+        }
+    }
+
+    fn write_var(&mut self, mut value: u32) {
+        while value >= 0x80 {
+            self.write_bits((value & 0x7f) | 0x80, 8);
+            value >>= 7;
+        }
+        self.write_bits(value, 8);
+    }
+
+    fn write_ubit_var(&mut self, value: u32) {
+        match value {
+            | 0..16 => self.write_bits(value, 6),
+            | 16..256 => {
+                self.write_bits(value & 15 | 16, 6);
+                self.write_bits(value >> 4, 4);
+            },
+            | _ => {
+                self.write_bits(value & 15 | 32, 6);
+                self.write_bits(value >> 4, 8);
+            },
+        }
+    }
+
+    fn write_code(&mut self, code: &str) {
+        for c in code.chars() {
+            self.write_bits((c == '1') as u32, 1);
+        }
+    }
+
+    fn write_value(&mut self, value: &Value) {
+        match value {
+            | Value::Int(v) => self.write_var(((v << 1) ^ (v >> 31)) as u32),
+            | Value::Uint(v) => self.write_var(*v),
+            | Value::Float(v) => self.write_bits(v.to_bits(), 32),
+            | Value::Bool(v) => self.write_bits(*v as u32, 1),
+            | Value::Str(s) => {
+                for b in s.bytes().chain([0]) {
+                    self.write_bits(b as u32, 8);
+                }
+            },
+            | Value::Vector(xs) => {
+                for x in xs {
+                    self.write_bits(x.to_bits(), 32);
+                }
+            },
+            | Value::Bytes(bytes) => {
+                for b in bytes {
+                    self.write_var(*b as u32);
+                }
+            },
+        }
+    }
+
+    fn append(&mut self, other: BitWriter) {
+        for b in other.buf {
+            self.write_bits(b as u32, 8);
+        }
+        self.write_bits(other.bit_val as u32, other.bit_count);
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.buf.push(self.bit_val as u8);
+        }
+        self.buf
+    }
+}
+
+/// Top level fields of a class, in order. Properties named like
+/// `CBodyComponent.m_cellX` go into a nested table behind a pointer.
+enum Field<'a> {
+    Simple(&'a str, &'a Value),
+    Component(&'a str, Vec<(&'a str, &'a Value)>),
+}
+
+fn fields<'a>(values: &'a [(&'a str, Value)]) -> Vec<Field<'a>> {
+    let mut fields: Vec<Field> = Vec::new();
+    for (name, value) in values {
+        match name.split_once('.') {
+            | Some((table, field)) => {
+                let existing = fields.iter_mut().find_map(|f| match f {
+                    | Field::Component(t, fields) if t == &table => Some(fields),
+                    | _ => None,
+                });
+                match existing {
+                    | Some(nested) => nested.push((field, value)),
+                    | None => fields.push(Field::Component(table, vec![(field, value)])),
+                }
+            },
+            | None => fields.push(Field::Simple(name, value)),
+        }
+    }
+    fields
+}
+
+fn serializer(classes: &[(&str, Vec<Field>)]) -> CsvcMsgFlattenedSerializer {
+    let mut symbols: Vec<String> = Vec::new();
+    let mut sym = |s: &str| {
+        symbols.iter().position(|x| x == s).unwrap_or_else(|| {
+            symbols.push(s.into());
+            symbols.len() - 1
+        }) as i32
+    };
+    let mut all_fields = Vec::new();
+    let mut field = |ty: &str, var: &str, child: Option<&str>| {
+        all_fields.push(ProtoFlattenedSerializerFieldT {
+            var_type_sym: Some(sym(ty)),
+            var_name_sym: Some(sym(var)),
+            field_serializer_name_sym: child.map(&mut sym),
+            ..Default::default()
+        });
+        all_fields.len() as i32 - 1
+    };
+    let mut serializers = Vec::new();
+    for (class, fields) in classes {
+        let mut top = Vec::new();
+        for f in fields {
+            match f {
+                | Field::Simple(name, value) => top.push(field(&value.field_type(), name, None)),
+                | Field::Component(table, nested) => {
+                    let fields_index = nested
+                        .iter()
+                        .map(|(name, value)| field(&value.field_type(), name, None))
+                        .collect();
+                    top.push(field(&format!("{table}*"), table, Some(table)));
+                    serializers.push((table.to_string(), fields_index));
+                },
+            }
+        }
+        serializers.push((class.to_string(), top));
+    }
+    let serializers = serializers
+        .into_iter()
+        .map(|(name, fields_index)| ProtoFlattenedSerializerT {
+            serializer_name_sym: Some(sym(&name)),
+            serializer_version: Some(0),
+            fields_index,
+        })
+        .collect();
+    CsvcMsgFlattenedSerializer {
+        serializers,
+        symbols,
+        fields: all_fields,
+    }
+}
+
+/// Messages declaring classes, each named with the property values of one
+/// of its entities, which determine the types of its fields. Class ids
+/// follow the order of `classes`.
+pub struct Classes {
+    pub server_info: CsvcMsgServerInfo,
+    /// A length prefixed `CSVCMsg_FlattenedSerializer`, the content of a
+    /// `DEM_SendTables` frame.
+    pub serializers: Vec<u8>,
+    pub class_info: CsvcMsgClassInfo,
+}
+
+pub fn classes(classes: &[(&str, &[(&str, Value)])]) -> Classes {
+    let with_fields: Vec<(&str, Vec<Field>)> = classes
+        .iter()
+        .map(|(class, values)| (*class, fields(values)))
+        .collect();
+    let msg = serializer(&with_fields).encode_to_vec();
+    let mut serializers = Vec::new();
+    prost::encoding::encode_varint(msg.len() as u64, &mut serializers);
+    serializers.extend(msg);
+    Classes {
+        server_info: CsvcMsgServerInfo {
+            max_classes: Some(classes.len() as i32),
+            ..Default::default()
+        },
+        serializers,
+        class_info: CsvcMsgClassInfo {
+            create_on_client: Some(false),
+            classes: classes
+                .iter()
+                .enumerate()
+                .map(|(id, (class, _))| ClassT {
+                    class_id: Some(id as i32),
+                    data_table_name: None,
+                    class_name: Some(class.to_string()),
+                })
+                .collect(),
+        },
+    }
+}
+
+/// A change of an entity in a `PacketEntities` message. Values must be
+/// those the entity's class was declared with, in the same order.
+pub enum Op<'a> {
+    Create {
+        class_id: u32,
+        index: i32,
+        serial: i32,
+        values: &'a [(&'a str, Value)],
+    },
+    Update {
+        index: i32,
+        values: &'a [(&'a str, Value)],
+    },
+}
+
+/// A `PacketEntities` message with `ops`, in order of their indices, for a
+/// demo with `class_count` classes.
+pub fn packet_entities(class_count: usize, ops: &[Op]) -> CsvcMsgPacketEntities {
+    let class_id_size = (class_count as f32).log2().floor() as u32 + 1;
+    let mut w = BitWriter::default();
+    let mut last = -1;
+    for op in ops {
+        let (index, values) = match op {
+            | Op::Create { index, values, .. } | Op::Update { index, values } => (*index, values),
+        };
+        w.write_ubit_var((index - last - 1) as u32);
+        last = index;
+        match op {
+            | Op::Create {
+                class_id, serial, ..
+            } => {
+                // Create, class id, serial, unused varint.
+                w.write_bits(2, 2);
+                w.write_bits(*class_id, class_id_size);
+                w.write_bits(*serial as u32, 17);
+                w.write_var(0);
+            },
+            | Op::Update { .. } => w.write_bits(0, 2),
+        }
+        write_fields(&mut w, &fields(values));
+    }
+    CsvcMsgPacketEntities {
+        max_entries: Some(last + 1),
+        updated_entries: Some(ops.len() as i32),
+        entity_data: Some(w.into_bytes()),
+        ..Default::default()
+    }
+}
+
+/// Writes the field paths of `fields`, then their values.
+fn write_fields(w: &mut BitWriter, fields: &[Field]) {
+    let mut v = BitWriter::default();
+    let mut nested = false;
+    for f in fields {
+        w.write_code(if nested {
+            POP_ALL_BUT_ONE_PLUS_ONE
+        } else {
+            PLUS_ONE
+        });
+        let elements: Vec<Value> = match f {
+            | Field::Simple(_, Value::Bytes(bytes)) => {
+                bytes.iter().map(|b| Value::Uint(*b as u32)).collect()
+            },
+            | Field::Simple(_, value) => {
+                v.write_value(value);
+                nested = false;
+                continue;
+            },
+            | Field::Component(_, fields) => fields.iter().map(|(_, v)| (*v).clone()).collect(),
+        };
+        // The table's pointer or the vector's length, then its elements.
+        match f {
+            | Field::Component(..) => v.write_value(&Value::Bool(true)),
+            | Field::Simple(..) => v.write_value(&Value::Uint(elements.len() as u32)),
+        }
+        for (i, value) in elements.iter().enumerate() {
+            w.write_code(if i == 0 { PUSH_ZERO } else { PLUS_ONE });
+            v.write_value(value);
+        }
+        nested = true;
+    }
+    w.write_code(FINISH);
+    w.append(v);
+}
+
+/// An entity of `class` with the given property values.
+pub fn entity(class: &str, index: i32, serial: i32, values: &[(&str, Value)]) -> Entity {
+    let classes = classes(&[(class, values)]);
+    let mut p = Parser::new();
+    p.on_server_info(&classes.server_info);
+    p.parse_packet(&classes.serializers).unwrap();
+    p.on_class_info(&classes.class_info);
+    let msg = packet_entities(
+        1,
+        &[Op::Create {
+            class_id: 0,
+            index,
+            serial,
+            values,
+        }],
+    );
+    let mut store = EntityStore::new();
+    let parsed = p.parse_packet_entities(&msg, &mut store);
+    assert_eq!(None, parsed.error);
+    store.remove(index).expect("entity created")
+}
+
+/// A Source 2 demo declaring some classes and game events, to which frames
+/// are appended.
+pub struct Demo {
+    data: Vec<u8>,
+    class_count: usize,
+    /// Names of the declared game events and their keys, by event id.
+    events: Vec<(String, Vec<String>)>,
+}
+
+impl Demo {
+    /// A demo declaring `classes`, see [`classes`], and the game events
+    /// `events`, each with the names of its keys.
+    pub fn new(classes: &[(&str, &[(&str, Value)])], events: &[(&str, &[&str])]) -> Self {
+        let mut data = b"PBDEMS2\0".to_vec();
+        data.resize(1072, 0);
+        let mut demo = Self {
+            data,
+            class_count: classes.len(),
+            events: events
+                .iter()
+                .map(|(name, keys)| {
+                    (
+                        name.to_string(),
+                        keys.iter().map(|k| k.to_string()).collect(),
+                    )
+                })
+                .collect(),
+        };
+        let classes = self::classes(classes);
+        let list = CsvcMsgGameEventList {
+            descriptors: demo
+                .events
+                .iter()
+                .enumerate()
+                .map(|(id, (name, keys))| csvc_msg_game_event_list::DescriptorT {
+                    eventid: Some(id as i32),
+                    name: Some(name.clone()),
+                    keys: keys
+                        .iter()
+                        .map(|k| csvc_msg_game_event_list::KeyT {
+                            r#type: Some(3),
+                            name: Some(k.clone()),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+        demo.message(SvcMessages::SvcServerInfo, 0, &classes.server_info);
+        // DEM_SendTables
+        demo.frame(4, 0, &classes.serializers);
+        demo.message(SvcMessages::SvcClassInfo, 0, &classes.class_info);
+        demo.message(SvcMessages::SvcGameEventList, 0, &list);
+        demo
+    }
+
+    /// Appends a frame at `tick` of the command `cmd`.
+    pub fn frame(&mut self, cmd: u32, tick: u32, payload: &[u8]) {
+        for v in [cmd, tick, payload.len() as u32] {
+            prost::encoding::encode_varint(v as u64, &mut self.data);
+        }
+        self.data.extend(payload);
+    }
+
+    pub fn message(&mut self, kind: SvcMessages, tick: u32, msg: &impl Message) {
+        self.frame(kind as u32, tick, &msg.encode_to_vec());
+    }
+
+    /// Appends a `PacketEntities` message with `ops`, see [`packet_entities`].
+    pub fn entities(&mut self, tick: u32, ops: &[Op]) {
+        let msg = packet_entities(self.class_count, ops);
+        self.message(SvcMessages::SvcPacketEntities, tick, &msg);
+    }
+
+    /// Appends the game event `name` with a value for each of its keys,
+    /// either `Value::Int` or `Value::Str`.
+    pub fn event(&mut self, tick: u32, name: &str, values: &[Value]) {
+        let id = self
+            .events
+            .iter()
+            .position(|(n, _)| n == name)
+            .expect("declared event");
+        let msg = CsvcMsgGameEvent {
+            eventid: Some(id as i32),
+            keys: values
+                .iter()
+                .map(|v| match v {
+                    | Value::Str(s) => csvc_msg_game_event::KeyT {
+                        r#type: Some(1),
+                        val_string: Some(s.to_string()),
+                        ..Default::default()
+                    },
+                    | Value::Float(v) => csvc_msg_game_event::KeyT {
+                        r#type: Some(2),
+                        val_float: Some(*v),
+                        ..Default::default()
+                    },
+                    | Value::Int(v) => csvc_msg_game_event::KeyT {
+                        r#type: Some(3),
+                        val_long: Some(*v),
+                        ..Default::default()
+                    },
+                    | _ => panic!("unsupported event value {v:?}"),
+                })
+                .collect(),
+            ..Default::default()
+        };
+        self.message(SvcMessages::SvcGameEvent, tick, &msg);
+    }
+
+    /// The demo's bytes, ending with a stop frame.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.data.extend([0, 0, 0]);
+        self.data
+    }
+}
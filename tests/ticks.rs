@@ -0,0 +1,243 @@
+use cs_demo_parser::common::{Player, Team};
+use cs_demo_parser::game_state::GameState;
+use cs_demo_parser::parser::Parser;
+use cs_demo_parser::proto::msg::{CsvcMsgCreateStringTable, SvcMessages};
+use cs_demo_parser::proto::msgs2::CMsgPlayerInfo;
+use cs_demo_parser::sendtables::entity::{Entity, FlattenedPropEntry, Property, PropertyValue};
+use cs_demo_parser::sendtables::propdecoder::SendTableProperty;
+use cs_demo_parser::sendtables::serverclass::ServerClass;
+use cs_demo_parser::ticks::{TickProperties, TickSampler, TickSamplerConfig};
+use prost::Message;
+use std::io::Cursor;
+use std::sync::Arc;
+use support::{Demo, Op, Value};
+
+mod support;
+
+fn make_entity(props: Vec<(&str, i32)>) -> Entity {
+    let props = props
+        .iter()
+        .map(|(name, val)| Property {
+            entry: FlattenedPropEntry {
+                name: name.to_string(),
+                prop: SendTableProperty::default(),
+                array_element_prop: None,
+            },
+            value: PropertyValue {
+                int_val: *val,
+                ..Default::default()
+            },
+        })
+        .collect();
+    Entity {
+        id: 0,
+        serial_num: 0,
+        server_class: Arc::new(ServerClass::default()),
+        props,
+    }
+}
+
+fn add_player(gs: &mut GameState, user_id: i32, team: Team) {
+    gs.players_by_user_id.insert(
+        user_id,
+        Player {
+            user_id,
+            team,
+            is_connected: true,
+            entity: Some(make_entity(vec![
+                ("m_iHealth", 100),
+                ("m_ArmorValue", 50),
+                ("m_iAccount", 800),
+            ])),
+            ..Default::default()
+        },
+    );
+}
+
+#[test]
+fn sample_respects_interval_and_properties() {
+    let mut gs = GameState::default();
+    add_player(&mut gs, 2, Team::CounterTerrorists);
+    add_player(&mut gs, 1, Team::Terrorists);
+    add_player(&mut gs, 3, Team::Spectators);
+
+    let mut sampler = TickSampler::new(TickSamplerConfig {
+        interval: 4,
+        properties: TickProperties::HEALTH | TickProperties::MONEY,
+        ..Default::default()
+    });
+    for tick in 0..10 {
+        gs.set_ingame_tick(tick);
+        sampler.sample(&gs);
+    }
+
+    let buf = sampler.buffer();
+    assert_eq!(6, buf.len());
+    assert_eq!(vec![0, 0, 4, 4, 8, 8], buf.tick);
+    assert_eq!(vec![1, 2, 1, 2, 1, 2], buf.user_id);
+    assert_eq!(vec![100; 6], buf.health);
+    assert_eq!(vec![800; 6], buf.money);
+    assert!(buf.armor.is_empty());
+    assert!(buf.x.is_empty());
+}
+
+/// Appends the bits of `value`, least significant first.
+fn bits(out: &mut Vec<bool>, value: u32, n: u32) {
+    out.extend((0..n).map(|i| value >> i & 1 == 1));
+}
+
+/// A `userinfo` string table with one entry per `(user_id, name)`.
+fn userinfo(players: &[(i32, &str)]) -> Vec<u8> {
+    let mut stream = vec![false];
+    for &(user_id, name) in players {
+        let info = CMsgPlayerInfo {
+            name: Some(name.into()),
+            userid: Some(user_id),
+            xuid: Some(76561197960265728 + user_id as u64),
+            ..Default::default()
+        };
+        let data = info.encode_to_vec();
+        // next index, no key, user data
+        stream.extend([true, false, true]);
+        bits(&mut stream, data.len() as u32, 14);
+        for b in data {
+            bits(&mut stream, b as u32, 8);
+        }
+    }
+    let mut string_data = vec![0u8; stream.len().div_ceil(8)];
+    for (i, bit) in stream.into_iter().enumerate() {
+        string_data[i / 8] |= (bit as u8) << (i % 8);
+    }
+    CsvcMsgCreateStringTable {
+        name: Some("userinfo".into()),
+        max_entries: Some(64),
+        num_entries: Some(players.len() as i32),
+        string_data: Some(string_data),
+        ..Default::default()
+    }
+    .encode_to_vec()
+}
+
+fn controller(pawn: u32) -> Vec<(&'static str, Value)> {
+    vec![
+        ("m_iTeamNum", Value::Int(2)),
+        ("m_hPlayerPawn", Value::Uint(1 << 14 | pawn)),
+    ]
+}
+
+fn pawn(health: i32, x: f32, yaw: f32) -> Vec<(&'static str, Value)> {
+    vec![
+        ("m_iHealth", Value::Int(health)),
+        ("m_ArmorValue", Value::Int(100)),
+        ("m_angEyeAngles", Value::Vector([-10.0, yaw, 0.0])),
+        ("m_bIsScoped", Value::Bool(true)),
+        ("m_pItemServices.m_bHasHelmet", Value::Bool(true)),
+        ("m_pItemServices.m_bHasDefuser", Value::Bool(false)),
+        ("CBodyComponent.m_cellX", Value::Uint(32)),
+        ("CBodyComponent.m_cellY", Value::Uint(32)),
+        ("CBodyComponent.m_cellZ", Value::Uint(32)),
+        ("CBodyComponent.m_vecX", Value::Float(x)),
+        ("CBodyComponent.m_vecY", Value::Float(0.0)),
+        ("CBodyComponent.m_vecZ", Value::Float(0.0)),
+    ]
+}
+
+/// A Source 2 demo creating `userinfo` and a controller and pawn for each
+/// of `players` at tick 0, followed by an empty frame for each of `ticks`.
+/// At tick 8 every pawn moves 32 units along x and turns to yaw 90.
+fn source2_demo(players: &[(i32, &str)], ticks: std::ops::Range<u32>) -> Vec<u8> {
+    let mut demo = Demo::new(
+        &[
+            ("CCSPlayerController", &controller(0)),
+            ("CCSPlayerPawn", &pawn(0, 0.0, 0.0)),
+        ],
+        &[],
+    );
+    demo.frame(
+        SvcMessages::SvcCreateStringTable as u32,
+        0,
+        &userinfo(players),
+    );
+    let mut sorted: Vec<i32> = players.iter().map(|&(user_id, _)| user_id).collect();
+    sorted.sort();
+    let (controllers, pawns): (Vec<_>, Vec<_>) = sorted
+        .iter()
+        .map(|&user_id| {
+            let index = 10 + user_id as u32;
+            (controller(index), pawn(100 - user_id, 0.0, 0.0))
+        })
+        .unzip();
+    let moved: Vec<_> = sorted
+        .iter()
+        .map(|&user_id| pawn(100 - user_id, 32.0, 90.0))
+        .collect();
+    let create = |class_id, index, values| Op::Create {
+        class_id,
+        index,
+        serial: 1,
+        values,
+    };
+    let mut ops: Vec<Op> = sorted
+        .iter()
+        .zip(&controllers)
+        .map(|(&user_id, values)| create(0, user_id, values))
+        .collect();
+    ops.extend(
+        sorted
+            .iter()
+            .zip(&pawns)
+            .map(|(&user_id, values)| create(1, 10 + user_id, values)),
+    );
+    demo.entities(0, &ops);
+    for tick in ticks {
+        if tick == 8 {
+            let ops: Vec<Op> = sorted
+                .iter()
+                .zip(&moved)
+                .map(|(&user_id, values)| Op::Update {
+                    index: 10 + user_id,
+                    values,
+                })
+                .collect();
+            demo.entities(tick, &ops);
+        }
+        demo.frame(1, tick, &[]);
+    }
+    demo.finish()
+}
+
+#[test]
+fn parser_runs_registered_sampler_on_frame_done() {
+    let demo = source2_demo(&[(2, "bob"), (1, "alice")], 1..14);
+    let mut parser = Parser::new(Cursor::new(demo));
+    let sampler = parser.register_tick_sampler(TickSamplerConfig {
+        interval: 4,
+        include_spectators: true,
+        properties: TickProperties::all(),
+    });
+    parser.parse_to_end().unwrap();
+
+    let sampler = sampler.lock().unwrap();
+    let buf = sampler.buffer();
+    // The demo's first frame at tick 0 has no players yet.
+    assert_eq!(vec![4, 4, 8, 8, 12, 12], buf.tick);
+    assert_eq!(vec![1, 2, 1, 2, 1, 2], buf.user_id);
+    assert_eq!(
+        vec!["alice", "bob", "alice", "bob", "alice", "bob"],
+        buf.name
+    );
+    assert_eq!(76561197960265729, buf.steam_id64[0]);
+    // Values of the pawns.
+    assert_eq!(vec![99, 98, 99, 98, 99, 98], buf.health);
+    assert_eq!(vec![100; 6], buf.armor);
+    assert_eq!(vec![Team::Terrorists; 6], buf.team);
+    assert_eq!(vec![-10.0; 6], buf.pitch);
+    assert_eq!(vec![0.0, 0.0, 90.0, 90.0, 90.0, 90.0], buf.yaw);
+    assert_eq!(vec![true; 6], buf.has_helmet);
+    assert_eq!(vec![false; 6], buf.has_defuser);
+    assert_eq!(vec![true; 6], buf.is_scoped);
+    assert_eq!(vec![true; 6], buf.is_alive);
+    assert_eq!(32.0, buf.x[2] - buf.x[0]);
+    // 32 units in 8 ticks at 64 ticks per second.
+    assert_eq!(vec![0.0, 0.0, 256.0, 256.0, 256.0, 256.0], buf.velocity_x);
+}